@@ -0,0 +1,83 @@
+use std::time::Instant;
+
+use remu::bus::Bus;
+use remu::cpm::bdos;
+use remu::loader;
+use remu::machine::SimpleMachine;
+
+// interpreter throughput on the instruction exercisers; run with
+// cargo bench --bench dispatch [-- <cycles>]
+fn exerciser(program: &str, budget: u64, cache: bool) {
+    let image = loader::read(format!("data/{}", program)).unwrap();
+    let mut machine = SimpleMachine::new();
+    machine.set_block_cache(cache);
+    machine.load_image(&image);
+    machine.cpu.pc = image.start.unwrap_or(0x0100);
+    machine.load(0x0000, &[0x76]); // HLT
+    machine.load(0x0005, &[0xC9]); // RET, console output is not needed
+    machine.set_trap(0x0000, true);
+
+    let start = Instant::now();
+    let stop = machine.run_for_cycles(budget);
+    match machine.block_cache() {
+        Some(cache) => {
+            report(&format!("{} cache", program), stop.ops, stop.cycles, start);
+            let stats = cache.stats();
+            println!(
+                "{:<16} {} translated, {} invalidated, {} blocks, {} interpreted",
+                "", stats.translated, stats.invalidated, stats.blocks, stats.interpreted
+            );
+        }
+        None => report(program, stop.ops, stop.cycles, start),
+    }
+}
+
+// the same loop stepping through a bus trait object
+fn exerciser_dyn(program: &str, budget: u64) {
+    let image = loader::read(format!("data/{}", program)).unwrap();
+    let mut machine = SimpleMachine::new();
+    machine.load_image(&image);
+    machine.cpu.pc = image.start.unwrap_or(0x0100);
+    machine.load(0x0000, &[0x76]); // HLT
+    machine.load(0x0005, &[0xC9]); // RET
+
+    let start = Instant::now();
+    let bus: &mut dyn Bus = &mut machine.bus;
+    let (mut ops, mut cycles) = (0, 0);
+    while cycles < budget && machine.cpu.pc != 0x0000 {
+        cycles += machine.cpu.step_dyn(bus);
+        ops += 1;
+    }
+    report(&format!("{} dyn", program), ops, cycles, start);
+}
+
+fn cputest() {
+    let image = loader::read("data/CPUTEST.COM").unwrap();
+    let mut machine = SimpleMachine::new();
+    let start = Instant::now();
+    let (ops, cycles) = bdos::run(&mut machine, &image, &mut std::io::sink()).unwrap();
+    report("CPUTEST.COM", ops, cycles, start);
+}
+
+fn report(name: &str, ops: u64, cycles: u64, start: Instant) {
+    let seconds = start.elapsed().as_secs_f64();
+    println!(
+        "{:<16} {:>11} ops {:>7.3} s {:>8.2} MIPS {:>8.2} MHz",
+        name,
+        ops,
+        seconds,
+        ops as f64 / seconds / 1e6,
+        cycles as f64 / seconds / 1e6
+    );
+}
+
+fn main() {
+    let budget = std::env::args()
+        .skip(1)
+        .find_map(|arg| arg.parse().ok())
+        .unwrap_or(1_600_000_000);
+    exerciser("8080EXM.COM", budget, false);
+    exerciser("8080EXM.COM", budget, true);
+    exerciser_dyn("8080EXM.COM", budget);
+    cputest();
+}
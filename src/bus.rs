@@ -1,20 +1,148 @@
+use std::cell::RefCell;
+use std::ops::RangeInclusive;
+use std::rc::Rc;
+
+// what the CPU is doing when it touches memory
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Access {
+    Fetch,
+    Operand,
+    Stack,
+    Data,
+}
+
+// Reads take `&mut self` so memory-mapped and port-mapped devices can have
+// side effects on read, like clearing a ready bit or popping a FIFO.
 pub trait Bus {
-    fn read(&self, addr: u16) -> u8;
+    fn read(&mut self, addr: u16) -> u8;
     fn write(&mut self, addr: u16, value: u8);
 
-    fn input(&self, port: u8) -> u8;
+    fn input(&mut self, port: u8) -> u8;
     fn output(&mut self, port: u8, value: u8);
 
-    fn read_word(&self, addr: u16) -> u16 {
-        let lo = self.read(addr);
-        let hi = self.read(addr.wrapping_add(1));
+    // the CPU accesses memory through these; override them to tell opcode
+    // fetches, operands, stack and data apart
+    fn read_access(&mut self, addr: u16, _access: Access) -> u8 {
+        self.read(addr)
+    }
+
+    fn write_access(&mut self, addr: u16, value: u8, _access: Access) {
+        self.write(addr, value);
+    }
+
+    fn read_word(&mut self, addr: u16, access: Access) -> u16 {
+        let lo = self.read_access(addr, access);
+        let hi = self.read_access(addr.wrapping_add(1), access);
         ((hi as u16) << 8) | (lo as u16)
     }
 
-    fn write_word(&mut self, addr: u16, value: u16) {
+    fn write_word(&mut self, addr: u16, value: u16, access: Access) {
         let lo = (value & 0x00FF) as u8;
         let hi = (value >> 8) as u8;
-        self.write(addr, lo);
-        self.write(addr.wrapping_add(1), hi);
+        self.write_access(addr, lo, access);
+        self.write_access(addr.wrapping_add(1), hi, access);
+    }
+}
+
+// a port-mapped peripheral; `port` is relative to the base it is attached at
+pub trait Device {
+    fn input(&mut self, port: u8) -> u8;
+    fn output(&mut self, port: u8, value: u8);
+}
+
+// routes I/O ports to attached devices; the devices are shared so host code
+// can keep a handle to drive their pins
+pub struct Ports {
+    devices: Vec<(u8, Rc<RefCell<dyn Device>>)>,
+    map: [Option<usize>; 256],
+}
+
+impl Ports {
+    pub fn new() -> Self {
+        Ports {
+            devices: Vec::new(),
+            map: [None; 256],
+        }
+    }
+
+    // a device attached over ports already in use takes them over, and
+    // devices left with no ports at all are dropped
+    pub fn attach(&mut self, ports: RangeInclusive<u8>, device: Rc<RefCell<dyn Device>>) {
+        let index = self.devices.len();
+        self.devices.push((*ports.start(), device));
+        for port in ports {
+            self.map[port as usize] = Some(index);
+        }
+
+        let mut used = vec![false; self.devices.len()];
+        for &index in self.map.iter().flatten() {
+            used[index] = true;
+        }
+        let mut renumber = Vec::with_capacity(used.len());
+        let mut kept = 0;
+        for used in &used {
+            renumber.push(kept);
+            kept += *used as usize;
+        }
+        let mut index = 0;
+        self.devices.retain(|_| {
+            index += 1;
+            used[index - 1]
+        });
+        for slot in self.map.iter_mut().flatten() {
+            *slot = renumber[*slot];
+        }
+    }
+
+    pub fn input(&self, port: u8) -> Option<u8> {
+        let (base, device) = &self.devices[self.map[port as usize]?];
+        Some(device.borrow_mut().input(port - base))
+    }
+
+    pub fn output(&self, port: u8, value: u8) -> bool {
+        match self.map[port as usize] {
+            Some(index) => {
+                let (base, device) = &self.devices[index];
+                device.borrow_mut().output(port - base, value);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl Default for Ports {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Echo(u8);
+
+    impl Device for Echo {
+        fn input(&mut self, port: u8) -> u8 {
+            self.0 + port
+        }
+
+        fn output(&mut self, _port: u8, _value: u8) {}
+    }
+
+    #[test]
+    fn test_attach() {
+        let mut ports = Ports::new();
+        for _ in 0..300 {
+            ports.attach(0x10..=0x11, Rc::new(RefCell::new(Echo(0x20))));
+        }
+        ports.attach(0x11..=0x12, Rc::new(RefCell::new(Echo(0x40))));
+        // each re-attachment replaced the one before
+        assert_eq!(ports.devices.len(), 2);
+        assert_eq!(ports.input(0x10), Some(0x20));
+        assert_eq!(ports.input(0x11), Some(0x40));
+        assert_eq!(ports.input(0x12), Some(0x41));
+        assert_eq!(ports.input(0x13), None);
     }
 }
@@ -0,0 +1,9 @@
+pub mod bdos;
+pub mod console;
+pub mod drive;
+pub mod expect;
+pub mod fs;
+pub mod hostdrive;
+pub mod imagedrive;
+pub mod mpm;
+pub mod peripherals;
@@ -0,0 +1,761 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::io::{self, Write};
+
+use super::drive::Drive;
+use super::fs::FileName;
+use super::peripherals::{DEFAULT_IOBYTE, Logical, Peripherals, Physical};
+use crate::bus::{Access, Bus};
+use crate::cpu::Cycles;
+use crate::devices::terminal::Terminal;
+use crate::loader::Image;
+use crate::machine::{SimpleMachine, StopReason};
+
+mod cpm3;
+mod files;
+
+// Where BDOS console output goes and keyboard input comes from. A console
+// without a keyboard has no input: reading from it ends the program.
+pub trait Console {
+    fn put(&mut self, char: u8) -> io::Result<()>;
+    fn flush(&mut self) -> io::Result<()>;
+
+    // a key if one is waiting, without blocking
+    fn poll(&mut self) -> io::Result<Option<u8>> {
+        Ok(None)
+    }
+
+    // waits for a key; None once the input has ended
+    fn get(&mut self) -> io::Result<Option<u8>> {
+        Ok(None)
+    }
+}
+
+// Plain writers only get printable characters and newlines: the
+// diagnostics use CR LF line endings and have no screen to address.
+impl<W: Write + ?Sized> Console for W {
+    fn put(&mut self, char: u8) -> io::Result<()> {
+        if char >= b' ' || char == b'\n' {
+            self.write_all(&[char])?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Write::flush(self)
+    }
+}
+
+// a terminal sees everything, escape sequences included
+impl Console for Terminal {
+    fn put(&mut self, char: u8) -> io::Result<()> {
+        Terminal::put(self, char);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn poll(&mut self) -> io::Result<Option<u8>> {
+        Ok(self.take_key())
+    }
+
+    fn get(&mut self) -> io::Result<Option<u8>> {
+        Ok(self.take_key())
+    }
+}
+
+// CALL 5 is trapped, and so is the BDOS entry it jumps to, whose address
+// at 0x0006 is also the top of the memory programs can use; the CCP's
+// stack is under it and returns to the warm boot
+const BDOS: u16 = 0x0005;
+const BDOS_ENTRY: u16 = 0xFC06;
+const STACK: u16 = 0xFBFE;
+// the character device entries are trapped; the rest of the jump table,
+// disk calls included, is plain RETs
+const BIOS: u16 = 0xFE00;
+const BOOT: u16 = BIOS;
+const WBOOT: u16 = BIOS + 3;
+const CONST: u16 = BIOS + 6;
+const CONIN: u16 = BIOS + 9;
+const CONOUT: u16 = BIOS + 12;
+const LIST: u16 = BIOS + 15;
+const PUNCH: u16 = BIOS + 18;
+const READER: u16 = BIOS + 21;
+const LISTST: u16 = BIOS + 45;
+// CP/M 3 memory management
+const MOVE: u16 = BIOS + 75;
+const SELMEM: u16 = BIOS + 81;
+const XMOVE: u16 = BIOS + 87;
+// the disk parameter block and allocation vector all drives share
+const DPB_ADDR: u16 = BIOS + 0x80;
+const ALV: u16 = BIOS + 0x90;
+const BIOS_ENTRIES: u16 = 17;
+const CPM3_BIOS_ENTRIES: u16 = 33;
+
+// CP/M 3 runs programs in bank 1 with the common memory the BIOS is in
+// shared by both banks
+pub(super) const COMMON: u16 = 0xC000;
+const TPA_BANK: usize = 1;
+
+const IOBYTE: u16 = 0x0003;
+
+const CTRL_C: u8 = 0x03;
+const CTRL_S: u8 = 0x13;
+const CTRL_U: u8 = 0x15;
+const CTRL_X: u8 = 0x18;
+const CTRL_Z: u8 = 0x1A;
+
+// CP/M 3 console mode bits: console status only reports Ctrl-C, Ctrl-S
+// does not stop output, and Ctrl-C does not warm boot
+const CTRL_C_STATUS: u16 = 0x01;
+const NO_CTRL_S: u16 = 0x02;
+const NO_CTRL_C: u16 = 0x08;
+
+// CP/M 3 return codes: below FAILURE is success; Ctrl-C and BDOS errors
+// that end the program set the last two
+pub const FAILURE: u16 = 0xFF00;
+pub const BDOS_ERROR: u16 = 0xFFFD;
+pub const CTRL_C_ABORT: u16 = 0xFFFE;
+
+// Minimal CP/M environment: a warm boot ends the program, BDOS calls at
+// 0x0005 and the BIOS console entries are trapped, and `console` does the
+// terminal I/O. A console error stops the run where it happened; host
+// consoles use io::ErrorKind::Interrupted for their escape key.
+pub fn run(
+    machine: &mut SimpleMachine,
+    image: &Image,
+    console: &mut dyn Console,
+) -> io::Result<(u64, Cycles)> {
+    let mut bdos = Bdos::boot(machine, image);
+    while bdos.run(machine, console, Cycles::MAX)? == Status::Running {}
+    Ok((bdos.ops(), bdos.cycles()))
+}
+
+// Run `image` as the CCP would for the command line "PROGRAM <tail>" and
+// return its return code, which is 0 unless it sets one with BDOS 108 or
+// is ended by Ctrl-C or a BDOS error.
+pub fn run_program(
+    machine: &mut SimpleMachine,
+    image: &Image,
+    tail: &str,
+    console: &mut dyn Console,
+) -> io::Result<u16> {
+    let mut bdos = Bdos::boot(machine, image);
+    bdos.set_command(machine, tail);
+    while bdos.run(machine, console, Cycles::MAX)? == Status::Running {}
+    Ok(bdos.return_code())
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Version {
+    Cpm22,
+    Cpm3,
+    // one process of an MP/M II system; see mpm.rs
+    Mpm2,
+}
+
+// how far a Bdos::run got
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Status {
+    // the cycle budget ran out
+    Running,
+    // the console returned io::ErrorKind::WouldBlock for a read; the call
+    // is made again on the next run
+    Waiting,
+    // the program warm booted or halted
+    Exited,
+    // MP/M II: the program gave up the processor for this many clock ticks,
+    // none for a plain dispatch
+    Delayed(u16),
+}
+
+enum Reply {
+    Done,
+    // returned in A and L, with B and H cleared
+    Value(u8),
+    // returned in HL, and in BA as well
+    Word(u16),
+    // return, and give up the processor for this many ticks
+    Yield(u16),
+    WarmBoot,
+}
+
+// a CP/M program run in slices, for hosts that need to look at the
+// console between them
+pub struct Bdos {
+    // a key read while looking for Ctrl-S, kept for the next input call
+    pending: Option<u8>,
+    // the input has ended and character input has had its Ctrl-Z
+    ended: bool,
+    // a read console buffer call that is waiting for more keys
+    line: Vec<u8>,
+    peripherals: Peripherals,
+    // drives A to P
+    drives: Vec<Option<Box<dyn Drive>>>,
+    disk: u8,
+    user: u8,
+    dma: u16,
+    // drives write protected by BDOS 28
+    protected: u16,
+    // what search next has left to return
+    search: VecDeque<[u8; 32]>,
+    version: Version,
+    // CP/M 3: records per read or write call
+    records: u8,
+    // CP/M 3: 0xFF returns errors, 0xFE displays and returns them, and
+    // anything else displays them and warm boots
+    error_mode: u8,
+    console_mode: u16,
+    // ends string output
+    delimiter: u8,
+    // CP/M 3 file passwords, which only last for the session
+    passwords: BTreeMap<(usize, FileName), files::Password>,
+    default_password: [u8; 8],
+    // files opened without the password their write protection needs
+    locked: BTreeSet<(usize, FileName)>,
+    // seconds the CP/M clock is ahead of the host's
+    clock: i64,
+    // the banks the next MOVE copies to and from, after XMOVE
+    xmove: Option<(usize, usize)>,
+    // MP/M II
+    console_number: u8,
+    // BDOS 108
+    return_code: u16,
+    exited: bool,
+    ops: u64,
+    cycles: Cycles,
+}
+
+impl Bdos {
+    // load `image` and set up page zero, the stack and the BIOS jump
+    // table, with an empty command line
+    pub fn boot(machine: &mut SimpleMachine, image: &Image) -> Bdos {
+        machine.load_image(image);
+        machine.cpu.pc = image.start.unwrap_or(0x0100);
+
+        let [lo, hi] = WBOOT.to_le_bytes();
+        machine.load(0x0000, &[0xC3, lo, hi]); // JMP WBOOT
+        let [lo, hi] = BDOS_ENTRY.to_le_bytes();
+        machine.load(BDOS, &[0xC3, lo, hi]); // JMP BDOS_ENTRY
+        machine.load(BDOS_ENTRY, &[0xC9]); // RET
+        machine.load(STACK, &[0, 0]);
+        machine.cpu.sp = STACK;
+        files::set_command(machine, "");
+        for entry in 0..BIOS_ENTRIES {
+            machine.load(BIOS + entry * 3, &[0xC9]);
+        }
+        machine.load(IOBYTE, &[DEFAULT_IOBYTE]);
+        machine.load(DPB_ADDR, &files::DPB);
+        let mut alv = [0; 32];
+        alv[0] = files::DPB[9];
+        machine.load(ALV, &alv);
+        for addr in [
+            0x0000, BDOS, BDOS_ENTRY, BOOT, WBOOT, CONST, CONIN, CONOUT, LIST, PUNCH, READER,
+            LISTST,
+        ] {
+            machine.set_trap(addr, true);
+        }
+
+        Bdos {
+            pending: None,
+            ended: false,
+            line: Vec::new(),
+            peripherals: Peripherals::new(),
+            drives: (0..16).map(|_| None).collect(),
+            disk: 0,
+            user: 0,
+            dma: 0x0080,
+            protected: 0,
+            search: VecDeque::new(),
+            version: Version::Cpm22,
+            records: 1,
+            error_mode: 0,
+            console_mode: 0,
+            delimiter: b'$',
+            passwords: BTreeMap::new(),
+            default_password: [b' '; 8],
+            locked: BTreeSet::new(),
+            clock: 0,
+            xmove: None,
+            console_number: 0,
+            return_code: 0,
+            exited: false,
+            ops: 0,
+            cycles: 0,
+        }
+    }
+
+    // The same for CP/M 3, with the CP/M 3 BDOS calls and a banked
+    // machine: the program runs in bank 1, bank 0 is free for it to use
+    // through the BIOS MOVE, XMOVE and SELMEM entries.
+    pub fn boot_cpm3(machine: &mut SimpleMachine, image: &Image) -> Bdos {
+        machine.set_banks(2, COMMON);
+        machine.select_bank(TPA_BANK);
+        let mut bdos = Bdos::boot(machine, image);
+        bdos.version = Version::Cpm3;
+        for entry in BIOS_ENTRIES..CPM3_BIOS_ENTRIES {
+            machine.load(BIOS + entry * 3, &[0xC9]);
+        }
+        for addr in [MOVE, SELMEM, XMOVE] {
+            machine.set_trap(addr, true);
+        }
+        bdos
+    }
+
+    // one MP/M II process, booted in the bank selected now, on `console`
+    pub fn boot_mpm(machine: &mut SimpleMachine, image: &Image, console: u8) -> Bdos {
+        let mut bdos = Bdos::boot(machine, image);
+        bdos.version = Version::Mpm2;
+        bdos.console_number = console;
+        bdos
+    }
+
+    // the command tail and default FCBs for "PROGRAM <tail>"
+    pub fn set_command(&mut self, machine: &mut SimpleMachine, tail: &str) {
+        files::set_command(machine, tail);
+    }
+
+    pub fn return_code(&self) -> u16 {
+        self.return_code
+    }
+
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    // where LST:, PUN: and RDR: go
+    pub fn peripherals(&mut self) -> &mut Peripherals {
+        &mut self.peripherals
+    }
+
+    // `drive` is 0 for A: to 15 for P:
+    pub fn mount(&mut self, drive: usize, backend: Box<dyn Drive>) {
+        self.drives[drive] = Some(backend);
+    }
+
+    pub fn ops(&self) -> u64 {
+        self.ops
+    }
+
+    pub fn cycles(&self) -> Cycles {
+        self.cycles
+    }
+
+    // run for at least `budget` cycles, or until the program exits or
+    // waits for a key
+    pub fn run(
+        &mut self,
+        machine: &mut SimpleMachine,
+        console: &mut dyn Console,
+        budget: Cycles,
+    ) -> io::Result<Status> {
+        let status = self.run_slice(machine, console, budget)?;
+        console.flush()?;
+        self.peripherals.flush()?;
+        Ok(status)
+    }
+
+    fn run_slice(
+        &mut self,
+        machine: &mut SimpleMachine,
+        console: &mut dyn Console,
+        budget: Cycles,
+    ) -> io::Result<Status> {
+        let mut spent = 0;
+        // a run that stopped at a trap has not made the call yet
+        let mut addr = machine.cpu.pc;
+        loop {
+            if self.exited {
+                return Ok(Status::Exited);
+            }
+            let reply = match addr {
+                // the BDOS works on the program's bank, whatever is selected
+                BDOS | BDOS_ENTRY if self.version == Version::Cpm3 => {
+                    let bank = machine.selected_bank();
+                    machine.select_bank(TPA_BANK);
+                    let reply = self.call(machine, console);
+                    machine.select_bank(bank);
+                    reply
+                }
+                BDOS | BDOS_ENTRY => self.call(machine, console),
+                0x0000 | BOOT | WBOOT => Ok(Reply::WarmBoot),
+                CONST | CONIN | CONOUT | LIST | PUNCH | READER | LISTST | MOVE | SELMEM | XMOVE => {
+                    self.bios(machine, console, addr)
+                }
+                _ => Ok(Reply::Done),
+            };
+            match reply {
+                Ok(Reply::Done) => {}
+                Ok(Reply::Value(value)) => {
+                    machine.cpu.a = value;
+                    machine.cpu.l = value;
+                    machine.cpu.h = 0;
+                    machine.cpu.b = 0;
+                }
+                Ok(Reply::Word(value)) => {
+                    let [lo, hi] = value.to_le_bytes();
+                    machine.cpu.a = lo;
+                    machine.cpu.l = lo;
+                    machine.cpu.h = hi;
+                    machine.cpu.b = hi;
+                }
+                Ok(Reply::WarmBoot) => {
+                    machine.cpu.pc = 0x0000;
+                    self.exited = true;
+                    continue;
+                }
+                Ok(Reply::Yield(ticks)) => {
+                    // the RET at the BDOS entry, so the call is not made
+                    // again when the program resumes
+                    machine.cpu.pc = machine.bus.read_word(machine.cpu.sp, Access::Stack);
+                    machine.cpu.sp = machine.cpu.sp.wrapping_add(2);
+                    return Ok(Status::Delayed(ticks));
+                }
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => return Ok(Status::Waiting),
+                Err(err) => return Err(err),
+            }
+            // return through the RET at the entry rather than the jump to it
+            if machine.cpu.pc == BDOS {
+                machine.cpu.pc = BDOS_ENTRY;
+            }
+
+            if spent >= budget {
+                return Ok(Status::Running);
+            }
+            let stop = machine.run_for_cycles(budget - spent);
+            self.ops += stop.ops;
+            self.cycles += stop.cycles;
+            spent += stop.cycles;
+            match stop.reason {
+                StopReason::Trap(next) => addr = next,
+                StopReason::CycleBudget => return Ok(Status::Running),
+                _ => self.exited = true,
+            }
+        }
+    }
+
+    fn call(
+        &mut self,
+        machine: &mut SimpleMachine,
+        console: &mut dyn Console,
+    ) -> io::Result<Reply> {
+        Ok(match machine.cpu.c {
+            0x00 => Reply::WarmBoot,
+            0x01 => {
+                // console input with echo
+                match self.key(console)? {
+                    Some(key) => {
+                        if key >= b' ' && key != 0x7F || matches!(key, b'\r' | b'\n' | b'\t' | 0x08)
+                        {
+                            console.put(key)?;
+                        }
+                        Reply::Value(key)
+                    }
+                    None => Reply::WarmBoot,
+                }
+            }
+            0x02 => {
+                // character output
+                if !self.pause(console)? {
+                    return Ok(Reply::WarmBoot);
+                }
+                console.put(machine.cpu.e)?;
+                Reply::Done
+            }
+            0x03 => Reply::Value(self.read(machine, console)?),
+            0x04 => {
+                self.write(machine, console, Logical::Pun, machine.cpu.e)?;
+                Reply::Done
+            }
+            0x05 => {
+                self.write(machine, console, Logical::Lst, machine.cpu.e)?;
+                Reply::Done
+            }
+            0x06 => {
+                // direct console I/O: FF polls, FE is the status and FD
+                // waits for a key, without echo or Ctrl-S/Ctrl-C handling
+                match machine.cpu.e {
+                    0xFF => Reply::Value(self.poll(console)?.unwrap_or(0)),
+                    0xFE => Reply::Value(self.status(console)?),
+                    0xFD => match self.key(console)? {
+                        Some(key) => Reply::Value(key),
+                        None => Reply::WarmBoot,
+                    },
+                    char => {
+                        console.put(char)?;
+                        Reply::Done
+                    }
+                }
+            }
+            0x07 => Reply::Value(machine.bus.read(IOBYTE)),
+            0x08 => {
+                machine.bus.write(IOBYTE, machine.cpu.e);
+                machine.invalidate(IOBYTE, 1);
+                Reply::Done
+            }
+            0x09 => {
+                // string output
+                if !self.pause(console)? {
+                    return Ok(Reply::WarmBoot);
+                }
+                let mut addr = machine.cpu.de();
+                loop {
+                    let char = machine.bus.read(addr);
+                    if char == self.delimiter {
+                        break;
+                    }
+                    console.put(char)?;
+                    addr = addr.wrapping_add(1);
+                }
+                Reply::Done
+            }
+            0x0A => self.read_buffer(machine, console)?,
+            0x0B => {
+                // console status
+                if !self.pause(console)? {
+                    return Ok(Reply::WarmBoot);
+                }
+                let ready = if self.console_mode & CTRL_C_STATUS != 0 {
+                    self.status(console)? != 0 && self.pending == Some(CTRL_C)
+                } else {
+                    self.status(console)? != 0
+                };
+                match (ready, self.version) {
+                    (false, _) => Reply::Value(0),
+                    (true, Version::Cpm3) => Reply::Value(1),
+                    (true, _) => Reply::Value(0xFF),
+                }
+            }
+            0x0C => match self.version {
+                Version::Cpm22 => Reply::Value(0x22),
+                Version::Cpm3 => Reply::Value(0x31),
+                // H = 1 for MP/M
+                Version::Mpm2 => Reply::Word(0x0130),
+            },
+            13..=40 => self.file_call(machine, console)?,
+            44..=47 | 102..=106 | 109 | 110 if self.version == Version::Cpm3 => {
+                self.cpm3_call(machine, console)?
+            }
+            // get or set the program return code
+            108 if machine.cpu.de() == 0xFFFF => Reply::Word(self.return_code),
+            108 => {
+                self.return_code = machine.cpu.de();
+                Reply::Done
+            }
+            // MP/M II: delay, dispatch and terminate
+            141 if self.version == Version::Mpm2 => Reply::Yield(machine.cpu.de()),
+            142 if self.version == Version::Mpm2 => Reply::Yield(0),
+            143 if self.version == Version::Mpm2 => Reply::WarmBoot,
+            // get console number
+            153 if self.version == Version::Mpm2 => Reply::Value(self.console_number),
+            // get date and time: the CP/M 3 date followed by seconds
+            155 if self.version == Version::Mpm2 => {
+                let (date, second) = self.date();
+                let addr = machine.cpu.de();
+                machine.load(addr, &date);
+                machine.load(addr.wrapping_add(4), &[second]);
+                Reply::Done
+            }
+            _ => {
+                let message = format!("unsupported {:02X} CP/M call\n", machine.cpu.c);
+                for char in message.bytes() {
+                    console.put(char)?;
+                }
+                Reply::Done
+            }
+        })
+    }
+
+    fn bios(
+        &mut self,
+        machine: &mut SimpleMachine,
+        console: &mut dyn Console,
+        addr: u16,
+    ) -> io::Result<Reply> {
+        let a = match addr {
+            CONST => self.status(console)?,
+            CONIN => match self.key(console)? {
+                Some(key) => key,
+                None => return Ok(Reply::WarmBoot),
+            },
+            CONOUT => {
+                console.put(machine.cpu.c)?;
+                return Ok(Reply::Done);
+            }
+            LIST | PUNCH => {
+                let device = if addr == LIST {
+                    Logical::Lst
+                } else {
+                    Logical::Pun
+                };
+                self.write(machine, console, device, machine.cpu.c)?;
+                return Ok(Reply::Done);
+            }
+            READER => self.read(machine, console)?,
+            // copy BC bytes from DE to HL, between the banks XMOVE gave
+            MOVE => {
+                let selected = machine.selected_bank();
+                let (to, from) = self.xmove.take().unwrap_or((selected, selected));
+                let (mut source, mut dest) = (machine.cpu.de(), machine.cpu.hl());
+                for _ in 0..machine.cpu.bc() {
+                    let byte = machine.read_bank(from, source);
+                    machine.write_bank(to, dest, byte);
+                    source = source.wrapping_add(1);
+                    dest = dest.wrapping_add(1);
+                }
+                machine.cpu.set_de(source);
+                machine.cpu.set_hl(dest);
+                return Ok(Reply::Done);
+            }
+            SELMEM => {
+                machine.select_bank(machine.cpu.a as usize);
+                return Ok(Reply::Done);
+            }
+            XMOVE => {
+                // B is the destination bank, C the source; a bank that does
+                // not exist leaves the next MOVE within the selected one
+                let (to, from) = (machine.cpu.b as usize, machine.cpu.c as usize);
+                let banks = machine.bank_count();
+                self.xmove = (to < banks && from < banks).then_some((to, from));
+                return Ok(Reply::Done);
+            }
+            // LISTST: host files are always ready
+            _ => 0xFF,
+        };
+        machine.cpu.a = a;
+        Ok(Reply::Done)
+    }
+
+    // PUN: or LST: output on the device the IOBYTE assigns
+    fn write(
+        &mut self,
+        machine: &mut SimpleMachine,
+        console: &mut dyn Console,
+        device: Logical,
+        char: u8,
+    ) -> io::Result<()> {
+        match device.assigned(machine.bus.read(IOBYTE)) {
+            Physical::Tty | Physical::Crt => console.put(char),
+            physical => self.peripherals.write(physical, char),
+        }
+    }
+
+    // RDR: input; the end of a file or of the console input is Ctrl-Z
+    fn read(&mut self, machine: &mut SimpleMachine, console: &mut dyn Console) -> io::Result<u8> {
+        match Logical::Rdr.assigned(machine.bus.read(IOBYTE)) {
+            Physical::Tty => Ok(self.key(console)?.unwrap_or(CTRL_Z)),
+            physical => self.peripherals.read(physical),
+        }
+    }
+
+    // Read console buffer: DE points at the maximum length, followed by
+    // the count and the line. Backspace and DEL erase a character, Ctrl-U
+    // and Ctrl-X the line, and Ctrl-C on an empty line warm boots.
+    fn read_buffer(
+        &mut self,
+        machine: &mut SimpleMachine,
+        console: &mut dyn Console,
+    ) -> io::Result<Reply> {
+        let addr = machine.cpu.de();
+        let max = machine.bus.read(addr) as usize;
+        while self.line.len() < max {
+            let key = match self.pending.take() {
+                Some(key) => Some(key),
+                None => console.get()?,
+            };
+            match key {
+                None if self.line.is_empty() => return Ok(Reply::WarmBoot),
+                None | Some(b'\r' | b'\n') => break,
+                Some(CTRL_C) if self.line.is_empty() && self.console_mode & NO_CTRL_C == 0 => {
+                    self.return_code = CTRL_C_ABORT;
+                    return Ok(Reply::WarmBoot);
+                }
+                Some(0x08 | 0x7F) => {
+                    if let Some(char) = self.line.pop() {
+                        self.erase(console, char)?;
+                    }
+                }
+                Some(CTRL_U | CTRL_X) => {
+                    while let Some(char) = self.line.pop() {
+                        self.erase(console, char)?;
+                    }
+                }
+                Some(char) => {
+                    if char < b' ' {
+                        console.put(b'^')?;
+                        console.put(char + b'@')?;
+                    } else {
+                        console.put(char)?;
+                    }
+                    self.line.push(char);
+                }
+            }
+        }
+        console.put(b'\r')?;
+        let line = std::mem::take(&mut self.line);
+
+        let mut dest = addr.wrapping_add(1);
+        for byte in std::iter::once(line.len() as u8).chain(line) {
+            machine.bus.write(dest, byte);
+            machine.invalidate(dest, 1);
+            dest = dest.wrapping_add(1);
+        }
+        Ok(Reply::Done)
+    }
+
+    // control characters were echoed as two columns
+    fn erase(&mut self, console: &mut dyn Console, char: u8) -> io::Result<()> {
+        for _ in 0..if char < b' ' { 2 } else { 1 } {
+            for byte in [0x08, b' ', 0x08] {
+                console.put(byte)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn poll(&mut self, console: &mut dyn Console) -> io::Result<Option<u8>> {
+        match self.pending.take() {
+            Some(key) => Ok(Some(key)),
+            None => console.poll(),
+        }
+    }
+
+    fn status(&mut self, console: &mut dyn Console) -> io::Result<u8> {
+        if self.pending.is_none() {
+            self.pending = console.poll()?;
+        }
+        Ok(if self.pending.is_some() { 0xFF } else { 0x00 })
+    }
+
+    // blocking character input; the end of the input reads as one Ctrl-Z
+    fn key(&mut self, console: &mut dyn Console) -> io::Result<Option<u8>> {
+        if let Some(key) = self.pending.take() {
+            return Ok(Some(key));
+        }
+        match console.get()? {
+            None if !self.ended => {
+                self.ended = true;
+                Ok(Some(CTRL_Z))
+            }
+            key => Ok(key),
+        }
+    }
+
+    // Ctrl-S stops output until the next key, which is dropped unless it is
+    // Ctrl-C; false means warm boot
+    fn pause(&mut self, console: &mut dyn Console) -> io::Result<bool> {
+        if self.pending.is_none() && self.console_mode & NO_CTRL_S == 0 {
+            match console.poll()? {
+                Some(CTRL_S) => {
+                    if console.get()? == Some(CTRL_C) && self.console_mode & NO_CTRL_C == 0 {
+                        self.return_code = CTRL_C_ABORT;
+                        return Ok(false);
+                    }
+                }
+                key => self.pending = key,
+            }
+        }
+        Ok(true)
+    }
+}
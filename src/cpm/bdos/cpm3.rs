@@ -0,0 +1,264 @@
+use std::io;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::files::{self, DELETE, DPB, Fault, Password, peek, poke};
+use super::{Bdos, Console, Reply, STACK};
+use crate::cpm::fs::FileName;
+use crate::machine::SimpleMachine;
+
+// CP/M 3 counts days from 1 January 1978 as day 1
+const EPOCH_DAYS: i64 = 2922 - 1;
+
+fn bcd(value: u8) -> u8 {
+    ((value / 10) << 4) | (value % 10)
+}
+
+fn binary(bcd: u8) -> i64 {
+    (bcd >> 4) as i64 * 10 + (bcd & 0x0F) as i64
+}
+
+// seconds since 1970 as CP/M 3's day count, BCD hour and minute, and BCD
+// second; UTC, as the host has no time zones to offer
+fn date(seconds: i64) -> ([u8; 4], u8) {
+    let days = (seconds.div_euclid(86400) - EPOCH_DAYS).clamp(0, 0xFFFF) as u16;
+    let time = seconds.rem_euclid(86400);
+    let [lo, hi] = days.to_le_bytes();
+    let hour = bcd((time / 3600) as u8);
+    let minute = bcd((time / 60 % 60) as u8);
+    ([lo, hi, hour, minute], bcd((time % 60) as u8))
+}
+
+fn unix_seconds(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => since.as_secs() as i64,
+        Err(err) => -(err.duration().as_secs() as i64),
+    }
+}
+
+// a date stamp as it goes in an FCB, zero where the drive has none
+fn stamp(time: Option<SystemTime>) -> [u8; 4] {
+    time.map_or([0; 4], |time| date(unix_seconds(time)).0)
+}
+
+impl Bdos {
+    // the CP/M clock as a date stamp and BCD seconds
+    pub(super) fn date(&self) -> ([u8; 4], u8) {
+        date(unix_seconds(SystemTime::now()) + self.clock)
+    }
+
+    // the BDOS calls CP/M 3 added
+    pub(super) fn cpm3_call(
+        &mut self,
+        machine: &mut SimpleMachine,
+        console: &mut dyn Console,
+    ) -> io::Result<Reply> {
+        let de = machine.cpu.de();
+        Ok(match machine.cpu.c {
+            // set multi-sector count
+            44 => match machine.cpu.e {
+                count @ 1..=128 => {
+                    self.records = count;
+                    Reply::Value(0)
+                }
+                _ => Reply::Value(0xFF),
+            },
+            // set BDOS error mode
+            45 => {
+                self.error_mode = machine.cpu.e;
+                Reply::Done
+            }
+            46 => self.free_space(machine, console)?,
+            47 => self.chain(machine, console)?,
+            102 => self.read_stamps(machine, de)?,
+            103 => self.write_xfcb(machine, console, de)?,
+            // set date and time
+            104 => {
+                let day =
+                    u16::from_le_bytes([peek(machine, de), peek(machine, de.wrapping_add(1))]);
+                let seconds = (day as i64 + EPOCH_DAYS) * 86400
+                    + binary(peek(machine, de.wrapping_add(2))) * 3600
+                    + binary(peek(machine, de.wrapping_add(3))) * 60;
+                self.clock = seconds - unix_seconds(SystemTime::now());
+                Reply::Done
+            }
+            // get date and time
+            105 => {
+                let (stamp, second) = self.date();
+                for (offset, byte) in stamp.into_iter().enumerate() {
+                    poke(machine, de.wrapping_add(offset as u16), byte);
+                }
+                Reply::Value(second)
+            }
+            // set default password
+            106 => {
+                self.default_password = std::array::from_fn(|i| {
+                    peek(machine, de.wrapping_add(i as u16)).to_ascii_uppercase()
+                });
+                Reply::Done
+            }
+            // get or set console mode
+            109 if de == 0xFFFF => Reply::Word(self.console_mode),
+            109 => {
+                self.console_mode = de;
+                Reply::Done
+            }
+            // get or set the string output delimiter
+            110 if de == 0xFFFF => Reply::Value(self.delimiter),
+            110 => {
+                self.delimiter = machine.cpu.e;
+                Reply::Done
+            }
+            _ => Reply::Value(0xFF),
+        })
+    }
+
+    // Free space on drive E as a three byte record count at the DMA
+    // address: what the shared DPB holds less what the files use.
+    fn free_space(
+        &mut self,
+        machine: &mut SimpleMachine,
+        console: &mut dyn Console,
+    ) -> io::Result<Reply> {
+        let disk = machine.cpu.e & 0x0F;
+        let Some(drive) = self.drives[disk as usize].as_mut() else {
+            return self.error(machine, console, disk, Fault::Select);
+        };
+        let blocks = u16::from_le_bytes([DPB[5], DPB[6]]) as u32 + 1;
+        let used: u32 = drive
+            .list()?
+            .iter()
+            .map(|entry| entry.records.div_ceil(8))
+            .sum();
+        let free = blocks.saturating_sub(used) * 8;
+        for (offset, byte) in free.to_le_bytes()[..3].iter().enumerate() {
+            poke(machine, self.dma.wrapping_add(offset as u16), *byte);
+        }
+        Ok(Reply::Value(0))
+    }
+
+    // Chain to program: the command line at the DMA address, ended by a
+    // zero, names a .COM file on the current drive to load and run in
+    // place of this one. "NAME?" and a warm boot if it is not there.
+    fn chain(
+        &mut self,
+        machine: &mut SimpleMachine,
+        console: &mut dyn Console,
+    ) -> io::Result<Reply> {
+        let line: Vec<u8> = (0..128)
+            .map(|offset| peek(machine, self.dma.wrapping_add(offset)))
+            .take_while(|&byte| byte != 0)
+            .collect();
+        let line = String::from_utf8_lossy(&line).trim().to_ascii_uppercase();
+        let (command, tail) = line.split_once(' ').unwrap_or((&line, ""));
+        let (disk, program) = match command.as_bytes() {
+            [drive @ b'A'..=b'P', b':', ..] => ((drive - b'A') as usize, &command[2..]),
+            _ => (self.disk as usize, command),
+        };
+        let file = if program.contains('.') {
+            program.to_string()
+        } else {
+            format!("{}.COM", program)
+        };
+        let name = FileName::parse(&file).ok().map(|mut name| {
+            name.user = self.user;
+            name
+        });
+        let drive = self.drives[disk].as_mut();
+        let entry = match (drive, name) {
+            (Some(drive), Some(name)) => drive.entry(&name)?.map(|entry| (drive, entry)),
+            _ => None,
+        };
+        // the program has to fit under the stack
+        let Some((drive, entry)) =
+            entry.filter(|(_, entry)| entry.records <= (STACK as u32 - 0x0100) / 128)
+        else {
+            for char in format!("{}?\r\n", command).bytes() {
+                console.put(char)?;
+            }
+            return Ok(Reply::WarmBoot);
+        };
+
+        let mut data = [0; 128];
+        for record in 0..entry.records {
+            if !drive.read(&entry.name, record, &mut data)? {
+                break;
+            }
+            machine.load(0x0100 + record as u16 * 128, &data);
+        }
+        drive.close(&entry.name)?;
+        files::set_command(machine, tail);
+        self.dma = 0x0080;
+        // a fresh stack that returns to the warm boot
+        machine.cpu.sp = STACK;
+        machine.load(STACK, &[0, 0]);
+        machine.cpu.pc = 0x0100;
+        Ok(Reply::Done)
+    }
+
+    // Read file date stamps and password mode: the password mode in EX,
+    // the create stamp at 24 and the update stamp at 28.
+    fn read_stamps(&mut self, machine: &mut SimpleMachine, fcb: u16) -> io::Result<Reply> {
+        let Some((disk, entry)) = self.file(machine, fcb)? else {
+            return Ok(Reply::Value(0xFF));
+        };
+        let mode = self
+            .passwords
+            .get(&(disk, entry.name))
+            .map_or(0, |password| password.mode);
+        poke(machine, fcb.wrapping_add(12), mode);
+        let stamps = stamp(entry.created)
+            .into_iter()
+            .chain(stamp(entry.modified));
+        for (offset, byte) in stamps.enumerate() {
+            poke(machine, fcb.wrapping_add(24 + offset as u16), byte);
+        }
+        Ok(Reply::Value(0))
+    }
+
+    // Write file XFCB: the current password at the DMA address, and with
+    // bit 0 of EX set a new one after it, protecting the modes in the top
+    // bits of EX. A blank password takes the protection off.
+    fn write_xfcb(
+        &mut self,
+        machine: &mut SimpleMachine,
+        console: &mut dyn Console,
+        fcb: u16,
+    ) -> io::Result<Reply> {
+        let Some((disk, entry)) = self.file(machine, fcb)? else {
+            return Ok(Reply::Value(0xFF));
+        };
+        if !self.unlocked(machine, disk, &entry, DELETE | files::WRITE | files::READ) {
+            return self.error(machine, console, disk as u8, Fault::Password);
+        }
+        let ex = peek(machine, fcb.wrapping_add(12));
+        if ex & 0x01 != 0 {
+            let key: [u8; 8] = std::array::from_fn(|i| {
+                peek(machine, self.dma.wrapping_add(8 + i as u16)).to_ascii_uppercase()
+            });
+            if key == [b' '; 8] || ex & 0xE0 == 0 {
+                self.passwords.remove(&(disk, entry.name));
+            } else {
+                let mode = ex & 0xE0;
+                self.passwords
+                    .insert((disk, entry.name), Password { key, mode });
+            }
+        } else if let Some(password) = self.passwords.get_mut(&(disk, entry.name)) {
+            password.mode = ex & 0xE0;
+        }
+        Ok(Reply::Value(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_date() {
+        // 1 January 1978 is day 1
+        assert_eq!(date(2922 * 86400 + 3661), ([1, 0, 0x01, 0x01], 0x01));
+        assert_eq!(date(unix_seconds(UNIX_EPOCH)).0, [0, 0, 0, 0]);
+        // 31 December 1999, 23:59:58
+        assert_eq!(date(946684798), ([0x63, 0x1F, 0x23, 0x59], 0x58));
+    }
+}
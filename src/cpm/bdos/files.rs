@@ -0,0 +1,712 @@
+use std::io;
+
+use super::{Bdos, Console, Reply, Version};
+use crate::bus::Bus;
+use crate::cpm::drive::{Drive, Entry};
+use crate::cpm::fs::FileName;
+use crate::machine::SimpleMachine;
+
+// FCB fields
+const EX: u16 = 12;
+const S2: u16 = 14;
+const RC: u16 = 15;
+const D0: u16 = 16;
+const CR: u16 = 32;
+const R0: u16 = 33;
+
+// records per logical extent, and extents per S2 module
+const EXTENT_RECORDS: u32 = 128;
+const EXTENTS: u32 = 32;
+
+// Errors BDOS reports on the console, or returns in A = 0xFF with the
+// code in H in the CP/M 3 return error modes
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(super) enum Fault {
+    ReadOnlyDisk = 2,
+    ReadOnlyFile = 3,
+    Select = 4,
+    Password = 7,
+}
+
+impl Fault {
+    fn message(self, version: Version) -> &'static str {
+        match (self, version == Version::Cpm3) {
+            (Fault::ReadOnlyDisk, false) => "R/O",
+            (Fault::ReadOnlyFile, false) => "File R/O",
+            (Fault::Select, false) => "Select",
+            (Fault::ReadOnlyDisk, _) => "Read/Only Disk",
+            (Fault::ReadOnlyFile, _) => "Read/Only File",
+            (Fault::Select, _) => "Invalid Drive",
+            (Fault::Password, _) => "Password Error",
+        }
+    }
+}
+
+// a CP/M 3 file password and the protection mode it unlocks
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(super) struct Password {
+    pub(super) key: [u8; 8],
+    pub(super) mode: u8,
+}
+
+// password modes that protect reading, writing and deleting; each
+// includes the ones after it
+pub(super) const READ: u8 = 0x80;
+pub(super) const WRITE: u8 = 0x40;
+pub(super) const DELETE: u8 = 0x20;
+
+// The disk parameter block every drive reports: 1K blocks, one logical
+// extent per directory entry, 256 blocks and 128 directory entries.
+// Programs that size the disk from it see a small one; nothing here
+// enforces it.
+pub(super) const DPB: [u8; 15] = [
+    32, 0, // SPT
+    3, 7, 0, // BSH, BLM, EXM
+    255, 0, // DSM
+    127, 0, // DRM
+    0xF0, 0x00, // AL0, AL1
+    0, 0, // CKS
+    0, 0, // OFF
+];
+
+pub(super) fn peek(machine: &mut SimpleMachine, addr: u16) -> u8 {
+    machine.bus.read(addr)
+}
+
+pub(super) fn poke(machine: &mut SimpleMachine, addr: u16, value: u8) {
+    machine.bus.write(addr, value);
+    machine.invalidate(addr, 1);
+}
+
+// where sequential I/O is in the file, from EX, S2 and CR
+fn position(machine: &mut SimpleMachine, fcb: u16) -> u32 {
+    let extent = (peek(machine, fcb.wrapping_add(S2)) as u32 & 0x3F) * EXTENTS
+        + peek(machine, fcb.wrapping_add(EX)) as u32;
+    extent * EXTENT_RECORDS + peek(machine, fcb.wrapping_add(CR)) as u32
+}
+
+// set EX, S2 and CR for `record`, and RC for the records of that extent
+fn seek(machine: &mut SimpleMachine, fcb: u16, record: u32, records: u32) {
+    let extent = record / EXTENT_RECORDS;
+    poke(
+        machine,
+        fcb.wrapping_add(CR),
+        (record % EXTENT_RECORDS) as u8,
+    );
+    poke(machine, fcb.wrapping_add(EX), (extent % EXTENTS) as u8);
+    poke(machine, fcb.wrapping_add(S2), (extent / EXTENTS) as u8);
+    let rc = records
+        .saturating_sub(extent * EXTENT_RECORDS)
+        .min(EXTENT_RECORDS);
+    poke(machine, fcb.wrapping_add(RC), rc as u8);
+}
+
+// The command line as the CCP leaves it: the tail, upper cased, at 0x80
+// with its length in front, and the first two arguments parsed into the
+// FCBs at 0x5C and 0x6C. The tail is cut short so its 0 terminator still
+// fits below the program at 0x100.
+pub(super) fn set_command(machine: &mut SimpleMachine, tail: &str) {
+    let tail = tail.trim().to_ascii_uppercase();
+    let mut text = Vec::new();
+    if !tail.is_empty() {
+        text.push(b' ');
+        text.extend(tail.bytes().take(125));
+    }
+    machine.load(0x0080, &[text.len() as u8]);
+    machine.load(0x0081, &text);
+    machine.load(0x0081 + text.len() as u16, &[0]);
+
+    let mut args = tail.split_whitespace();
+    let mut fcbs = [0; 36];
+    for fcb in fcbs[..32].chunks_mut(16) {
+        fcb[1..12].fill(b' ');
+        let Some(arg) = args.next() else {
+            continue;
+        };
+        let arg = match arg.as_bytes() {
+            [drive @ b'A'..=b'P', b':', rest @ ..] => {
+                fcb[0] = drive - b'A' + 1;
+                rest
+            }
+            arg => arg,
+        };
+        let (name, ext) = match arg.iter().position(|&b| b == b'.') {
+            Some(dot) => (&arg[..dot], &arg[dot + 1..]),
+            None => (arg, &[][..]),
+        };
+        let (name_field, ext_field) = fcb[1..12].split_at_mut(8);
+        for (field, part) in [(name_field, name), (ext_field, ext)] {
+            for (i, &byte) in part.iter().take(field.len()).enumerate() {
+                if byte == b'*' {
+                    field[i..].fill(b'?');
+                    break;
+                }
+                field[i] = byte;
+            }
+        }
+    }
+    machine.load(0x005C, &fcbs);
+}
+
+// the 32 byte directory entry for one logical extent of a file
+fn directory_entry(entry: &Entry, extent: u32) -> [u8; 32] {
+    let mut bytes = [0; 32];
+    bytes[0] = entry.name.user;
+    bytes[1..9].copy_from_slice(&entry.name.name);
+    bytes[9..12].copy_from_slice(&entry.name.ext);
+    bytes[9] |= if entry.read_only { 0x80 } else { 0 };
+    bytes[10] |= if entry.system { 0x80 } else { 0 };
+    bytes[12] = (extent % EXTENTS) as u8;
+    bytes[14] = (extent / EXTENTS) as u8;
+    let records = entry
+        .records
+        .saturating_sub(extent * EXTENT_RECORDS)
+        .min(EXTENT_RECORDS);
+    bytes[15] = records as u8;
+    // one made up 1K block per 8 records, so block counts come out right
+    for (block, byte) in bytes[16..].iter_mut().enumerate() {
+        if (block as u32) * 8 < records {
+            *byte = block as u8 + 1;
+        }
+    }
+    bytes
+}
+
+impl Bdos {
+    // BDOS 13 to 40
+    pub(super) fn file_call(
+        &mut self,
+        machine: &mut SimpleMachine,
+        console: &mut dyn Console,
+    ) -> io::Result<Reply> {
+        let fcb = machine.cpu.de();
+        Ok(match machine.cpu.c {
+            // reset disk system
+            13 => {
+                self.dma = 0x0080;
+                self.disk = 0;
+                self.protected = 0;
+                Reply::Value(0)
+            }
+            // select disk
+            14 => {
+                let disk = machine.cpu.e & 0x0F;
+                if self.drives[disk as usize].is_none() {
+                    return self.error(machine, console, disk, Fault::Select);
+                }
+                self.disk = disk;
+                Reply::Value(0)
+            }
+            15 => self.open(machine, console, fcb)?,
+            // close
+            16 => match self.file(machine, fcb)? {
+                Some((disk, entry)) => {
+                    self.drives[disk].as_mut().unwrap().close(&entry.name)?;
+                    Reply::Value(0)
+                }
+                None => Reply::Value(0xFF),
+            },
+            17 => {
+                self.search = self.search(machine, fcb)?.into();
+                self.search_next(machine)
+            }
+            18 => self.search_next(machine),
+            19 => self.delete(machine, console, fcb)?,
+            20 | 21 | 33 | 34 | 40 => self.transfer(machine, console, fcb)?,
+            22 => self.make(machine, console, fcb)?,
+            23 => self.rename(machine, console, fcb)?,
+            // login vector
+            24 => Reply::Word(self.vector(|_| true)),
+            // current disk
+            25 => Reply::Value(self.disk),
+            // set DMA address
+            26 => {
+                self.dma = fcb;
+                Reply::Done
+            }
+            27 => Reply::Word(super::ALV),
+            // write protect disk
+            28 => {
+                self.protected |= 1 << self.disk;
+                Reply::Value(0)
+            }
+            // read-only vector
+            29 => Reply::Word(self.protected | self.vector(|drive| drive.read_only())),
+            // set file attributes
+            30 => {
+                let read_only = peek(machine, fcb.wrapping_add(9)) & 0x80 != 0;
+                let system = peek(machine, fcb.wrapping_add(10)) & 0x80 != 0;
+                match self.file(machine, fcb)? {
+                    Some((disk, entry)) if !self.unlocked(machine, disk, &entry, DELETE) => {
+                        self.error(machine, console, disk as u8, Fault::Password)?
+                    }
+                    Some((disk, entry)) => {
+                        self.drives[disk].as_mut().unwrap().set_attributes(
+                            &entry.name,
+                            read_only,
+                            system,
+                        )?;
+                        Reply::Value(0)
+                    }
+                    None => Reply::Value(0xFF),
+                }
+            }
+            31 => Reply::Word(super::DPB_ADDR),
+            // get or set user code
+            32 => {
+                if machine.cpu.e == 0xFF {
+                    Reply::Value(self.user)
+                } else {
+                    self.user = machine.cpu.e & 0x0F;
+                    Reply::Done
+                }
+            }
+            // compute file size
+            35 => match self.file(machine, fcb)? {
+                Some((_, entry)) => {
+                    for (offset, byte) in entry.records.to_le_bytes()[..3].iter().enumerate() {
+                        poke(machine, fcb.wrapping_add(R0 + offset as u16), *byte);
+                    }
+                    Reply::Value(0)
+                }
+                None => Reply::Value(0xFF),
+            },
+            // set random record
+            36 => {
+                let record = position(machine, fcb);
+                for (offset, byte) in record.to_le_bytes()[..3].iter().enumerate() {
+                    poke(machine, fcb.wrapping_add(R0 + offset as u16), *byte);
+                }
+                Reply::Done
+            }
+            _ => Reply::Value(0xFF),
+        })
+    }
+
+    // "Bdos Err On X: ..." and a warm boot, as CP/M 2.2 does, or as the
+    // CP/M 3 error mode says
+    pub(super) fn error(
+        &mut self,
+        machine: &mut SimpleMachine,
+        console: &mut dyn Console,
+        disk: u8,
+        fault: Fault,
+    ) -> io::Result<Reply> {
+        let drive = (b'A' + disk) as char;
+        let message = fault.message(self.version);
+        let text = match self.version {
+            Version::Cpm22 | Version::Mpm2 => {
+                format!("\r\nBdos Err On {}: {}\r\n", drive, message)
+            }
+            Version::Cpm3 => format!(
+                "\r\nCP/M Error On {}: {}\r\nBDOS Function = {}\r\n",
+                drive, message, machine.cpu.c
+            ),
+        };
+        if self.error_mode != 0xFF {
+            for char in text.bytes() {
+                console.put(char)?;
+            }
+        }
+        Ok(match self.error_mode {
+            0xFE | 0xFF => Reply::Word(u16::from_le_bytes([0xFF, fault as u8])),
+            _ => {
+                self.return_code = super::BDOS_ERROR;
+                Reply::WarmBoot
+            }
+        })
+    }
+
+    // whether the password at the DMA address, or the default one, lets
+    // `entry` be used in a way the modes in `protection` protect
+    pub(super) fn unlocked(
+        &self,
+        machine: &mut SimpleMachine,
+        disk: usize,
+        entry: &Entry,
+        protection: u8,
+    ) -> bool {
+        let Some(password) = self.passwords.get(&(disk, entry.name)) else {
+            return true;
+        };
+        let given: [u8; 8] = std::array::from_fn(|i| {
+            peek(machine, self.dma.wrapping_add(i as u16)).to_ascii_uppercase()
+        });
+        password.mode & protection == 0
+            || password.key == given
+            || password.key == self.default_password
+    }
+
+    // Sequential and random reads and writes, a multi-sector count of
+    // records at a time from the DMA address on. An error after the first
+    // record returns how many were transferred in H.
+    fn transfer(
+        &mut self,
+        machine: &mut SimpleMachine,
+        console: &mut dyn Console,
+        fcb: u16,
+    ) -> io::Result<Reply> {
+        let function = machine.cpu.c;
+        let sequential = function <= 21;
+        let dma = self.dma;
+        let mut reply = Reply::Value(0);
+        for count in 0..self.records {
+            let record = if sequential {
+                position(machine, fcb)
+            } else {
+                match self.random(machine, fcb) {
+                    Some(record) => record + count as u32,
+                    None => {
+                        reply = Reply::Value(6);
+                        break;
+                    }
+                }
+            };
+            self.dma = dma.wrapping_add(count as u16 * 128);
+            let result = match function {
+                20 | 33 => self.read_record(machine, fcb, record, sequential),
+                // write random with zero fill too: the host fills gaps anyway
+                _ => self.write_record(machine, console, fcb, record, sequential),
+            };
+            match result {
+                Ok(Reply::Value(0)) => {}
+                Ok(Reply::Value(code)) if count > 0 => {
+                    reply = Reply::Word(u16::from_le_bytes([code, count]));
+                    break;
+                }
+                result => {
+                    reply = result?;
+                    break;
+                }
+            }
+        }
+        self.dma = dma;
+        Ok(reply)
+    }
+
+    // a bit for each drive that is mounted and passes `test`
+    fn vector(&self, test: impl Fn(&dyn Drive) -> bool) -> u16 {
+        self.drives
+            .iter()
+            .enumerate()
+            .filter(|(_, drive)| drive.as_deref().is_some_and(&test))
+            .fold(0, |vector, (disk, _)| vector | 1 << disk)
+    }
+
+    // the FCB's drive, if it is mounted
+    pub(super) fn fcb_disk(&self, machine: &mut SimpleMachine, fcb: u16) -> Option<usize> {
+        let disk = match peek(machine, fcb) {
+            0 => self.disk as usize,
+            drive => drive as usize - 1,
+        };
+        self.drives.get(disk)?.as_ref().map(|_| disk)
+    }
+
+    // the FCB name with attribute bits stripped; '?' matches anything
+    fn pattern(machine: &mut SimpleMachine, fcb: u16) -> [u8; 11] {
+        std::array::from_fn(|i| peek(machine, fcb.wrapping_add(1 + i as u16)) & 0x7F)
+    }
+
+    fn matching(
+        &mut self,
+        machine: &mut SimpleMachine,
+        fcb: u16,
+    ) -> io::Result<Option<(usize, Vec<Entry>)>> {
+        let Some(disk) = self.fcb_disk(machine, fcb) else {
+            return Ok(None);
+        };
+        let pattern = Self::pattern(machine, fcb);
+        let user = self.user;
+        let entries = self.drives[disk].as_mut().unwrap().list()?;
+        let matches = entries
+            .into_iter()
+            .filter(|entry| {
+                let name = entry.name.name.iter().chain(&entry.name.ext);
+                entry.name.user == user
+                    && pattern.iter().zip(name).all(|(&p, &c)| p == b'?' || p == c)
+            })
+            .collect();
+        Ok(Some((disk, matches)))
+    }
+
+    // the one file an FCB names, wildcards taking the first match
+    pub(super) fn file(
+        &mut self,
+        machine: &mut SimpleMachine,
+        fcb: u16,
+    ) -> io::Result<Option<(usize, Entry)>> {
+        if let (Some(disk), Some(name)) = (self.fcb_disk(machine, fcb), self.name(machine, fcb, 0))
+        {
+            let entry = self.drives[disk].as_mut().unwrap().entry(&name)?;
+            return Ok(entry.map(|entry| (disk, entry)));
+        }
+        Ok(self
+            .matching(machine, fcb)?
+            .and_then(|(disk, entries)| Some((disk, *entries.first()?))))
+    }
+
+    // record numbers past what R0-R2 can address are error 6
+    fn random(&self, machine: &mut SimpleMachine, fcb: u16) -> Option<u32> {
+        let record = u32::from_le_bytes([
+            peek(machine, fcb.wrapping_add(R0)),
+            peek(machine, fcb.wrapping_add(R0 + 1)),
+            peek(machine, fcb.wrapping_add(R0 + 2)),
+            0,
+        ]);
+        (record < 0x10000).then_some(record)
+    }
+
+    fn open(
+        &mut self,
+        machine: &mut SimpleMachine,
+        console: &mut dyn Console,
+        fcb: u16,
+    ) -> io::Result<Reply> {
+        let Some((disk, entry)) = self.file(machine, fcb)? else {
+            return Ok(Reply::Value(0xFF));
+        };
+        // without the password for writing the file opens read only
+        if !self.unlocked(machine, disk, &entry, READ) {
+            return self.error(machine, console, disk as u8, Fault::Password);
+        }
+        if self.unlocked(machine, disk, &entry, WRITE) {
+            self.locked.remove(&(disk, entry.name));
+        } else {
+            self.locked.insert((disk, entry.name));
+        }
+        // the name as found, with its attributes, and the requested extent
+        let record = (peek(machine, fcb.wrapping_add(S2)) as u32 & 0x3F) * EXTENTS * EXTENT_RECORDS
+            + peek(machine, fcb.wrapping_add(EX)) as u32 * EXTENT_RECORDS;
+        let extent = record / EXTENT_RECORDS;
+        let bytes = directory_entry(&entry, extent);
+        for offset in 1..12 {
+            poke(machine, fcb.wrapping_add(offset), bytes[offset as usize]);
+        }
+        for offset in D0..CR {
+            poke(machine, fcb.wrapping_add(offset), bytes[offset as usize]);
+        }
+        let cr = peek(machine, fcb.wrapping_add(CR));
+        seek(machine, fcb, record, entry.records);
+        poke(machine, fcb.wrapping_add(CR), cr);
+        Ok(Reply::Value(0))
+    }
+
+    fn search(&mut self, machine: &mut SimpleMachine, fcb: u16) -> io::Result<Vec<[u8; 32]>> {
+        // DR = '?' finds every extent of every file in every user area
+        let all = peek(machine, fcb) == b'?';
+        let mut found = Vec::new();
+        if all {
+            let Some(drive) = self.drives[self.disk as usize].as_mut() else {
+                return Ok(found);
+            };
+            for entry in drive.list()? {
+                for extent in 0..entry.records.div_ceil(EXTENT_RECORDS).max(1) {
+                    found.push(directory_entry(&entry, extent));
+                }
+            }
+            return Ok(found);
+        }
+        let Some((_, entries)) = self.matching(machine, fcb)? else {
+            return Ok(found);
+        };
+        let ex = peek(machine, fcb.wrapping_add(EX));
+        for entry in entries {
+            for extent in 0..entry.records.div_ceil(EXTENT_RECORDS).max(1) {
+                if ex == b'?' || extent % EXTENTS == ex as u32 {
+                    found.push(directory_entry(&entry, extent));
+                }
+            }
+        }
+        Ok(found)
+    }
+
+    // the entry goes first in a directory record at the DMA address
+    fn search_next(&mut self, machine: &mut SimpleMachine) -> Reply {
+        let Some(entry) = self.search.pop_front() else {
+            return Reply::Value(0xFF);
+        };
+        for offset in 0..128u16 {
+            let byte = entry.get(offset as usize).copied().unwrap_or(0xE5);
+            poke(machine, self.dma.wrapping_add(offset), byte);
+        }
+        Reply::Value(0)
+    }
+
+    fn writable(
+        &mut self,
+        machine: &mut SimpleMachine,
+        console: &mut dyn Console,
+        disk: usize,
+    ) -> io::Result<Option<Reply>> {
+        let drive = self.drives[disk].as_ref().unwrap();
+        if drive.read_only() || self.protected & 1 << disk != 0 {
+            return self
+                .error(machine, console, disk as u8, Fault::ReadOnlyDisk)
+                .map(Some);
+        }
+        Ok(None)
+    }
+
+    fn delete(
+        &mut self,
+        machine: &mut SimpleMachine,
+        console: &mut dyn Console,
+        fcb: u16,
+    ) -> io::Result<Reply> {
+        let Some((disk, entries)) = self.matching(machine, fcb)? else {
+            return Ok(Reply::Value(0xFF));
+        };
+        if entries.is_empty() {
+            return Ok(Reply::Value(0xFF));
+        }
+        if let Some(reply) = self.writable(machine, console, disk)? {
+            return Ok(reply);
+        }
+        if entries.iter().any(|entry| entry.read_only) {
+            return self.error(machine, console, disk as u8, Fault::ReadOnlyFile);
+        }
+        if entries
+            .iter()
+            .any(|entry| !self.unlocked(machine, disk, entry, DELETE))
+        {
+            return self.error(machine, console, disk as u8, Fault::Password);
+        }
+        for entry in entries {
+            self.drives[disk].as_mut().unwrap().delete(&entry.name)?;
+            self.passwords.remove(&(disk, entry.name));
+        }
+        Ok(Reply::Value(0))
+    }
+
+    fn make(
+        &mut self,
+        machine: &mut SimpleMachine,
+        console: &mut dyn Console,
+        fcb: u16,
+    ) -> io::Result<Reply> {
+        let Some(disk) = self.fcb_disk(machine, fcb) else {
+            return Ok(Reply::Value(0xFF));
+        };
+        if let Some(reply) = self.writable(machine, console, disk)? {
+            return Ok(reply);
+        }
+        let Some(name) = self.name(machine, fcb, 0) else {
+            return Ok(Reply::Value(0xFF));
+        };
+        self.drives[disk].as_mut().unwrap().create(&name)?;
+        poke(machine, fcb.wrapping_add(RC), 0);
+        for offset in D0..CR {
+            poke(machine, fcb.wrapping_add(offset), 0);
+        }
+        Ok(Reply::Value(0))
+    }
+
+    fn rename(
+        &mut self,
+        machine: &mut SimpleMachine,
+        console: &mut dyn Console,
+        fcb: u16,
+    ) -> io::Result<Reply> {
+        let (Some((disk, entry)), Some(to)) =
+            (self.file(machine, fcb)?, self.name(machine, fcb, 16))
+        else {
+            return Ok(Reply::Value(0xFF));
+        };
+        if let Some(reply) = self.writable(machine, console, disk)? {
+            return Ok(reply);
+        }
+        if entry.read_only {
+            return self.error(machine, console, disk as u8, Fault::ReadOnlyFile);
+        }
+        if !self.unlocked(machine, disk, &entry, DELETE) {
+            return self.error(machine, console, disk as u8, Fault::Password);
+        }
+        let drive = self.drives[disk].as_mut().unwrap();
+        if drive.entry(&to)?.is_some() {
+            return Ok(Reply::Value(0xFF));
+        }
+        drive.rename(&entry.name, &to)?;
+        if let Some(password) = self.passwords.remove(&(disk, entry.name)) {
+            self.passwords.insert((disk, to), password);
+        }
+        Ok(Reply::Value(0))
+    }
+
+    // the name at `offset` in the FCB, in the current user area, if it
+    // is a valid one without wildcards
+    pub(super) fn name(
+        &self,
+        machine: &mut SimpleMachine,
+        fcb: u16,
+        offset: u16,
+    ) -> Option<FileName> {
+        let pattern = Self::pattern(machine, fcb.wrapping_add(offset));
+        let name = String::from_utf8_lossy(&pattern[..8])
+            .trim_end()
+            .to_string();
+        let ext = String::from_utf8_lossy(&pattern[8..])
+            .trim_end()
+            .to_string();
+        let mut name = FileName::parse(&format!("{}.{}", name, ext)).ok()?;
+        name.user = self.user;
+        Some(name)
+    }
+
+    // 0 with the record at the DMA address, 1 past the end of the file;
+    // sequential reads and writes move the FCB on to the next record
+    fn read_record(
+        &mut self,
+        machine: &mut SimpleMachine,
+        fcb: u16,
+        record: u32,
+        advance: bool,
+    ) -> io::Result<Reply> {
+        let Some((disk, entry)) = self.file(machine, fcb)? else {
+            return Ok(Reply::Value(0xFF));
+        };
+        let mut data = [0; 128];
+        if !self.drives[disk]
+            .as_mut()
+            .unwrap()
+            .read(&entry.name, record, &mut data)?
+        {
+            return Ok(Reply::Value(1));
+        }
+        for (offset, byte) in data.iter().enumerate() {
+            poke(machine, self.dma.wrapping_add(offset as u16), *byte);
+        }
+        seek(machine, fcb, record + advance as u32, entry.records);
+        Ok(Reply::Value(0))
+    }
+
+    fn write_record(
+        &mut self,
+        machine: &mut SimpleMachine,
+        console: &mut dyn Console,
+        fcb: u16,
+        record: u32,
+        advance: bool,
+    ) -> io::Result<Reply> {
+        let Some((disk, entry)) = self.file(machine, fcb)? else {
+            return Ok(Reply::Value(0xFF));
+        };
+        if let Some(reply) = self.writable(machine, console, disk)? {
+            return Ok(reply);
+        }
+        if entry.read_only {
+            return self.error(machine, console, disk as u8, Fault::ReadOnlyFile);
+        }
+        if self.locked.contains(&(disk, entry.name)) {
+            return self.error(machine, console, disk as u8, Fault::Password);
+        }
+        let data: [u8; 128] =
+            std::array::from_fn(|offset| peek(machine, self.dma.wrapping_add(offset as u16)));
+        self.drives[disk]
+            .as_mut()
+            .unwrap()
+            .write(&entry.name, record, &data)?;
+        let records = entry.records.max(record + 1);
+        seek(machine, fcb, record + advance as u32, records);
+        Ok(Reply::Value(0))
+    }
+}
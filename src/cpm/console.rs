@@ -0,0 +1,386 @@
+use std::io::{self, BufRead, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+use super::bdos::Console;
+use crate::devices::terminal::Terminal;
+
+// typed on the host keyboard to break into the emulator; the run stops
+// with io::ErrorKind::Interrupted
+pub const ESCAPE: u8 = 0x1D; // Ctrl-]
+
+enum Source {
+    // keys from a reader thread, so polling never blocks
+    Keyboard(Receiver<u8>),
+    // a pipe or file read as needed, with LF and CR LF line endings
+    // turned into the CR that CP/M expects
+    Script { reader: Box<dyn BufRead>, cr: bool },
+}
+
+// The host side of the CP/M console. Output is passed through untouched,
+// escape sequences and all, or drawn on an emulated terminal that is
+// redrawn on the host whenever the program waits for input.
+pub struct HostConsole<W: Write> {
+    source: Source,
+    output: W,
+    screen: Option<Terminal>,
+    _raw: Option<raw::RawMode>,
+}
+
+impl<W: Write> HostConsole<W> {
+    // Keys come from stdin, which is put in raw mode if it is a terminal
+    // so that Ctrl-C, Ctrl-S and Ctrl-Z reach the program instead of the
+    // host and nothing waits for a newline.
+    pub fn keyboard(output: W) -> Self {
+        let raw = raw::RawMode::enter();
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let mut stdin = io::stdin().lock();
+            let mut byte = [0];
+            while let Ok(1) = stdin.read(&mut byte) {
+                if sender.send(byte[0]).is_err() {
+                    break;
+                }
+            }
+        });
+        HostConsole {
+            source: Source::Keyboard(receiver),
+            output,
+            screen: None,
+            _raw: raw,
+        }
+    }
+
+    // scripted input from a pipe or file; the program ends with the input
+    pub fn script(input: impl BufRead + 'static, output: W) -> Self {
+        HostConsole {
+            source: Source::Script {
+                reader: Box::new(input),
+                cr: false,
+            },
+            output,
+            screen: None,
+            _raw: None,
+        }
+    }
+
+    pub fn with_screen(mut self, terminal: Terminal) -> Self {
+        self.screen = Some(terminal);
+        self
+    }
+
+    pub fn screen(&self) -> Option<&Terminal> {
+        self.screen.as_ref()
+    }
+
+    pub fn output(&self) -> &W {
+        &self.output
+    }
+
+    fn read(&mut self, wait: bool) -> io::Result<Option<u8>> {
+        let key = match &mut self.source {
+            Source::Keyboard(receiver) => {
+                let key = if wait {
+                    receiver.recv().ok()
+                } else {
+                    receiver.try_recv().ok()
+                };
+                if key == Some(ESCAPE) {
+                    return Err(io::Error::new(io::ErrorKind::Interrupted, "escape key"));
+                }
+                key
+            }
+            Source::Script { reader, cr } => loop {
+                let Some(&byte) = reader.fill_buf()?.first() else {
+                    break None;
+                };
+                reader.consume(1);
+                let last = std::mem::replace(cr, byte == b'\r');
+                match byte {
+                    b'\n' if last => continue,
+                    b'\n' => break Some(b'\r'),
+                    byte => break Some(byte),
+                }
+            },
+        };
+        Ok(key)
+    }
+}
+
+impl<W: Write> Console for HostConsole<W> {
+    fn put(&mut self, char: u8) -> io::Result<()> {
+        match &mut self.screen {
+            Some(terminal) => terminal.put(char),
+            None => self.output.write_all(&[char])?,
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if let Some(terminal) = &mut self.screen {
+            terminal.render(&mut self.output)?;
+        }
+        self.output.flush()
+    }
+
+    fn poll(&mut self) -> io::Result<Option<u8>> {
+        self.read(false)
+    }
+
+    fn get(&mut self) -> io::Result<Option<u8>> {
+        self.flush()?;
+        self.read(true)
+    }
+}
+
+// the termios layout and flag values of glibc and musl on x86_64 and
+// aarch64; other architectures differ and get the stub below
+#[cfg(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+mod raw {
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct Termios {
+        iflag: u32,
+        oflag: u32,
+        cflag: u32,
+        lflag: u32,
+        line: u8,
+        cc: [u8; 32],
+        ispeed: u32,
+        ospeed: u32,
+    }
+
+    unsafe extern "C" {
+        fn tcgetattr(fd: i32, termios: *mut Termios) -> i32;
+        fn tcsetattr(fd: i32, action: i32, termios: *const Termios) -> i32;
+    }
+
+    const ICRNL: u32 = 0o400;
+    const IXON: u32 = 0o2000;
+    const ISIG: u32 = 0o1;
+    const ICANON: u32 = 0o2;
+    const ECHO: u32 = 0o10;
+    const IEXTEN: u32 = 0o100000;
+    const VTIME: usize = 5;
+    const VMIN: usize = 6;
+    const TCSANOW: i32 = 0;
+
+    // the stdin settings to put back when the console goes away
+    pub struct RawMode(Termios);
+
+    impl RawMode {
+        // None if stdin is not a terminal
+        pub fn enter() -> Option<RawMode> {
+            let mut termios = std::mem::MaybeUninit::<Termios>::uninit();
+            // SAFETY: tcgetattr fills in the whole struct when it succeeds
+            let saved = unsafe {
+                if tcgetattr(0, termios.as_mut_ptr()) != 0 {
+                    return None;
+                }
+                termios.assume_init()
+            };
+            // output processing stays on, so a bare LF still starts a line
+            let mut raw = saved;
+            raw.iflag &= !(ICRNL | IXON);
+            raw.lflag &= !(ISIG | ICANON | ECHO | IEXTEN);
+            raw.cc[VMIN] = 1;
+            raw.cc[VTIME] = 0;
+            // SAFETY: raw is a valid termios read back from the terminal
+            if unsafe { tcsetattr(0, TCSANOW, &raw) } != 0 {
+                return None;
+            }
+            Some(RawMode(saved))
+        }
+    }
+
+    impl Drop for RawMode {
+        fn drop(&mut self) {
+            // SAFETY: restores the settings read in enter
+            unsafe {
+                tcsetattr(0, TCSANOW, &self.0);
+            }
+        }
+    }
+}
+
+// elsewhere keys arrive a line at a time, as the host terminal sends them
+#[cfg(not(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+)))]
+mod raw {
+    pub struct RawMode;
+
+    impl RawMode {
+        pub fn enter() -> Option<RawMode> {
+            None
+        }
+    }
+}
+
+// A console on a TCP port, for nc or telnet on the loopback interface.
+// Output made before anyone connects is kept for the first connection;
+// a read with nothing typed returns io::ErrorKind::WouldBlock, as does
+// one with nobody connected, so the program waits for a user.
+pub struct TcpConsole {
+    listener: TcpListener,
+    stream: Option<TcpStream>,
+    output: Vec<u8>,
+    // the last key was CR, so an LF after it is dropped
+    cr: bool,
+}
+
+impl TcpConsole {
+    // port 0 picks a free one; see `address`
+    pub fn bind(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", port))?;
+        listener.set_nonblocking(true)?;
+        Ok(TcpConsole {
+            listener,
+            stream: None,
+            output: Vec::new(),
+            cr: false,
+        })
+    }
+
+    pub fn address(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.stream.is_some()
+    }
+
+    fn accept(&mut self) -> io::Result<()> {
+        if self.stream.is_none() {
+            match self.listener.accept() {
+                Ok((stream, _)) => {
+                    stream.set_nonblocking(true)?;
+                    stream.set_nodelay(true)?;
+                    self.stream = Some(stream);
+                    self.cr = false;
+                }
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
+    }
+
+    fn read(&mut self) -> io::Result<Option<u8>> {
+        self.accept()?;
+        let Some(stream) = &mut self.stream else {
+            return Ok(None);
+        };
+        let mut byte = [0];
+        loop {
+            match stream.read(&mut byte) {
+                // the user hung up; the next one gets the console
+                Ok(0) => {
+                    self.stream = None;
+                    return Ok(None);
+                }
+                Ok(_) => {}
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => return Ok(None),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(_) => {
+                    self.stream = None;
+                    return Ok(None);
+                }
+            }
+            let last = std::mem::replace(&mut self.cr, byte[0] == b'\r');
+            match byte[0] {
+                b'\n' if last => continue,
+                b'\n' => return Ok(Some(b'\r')),
+                key => return Ok(Some(key)),
+            }
+        }
+    }
+}
+
+impl Console for TcpConsole {
+    fn put(&mut self, char: u8) -> io::Result<()> {
+        self.output.push(char);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.accept()?;
+        let Some(stream) = &mut self.stream else {
+            return Ok(());
+        };
+        while !self.output.is_empty() {
+            match stream.write(&self.output) {
+                Ok(0) => break,
+                Ok(written) => {
+                    self.output.drain(..written);
+                }
+                // the rest goes with the next flush
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(_) => {
+                    self.stream = None;
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn poll(&mut self) -> io::Result<Option<u8>> {
+        self.read()
+    }
+
+    fn get(&mut self) -> io::Result<Option<u8>> {
+        self.flush()?;
+        match self.read()? {
+            Some(key) => Ok(Some(key)),
+            None => Err(io::ErrorKind::WouldBlock.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_line_endings() {
+        let mut console = HostConsole::script(&b"a\nb\r\nc\rd"[..], Vec::new());
+        let mut keys = Vec::new();
+        while let Some(key) = console.get().unwrap() {
+            keys.push(key);
+        }
+        assert_eq!(keys, b"a\rb\rc\rd");
+        assert_eq!(console.poll().unwrap(), None);
+    }
+
+    #[test]
+    fn test_tcp_console() {
+        let mut console = TcpConsole::bind(0).unwrap();
+        console.put(b'>').unwrap();
+        assert!(matches!(console.get(), Err(err) if err.kind() == io::ErrorKind::WouldBlock));
+
+        let mut client = TcpStream::connect(console.address().unwrap()).unwrap();
+        client.write_all(b"a\r\nb\n").unwrap();
+        let mut keys = Vec::new();
+        while keys.len() < 3 {
+            match console.get() {
+                Ok(Some(key)) => keys.push(key),
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => thread::yield_now(),
+                result => panic!("{:?}", result),
+            }
+        }
+        assert_eq!(keys, b"a\rb");
+        assert!(console.is_connected());
+
+        // output from before the connection arrives with it
+        let mut prompt = [0];
+        client.read_exact(&mut prompt).unwrap();
+        assert_eq!(prompt, *b">");
+    }
+}
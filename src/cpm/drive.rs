@@ -0,0 +1,49 @@
+use std::io;
+use std::time::SystemTime;
+
+use super::fs::FileName;
+
+// a file as the drive lists it
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Entry {
+    pub name: FileName,
+    // 128 byte records, counting a partial last one
+    pub records: u32,
+    pub read_only: bool,
+    pub system: bool,
+    // for CP/M 3 date stamps, where the drive keeps them
+    pub created: Option<SystemTime>,
+    pub modified: Option<SystemTime>,
+}
+
+// What the BDOS needs from a drive: files by name, read and written a
+// 128 byte record at a time. A missing file is io::ErrorKind::NotFound.
+pub trait Drive {
+    // every file, in every user area
+    fn list(&mut self) -> io::Result<Vec<Entry>>;
+
+    fn entry(&mut self, name: &FileName) -> io::Result<Option<Entry>> {
+        Ok(self.list()?.into_iter().find(|entry| entry.name == *name))
+    }
+
+    // false past the end of the file; a partial last record is padded
+    // with Ctrl-Z
+    fn read(&mut self, name: &FileName, record: u32, data: &mut [u8; 128]) -> io::Result<bool>;
+    fn write(&mut self, name: &FileName, record: u32, data: &[u8; 128]) -> io::Result<()>;
+
+    // an empty file, replacing any file of that name
+    fn create(&mut self, name: &FileName) -> io::Result<()>;
+    fn delete(&mut self, name: &FileName) -> io::Result<()>;
+    fn rename(&mut self, from: &FileName, to: &FileName) -> io::Result<()>;
+    fn set_attributes(&mut self, name: &FileName, read_only: bool, system: bool) -> io::Result<()>;
+
+    // the program is done with the file for now
+    fn close(&mut self, _name: &FileName) -> io::Result<()> {
+        Ok(())
+    }
+
+    // the whole drive is write protected
+    fn read_only(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,317 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::io;
+
+use super::bdos::{Bdos, Console, Status};
+use crate::cpu::Cycles;
+use crate::loader::Image;
+use crate::machine::SimpleMachine;
+use crate::regex::{self, Regex};
+
+// how long an expect step waits unless a timeout step says otherwise: ten
+// seconds at 2 MHz
+pub const DEFAULT_TIMEOUT: Cycles = 20_000_000;
+
+// how often the output is looked at while waiting for a match
+const SLICE: Cycles = 10_000;
+
+#[derive(Clone, Debug)]
+pub enum Step {
+    // queue keys for the program
+    Send(Vec<u8>),
+    // run until the output since the last match matches
+    Expect(Regex),
+    // cycles the following expect steps may take
+    Timeout(Cycles),
+}
+
+// why an expect step failed
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Reason {
+    Timeout(Cycles),
+    // the program is waiting for a key and the script has sent none
+    Waiting,
+    Exited,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Syntax {
+        line: usize,
+        message: String,
+    },
+    Failed {
+        step: usize,
+        pattern: String,
+        reason: Reason,
+        transcript: Vec<u8>,
+    },
+    Io(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+            Error::Failed {
+                step,
+                pattern,
+                reason,
+                transcript,
+            } => {
+                write!(f, "step {}: /{}/ not seen: ", step, pattern)?;
+                match reason {
+                    Reason::Timeout(cycles) => write!(f, "timed out after {} cycles", cycles)?,
+                    Reason::Waiting => write!(f, "program is waiting for input")?,
+                    Reason::Exited => write!(f, "program exited")?,
+                }
+                write!(f, "\n--- transcript ---\n{}", printable(transcript))
+            }
+            Error::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+// Send/expect automation for a CP/M program, built step by step or parsed
+// from a script file with one step per line:
+//
+//     # comment
+//     timeout 5000000
+//     expect ^A>
+//     send DIR\r
+//
+// Sent text takes \r \n \t \e \\ and \xNN escapes; expect takes a regex.
+#[derive(Clone, Debug, Default)]
+pub struct Script {
+    pub steps: Vec<Step>,
+}
+
+impl Script {
+    pub fn new() -> Self {
+        Script::default()
+    }
+
+    pub fn send(mut self, text: impl AsRef<[u8]>) -> Self {
+        self.steps.push(Step::Send(text.as_ref().to_vec()));
+        self
+    }
+
+    pub fn expect(mut self, pattern: &str) -> std::result::Result<Self, regex::Error> {
+        self.steps.push(Step::Expect(Regex::new(pattern)?));
+        Ok(self)
+    }
+
+    pub fn timeout(mut self, cycles: Cycles) -> Self {
+        self.steps.push(Step::Timeout(cycles));
+        self
+    }
+
+    pub fn parse(text: &str) -> Result<Script> {
+        let mut script = Script::new();
+        for (index, line) in text.lines().enumerate() {
+            let syntax = |message: String| Error::Syntax {
+                line: index + 1,
+                message,
+            };
+            if line.trim().is_empty() || line.trim_start().starts_with('#') {
+                continue;
+            }
+            let (command, operand) = line.split_once(' ').unwrap_or((line, ""));
+            let step = match command {
+                "send" => Step::Send(unescape(operand).map_err(syntax)?),
+                "expect" => {
+                    Step::Expect(Regex::new(operand).map_err(|err| syntax(err.to_string()))?)
+                }
+                "timeout" => Step::Timeout(
+                    operand
+                        .trim()
+                        .parse()
+                        .map_err(|_| syntax(format!("invalid cycle count {}", operand)))?,
+                ),
+                _ => return Err(syntax(format!("unknown step {}", command))),
+            };
+            script.steps.push(step);
+        }
+        Ok(script)
+    }
+}
+
+fn unescape(text: &str) -> std::result::Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    let mut rest = text.as_bytes();
+    while let [byte, tail @ ..] = rest {
+        rest = tail;
+        if *byte != b'\\' {
+            bytes.push(*byte);
+            continue;
+        }
+        let [escape, tail @ ..] = rest else {
+            return Err("trailing \\".to_string());
+        };
+        rest = tail;
+        bytes.push(match escape {
+            b'r' => b'\r',
+            b'n' => b'\n',
+            b't' => b'\t',
+            b'e' => 0x1B,
+            b'\\' => b'\\',
+            b'x' => {
+                let value = rest
+                    .get(..2)
+                    .and_then(|digits| std::str::from_utf8(digits).ok())
+                    .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                    .ok_or_else(|| "invalid \\x escape".to_string())?;
+                rest = &rest[2..];
+                value
+            }
+            other => return Err(format!("unknown escape \\{}", *other as char)),
+        });
+    }
+    Ok(bytes)
+}
+
+// CR LF and LF as line breaks, other control characters as ^X
+fn printable(transcript: &[u8]) -> String {
+    let mut text = String::new();
+    let mut bytes = transcript.iter().peekable();
+    while let Some(&byte) = bytes.next() {
+        match byte {
+            b'\r' if bytes.peek() == Some(&&b'\n') => {}
+            b'\n' | b' '..=b'~' => text.push(byte as char),
+            0x00..=0x1F => {
+                text.push('^');
+                text.push((byte + b'@') as char);
+            }
+            _ => text.push_str(&format!("\\x{:02X}", byte)),
+        }
+    }
+    text
+}
+
+// keys come from send steps; a read with none queued would block
+#[derive(Default)]
+struct Automation {
+    keys: VecDeque<u8>,
+    transcript: Vec<u8>,
+}
+
+impl Console for Automation {
+    fn put(&mut self, char: u8) -> io::Result<()> {
+        self.transcript.push(char);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn poll(&mut self) -> io::Result<Option<u8>> {
+        Ok(self.keys.pop_front())
+    }
+
+    fn get(&mut self) -> io::Result<Option<u8>> {
+        match self.keys.pop_front() {
+            Some(key) => Ok(Some(key)),
+            None => Err(io::ErrorKind::WouldBlock.into()),
+        }
+    }
+}
+
+// Run `image` under `script` and return everything the program printed.
+// After the last step the program runs on until it exits or has used up
+// the sent keys, within the timeout.
+pub fn run(machine: &mut SimpleMachine, image: &Image, script: &Script) -> Result<Vec<u8>> {
+    let mut bdos = Bdos::boot(machine, image);
+    run_session(machine, &mut bdos, script)
+}
+
+// the same for a program already booted, with its peripherals attached
+pub fn run_session(
+    machine: &mut SimpleMachine,
+    bdos: &mut Bdos,
+    script: &Script,
+) -> Result<Vec<u8>> {
+    let mut console = Automation::default();
+    let mut timeout = DEFAULT_TIMEOUT;
+    let mut status = Status::Running;
+    // output before this has been matched
+    let mut mark = 0;
+
+    for (index, step) in script.steps.iter().enumerate() {
+        match step {
+            Step::Send(text) => console.keys.extend(text),
+            Step::Timeout(cycles) => timeout = *cycles,
+            Step::Expect(regex) => {
+                let deadline = bdos.cycles().saturating_add(timeout);
+                loop {
+                    if let Some(found) = regex.find(&console.transcript[mark..]) {
+                        mark += found.end;
+                        break;
+                    }
+                    let reason = match status {
+                        Status::Exited => Some(Reason::Exited),
+                        Status::Waiting if console.keys.is_empty() => Some(Reason::Waiting),
+                        _ if bdos.cycles() >= deadline => Some(Reason::Timeout(timeout)),
+                        _ => None,
+                    };
+                    if let Some(reason) = reason {
+                        return Err(Error::Failed {
+                            step: index + 1,
+                            pattern: regex.as_str().to_string(),
+                            reason,
+                            transcript: console.transcript,
+                        });
+                    }
+                    let budget = SLICE.min(deadline - bdos.cycles());
+                    status = bdos.run(machine, &mut console, budget)?;
+                }
+            }
+        }
+    }
+
+    let deadline = bdos.cycles().saturating_add(timeout);
+    while bdos.cycles() < deadline {
+        match status {
+            Status::Exited => break,
+            Status::Waiting if console.keys.is_empty() => break,
+            _ => status = bdos.run(machine, &mut console, deadline - bdos.cycles())?,
+        }
+    }
+    Ok(console.transcript)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let script =
+            Script::parse("# log in\ntimeout 100\nexpect ^A>$\nsend dir\\r\\x03\n\n").unwrap();
+        match &script.steps[..] {
+            [Step::Timeout(100), Step::Expect(regex), Step::Send(keys)] => {
+                assert_eq!(regex.as_str(), "^A>$");
+                assert_eq!(keys, b"dir\r\x03");
+            }
+            steps => panic!("{:?}", steps),
+        }
+
+        for text in ["sent x", "send \\q", "timeout soon", "expect (a"] {
+            assert!(
+                matches!(Script::parse(text), Err(Error::Syntax { line: 1, .. })),
+                "{}",
+                text
+            );
+        }
+    }
+}
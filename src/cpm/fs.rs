@@ -0,0 +1,460 @@
+use std::fmt;
+
+use crate::disk::diskdef::Os;
+use crate::disk::{self, Volume};
+
+const EMPTY: u8 = 0xE5;
+const ENTRY_SIZE: usize = 32;
+// records per logical extent
+const EXTENT_RECORDS: usize = 128;
+
+#[derive(Debug)]
+pub enum Error {
+    Disk(disk::Error),
+    NotFound(String),
+    InvalidName(String),
+    DirectoryFull,
+    DiskFull,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Disk(err) => write!(f, "{}", err),
+            Error::NotFound(name) => write!(f, "{}: file not found", name),
+            Error::InvalidName(name) => write!(f, "{}: invalid file name", name),
+            Error::DirectoryFull => write!(f, "directory full"),
+            Error::DiskFull => write!(f, "disk full"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<disk::Error> for Error {
+    fn from(err: disk::Error) -> Self {
+        Error::Disk(err)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+// a file name as stored in the directory: user number, 8 + 3 characters
+// padded with spaces
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct FileName {
+    pub user: u8,
+    pub name: [u8; 8],
+    pub ext: [u8; 3],
+}
+
+impl FileName {
+    // "[user:]NAME[.EXT]", case insensitive; user defaults to 0
+    pub fn parse(text: &str) -> Result<FileName> {
+        let invalid = || Error::InvalidName(text.to_string());
+        let (user, file) = match text.split_once(':') {
+            Some((user, file)) => (user.parse::<u8>().map_err(|_| invalid())?, file),
+            None => (0, text),
+        };
+        let (name, ext) = file.split_once('.').unwrap_or((file, ""));
+        let valid = |part: &str, len: usize| {
+            part.len() <= len
+                && part
+                    .bytes()
+                    .all(|b| b.is_ascii_graphic() && !b"<>.,;:=?*[]_%|()/\\".contains(&b))
+        };
+        if user > 15 || name.is_empty() || !valid(name, 8) || !valid(ext, 3) {
+            return Err(invalid());
+        }
+
+        let mut result = FileName {
+            user,
+            name: [b' '; 8],
+            ext: [b' '; 3],
+        };
+        result.name[..name.len()].copy_from_slice(name.to_ascii_uppercase().as_bytes());
+        result.ext[..ext.len()].copy_from_slice(ext.to_ascii_uppercase().as_bytes());
+        Ok(result)
+    }
+
+    // name and extension without padding or the user number
+    pub fn file_name(&self) -> String {
+        let name = String::from_utf8_lossy(&self.name).trim_end().to_string();
+        let ext = String::from_utf8_lossy(&self.ext).trim_end().to_string();
+        if ext.is_empty() {
+            name
+        } else {
+            format!("{}.{}", name, ext)
+        }
+    }
+
+    fn matches(&self, entry: &[u8]) -> bool {
+        entry[0] == self.user
+            && entry[1..9]
+                .iter()
+                .zip(&self.name)
+                .all(|(a, b)| a & 0x7F == *b)
+            && entry[9..12]
+                .iter()
+                .zip(&self.ext)
+                .all(|(a, b)| a & 0x7F == *b)
+    }
+}
+
+impl fmt::Display for FileName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.user, self.file_name())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FileInfo {
+    pub name: FileName,
+    pub size: usize,
+    pub read_only: bool,
+    pub system: bool,
+}
+
+// a CP/M 2.2 or 3 file system: the directory sits in the first blocks of
+// the data area and each 32 byte entry maps up to 16K per logical extent
+pub struct FileSystem {
+    volume: Volume,
+    directory: Vec<u8>,
+}
+
+impl FileSystem {
+    pub fn open(mut volume: Volume) -> Result<Self> {
+        let mut directory = Vec::new();
+        for block in 0..volume.def().directory_blocks() {
+            directory.extend(volume.read_block(block)?);
+        }
+        directory.truncate(volume.def().maxdir * ENTRY_SIZE);
+        Ok(FileSystem { volume, directory })
+    }
+
+    // empty the directory; data blocks are left as they are
+    pub fn format(volume: Volume) -> Result<Self> {
+        let directory = vec![EMPTY; volume.def().maxdir * ENTRY_SIZE];
+        let mut fs = FileSystem { volume, directory };
+        fs.write_directory()?;
+        Ok(fs)
+    }
+
+    pub fn volume(&mut self) -> &mut Volume {
+        &mut self.volume
+    }
+
+    pub fn into_volume(self) -> Volume {
+        self.volume
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        Ok(self.volume.flush()?)
+    }
+
+    fn entries(&self) -> impl Iterator<Item = (usize, &[u8])> {
+        self.directory
+            .chunks(ENTRY_SIZE)
+            .enumerate()
+            .filter(|(_, entry)| entry[0] <= 15)
+    }
+
+    fn wide_blocks(&self) -> bool {
+        self.volume.def().blocks() > 256
+    }
+
+    fn exm(&self) -> usize {
+        self.volume.def().dpb().exm as usize
+    }
+
+    // the block pointers in order, 0 where nothing is allocated
+    fn block_list(&self, entry: &[u8]) -> Vec<usize> {
+        let pointers = &entry[16..32];
+        if self.wide_blocks() {
+            pointers
+                .chunks(2)
+                .map(|b| u16::from_le_bytes([b[0], b[1]]) as usize)
+                .collect()
+        } else {
+            pointers.iter().map(|&b| b as usize).collect()
+        }
+    }
+
+    // logical extent number of the last extent an entry covers
+    fn extent(entry: &[u8]) -> usize {
+        (entry[14] as usize & 0x3F) << 5 | (entry[12] as usize & 0x1F)
+    }
+
+    pub fn list(&self) -> Vec<FileInfo> {
+        let mut files: Vec<FileInfo> = Vec::new();
+        for (_, entry) in self.entries() {
+            let name = FileName {
+                user: entry[0],
+                name: std::array::from_fn(|i| entry[1 + i] & 0x7F),
+                ext: std::array::from_fn(|i| entry[9 + i] & 0x7F),
+            };
+            let size = self.entry_size(entry);
+            match files.iter_mut().find(|f| f.name == name) {
+                Some(file) => file.size = file.size.max(size),
+                None => files.push(FileInfo {
+                    name,
+                    size,
+                    read_only: entry[9] & 0x80 != 0,
+                    system: entry[10] & 0x80 != 0,
+                }),
+            }
+        }
+        files.sort_by_key(|f| f.name);
+        files
+    }
+
+    // file length up to and including the extent this entry ends
+    fn entry_size(&self, entry: &[u8]) -> usize {
+        let records = Self::extent(entry) * EXTENT_RECORDS + entry[15] as usize;
+        let last = entry[13] as usize;
+        if self.volume.def().os == Os::Cpm3 && last != 0 && records > 0 {
+            (records - 1) * 128 + last
+        } else {
+            records * 128
+        }
+    }
+
+    pub fn read(&mut self, name: &FileName) -> Result<Vec<u8>> {
+        let mut entries: Vec<Vec<u8>> = self
+            .entries()
+            .filter(|(_, entry)| name.matches(entry))
+            .map(|(_, entry)| entry.to_vec())
+            .collect();
+        if entries.is_empty() {
+            return Err(Error::NotFound(name.to_string()));
+        }
+        entries.sort_by_key(|entry| Self::extent(entry));
+
+        // each block goes where its extent and pointer put it, so holes
+        // left by random writes and missing extents read as zeros
+        let size = self.entry_size(entries.last().unwrap());
+        let block_size = self.volume.def().blocksize;
+        let mut data = vec![0; size];
+        for entry in &entries {
+            let base = (Self::extent(entry) & !self.exm()) * EXTENT_RECORDS * 128;
+            for (i, block) in self.block_list(entry).into_iter().enumerate() {
+                let start = base + i * block_size;
+                if block == 0 || start >= size {
+                    continue;
+                }
+                let contents = self.volume.read_block(block)?;
+                let end = (start + block_size).min(size);
+                data[start..end].copy_from_slice(&contents[..end - start]);
+            }
+        }
+        Ok(data)
+    }
+
+    pub fn remove(&mut self, name: &FileName) -> Result<()> {
+        for index in self.indices(name)? {
+            self.directory[index * ENTRY_SIZE] = EMPTY;
+        }
+        self.write_directory()
+    }
+
+    // give every extent of `from` the name `to`, keeping its attributes
+    pub fn rename(&mut self, from: &FileName, to: &FileName) -> Result<()> {
+        let indices = self.indices(from)?;
+        for index in indices {
+            let entry = &mut self.directory[index * ENTRY_SIZE..(index + 1) * ENTRY_SIZE];
+            entry[0] = to.user;
+            for (byte, &char) in entry[1..12].iter_mut().zip(to.name.iter().chain(&to.ext)) {
+                *byte = (*byte & 0x80) | char;
+            }
+        }
+        self.write_directory()
+    }
+
+    // the attribute bits are the high bits of the first two extension bytes
+    pub fn set_attributes(&mut self, name: &FileName, read_only: bool, system: bool) -> Result<()> {
+        let indices = self.indices(name)?;
+        for index in indices {
+            let entry = &mut self.directory[index * ENTRY_SIZE..(index + 1) * ENTRY_SIZE];
+            entry[9] = (entry[9] & 0x7F) | if read_only { 0x80 } else { 0 };
+            entry[10] = (entry[10] & 0x7F) | if system { 0x80 } else { 0 };
+        }
+        self.write_directory()
+    }
+
+    fn indices(&self, name: &FileName) -> Result<Vec<usize>> {
+        let indices: Vec<usize> = self
+            .entries()
+            .filter(|(_, entry)| name.matches(entry))
+            .map(|(index, _)| index)
+            .collect();
+        if indices.is_empty() {
+            return Err(Error::NotFound(name.to_string()));
+        }
+        Ok(indices)
+    }
+
+    // create or replace a file
+    pub fn write(&mut self, name: &FileName, data: &[u8]) -> Result<()> {
+        let def = self.volume.def().clone();
+        let block_size = def.blocksize;
+        let per_entry = if self.wide_blocks() { 8 } else { 16 };
+        let records = data.len().div_ceil(128);
+        let blocks_needed = data.len().div_ceil(block_size);
+        let entries_needed = blocks_needed.div_ceil(per_entry).max(1);
+
+        // free the old copy first so its space can be reused
+        let mut directory = self.directory.clone();
+        for (index, entry) in self.entries() {
+            if name.matches(entry) {
+                directory[index * ENTRY_SIZE] = EMPTY;
+            }
+        }
+
+        let mut used = vec![false; def.blocks()];
+        used[..def.directory_blocks()].fill(true);
+        for entry in directory.chunks(ENTRY_SIZE).filter(|e| e[0] <= 15) {
+            for block in self.block_list(entry).into_iter().filter(|&b| b != 0) {
+                if let Some(slot) = used.get_mut(block) {
+                    *slot = true;
+                }
+            }
+        }
+        let blocks: Vec<usize> = (0..used.len())
+            .filter(|&b| !used[b])
+            .take(blocks_needed)
+            .collect();
+        if blocks.len() < blocks_needed {
+            return Err(Error::DiskFull);
+        }
+        let slots: Vec<usize> = directory
+            .chunks(ENTRY_SIZE)
+            .enumerate()
+            .filter(|(_, entry)| entry[0] == EMPTY)
+            .map(|(index, _)| index)
+            .take(entries_needed)
+            .collect();
+        if slots.len() < entries_needed {
+            return Err(Error::DirectoryFull);
+        }
+
+        for (n, &block) in blocks.iter().enumerate() {
+            let start = n * block_size;
+            let end = (start + block_size).min(data.len());
+            let mut contents = data[start..end].to_vec();
+            // pad the last record the way CP/M text files end
+            contents.resize(contents.len().div_ceil(128) * 128, 0x1A);
+            self.volume.write_block(block, &contents)?;
+        }
+
+        let entry_records = per_entry * def.records_per_block();
+        for (i, &slot) in slots.iter().enumerate() {
+            let first = i * entry_records;
+            let count = records.saturating_sub(first).min(entry_records);
+            // extent of the last record in this entry, and the records used
+            // in that extent
+            let extent = i * (self.exm() + 1) + count.saturating_sub(1) / EXTENT_RECORDS;
+            let rc = count - count.saturating_sub(1) / EXTENT_RECORDS * EXTENT_RECORDS;
+
+            let entry = &mut directory[slot * ENTRY_SIZE..(slot + 1) * ENTRY_SIZE];
+            entry.fill(0);
+            entry[0] = name.user;
+            entry[1..9].copy_from_slice(&name.name);
+            entry[9..12].copy_from_slice(&name.ext);
+            entry[12] = (extent & 0x1F) as u8;
+            entry[14] = (extent >> 5) as u8;
+            entry[15] = rc as u8;
+            if def.os == Os::Cpm3 && i == slots.len() - 1 {
+                entry[13] = (data.len() % 128) as u8;
+            }
+            let pointers = blocks.iter().skip(i * per_entry).take(per_entry);
+            for (n, &block) in pointers.enumerate() {
+                if per_entry == 8 {
+                    entry[16 + n * 2..18 + n * 2].copy_from_slice(&(block as u16).to_le_bytes());
+                } else {
+                    entry[16 + n] = block as u8;
+                }
+            }
+        }
+
+        self.directory = directory;
+        self.write_directory()
+    }
+
+    fn write_directory(&mut self) -> Result<()> {
+        let block_size = self.volume.def().blocksize;
+        let mut contents = self.directory.clone();
+        contents.resize(self.volume.def().directory_blocks() * block_size, EMPTY);
+        for (block, data) in contents.chunks(block_size).enumerate() {
+            self.volume.write_block(block, data)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::disk::{DiskDef, RawDisk};
+
+    fn blank(name: &str) -> FileSystem {
+        let def = DiskDef::builtin(name).unwrap();
+        let disk = RawDisk::new(def.geometry());
+        FileSystem::format(Volume::new(def, Box::new(disk))).unwrap()
+    }
+
+    #[test]
+    fn test_names() {
+        let name = FileName::parse("3:pip.com").unwrap();
+        assert_eq!((name.user, &name.name, &name.ext), (3, b"PIP     ", b"COM"));
+        assert_eq!(name.to_string(), "3:PIP.COM");
+        assert!(FileName::parse("toolongname.com").is_err());
+        assert!(FileName::parse("16:a").is_err());
+        assert!(FileName::parse("a*.com").is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut fs = blank("ibm-3740");
+        let big: Vec<u8> = (0..40_000).map(|i| i as u8).collect();
+        let big_name = FileName::parse("big.dat").unwrap();
+        let small_name = FileName::parse("1:small").unwrap();
+        fs.write(&big_name, &big).unwrap();
+        fs.write(&small_name, b"hello").unwrap();
+
+        let mut fs = FileSystem::open(fs.into_volume()).unwrap();
+        let files = fs.list();
+        assert_eq!(files.len(), 2);
+        assert_eq!((files[0].name, files[0].size), (big_name, 40_064));
+        assert_eq!(&fs.read(&big_name).unwrap()[..40_000], &big[..]);
+        assert_eq!(fs.read(&small_name).unwrap()[..6], *b"hello\x1A");
+
+        // space is reused after removal
+        fs.remove(&big_name).unwrap();
+        for n in 0..6 {
+            let name = FileName::parse(&format!("f{}", n)).unwrap();
+            fs.write(&name, &big).unwrap();
+        }
+        assert!(matches!(fs.write(&big_name, &big), Err(Error::DiskFull)));
+    }
+
+    #[test]
+    fn test_sparse() {
+        let mut fs = blank("ibm-3740");
+        let name = FileName::parse("sparse.dat").unwrap();
+        let data: Vec<u8> = (0..20 * 1024).map(|i| (i / 1024) as u8 + 1).collect();
+        fs.write(&name, &data).unwrap();
+
+        // a hole in the second block, and no first extent at all
+        let indices = fs.indices(&name).unwrap();
+        fs.directory[indices[0] * ENTRY_SIZE + 17] = 0;
+        let read = fs.read(&name).unwrap();
+        assert_eq!(
+            (read[1023], read[1024], read[2047], read[2048]),
+            (1, 0, 0, 3)
+        );
+        fs.directory[indices[0] * ENTRY_SIZE] = EMPTY;
+        let read = fs.read(&name).unwrap();
+        assert_eq!(read.len(), data.len());
+        assert_eq!((read[16 * 1024 - 1], read[16 * 1024]), (0, 17));
+    }
+}
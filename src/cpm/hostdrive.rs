@@ -0,0 +1,284 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File, Metadata, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+use super::drive::{Drive, Entry};
+use super::fs::FileName;
+
+// A host directory as a CP/M drive. User 0 is the directory itself and
+// users 1 to 15 are subdirectories named by number. Host files show up
+// when their names fit 8.3, upper cased; new files get lower case names.
+// The read-only attribute is the host's; there is nowhere to keep the
+// system attribute, so it is always clear.
+pub struct HostDrive {
+    root: PathBuf,
+    read_only: bool,
+    // the last file read or written, and whether it was opened for writing
+    open: Option<(PathBuf, File, bool)>,
+    // names already looked up, so reading and writing records does not
+    // scan the directory each time; forgotten on close and when the
+    // directory changes
+    paths: BTreeMap<FileName, PathBuf>,
+}
+
+impl HostDrive {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        HostDrive {
+            root: root.into(),
+            read_only: false,
+            open: None,
+            paths: BTreeMap::new(),
+        }
+    }
+
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
+    fn directory(&self, user: u8) -> PathBuf {
+        if user == 0 {
+            self.root.clone()
+        } else {
+            self.root.join(user.to_string())
+        }
+    }
+
+    // the files of one user area that have CP/M names, in name order; of
+    // names that only differ in case the first one wins
+    fn files(&self, user: u8) -> io::Result<Vec<(FileName, PathBuf, Metadata)>> {
+        let entries = match fs::read_dir(self.directory(user)) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err),
+        };
+        let mut files = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            let host_name = entry.file_name();
+            let Some(host_name) = host_name.to_str().filter(|name| !name.contains(':')) else {
+                continue;
+            };
+            if let (true, Ok(mut name)) = (metadata.is_file(), FileName::parse(host_name)) {
+                name.user = user;
+                files.push((name, entry.path(), metadata));
+            }
+        }
+        files.sort_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+        files.dedup_by(|a, b| a.0 == b.0);
+        Ok(files)
+    }
+
+    fn find(&self, name: &FileName) -> io::Result<(PathBuf, Metadata)> {
+        self.files(name.user)?
+            .into_iter()
+            .find(|(found, _, _)| found == name)
+            .map(|(_, path, metadata)| (path, metadata))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, name.to_string()))
+    }
+
+    fn path(&mut self, name: &FileName) -> io::Result<PathBuf> {
+        if let Some(path) = self.paths.get(name) {
+            return Ok(path.clone());
+        }
+        let (path, _) = self.find(name)?;
+        self.paths.insert(*name, path.clone());
+        Ok(path)
+    }
+
+    fn file(&mut self, name: &FileName, write: bool) -> io::Result<&mut File> {
+        let path = self.path(name)?;
+        let reuse = matches!(&self.open, Some((open, _, writable)) if *open == path && (*writable || !write));
+        if !reuse {
+            let file = OpenOptions::new().read(true).write(write).open(&path)?;
+            self.open = Some((path, file, write));
+        }
+        Ok(&mut self.open.as_mut().unwrap().1)
+    }
+}
+
+fn entry(name: FileName, metadata: &Metadata) -> Entry {
+    Entry {
+        name,
+        records: metadata.len().div_ceil(128) as u32,
+        read_only: metadata.permissions().readonly(),
+        system: false,
+        created: metadata.created().ok(),
+        modified: metadata.modified().ok(),
+    }
+}
+
+impl Drive for HostDrive {
+    fn list(&mut self) -> io::Result<Vec<Entry>> {
+        let mut entries = Vec::new();
+        for user in 0..16 {
+            for (name, _, metadata) in self.files(user)? {
+                entries.push(entry(name, &metadata));
+            }
+        }
+        Ok(entries)
+    }
+
+    fn entry(&mut self, name: &FileName) -> io::Result<Option<Entry>> {
+        match self.path(name).and_then(fs::metadata) {
+            Ok(metadata) => Ok(Some(entry(*name, &metadata))),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn read(&mut self, name: &FileName, record: u32, data: &mut [u8; 128]) -> io::Result<bool> {
+        let file = self.file(name, false)?;
+        file.seek(SeekFrom::Start(record as u64 * 128))?;
+        let mut len = 0;
+        while len < data.len() {
+            match file.read(&mut data[len..])? {
+                0 => break,
+                n => len += n,
+            }
+        }
+        data[len..].fill(0x1A);
+        Ok(len > 0)
+    }
+
+    fn write(&mut self, name: &FileName, record: u32, data: &[u8; 128]) -> io::Result<()> {
+        let file = self.file(name, true)?;
+        file.seek(SeekFrom::Start(record as u64 * 128))?;
+        file.write_all(data)
+    }
+
+    fn create(&mut self, name: &FileName) -> io::Result<()> {
+        let path = match self.find(name) {
+            Ok((path, _)) => path,
+            Err(_) => {
+                let directory = self.directory(name.user);
+                fs::create_dir_all(&directory)?;
+                directory.join(name.file_name().to_ascii_lowercase())
+            }
+        };
+        self.open = None;
+        self.paths.clear();
+        File::create(path)?;
+        Ok(())
+    }
+
+    fn delete(&mut self, name: &FileName) -> io::Result<()> {
+        let (path, _) = self.find(name)?;
+        self.open = None;
+        self.paths.clear();
+        fs::remove_file(path)
+    }
+
+    fn rename(&mut self, from: &FileName, to: &FileName) -> io::Result<()> {
+        let (path, _) = self.find(from)?;
+        self.open = None;
+        self.paths.clear();
+        let directory = self.directory(to.user);
+        fs::create_dir_all(&directory)?;
+        fs::rename(path, directory.join(to.file_name().to_ascii_lowercase()))
+    }
+
+    fn set_attributes(
+        &mut self,
+        name: &FileName,
+        read_only: bool,
+        _system: bool,
+    ) -> io::Result<()> {
+        let (path, metadata) = self.find(name)?;
+        let mut permissions = metadata.permissions();
+        // clearing R/O gives write access back to the owner only;
+        // set_readonly(false) would make the file writable by everyone
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = permissions.mode();
+            permissions.set_mode(if read_only {
+                mode & !0o222
+            } else {
+                mode | 0o200
+            });
+        }
+        #[cfg(not(unix))]
+        permissions.set_readonly(read_only);
+        fs::set_permissions(path, permissions)
+    }
+
+    fn close(&mut self, name: &FileName) -> io::Result<()> {
+        self.open = None;
+        self.paths.remove(name);
+        Ok(())
+    }
+
+    fn read_only(&self) -> bool {
+        self.read_only
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TempDir(PathBuf);
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_host_drive() {
+        let root = std::env::temp_dir().join(format!("remu-hostdrive-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let _dir = TempDir(root.clone());
+        fs::create_dir_all(root.join("3")).unwrap();
+        fs::write(root.join("Hello.txt"), [b'h'; 200]).unwrap();
+        fs::write(root.join("much too long.txt"), b"x").unwrap();
+        fs::write(root.join("3").join("data"), b"").unwrap();
+
+        let mut drive = HostDrive::new(&root);
+        let names: Vec<_> = drive
+            .list()
+            .unwrap()
+            .iter()
+            .map(|e| (e.name.to_string(), e.records))
+            .collect();
+        assert_eq!(
+            names,
+            [("0:HELLO.TXT".to_string(), 2), ("3:DATA".to_string(), 0)]
+        );
+
+        // the partial second record is padded, and a third is past the end
+        let hello = FileName::parse("hello.txt").unwrap();
+        let mut record = [0; 128];
+        assert!(drive.read(&hello, 1, &mut record).unwrap());
+        assert_eq!(record[71..73], [b'h', 0x1A]);
+        assert!(!drive.read(&hello, 2, &mut record).unwrap());
+
+        // new files are written a whole record at a time
+        let new = FileName::parse("5:NEW.COM").unwrap();
+        drive.create(&new).unwrap();
+        drive.write(&new, 1, &[0xC9; 128]).unwrap();
+        drive.close(&new).unwrap();
+        assert_eq!(fs::read(root.join("5").join("new.com")).unwrap().len(), 256);
+
+        drive.set_attributes(&hello, true, false).unwrap();
+        assert!(drive.entry(&hello).unwrap().unwrap().read_only);
+        drive.set_attributes(&hello, false, false).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(root.join("Hello.txt"))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o222, 0o200);
+        }
+        drive
+            .rename(&hello, &FileName::parse("BYE.TXT").unwrap())
+            .unwrap();
+        assert!(root.join("bye.txt").exists());
+        assert!(drive.entry(&hello).unwrap().is_none());
+    }
+}
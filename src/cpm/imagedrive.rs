@@ -0,0 +1,211 @@
+use std::collections::BTreeMap;
+use std::io;
+
+use super::drive::{Drive, Entry};
+use super::fs::{self, FileName, FileSystem};
+use crate::disk;
+
+// A CP/M disk image as a drive. A file is read into memory the first time
+// it is used, and written back to the image when it is closed or renamed
+// and when the drive goes away. The directory changes at once.
+pub struct ImageDrive {
+    fs: FileSystem,
+    read_only: bool,
+    // open files, and whether they were written to
+    open: BTreeMap<FileName, (Vec<u8>, bool)>,
+}
+
+fn io_error(err: fs::Error) -> io::Error {
+    match err {
+        fs::Error::NotFound(name) => io::Error::new(io::ErrorKind::NotFound, name),
+        fs::Error::Disk(disk::Error::Io(err)) => err,
+        err => io::Error::other(err),
+    }
+}
+
+impl ImageDrive {
+    pub fn new(mut fs: FileSystem) -> Self {
+        let read_only = fs.volume().disk().write_protected();
+        ImageDrive {
+            fs,
+            read_only,
+            open: BTreeMap::new(),
+        }
+    }
+
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
+    // the file system with every open file written back
+    pub fn file_system(&mut self) -> io::Result<&mut FileSystem> {
+        self.save_all()?;
+        Ok(&mut self.fs)
+    }
+
+    fn contents(&mut self, name: &FileName) -> io::Result<&mut (Vec<u8>, bool)> {
+        if !self.open.contains_key(name) {
+            let data = self.fs.read(name).map_err(io_error)?;
+            self.open.insert(*name, (data, false));
+        }
+        Ok(self.open.get_mut(name).unwrap())
+    }
+
+    // write a changed file back, keeping its attributes
+    fn save(&mut self, name: &FileName) -> io::Result<()> {
+        let Some((data, true)) = self.open.get(name) else {
+            return Ok(());
+        };
+        let attributes = self
+            .fs
+            .list()
+            .into_iter()
+            .find(|file| file.name == *name)
+            .map(|file| (file.read_only, file.system));
+        self.fs.write(name, data).map_err(io_error)?;
+        if let Some((read_only, system)) = attributes.filter(|&(r, s)| r || s) {
+            self.fs
+                .set_attributes(name, read_only, system)
+                .map_err(io_error)?;
+        }
+        self.open.get_mut(name).unwrap().1 = false;
+        self.fs.flush().map_err(io_error)
+    }
+
+    fn save_all(&mut self) -> io::Result<()> {
+        let names: Vec<FileName> = self.open.keys().copied().collect();
+        for name in names {
+            self.save(&name)?;
+        }
+        Ok(())
+    }
+}
+
+impl Drive for ImageDrive {
+    fn list(&mut self) -> io::Result<Vec<Entry>> {
+        let files = self.fs.list();
+        Ok(files
+            .into_iter()
+            .map(|file| {
+                // files being written are as long as the program has made them
+                let size = match self.open.get(&file.name) {
+                    Some((data, true)) => data.len(),
+                    _ => file.size,
+                };
+                Entry {
+                    name: file.name,
+                    records: size.div_ceil(128) as u32,
+                    read_only: file.read_only,
+                    system: file.system,
+                    created: None,
+                    modified: None,
+                }
+            })
+            .collect())
+    }
+
+    fn read(&mut self, name: &FileName, record: u32, data: &mut [u8; 128]) -> io::Result<bool> {
+        let (contents, _) = self.contents(name)?;
+        let start = record as usize * 128;
+        let Some(rest) = contents.get(start..).filter(|rest| !rest.is_empty()) else {
+            return Ok(false);
+        };
+        let len = rest.len().min(128);
+        data[..len].copy_from_slice(&rest[..len]);
+        data[len..].fill(0x1A);
+        Ok(true)
+    }
+
+    fn write(&mut self, name: &FileName, record: u32, data: &[u8; 128]) -> io::Result<()> {
+        let (contents, dirty) = self.contents(name)?;
+        let start = record as usize * 128;
+        if contents.len() < start + 128 {
+            contents.resize(start + 128, 0x1A);
+        }
+        contents[start..start + 128].copy_from_slice(data);
+        *dirty = true;
+        Ok(())
+    }
+
+    fn create(&mut self, name: &FileName) -> io::Result<()> {
+        self.open.remove(name);
+        self.fs.write(name, &[]).map_err(io_error)?;
+        self.fs.flush().map_err(io_error)
+    }
+
+    fn delete(&mut self, name: &FileName) -> io::Result<()> {
+        self.open.remove(name);
+        self.fs.remove(name).map_err(io_error)?;
+        self.fs.flush().map_err(io_error)
+    }
+
+    fn rename(&mut self, from: &FileName, to: &FileName) -> io::Result<()> {
+        self.save(from)?;
+        self.open.remove(from);
+        self.fs.rename(from, to).map_err(io_error)?;
+        self.fs.flush().map_err(io_error)
+    }
+
+    fn set_attributes(&mut self, name: &FileName, read_only: bool, system: bool) -> io::Result<()> {
+        self.save(name)?;
+        self.fs
+            .set_attributes(name, read_only, system)
+            .map_err(io_error)?;
+        self.fs.flush().map_err(io_error)
+    }
+
+    fn close(&mut self, name: &FileName) -> io::Result<()> {
+        self.save(name)?;
+        self.open.remove(name);
+        Ok(())
+    }
+
+    fn read_only(&self) -> bool {
+        self.read_only
+    }
+}
+
+// a program that ends without closing its files still gets them saved
+impl Drop for ImageDrive {
+    fn drop(&mut self) {
+        let _ = self.save_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::disk::{DiskDef, RawDisk, Volume};
+
+    #[test]
+    fn test_image_drive() {
+        let def = DiskDef::builtin("ibm-3740").unwrap();
+        let disk = RawDisk::new(def.geometry());
+        let mut drive =
+            ImageDrive::new(FileSystem::format(Volume::new(def, Box::new(disk))).unwrap());
+        let name = FileName::parse("data.txt").unwrap();
+        let renamed = FileName::parse("2:done.txt").unwrap();
+
+        drive.create(&name).unwrap();
+        for record in 0..130 {
+            drive.write(&name, record, &[record as u8; 128]).unwrap();
+        }
+        // the program sees what it wrote before the file is closed
+        assert_eq!(drive.entry(&name).unwrap().unwrap().records, 130);
+        drive.close(&name).unwrap();
+        drive.set_attributes(&name, true, false).unwrap();
+        drive.rename(&name, &renamed).unwrap();
+
+        let fs = drive.file_system().unwrap();
+        let files = fs.list();
+        assert_eq!((files[0].name, files[0].read_only), (renamed, true));
+        let data = fs.read(&renamed).unwrap();
+        assert_eq!((data.len(), data[129 * 128]), (130 * 128, 129));
+
+        let mut record = [0; 128];
+        assert!(drive.read(&renamed, 129, &mut record).unwrap());
+        assert!(!drive.read(&renamed, 130, &mut record).unwrap());
+        let err = drive.read(&name, 0, &mut record).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,197 @@
+use std::io;
+
+use super::bdos::{self, Bdos, Console, Status};
+use crate::cpu::{Cpu, Cycles};
+use crate::loader::Image;
+use crate::machine::SimpleMachine;
+
+// The clock interrupt is RST 1. Its vector in every bank is EI; RET, and
+// the dispatcher switches processes while the interrupted one is there.
+const RST: u8 = 0xCF;
+const VECTOR: u16 = 0x0008;
+
+// 60 Hz at 2 MHz
+pub const DEFAULT_TICK: Cycles = 2_000_000 / 60;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum State {
+    Ready,
+    // for a key; tried again on the next tick
+    Waiting,
+    // until this tick
+    Sleeping(u64),
+    Exited,
+}
+
+struct Process {
+    bdos: Bdos,
+    console: Box<dyn Console>,
+    // the registers while another process has the processor
+    cpu: Cpu,
+    bank: usize,
+    state: State,
+}
+
+// An MP/M II system: a process per console, each running `image` in its
+// own bank below the common memory. The processor goes round robin to
+// the processes that are ready. The clock interrupt takes it from one
+// that has had a whole tick, unless that process has interrupts
+// disabled. Waiting for a key, a delay, a dispatch call and exiting give
+// it up too.
+pub struct Mpm {
+    processes: Vec<Process>,
+    // the process whose registers and bank are in the machine
+    current: Option<usize>,
+    // where the search for the next ready process starts
+    turn: usize,
+    tick: Cycles,
+    ticks: u64,
+    cycles: Cycles,
+    next_tick: Cycles,
+}
+
+impl Mpm {
+    pub fn boot(
+        machine: &mut SimpleMachine,
+        image: &Image,
+        consoles: Vec<Box<dyn Console>>,
+    ) -> Mpm {
+        machine.set_banks(consoles.len().max(1), bdos::COMMON);
+        let mut processes = Vec::new();
+        for (bank, console) in consoles.into_iter().enumerate() {
+            machine.select_bank(bank);
+            let bdos = Bdos::boot_mpm(machine, image, bank as u8);
+            machine.load(VECTOR, &[0xFB, 0xC9]); // EI; RET
+            // a stack in the process's own bank that returns to the warm
+            // boot, and interrupts on
+            let stack = bdos::COMMON - 2;
+            machine.load(stack, &[0, 0]);
+            let mut cpu = Cpu::new();
+            cpu.pc = machine.cpu.pc;
+            cpu.sp = stack;
+            cpu.iff = true;
+            processes.push(Process {
+                bdos,
+                console,
+                cpu,
+                bank,
+                state: State::Ready,
+            });
+        }
+        Mpm {
+            processes,
+            current: None,
+            turn: 0,
+            tick: DEFAULT_TICK,
+            ticks: 0,
+            cycles: 0,
+            next_tick: DEFAULT_TICK,
+        }
+    }
+
+    // cycles between clock interrupts
+    pub fn set_tick(&mut self, tick: Cycles) {
+        self.tick = tick;
+        self.next_tick = self.cycles + tick;
+    }
+
+    // the session of the process on console `console`, to mount drives
+    pub fn bdos(&mut self, console: usize) -> &mut Bdos {
+        &mut self.processes[console].bdos
+    }
+
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    pub fn cycles(&self) -> Cycles {
+        self.cycles
+    }
+
+    // Run for at least `budget` cycles. Waiting means every process that
+    // is left waits for a key; time stands still until the next run.
+    pub fn run(&mut self, machine: &mut SimpleMachine, budget: Cycles) -> io::Result<Status> {
+        let end = self.cycles.saturating_add(budget);
+        self.wake();
+        while self.cycles < end {
+            let Some(index) = self.next_ready() else {
+                if self.processes.iter().all(|p| p.state == State::Exited) {
+                    return Ok(Status::Exited);
+                }
+                if !self
+                    .processes
+                    .iter()
+                    .any(|p| matches!(p.state, State::Sleeping(_)))
+                {
+                    return Ok(Status::Waiting);
+                }
+                // idle until the clock wakes someone
+                self.cycles = self.cycles.max(self.next_tick);
+                self.clock();
+                continue;
+            };
+            self.switch(machine, index);
+
+            let slice = self.next_tick.saturating_sub(self.cycles).max(1);
+            let process = &mut self.processes[index];
+            let before = process.bdos.cycles();
+            let status = process.bdos.run(machine, process.console.as_mut(), slice)?;
+            self.cycles += process.bdos.cycles() - before;
+            match status {
+                Status::Running => {}
+                Status::Waiting => process.state = State::Waiting,
+                Status::Delayed(0) => self.turn = index + 1,
+                Status::Delayed(ticks) => {
+                    process.state = State::Sleeping(self.ticks + ticks as u64)
+                }
+                Status::Exited => process.state = State::Exited,
+            }
+
+            if self.cycles >= self.next_tick {
+                self.clock();
+                if self.processes[index].state == State::Ready
+                    && let Some(cycles) = machine.interrupt(&[RST])
+                {
+                    self.cycles += cycles;
+                    self.turn = index + 1;
+                }
+            }
+        }
+        Ok(Status::Running)
+    }
+
+    fn clock(&mut self) {
+        self.ticks += 1;
+        self.next_tick += self.tick;
+        self.wake();
+    }
+
+    fn wake(&mut self) {
+        for process in &mut self.processes {
+            match process.state {
+                State::Waiting => process.state = State::Ready,
+                State::Sleeping(until) if until <= self.ticks => process.state = State::Ready,
+                _ => {}
+            }
+        }
+    }
+
+    fn next_ready(&self) -> Option<usize> {
+        let count = self.processes.len();
+        (0..count)
+            .map(|offset| (self.turn + offset) % count)
+            .find(|&index| self.processes[index].state == State::Ready)
+    }
+
+    fn switch(&mut self, machine: &mut SimpleMachine, index: usize) {
+        if self.current == Some(index) {
+            return;
+        }
+        if let Some(current) = self.current {
+            self.processes[current].cpu = machine.cpu;
+        }
+        machine.select_bank(self.processes[index].bank);
+        machine.cpu = self.processes[index].cpu;
+        self.current = Some(index);
+    }
+}
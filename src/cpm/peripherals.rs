@@ -0,0 +1,148 @@
+use std::io::{self, Read, Write};
+
+// the physical devices the IOBYTE can assign
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Physical {
+    Tty,
+    Crt,
+    Bat,
+    Uc1,
+    Ptr,
+    Ur1,
+    Ur2,
+    Ptp,
+    Up1,
+    Up2,
+    Lpt,
+    Ul1,
+}
+
+const NAMES: [(&str, Physical); 12] = [
+    ("TTY", Physical::Tty),
+    ("CRT", Physical::Crt),
+    ("BAT", Physical::Bat),
+    ("UC1", Physical::Uc1),
+    ("PTR", Physical::Ptr),
+    ("UR1", Physical::Ur1),
+    ("UR2", Physical::Ur2),
+    ("PTP", Physical::Ptp),
+    ("UP1", Physical::Up1),
+    ("UP2", Physical::Up2),
+    ("LPT", Physical::Lpt),
+    ("UL1", Physical::Ul1),
+];
+
+impl Physical {
+    // "LPT" or "lpt:"
+    pub fn parse(name: &str) -> Option<Physical> {
+        let name = name.strip_suffix(':').unwrap_or(name);
+        NAMES
+            .iter()
+            .find(|(known, _)| known.eq_ignore_ascii_case(name))
+            .map(|&(_, device)| device)
+    }
+
+    // the devices that only ever feed RDR:
+    pub fn is_input(self) -> bool {
+        matches!(self, Physical::Ptr | Physical::Ur1 | Physical::Ur2)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Logical {
+    Con,
+    Rdr,
+    Pun,
+    Lst,
+}
+
+// CON: = CRT:, RDR: = PTR:, PUN: = PTP:, LST: = LPT:
+pub const DEFAULT_IOBYTE: u8 = 0x95;
+
+impl Logical {
+    // the physical device `iobyte` assigns, from its two-bit field
+    pub fn assigned(self, iobyte: u8) -> Physical {
+        use Physical::*;
+        let (shift, choices) = match self {
+            Logical::Con => (0, [Tty, Crt, Bat, Uc1]),
+            Logical::Rdr => (2, [Tty, Ptr, Ur1, Ur2]),
+            Logical::Pun => (4, [Tty, Ptp, Up1, Up2]),
+            Logical::Lst => (6, [Tty, Crt, Lpt, Ul1]),
+        };
+        choices[(iobyte >> shift) as usize & 3]
+    }
+}
+
+// Host files and pipes behind the physical devices. TTY: and CRT: are the
+// console and are not attached here; other devices discard output and
+// read as end of file (Ctrl-Z) until something is attached.
+#[derive(Default)]
+pub struct Peripherals {
+    outputs: Vec<(Physical, Box<dyn Write>)>,
+    inputs: Vec<(Physical, Box<dyn Read>)>,
+}
+
+impl Peripherals {
+    pub fn new() -> Self {
+        Peripherals::default()
+    }
+
+    pub fn attach_output(&mut self, device: Physical, output: impl Write + 'static) {
+        self.outputs.retain(|(attached, _)| *attached != device);
+        self.outputs.push((device, Box::new(output)));
+    }
+
+    pub fn attach_input(&mut self, device: Physical, input: impl Read + 'static) {
+        self.inputs.retain(|(attached, _)| *attached != device);
+        self.inputs.push((device, Box::new(input)));
+    }
+
+    pub fn write(&mut self, device: Physical, byte: u8) -> io::Result<()> {
+        match self
+            .outputs
+            .iter_mut()
+            .find(|(attached, _)| *attached == device)
+        {
+            Some((_, output)) => output.write_all(&[byte]),
+            None => Ok(()),
+        }
+    }
+
+    pub fn read(&mut self, device: Physical) -> io::Result<u8> {
+        let mut byte = [0];
+        let read = match self
+            .inputs
+            .iter_mut()
+            .find(|(attached, _)| *attached == device)
+        {
+            Some((_, input)) => input.read(&mut byte)?,
+            None => 0,
+        };
+        // the end of the file, or no file at all
+        Ok(if read == 1 { byte[0] } else { 0x1A })
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        for (_, output) in &mut self.outputs {
+            output.flush()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iobyte() {
+        assert_eq!(Logical::Con.assigned(DEFAULT_IOBYTE), Physical::Crt);
+        assert_eq!(Logical::Rdr.assigned(DEFAULT_IOBYTE), Physical::Ptr);
+        assert_eq!(Logical::Pun.assigned(DEFAULT_IOBYTE), Physical::Ptp);
+        assert_eq!(Logical::Lst.assigned(DEFAULT_IOBYTE), Physical::Lpt);
+        assert_eq!(Logical::Lst.assigned(0xC0), Physical::Ul1);
+        assert_eq!(Logical::Rdr.assigned(0x08), Physical::Ur1);
+        assert_eq!(Physical::parse("up2:"), Some(Physical::Up2));
+        assert_eq!(Physical::parse("PRN"), None);
+    }
+}
@@ -1,4 +1,11 @@
-use crate::bus::Bus;
+use std::fmt;
+
+use crate::bus::{Access, Bus};
+
+pub mod cache;
+mod table;
+
+use table::{Handler, Handlers};
 
 pub type Cycles = u64;
 
@@ -8,12 +15,15 @@ pub enum State {
     Halted,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+// Flags are kept as the values they derive from and only worked out when
+// read, most results are overwritten before anything tests them. Equality
+// and Debug go by the flag byte.
+#[derive(Clone, Copy)]
 pub struct Flags {
-    zero: bool,
-    sign: bool,
-    parity: bool,
-    aux_carry: bool,
+    zero: u8,   // Z when zero
+    sign: u8,   // S is bit 7
+    parity: u8, // P when of even parity
+    aux: u8,    // AC is bit 4
     carry: bool,
 }
 
@@ -31,19 +41,15 @@ pub struct Cpu {
     pub pc: u16,
     pub state: State,
     pub iff: bool,
+    inta: [u8; 3],
+    inta_len: u8,
 }
 
 impl Cpu {
     pub fn new() -> Self {
         Cpu {
             a: 0,
-            flags: Flags {
-                zero: false,
-                sign: false,
-                parity: false,
-                aux_carry: false,
-                carry: false,
-            },
+            flags: Flags::from_byte(0x02),
             b: 0,
             c: 0,
             d: 0,
@@ -54,524 +60,93 @@ impl Cpu {
             pc: 0,
             state: State::Running,
             iff: true,
+            inta: [0; 3],
+            inta_len: 0,
         }
     }
 
-    pub fn step(&mut self, bus: &mut dyn Bus) -> Cycles {
+    // interrupt acknowledge: the instruction is supplied by the interrupting
+    // device instead of memory, and PC is not advanced while it is fetched
+    pub fn interrupt<B: Bus + ?Sized>(
+        &mut self,
+        bus: &mut B,
+        instruction: &[u8],
+    ) -> Option<Cycles> {
+        if !self.iff {
+            return None;
+        }
+        assert!(
+            (1..=3).contains(&instruction.len()),
+            "interrupt instruction must be 1 to 3 bytes"
+        );
+
+        self.iff = false;
+        self.state = State::Running;
+
+        self.inta[..instruction.len()].copy_from_slice(instruction);
+        self.inta_len = instruction.len() as u8;
+        let cycles = self.step(bus);
+        self.inta_len = 0;
+
+        Some(cycles)
+    }
+
+    // generic so a concrete bus gets its memory access inlined; see step_dyn
+    pub fn step<B: Bus + ?Sized>(&mut self, bus: &mut B) -> Cycles {
         if self.state == State::Halted {
             return 4;
         }
 
-        let opcode = self.fetch_byte(bus);
-
-        match opcode {
-            // NOP
-            0x00 | 0x08 | 0x10 | 0x18 | 0x20 | 0x28 | 0x30 | 0x38 => 4,
-
-            // HALT
-            0x76 => {
-                self.state = State::Halted;
-                7
-            }
-
-            // LD r,r'
-            0x40..=0x7F => {
-                let src = opcode & 0x07;
-                let dest = (opcode >> 3) & 0x07;
-
-                let value = self.reg(src, bus);
-                self.set_reg(dest, value, bus);
-
-                if src == 0x06 || dest == 0x06 { 7 } else { 5 }
-            }
-
-            // LD r,n
-            0x06 | 0x0E | 0x16 | 0x1E | 0x26 | 0x2E | 0x36 | 0x3E => {
-                let dest = (opcode >> 3) & 0x07;
-
-                let value = self.fetch_byte(bus);
-                self.set_reg(dest, value, bus);
-
-                if dest == 0x06 { 10 } else { 7 }
-            }
-
-            // LD A,(BC)
-            0x0A => {
-                self.a = bus.read(self.bc());
-                7
-            }
-            // LD A,(DE)
-            0x1A => {
-                self.a = bus.read(self.de());
-                7
-            }
-            // LD A,(nn)
-            0x3A => {
-                self.a = bus.read(self.fetch_word(bus));
-                13
-            }
-
-            // LD (BC),A
-            0x02 => {
-                bus.write(self.bc(), self.a);
-                7
-            }
-            // LD (DE),A
-            0x12 => {
-                bus.write(self.de(), self.a);
-                7
-            }
-            // LD (nn),A
-            0x32 => {
-                bus.write(self.fetch_word(bus), self.a);
-                13
-            }
-
-            // LD BC,nn
-            0x01 => {
-                let value = self.fetch_word(bus);
-                self.set_bc(value);
-                10
-            }
-            // LD DE,nn
-            0x11 => {
-                let value = self.fetch_word(bus);
-                self.set_de(value);
-                10
-            }
-            // LD HL,nn
-            0x21 => {
-                let value = self.fetch_word(bus);
-                self.set_hl(value);
-                10
-            }
-            // LD SP,nn
-            0x31 => {
-                let value = self.fetch_word(bus);
-                self.sp = value;
-                10
-            }
-
-            // LD SP,HL
-            0xF9 => {
-                self.sp = self.hl();
-                6
-            }
-            // LD HL,(nn)
-            0x2A => {
-                let addr = self.fetch_word(bus);
-                let value = bus.read_word(addr);
-                self.set_hl(value);
-                16
-            }
-            // LD (nn),HL
-            0x22 => {
-                let addr = self.fetch_word(bus);
-                let value = self.hl();
-                bus.write_word(addr, value);
-                16
-            }
-
-            // EX (SP),HL
-            0xE3 => {
-                let hl_old = self.hl();
-                let sp_old = bus.read_word(self.sp);
-                self.set_hl(sp_old);
-                bus.write_word(self.sp, hl_old);
-                18
-            }
-            // EX DE,HL
-            0xEB => {
-                let de_old = self.de();
-                let hl_old = self.hl();
-                self.set_de(hl_old);
-                self.set_hl(de_old);
-                5
-            }
-
-            // PUSH BC
-            0xC5 => {
-                self.op_push(bus, self.bc());
-                11
-            }
-            // PUSH DE
-            0xD5 => {
-                self.op_push(bus, self.de());
-                11
-            }
-            // PUSH HL
-            0xE5 => {
-                self.op_push(bus, self.hl());
-                11
-            }
-            // PUSH AF
-            0xF5 => {
-                self.op_push(bus, self.af());
-                11
-            }
-
-            // POP BC
-            0xC1 => {
-                let value = self.op_pop(bus);
-                self.set_bc(value);
-                10
-            }
-            // POP DE
-            0xD1 => {
-                let value = self.op_pop(bus);
-                self.set_de(value);
-                10
-            }
-            // POP HL
-            0xE1 => {
-                let value = self.op_pop(bus);
-                self.set_hl(value);
-                10
-            }
-            // POP AF
-            0xF1 => {
-                let value = self.op_pop(bus);
-                self.set_af(value);
-                10
-            }
-
-            // ADD A,r
-            0x80..=0x87 => {
-                let src = opcode & 0x07;
-                let value = self.reg(src, bus);
-                self.op_add(value);
-                if src == 0x06 { 7 } else { 4 }
-            }
-            // ADC A,r
-            0x88..=0x8F => {
-                let src = opcode & 0x07;
-                let value = self.reg(src, bus);
-                self.op_adc(value);
-                if src == 0x06 { 7 } else { 4 }
-            }
-            // SUB A,r
-            0x90..=0x97 => {
-                let src = opcode & 0x07;
-                let value = self.reg(src, bus);
-                self.op_sub(value);
-                if src == 0x06 { 7 } else { 4 }
-            }
-            // SBC A,r
-            0x98..=0x9F => {
-                let src = opcode & 0x07;
-                let value = self.reg(src, bus);
-                self.op_sbc(value);
-                if src == 0x06 { 7 } else { 4 }
-            }
-            // AND A,r
-            0xA0..=0xA7 => {
-                let src = opcode & 0x07;
-                let value = self.reg(src, bus);
-                self.op_and(value);
-                if src == 0x06 { 7 } else { 4 }
-            }
-            // OR A,r
-            0xB0..=0xB7 => {
-                let src = opcode & 0x07;
-                let value = self.reg(src, bus);
-                self.op_or(value);
-                if src == 0x06 { 7 } else { 4 }
-            }
-            // XOR A,r
-            0xA8..=0xAF => {
-                let src = opcode & 0x07;
-                let value = self.reg(src, bus);
-                self.op_xor(value);
-                if src == 0x06 { 7 } else { 4 }
-            }
-            // CP A,r
-            0xB8..=0xBF => {
-                let src = opcode & 0x07;
-                let value = self.reg(src, bus);
-                self.op_cp(value);
-                if src == 0x06 { 7 } else { 4 }
-            }
-
-            // ADD A,n
-            0xC6 => {
-                let value = self.fetch_byte(bus);
-                self.op_add(value);
-                7
-            }
-            // ADC A,n
-            0xCE => {
-                let value = self.fetch_byte(bus);
-                self.op_adc(value);
-                7
-            }
-            // SUB A,n
-            0xD6 => {
-                let value = self.fetch_byte(bus);
-                self.op_sub(value);
-                7
-            }
-            // SBC A,n
-            0xDE => {
-                let value = self.fetch_byte(bus);
-                self.op_sbc(value);
-                7
-            }
-            // AND A,n
-            0xE6 => {
-                let value = self.fetch_byte(bus);
-                self.op_and(value);
-                7
-            }
-            // OR A,n
-            0xF6 => {
-                let value = self.fetch_byte(bus);
-                self.op_or(value);
-                7
-            }
-            // XOR A,n
-            0xEE => {
-                let value = self.fetch_byte(bus);
-                self.op_xor(value);
-                7
-            }
-            // CP A,n
-            0xFE => {
-                let value = self.fetch_byte(bus);
-                self.op_cp(value);
-                7
-            }
-
-            // INC r
-            0x04 | 0x0C | 0x14 | 0x1C | 0x24 | 0x2C | 0x34 | 0x3C => {
-                let dest = (opcode >> 3) & 0x07;
-                let value = self.reg(dest, bus);
-                let result = self.op_inc(value);
-                self.set_reg(dest, result, bus);
-                if dest == 0x06 { 10 } else { 5 }
-            }
-            // DEC r
-            0x05 | 0x0D | 0x15 | 0x1D | 0x25 | 0x2D | 0x35 | 0x3D => {
-                let dest = (opcode >> 3) & 0x07;
-                let value = self.reg(dest, bus);
-                let result = self.op_dec(value);
-                self.set_reg(dest, result, bus);
-                if dest == 0x06 { 10 } else { 5 }
-            }
-
-            // ADD HL,BC
-            0x09 => {
-                self.op_add16(self.bc());
-                10
-            }
-            // ADD HL,DE
-            0x19 => {
-                self.op_add16(self.de());
-                10
-            }
-            // ADD HL,HL
-            0x29 => {
-                self.op_add16(self.hl());
-                10
-            }
-            // ADD HL,SP
-            0x39 => {
-                self.op_add16(self.sp);
-                10
-            }
-
-            // INC BC
-            0x03 => {
-                let value = self.bc().wrapping_add(1);
-                self.set_bc(value);
-                5
-            }
-            // INC DE
-            0x13 => {
-                let value = self.de().wrapping_add(1);
-                self.set_de(value);
-                5
-            }
-            // INC HL
-            0x23 => {
-                let value = self.hl().wrapping_add(1);
-                self.set_hl(value);
-                5
-            }
-            // INC SP
-            0x33 => {
-                self.sp = self.sp.wrapping_add(1);
-                5
-            }
-            // DEC BC
-            0x0B => {
-                let value = self.bc().wrapping_sub(1);
-                self.set_bc(value);
-                5
-            }
-            // DEC DE
-            0x1B => {
-                let value = self.de().wrapping_sub(1);
-                self.set_de(value);
-                5
-            }
-            // DEC HL
-            0x2B => {
-                let value = self.hl().wrapping_sub(1);
-                self.set_hl(value);
-                5
-            }
-            // DEC SP
-            0x3B => {
-                self.sp = self.sp.wrapping_sub(1);
-                5
-            }
-
-            // RLCA
-            0x07 => {
-                let msb = self.a & 0x80;
-                self.a = self.a.rotate_left(1);
-                self.flags.carry = msb != 0;
-                4
-            }
-            // RRCA
-            0x0F => {
-                let lsb = self.a & 0x01;
-                self.a = self.a.rotate_right(1);
-                self.flags.carry = lsb != 0;
-                4
-            }
-            // RLA
-            0x17 => {
-                let msb = self.a & 0x80;
-                self.a = (self.a << 1) | if self.flags.carry { 0x01 } else { 0 };
-                self.flags.carry = msb != 0;
-                4
-            }
-            // RRA
-            0x1F => {
-                let lsb = self.a & 0x01;
-                self.a = (self.a >> 1) | if self.flags.carry { 0x80 } else { 0 };
-                self.flags.carry = lsb != 0;
-                4
-            }
-
-            // DAA
-            0x27 => {
-                self.op_daa();
-                4
-            }
-            // CPL
-            0x2F => {
-                self.a = !self.a;
-                4
-            }
-            // SCF
-            0x37 => {
-                self.flags.carry = true;
-                4
-            }
-            // CCF
-            0x3F => {
-                self.flags.carry = !self.flags.carry;
-                4
-            }
-
-            // JP addr
-            0xC3 | 0xCB => {
-                self.op_jp(bus, true);
-                10
-            }
-            // JP cc,addr
-            0xC2 | 0xCA | 0xD2 | 0xDA | 0xE2 | 0xEA | 0xF2 | 0xFA => {
-                let cc = (opcode >> 3) & 0x07;
-                let condition = self.condition(cc);
-                self.op_jp(bus, condition);
-                10
-            }
-
-            // RET
-            0xC9 | 0xD9 => {
-                self.op_ret(bus, true);
-                10
-            }
-            // RET cc
-            0xC0 | 0xC8 | 0xD0 | 0xD8 | 0xE0 | 0xE8 | 0xF0 | 0xF8 => {
-                let cc = (opcode >> 3) & 0x07;
-                let condition = self.condition(cc);
-                self.op_ret(bus, condition);
-                if condition { 11 } else { 5 }
-            }
-
-            // CALL addr
-            0xCD | 0xDD | 0xED | 0xFD => {
-                self.op_call(bus, true);
-                17
-            }
-            // CALL cc,addr
-            0xC4 | 0xCC | 0xD4 | 0xDC | 0xE4 | 0xEC | 0xF4 | 0xFC => {
-                let cc = (opcode >> 3) & 0x07;
-                let condition = self.condition(cc);
-                self.op_call(bus, condition);
-                if condition { 17 } else { 11 }
-            }
-
-            // RST p
-            0xC7 | 0xCF | 0xD7 | 0xDF | 0xE7 | 0xEF | 0xF7 | 0xFF => {
-                let addr = (opcode & 0b00_111_000) as u16;
-                self.op_push(bus, self.pc);
-                self.pc = addr;
-                11
-            }
-            // JP (HL)
-            0xE9 => {
-                self.pc = self.hl();
-                5
-            }
-
-            // EI
-            0xFB => {
-                self.iff = true;
-                4
-            }
-            // DI
-            0xF3 => {
-                self.iff = false;
-                4
-            }
-
-            // IN A,(n)
-            0xDB => {
-                let port = self.fetch_byte(bus);
-                self.a = bus.input(port);
-                10
-            }
-            // OUT (n),A
-            0xD3 => {
-                let port = self.fetch_byte(bus);
-                bus.output(port, self.a);
-                10
-            }
-        }
+        let opcode = if self.inta_len > 0 {
+            self.fetch_inta()
+        } else {
+            let byte = bus.read_access(self.pc, Access::Fetch);
+            self.pc = self.pc.wrapping_add(1);
+            byte
+        };
+        let handlers: &[Handler<B>; 256] = &Handlers::<B>::TABLE;
+        handlers[opcode as usize](self, bus)
     }
 
-    fn fetch_byte(&mut self, bus: &dyn Bus) -> u8 {
-        let byte = bus.read(self.pc);
+    // a single instantiation for callers holding a bus trait object
+    pub fn step_dyn(&mut self, bus: &mut dyn Bus) -> Cycles {
+        self.step(bus)
+    }
+
+    fn fetch_byte<B: Bus + ?Sized>(&mut self, bus: &mut B) -> u8 {
+        if self.inta_len > 0 {
+            return self.fetch_inta();
+        }
+        let byte = bus.read_access(self.pc, Access::Operand);
         self.pc = self.pc.wrapping_add(1);
         byte
     }
 
-    fn fetch_word(&mut self, bus: &dyn Bus) -> u16 {
-        let word = bus.read_word(self.pc);
+    fn fetch_word<B: Bus + ?Sized>(&mut self, bus: &mut B) -> u16 {
+        if self.inta_len > 0 {
+            let lo = self.fetch_byte(bus);
+            let hi = self.fetch_byte(bus);
+            return ((hi as u16) << 8) | (lo as u16);
+        }
+        let word = bus.read_word(self.pc, Access::Operand);
         self.pc = self.pc.wrapping_add(2);
         word
     }
 
+    fn fetch_inta(&mut self) -> u8 {
+        let byte = self.inta[0];
+        self.inta.rotate_left(1);
+        self.inta_len -= 1;
+        byte
+    }
+
     fn op_arith(&mut self, value: u8, carry: bool, complement: bool) {
-        let (r, c, ac) = arith(self.a, value, carry, complement);
+        let (r, c, _) = arith(self.a, value, carry, complement);
+        let b = if complement { !value } else { value };
+        self.flags.aux = r ^ self.a ^ b;
         self.a = r;
         self.set_zsp(r);
         self.flags.carry = c;
-        self.flags.aux_carry = ac;
     }
 
     fn op_add(&mut self, value: u8) {
@@ -595,7 +170,7 @@ impl Cpu {
 
         self.set_zsp(r);
         self.flags.carry = false;
-        self.flags.aux_carry = (self.a | value) & 0x08 != 0; // special case
+        self.flags.aux = (self.a | value) << 1; // special case, bit 3 of either
 
         self.a = r;
     }
@@ -605,7 +180,7 @@ impl Cpu {
 
         self.set_zsp(self.a);
         self.flags.carry = false;
-        self.flags.aux_carry = false;
+        self.flags.aux = 0;
     }
 
     fn op_xor(&mut self, value: u8) {
@@ -613,28 +188,28 @@ impl Cpu {
 
         self.set_zsp(self.a);
         self.flags.carry = false;
-        self.flags.aux_carry = false;
+        self.flags.aux = 0;
     }
 
     fn op_cp(&mut self, value: u8) {
-        let (r, c, ac) = arith(self.a, value, false, true);
+        let (r, c, _) = arith(self.a, value, false, true);
 
         self.set_zsp(r);
         self.flags.carry = c;
-        self.flags.aux_carry = ac;
+        self.flags.aux = r ^ self.a ^ !value;
     }
 
     fn op_inc(&mut self, value: u8) -> u8 {
         let r = value.wrapping_add(1);
         self.set_zsp(r);
-        self.flags.aux_carry = (value & 0x0F) + 1 > 0x0F;
+        self.flags.aux = r ^ value ^ 0x01;
         r
     }
 
     fn op_dec(&mut self, value: u8) -> u8 {
         let r = value.wrapping_sub(1);
         self.set_zsp(r);
-        self.flags.aux_carry = (r & 0x0F) != 0x0F;
+        self.flags.aux = r ^ value ^ 0xFF;
         r
     }
 
@@ -644,14 +219,14 @@ impl Cpu {
         self.flags.carry = carry;
     }
 
-    fn op_daa(&mut self) {
+    pub fn daa(&mut self) {
         let mut correction: u8 = 0;
         let mut carry = self.flags.carry;
 
         let hi = self.a >> 4;
         let lo = self.a & 0x0F;
 
-        if lo > 9 || self.flags.aux_carry {
+        if lo > 9 || self.flags.aux_carry() {
             correction |= 0x06;
         }
         if hi > 9 || self.flags.carry || (hi >= 9 && lo > 9) {
@@ -663,14 +238,14 @@ impl Cpu {
         self.flags.carry = carry;
     }
 
-    fn op_jp(&mut self, bus: &dyn Bus, condition: bool) {
+    fn op_jp<B: Bus + ?Sized>(&mut self, bus: &mut B, condition: bool) {
         let addr = self.fetch_word(bus);
         if condition {
             self.pc = addr;
         }
     }
 
-    fn op_call(&mut self, bus: &mut dyn Bus, condition: bool) {
+    fn op_call<B: Bus + ?Sized>(&mut self, bus: &mut B, condition: bool) {
         let addr = self.fetch_word(bus);
         if condition {
             self.op_push(bus, self.pc);
@@ -678,58 +253,46 @@ impl Cpu {
         }
     }
 
-    fn op_ret(&mut self, bus: &dyn Bus, condition: bool) {
+    fn op_ret<B: Bus + ?Sized>(&mut self, bus: &mut B, condition: bool) {
         if condition {
             let addr = self.op_pop(bus);
             self.pc = addr;
         }
     }
 
-    fn op_push(&mut self, bus: &mut dyn Bus, value: u16) {
+    fn op_push<B: Bus + ?Sized>(&mut self, bus: &mut B, value: u16) {
         self.sp = self.sp.wrapping_sub(2);
-        bus.write_word(self.sp, value);
+        bus.write_word(self.sp, value, Access::Stack);
     }
 
-    fn op_pop(&mut self, bus: &dyn Bus) -> u16 {
-        let value = bus.read_word(self.sp);
+    fn op_pop<B: Bus + ?Sized>(&mut self, bus: &mut B) -> u16 {
+        let value = bus.read_word(self.sp, Access::Stack);
         self.sp = self.sp.wrapping_add(2);
         value
     }
 
-    fn set_zsp(&mut self, value: u8) {
-        self.flags.zero = value == 0;
-        self.flags.sign = (value & 0x80) != 0;
-        self.flags.parity = value.count_ones() % 2 == 0;
+    // flag helpers, also used by recompiled code
+
+    #[inline(always)]
+    pub fn set_zsp(&mut self, value: u8) {
+        self.flags.set_zsp(value);
     }
 
-    fn flags8(&self) -> u8 {
-        0x02 | (if self.flags.zero { 0x40 } else { 0 })
-            | (if self.flags.sign { 0x80 } else { 0 })
-            | (if self.flags.parity { 0x04 } else { 0 })
-            | (if self.flags.aux_carry { 0x10 } else { 0 })
-            | (if self.flags.carry { 0x01 } else { 0 })
+    pub fn carry(&self) -> bool {
+        self.flags.carry
     }
 
-    pub fn to_string(&self) -> String {
-        format!(
-            "PC: {:04X}, AF: {:02X}{:02X}, BC: {:02X}{:02X}, DE: {:02X}{:02X}, HL: {:02X}{:02X}, SP: {:04X}, F=[{} {} 0 {} 0 {} 1 {}] ({:?})",
-            self.pc,
-            self.a,
-            self.flags8(),
-            self.b,
-            self.c,
-            self.d,
-            self.e,
-            self.h,
-            self.l,
-            self.sp,
-            if self.flags.sign { 'S' } else { 's' },
-            if self.flags.zero { 'Z' } else { 'z' },
-            if self.flags.aux_carry { 'A' } else { 'a' },
-            if self.flags.parity { 'P' } else { 'p' },
-            if self.flags.carry { 'C' } else { 'c' },
-            self.state,
-        )
+    pub fn set_carry(&mut self, carry: bool) {
+        self.flags.carry = carry;
+    }
+
+    pub fn set_aux_carry(&mut self, aux_carry: bool) {
+        self.flags.set_aux_carry(aux_carry);
+    }
+
+    #[inline(always)]
+    pub fn condition(&self, code: u8) -> bool {
+        self.flags.condition(code)
     }
 
     pub fn bc(&self) -> u16 {
@@ -760,63 +323,131 @@ impl Cpu {
     }
 
     pub fn af(&self) -> u16 {
-        ((self.a as u16) << 8) | (self.flags8() as u16)
+        ((self.a as u16) << 8) | (self.flags.to_byte() as u16)
     }
 
     pub fn set_af(&mut self, value: u16) {
         self.a = (value >> 8) as u8;
-        let f = (value & 0xFF) as u8;
-        self.flags.zero = (f & 0x40) != 0;
-        self.flags.sign = (f & 0x80) != 0;
-        self.flags.parity = (f & 0x04) != 0;
-        self.flags.aux_carry = (f & 0x10) != 0;
-        self.flags.carry = (f & 0x01) != 0;
+        self.flags = Flags::from_byte(value as u8);
     }
+}
 
-    fn reg(&self, code: u8, bus: &dyn Bus) -> u8 {
-        match code {
-            0 => self.b,
-            1 => self.c,
-            2 => self.d,
-            3 => self.e,
-            4 => self.h,
-            5 => self.l,
-            6 => bus.read(self.hl()),
-            7 => self.a,
-            _ => unreachable!(),
-        }
+impl Flags {
+    #[inline(always)]
+    fn set_zsp(&mut self, value: u8) {
+        self.zero = value;
+        self.sign = value;
+        self.parity = value;
     }
 
-    fn set_reg(&mut self, code: u8, value: u8, bus: &mut dyn Bus) {
-        match code {
-            0 => self.b = value,
-            1 => self.c = value,
-            2 => self.d = value,
-            3 => self.e = value,
-            4 => self.h = value,
-            5 => self.l = value,
-            6 => bus.write(self.hl(), value),
-            7 => self.a = value,
-            _ => unreachable!(),
-        }
+    fn set_aux_carry(&mut self, aux_carry: bool) {
+        self.aux = if aux_carry { 0x10 } else { 0 };
+    }
+
+    #[inline(always)]
+    fn zero(&self) -> bool {
+        self.zero == 0
     }
 
+    #[inline(always)]
+    fn sign(&self) -> bool {
+        self.sign & 0x80 != 0
+    }
+
+    #[inline(always)]
+    fn parity(&self) -> bool {
+        // 0x6996 holds the odd parity of each nibble
+        let nibble = (self.parity ^ (self.parity >> 4)) & 0x0F;
+        (0x6996u16 >> nibble) & 1 == 0
+    }
+
+    #[inline(always)]
+    fn aux_carry(&self) -> bool {
+        self.aux & 0x10 != 0
+    }
+
+    #[inline(always)]
     fn condition(&self, code: u8) -> bool {
         match code {
-            0 => !self.flags.zero,   // NZ
-            1 => self.flags.zero,    // Z
-            2 => !self.flags.carry,  // NC
-            3 => self.flags.carry,   // C
-            4 => !self.flags.parity, // PO
-            5 => self.flags.parity,  // PE
-            6 => !self.flags.sign,   // P
-            7 => self.flags.sign,    // M
-            _ => unreachable!(),
+            0 => !self.zero(),   // NZ
+            1 => self.zero(),    // Z
+            2 => !self.carry,    // NC
+            3 => self.carry,     // C
+            4 => !self.parity(), // PO
+            5 => self.parity(),  // PE
+            6 => !self.sign(),   // P
+            _ => self.sign(),    // M
+        }
+    }
+
+    #[inline(always)]
+    fn to_byte(self) -> u8 {
+        0x02 | (self.zero() as u8) << 6
+            | (self.sign & 0x80)
+            | (self.parity() as u8) << 2
+            | (self.aux & 0x10)
+            | self.carry as u8
+    }
+
+    // sources that give back exactly the flags in `f`
+    #[inline(always)]
+    fn from_byte(f: u8) -> Flags {
+        Flags {
+            zero: (!f >> 6) & 1,
+            sign: f,
+            parity: (!f >> 2) & 1,
+            aux: f,
+            carry: (f & 0x01) != 0,
         }
     }
 }
 
-fn arith(a: u8, b: u8, carry: bool, complement: bool) -> (u8, bool, bool) {
+impl PartialEq for Flags {
+    fn eq(&self, other: &Flags) -> bool {
+        self.to_byte() == other.to_byte()
+    }
+}
+
+impl Eq for Flags {}
+
+impl fmt::Debug for Flags {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Flags({:02X})", self.to_byte())
+    }
+}
+
+impl fmt::Display for Cpu {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "PC: {:04X}, AF: {:02X}{:02X}, BC: {:02X}{:02X}, DE: {:02X}{:02X}, HL: {:02X}{:02X}, SP: {:04X}, F=[{} {} 0 {} 0 {} 1 {}] ({:?})",
+            self.pc,
+            self.a,
+            self.flags.to_byte(),
+            self.b,
+            self.c,
+            self.d,
+            self.e,
+            self.h,
+            self.l,
+            self.sp,
+            if self.flags.sign() { 'S' } else { 's' },
+            if self.flags.zero() { 'Z' } else { 'z' },
+            if self.flags.aux_carry() { 'A' } else { 'a' },
+            if self.flags.parity() { 'P' } else { 'p' },
+            if self.flags.carry { 'C' } else { 'c' },
+            self.state,
+        )
+    }
+}
+
+impl Default for Cpu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn arith(a: u8, b: u8, carry: bool, complement: bool) -> (u8, bool, bool) {
     let c = if complement { !carry } else { carry };
     let b = if complement { !b as u16 } else { b as u16 };
     let r = a as u16 + b + c as u16;
@@ -835,22 +466,37 @@ mod tests {
     fn test_arith() {
         let (result, carry, aux_carry) = arith(0x14, 0x27, false, false);
         assert_eq!(result, 0x3B);
-        assert_eq!(carry, false);
-        assert_eq!(aux_carry, false);
+        assert!(!carry);
+        assert!(!aux_carry);
 
         let (result, carry, aux_carry) = arith(0xFF, 0x01, false, false);
         assert_eq!(result, 0x00);
-        assert_eq!(carry, true);
-        assert_eq!(aux_carry, true);
+        assert!(carry);
+        assert!(aux_carry);
 
         let (result, carry, aux_carry) = arith(0x10, 0x01, true, false);
         assert_eq!(result, 0x12);
-        assert_eq!(carry, false);
-        assert_eq!(aux_carry, false);
+        assert!(!carry);
+        assert!(!aux_carry);
 
         let (result, carry, aux_carry) = arith(0x00, 0x01, true, true);
         assert_eq!(result, 0xFE);
-        assert_eq!(carry, true);
-        assert_eq!(aux_carry, true);
+        assert!(carry);
+        assert!(!aux_carry);
+    }
+
+    #[test]
+    fn test_flags() {
+        // every flag byte survives a round trip through the lazy sources
+        for f in 0..=255u8 {
+            assert_eq!(Flags::from_byte(f).to_byte(), f & 0xD7 | 0x02);
+        }
+
+        let mut cpu = Cpu::new();
+        cpu.a = 0x3A;
+        cpu.op_cp(0x3A);
+        assert!(cpu.condition(1) && cpu.condition(5)); // Z, PE
+        cpu.op_inc(0x7F);
+        assert_eq!(cpu.af() & 0xFF, 0x92); // S, AC
     }
 }
@@ -0,0 +1,855 @@
+use super::{Cpu, Cycles, Flags, State, arith};
+use crate::bus::{Access, Bus};
+
+// A block cache: hot straight-line code is translated into a small IR with
+// operands decoded, run on a local copy of the registers, and only the flags
+// that something later can observe are computed. Writes to translated bytes
+// invalidate the blocks covering them; IN, OUT, EI, DI, HLT and interrupts
+// always go through `Cpu::step`.
+
+// visits to an address before a block is translated there
+const HOT: u8 = 8;
+// invalidations after which an address is left to the interpreter
+const UNSTABLE: u8 = 4;
+const MAX_OPS: usize = 32;
+
+// flag groups; zero, sign and parity are always set together
+const ZSP: u8 = 0x01;
+const AUX: u8 = 0x02;
+const CARRY: u8 = 0x04;
+const ALL: u8 = ZSP | AUX | CARRY;
+
+// register file indices as encoded in the opcode, M (6) is not a register
+const M: u8 = 6;
+const A: usize = 7;
+
+// accumulator operations
+const ADC: u8 = 1;
+const SUB: u8 = 2;
+const SBB: u8 = 3;
+const ANA: u8 = 4;
+const XRA: u8 = 5;
+const ORA: u8 = 6;
+const CMP: u8 = 7;
+
+// register pairs, PSW for PUSH and POP
+const HL: u8 = 2;
+const PSW: u8 = 3;
+
+#[derive(Clone, Copy, Debug)]
+enum Operand {
+    Reg(u8),
+    Mem,
+    Imm(u8),
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Op {
+    Nop,
+    Mov(u8, u8),
+    Mvi(u8, u8),
+    Load(u8),
+    Store(Operand),
+    Lxi(u8, u16),
+    Ldax(u8),
+    Stax(u8),
+    Lda(u16),
+    Sta(u16),
+    Lhld(u16),
+    Shld(u16),
+    Alu(u8, Operand),
+    Inr(u8),
+    Dcr(u8),
+    InrM,
+    DcrM,
+    Inx(u8),
+    Dcx(u8),
+    Dad(u8),
+    Rlc,
+    Rrc,
+    Ral,
+    Rar,
+    Daa,
+    Cma,
+    Stc,
+    Cmc,
+    Push(u8),
+    Pop(u8),
+    Xthl,
+    Xchg,
+    Sphl,
+}
+
+impl Op {
+    // flags read and written, for liveness
+    fn flags(self) -> (u8, u8) {
+        match self {
+            Op::Alu(ADC | SBB, _) => (CARRY, ALL),
+            Op::Alu(..) => (0, ALL),
+            Op::Inr(_) | Op::Dcr(_) | Op::InrM | Op::DcrM => (0, ZSP | AUX),
+            Op::Dad(_) | Op::Rlc | Op::Rrc | Op::Stc => (0, CARRY),
+            Op::Ral | Op::Rar | Op::Cmc => (CARRY, CARRY),
+            Op::Daa => (AUX | CARRY, ALL),
+            Op::Push(PSW) => (ALL, 0),
+            Op::Pop(PSW) => (0, ALL),
+            _ => (0, 0),
+        }
+    }
+
+    // a store may end the block early, so every flag must be settled by then
+    fn stores(self) -> bool {
+        matches!(
+            self,
+            Op::Store(_)
+                | Op::Stax(_)
+                | Op::Sta(_)
+                | Op::Shld(_)
+                | Op::InrM
+                | Op::DcrM
+                | Op::Push(_)
+                | Op::Xthl
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Step {
+    op: Op,
+    // flags this op has to compute
+    flags: u8,
+    cycles: u8,
+    // address of the following instruction
+    next: u16,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Exit {
+    Next(u16),
+    Jump(u16),
+    Branch(u8, u16, u16),
+    Call(u16, u16, Cycles),
+    CallIf(u8, u16, u16),
+    Ret,
+    RetIf(u8, u16),
+    Pchl,
+}
+
+#[derive(Debug)]
+struct Block {
+    start: u16,
+    len: u16,
+    steps: Vec<Step>,
+    // of the steps, the exit adds its own
+    cycles: Cycles,
+    exit: Exit,
+}
+
+impl Block {
+    fn covers(&self, addr: u16) -> bool {
+        addr.wrapping_sub(self.start) < self.len
+    }
+
+    fn pages(&self) -> impl Iterator<Item = usize> + use<> {
+        let first = self.start >> 8;
+        let last = self.start.wrapping_add(self.len - 1) >> 8;
+        let count = last.wrapping_sub(first) as u8 as usize + 1;
+        (0..count).map(move |i| (first as usize + i) & 0xFF)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct Stats {
+    pub translated: u64,
+    pub invalidated: u64,
+    pub blocks: u64,
+    pub interpreted: u64,
+}
+
+pub struct BlockCache {
+    blocks: Vec<Option<Box<Block>>>,
+    // number of blocks translated from each byte
+    covered: Box<[u16; 0x10000]>,
+    // starts of the blocks touching each 256-byte page
+    pages: Vec<Vec<u16>>,
+    heat: Box<[u8; 0x10000]>,
+    strikes: Box<[u8; 0x10000]>,
+    stats: Stats,
+}
+
+impl BlockCache {
+    pub fn new() -> Self {
+        BlockCache {
+            blocks: (0..0x10000).map(|_| None).collect(),
+            covered: Box::new([0; 0x10000]),
+            pages: vec![Vec::new(); 256],
+            heat: Box::new([0; 0x10000]),
+            strikes: Box::new([0; 0x10000]),
+            stats: Stats::default(),
+        }
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    // Run the block at PC, translating it first once it is hot, or a single
+    // instruction. Blocks never extend onto an address `boundary` holds for,
+    // so a caller can still stop there. Returns (ops, cycles).
+    #[inline(always)]
+    pub fn step<B: Bus + ?Sized>(
+        &mut self,
+        cpu: &mut Cpu,
+        bus: &mut B,
+        boundary: impl Fn(u16) -> bool,
+    ) -> (u64, Cycles) {
+        let pc = cpu.pc;
+        if cpu.state == State::Halted {
+            return (1, cpu.step(bus));
+        }
+        if self.blocks[pc as usize].is_none() {
+            let heat = &mut self.heat[pc as usize];
+            if *heat < HOT || self.strikes[pc as usize] >= UNSTABLE {
+                *heat = heat.saturating_add(1);
+                return (1, self.interpret(cpu, bus));
+            }
+            match translate(bus, pc, boundary) {
+                Some(block) => self.register(block),
+                None => {
+                    self.strikes[pc as usize] = UNSTABLE;
+                    return (1, self.interpret(cpu, bus));
+                }
+            }
+        }
+
+        let block = self.blocks[pc as usize].as_ref().unwrap();
+        let run = execute(block, cpu, bus, &self.covered);
+        self.stats.blocks += 1;
+        if let Some(addr) = run.hit {
+            self.invalidate(addr);
+            self.invalidate(addr.wrapping_add(1));
+        }
+        (run.ops, run.cycles)
+    }
+
+    // one instruction through the interpreter, watching for writes to code
+    pub fn interpret<B: Bus + ?Sized>(&mut self, cpu: &mut Cpu, bus: &mut B) -> Cycles {
+        let mut watch = Watch::new(bus, &self.covered);
+        let cycles = cpu.step(&mut watch);
+        let hits = watch.hits;
+        for addr in hits {
+            self.invalidate(addr);
+        }
+        self.stats.interpreted += 1;
+        cycles
+    }
+
+    pub fn interrupt<B: Bus + ?Sized>(
+        &mut self,
+        cpu: &mut Cpu,
+        bus: &mut B,
+        instruction: &[u8],
+    ) -> Option<Cycles> {
+        let mut watch = Watch::new(bus, &self.covered);
+        let cycles = cpu.interrupt(&mut watch, instruction);
+        let hits = watch.hits;
+        for addr in hits {
+            self.invalidate(addr);
+        }
+        cycles
+    }
+
+    // drop the blocks translated from `addr`; the host must call this (or
+    // invalidate_range) when it writes memory behind the CPU's back
+    pub fn invalidate(&mut self, addr: u16) {
+        if self.covered[addr as usize] == 0 {
+            return;
+        }
+        let starts: Vec<u16> = self.pages[addr as usize >> 8]
+            .iter()
+            .copied()
+            .filter(|&start| self.blocks[start as usize].as_ref().unwrap().covers(addr))
+            .collect();
+        for start in starts {
+            self.remove(start);
+        }
+    }
+
+    pub fn invalidate_range(&mut self, start: u16, len: usize) {
+        for i in 0..len.min(0x10000) {
+            self.invalidate(start.wrapping_add(i as u16));
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = BlockCache {
+            stats: self.stats,
+            ..BlockCache::new()
+        };
+    }
+
+    fn register(&mut self, block: Block) {
+        for i in 0..block.len {
+            self.covered[block.start.wrapping_add(i) as usize] += 1;
+        }
+        for page in block.pages() {
+            self.pages[page].push(block.start);
+        }
+        self.stats.translated += 1;
+        let start = block.start as usize;
+        self.blocks[start] = Some(Box::new(block));
+    }
+
+    fn remove(&mut self, start: u16) {
+        let block = self.blocks[start as usize].take().unwrap();
+        for i in 0..block.len {
+            self.covered[start.wrapping_add(i) as usize] -= 1;
+        }
+        for page in block.pages() {
+            self.pages[page].retain(|&s| s != start);
+        }
+        self.heat[start as usize] = 0;
+        self.strikes[start as usize] = self.strikes[start as usize].saturating_add(1);
+        self.stats.invalidated += 1;
+    }
+}
+
+impl Default for BlockCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// passes everything through, noting writes to translated bytes
+struct Watch<'a, B: ?Sized> {
+    bus: &'a mut B,
+    covered: &'a [u16; 0x10000],
+    hits: Vec<u16>,
+}
+
+impl<'a, B: Bus + ?Sized> Watch<'a, B> {
+    fn new(bus: &'a mut B, covered: &'a [u16; 0x10000]) -> Self {
+        Watch {
+            bus,
+            covered,
+            hits: Vec::new(),
+        }
+    }
+
+    fn watch(&mut self, addr: u16) {
+        if self.covered[addr as usize] != 0 {
+            self.hits.push(addr);
+        }
+    }
+}
+
+impl<B: Bus + ?Sized> Bus for Watch<'_, B> {
+    fn read(&mut self, addr: u16) -> u8 {
+        self.bus.read(addr)
+    }
+
+    fn write(&mut self, addr: u16, value: u8) {
+        self.watch(addr);
+        self.bus.write(addr, value);
+    }
+
+    fn input(&mut self, port: u8) -> u8 {
+        self.bus.input(port)
+    }
+
+    fn output(&mut self, port: u8, value: u8) {
+        self.bus.output(port, value);
+    }
+
+    fn read_access(&mut self, addr: u16, access: Access) -> u8 {
+        self.bus.read_access(addr, access)
+    }
+
+    fn write_access(&mut self, addr: u16, value: u8, access: Access) {
+        self.watch(addr);
+        self.bus.write_access(addr, value, access);
+    }
+}
+
+fn length(opcode: u8) -> u16 {
+    match opcode {
+        0x22 | 0x2A | 0x32 | 0x3A => 3,
+        0xC3 | 0xCB | 0xCD | 0xDD | 0xED | 0xFD => 3,
+        0xD3 | 0xDB => 2,
+        _ if opcode & 0xCF == 0x01 => 3, // LXI
+        _ if opcode & 0xC7 == 0xC2 || opcode & 0xC7 == 0xC4 => 3, // Jcc, Ccc
+        _ if opcode & 0xC7 == 0x06 || opcode & 0xC7 == 0xC6 => 2, // MVI, immediate
+        _ => 1,
+    }
+}
+
+enum Decoded {
+    Op(Op, u8),
+    Exit(Exit),
+    Fallback,
+}
+
+fn decode(opcode: u8, word: u16, next: u16) -> Decoded {
+    let dst = (opcode >> 3) & 7;
+    let src = opcode & 7;
+    let pair = (opcode >> 4) & 3;
+    let byte = word as u8;
+    let operand = |r: u8| {
+        if r == M {
+            Operand::Mem
+        } else {
+            Operand::Reg(r)
+        }
+    };
+    let op = match opcode {
+        0x76 | 0xD3 | 0xDB | 0xF3 | 0xFB => return Decoded::Fallback,
+        0x40..=0x7F if dst == M => (Op::Store(Operand::Reg(src)), 7),
+        0x40..=0x7F if src == M => (Op::Load(dst), 7),
+        0x40..=0x7F => (Op::Mov(dst, src), 5),
+        0x80..=0xBF => (Op::Alu(dst, operand(src)), if src == M { 7 } else { 4 }),
+        0x22 => (Op::Shld(word), 16),
+        0x2A => (Op::Lhld(word), 16),
+        0x32 => (Op::Sta(word), 13),
+        0x3A => (Op::Lda(word), 13),
+        0x02 | 0x12 => (Op::Stax(pair), 7),
+        0x0A | 0x1A => (Op::Ldax(pair), 7),
+        0x07 => (Op::Rlc, 4),
+        0x0F => (Op::Rrc, 4),
+        0x17 => (Op::Ral, 4),
+        0x1F => (Op::Rar, 4),
+        0x27 => (Op::Daa, 4),
+        0x2F => (Op::Cma, 4),
+        0x37 => (Op::Stc, 4),
+        0x3F => (Op::Cmc, 4),
+        0xE3 => (Op::Xthl, 18),
+        0xEB => (Op::Xchg, 5),
+        0xF9 => (Op::Sphl, 6),
+        0xE9 => return Decoded::Exit(Exit::Pchl),
+        0xC3 | 0xCB => return Decoded::Exit(Exit::Jump(word)),
+        0xCD | 0xDD | 0xED | 0xFD => return Decoded::Exit(Exit::Call(word, next, 17)),
+        0xC9 | 0xD9 => return Decoded::Exit(Exit::Ret),
+        _ => match opcode & 0xC7 {
+            0x00 => (Op::Nop, 4),
+            0x04 if dst == M => (Op::InrM, 10),
+            0x04 => (Op::Inr(dst), 5),
+            0x05 if dst == M => (Op::DcrM, 10),
+            0x05 => (Op::Dcr(dst), 5),
+            0x06 if dst == M => (Op::Store(Operand::Imm(byte)), 10),
+            0x06 => (Op::Mvi(dst, byte), 7),
+            0xC6 => (Op::Alu(dst, Operand::Imm(byte)), 7),
+            0xC0 => return Decoded::Exit(Exit::RetIf(dst, next)),
+            0xC2 => return Decoded::Exit(Exit::Branch(dst, word, next)),
+            0xC4 => return Decoded::Exit(Exit::CallIf(dst, word, next)),
+            0xC7 => return Decoded::Exit(Exit::Call((opcode & 0x38) as u16, next, 11)),
+            _ => match opcode & 0xCF {
+                0x01 => (Op::Lxi(pair, word), 10),
+                0x03 => (Op::Inx(pair), 5),
+                0x09 => (Op::Dad(pair), 10),
+                0x0B => (Op::Dcx(pair), 5),
+                0xC1 => (Op::Pop(pair), 10),
+                _ => (Op::Push(pair), 11), // 0xC5
+            },
+        },
+    };
+    Decoded::Op(op.0, op.1)
+}
+
+fn translate<B: Bus + ?Sized>(
+    bus: &mut B,
+    start: u16,
+    boundary: impl Fn(u16) -> bool,
+) -> Option<Block> {
+    let mut steps = Vec::new();
+    let mut addr = start;
+    let exit = loop {
+        if steps.len() == MAX_OPS || (addr != start && boundary(addr)) {
+            break Exit::Next(addr);
+        }
+        let opcode = bus.read(addr);
+        let len = length(opcode);
+        let word = match len {
+            1 => 0,
+            2 => bus.read(addr.wrapping_add(1)) as u16,
+            _ => u16::from_le_bytes([
+                bus.read(addr.wrapping_add(1)),
+                bus.read(addr.wrapping_add(2)),
+            ]),
+        };
+        let next = addr.wrapping_add(len);
+        match decode(opcode, word, next) {
+            Decoded::Op(op, cycles) => steps.push(Step {
+                op,
+                flags: ALL,
+                cycles,
+                next,
+            }),
+            Decoded::Exit(exit) => {
+                addr = next;
+                break exit;
+            }
+            Decoded::Fallback => break Exit::Next(addr),
+        }
+        addr = next;
+    };
+    if steps.is_empty() && matches!(exit, Exit::Next(_)) {
+        return None;
+    }
+
+    // everything is observable after the block, and after any store since
+    // one to translated code ends the block there
+    let mut live = ALL;
+    for step in steps.iter_mut().rev() {
+        if step.op.stores() {
+            live = ALL;
+        }
+        let (reads, writes) = step.op.flags();
+        step.flags = writes & live;
+        live = (live & !writes) | reads;
+    }
+
+    Some(Block {
+        start,
+        len: addr.wrapping_sub(start),
+        cycles: steps.iter().map(|s| s.cycles as Cycles).sum(),
+        steps,
+        exit,
+    })
+}
+
+struct Run {
+    ops: u64,
+    cycles: Cycles,
+    // a store to translated code, of one or two bytes
+    hit: Option<u16>,
+}
+
+#[inline(always)]
+fn set_flags(f: &mut Flags, mask: u8, value: u8, aux: u8, carry: bool) {
+    if mask & ZSP != 0 {
+        f.set_zsp(value);
+    }
+    if mask & AUX != 0 {
+        f.aux = aux;
+    }
+    if mask & CARRY != 0 {
+        f.carry = carry;
+    }
+}
+
+#[inline(always)]
+fn accumulate(f: &mut Flags, mask: u8, a: u8, op: u8, value: u8) -> u8 {
+    let (r, carry, aux) = match op {
+        ANA => (a & value, false, (a | value) << 1),
+        XRA => (a ^ value, false, 0),
+        ORA => (a | value, false, 0),
+        _ => {
+            let complement = op >= SUB;
+            let (r, carry, _) = arith(a, value, (op == ADC || op == SBB) && f.carry, complement);
+            (r, carry, r ^ a ^ if complement { !value } else { value })
+        }
+    };
+    set_flags(f, mask, r, aux, carry);
+    if op == CMP { a } else { r }
+}
+
+#[inline(always)]
+fn execute<B: Bus + ?Sized>(
+    block: &Block,
+    cpu: &mut Cpu,
+    bus: &mut B,
+    covered: &[u16; 0x10000],
+) -> Run {
+    let mut r = [cpu.b, cpu.c, cpu.d, cpu.e, cpu.h, cpu.l, 0, cpu.a];
+    let mut f = cpu.flags;
+    let mut sp = cpu.sp;
+    let mut cycles = block.cycles;
+    let mut ops = block.steps.len() as u64;
+    let mut hit = None;
+
+    let pair = |r: &[u8; 8], sp: u16, p: u8| match p {
+        3 => sp,
+        _ => u16::from_be_bytes([r[p as usize * 2], r[p as usize * 2 + 1]]),
+    };
+    let set_pair = |r: &mut [u8; 8], sp: &mut u16, p: u8, value: u16| match p {
+        3 => *sp = value,
+        _ => [r[p as usize * 2], r[p as usize * 2 + 1]] = value.to_be_bytes(),
+    };
+
+    'steps: for (i, step) in block.steps.iter().enumerate() {
+        // a store to translated code ends the block after the instruction
+        macro_rules! hit {
+            ($addr:expr) => {{
+                hit = Some($addr);
+                cpu.pc = step.next;
+                ops = i as u64 + 1;
+                cycles = block.steps[..=i].iter().map(|s| s.cycles as Cycles).sum();
+                break 'steps;
+            }};
+        }
+        macro_rules! store {
+            ($addr:expr, $value:expr) => {{
+                let addr = $addr;
+                bus.write_access(addr, $value, Access::Data);
+                if covered[addr as usize] != 0 {
+                    hit!(addr);
+                }
+            }};
+        }
+        macro_rules! store_word {
+            ($addr:expr, $value:expr, $access:expr) => {{
+                let addr = $addr;
+                bus.write_word(addr, $value, $access);
+                if covered[addr as usize] != 0 || covered[addr.wrapping_add(1) as usize] != 0 {
+                    hit!(addr);
+                }
+            }};
+        }
+
+        macro_rules! hl {
+            () => {
+                u16::from_be_bytes([r[4], r[5]])
+            };
+        }
+
+        match step.op {
+            Op::Nop => {}
+            Op::Mov(d, s) => r[d as usize & 7] = r[s as usize & 7],
+            Op::Mvi(d, n) => r[d as usize & 7] = n,
+            Op::Load(d) => r[d as usize & 7] = bus.read_access(hl!(), Access::Data),
+            Op::Store(Operand::Reg(s)) => store!(hl!(), r[s as usize & 7]),
+            Op::Store(Operand::Imm(n)) => store!(hl!(), n),
+            Op::Store(Operand::Mem) => unreachable!(),
+            Op::Lxi(p, w) => set_pair(&mut r, &mut sp, p, w),
+            Op::Ldax(p) => r[A] = bus.read_access(pair(&r, sp, p), Access::Data),
+            Op::Stax(p) => store!(pair(&r, sp, p), r[A]),
+            Op::Lda(w) => r[A] = bus.read_access(w, Access::Data),
+            Op::Sta(w) => store!(w, r[A]),
+            Op::Lhld(w) => [r[4], r[5]] = bus.read_word(w, Access::Data).to_be_bytes(),
+            Op::Shld(w) => store_word!(w, hl!(), Access::Data),
+            Op::Alu(op, src) => {
+                let value = match src {
+                    Operand::Reg(s) => r[s as usize & 7],
+                    Operand::Mem => bus.read_access(hl!(), Access::Data),
+                    Operand::Imm(n) => n,
+                };
+                r[A] = accumulate(&mut f, step.flags, r[A], op, value);
+            }
+            Op::Inr(d) => {
+                let value = r[d as usize & 7];
+                let result = value.wrapping_add(1);
+                set_flags(&mut f, step.flags, result, result ^ value ^ 0x01, false);
+                r[d as usize & 7] = result;
+            }
+            Op::Dcr(d) => {
+                let value = r[d as usize & 7];
+                let result = value.wrapping_sub(1);
+                set_flags(&mut f, step.flags, result, result ^ value ^ 0xFF, false);
+                r[d as usize & 7] = result;
+            }
+            Op::InrM => {
+                let value = bus.read_access(hl!(), Access::Data);
+                let result = value.wrapping_add(1);
+                set_flags(&mut f, step.flags, result, result ^ value ^ 0x01, false);
+                store!(hl!(), result);
+            }
+            Op::DcrM => {
+                let value = bus.read_access(hl!(), Access::Data);
+                let result = value.wrapping_sub(1);
+                set_flags(&mut f, step.flags, result, result ^ value ^ 0xFF, false);
+                store!(hl!(), result);
+            }
+            Op::Inx(p) => {
+                let value = pair(&r, sp, p).wrapping_add(1);
+                set_pair(&mut r, &mut sp, p, value);
+            }
+            Op::Dcx(p) => {
+                let value = pair(&r, sp, p).wrapping_sub(1);
+                set_pair(&mut r, &mut sp, p, value);
+            }
+            Op::Dad(p) => {
+                let (result, carry) = hl!().carrying_add(pair(&r, sp, p), false);
+                set_pair(&mut r, &mut sp, HL, result);
+                f.carry = carry;
+            }
+            Op::Rlc => {
+                f.carry = r[A] & 0x80 != 0;
+                r[A] = r[A].rotate_left(1);
+            }
+            Op::Rrc => {
+                f.carry = r[A] & 0x01 != 0;
+                r[A] = r[A].rotate_right(1);
+            }
+            Op::Ral => {
+                let msb = r[A] & 0x80;
+                r[A] = (r[A] << 1) | f.carry as u8;
+                f.carry = msb != 0;
+            }
+            Op::Rar => {
+                let lsb = r[A] & 0x01;
+                r[A] = (r[A] >> 1) | if f.carry { 0x80 } else { 0 };
+                f.carry = lsb != 0;
+            }
+            Op::Daa => {
+                let (hi, lo) = (r[A] >> 4, r[A] & 0x0F);
+                let mut correction = 0;
+                let mut carry = f.carry;
+                if lo > 9 || f.aux_carry() {
+                    correction |= 0x06;
+                }
+                if hi > 9 || f.carry || (hi >= 9 && lo > 9) {
+                    correction |= 0x60;
+                    carry = true;
+                }
+                let (result, _, _) = arith(r[A], correction, false, false);
+                set_flags(&mut f, ALL, result, result ^ r[A] ^ correction, carry);
+                r[A] = result;
+            }
+            Op::Cma => r[A] = !r[A],
+            Op::Stc => f.carry = true,
+            Op::Cmc => f.carry = !f.carry,
+            Op::Push(p) => {
+                let value = match p {
+                    PSW => u16::from_be_bytes([r[A], f.to_byte()]),
+                    _ => pair(&r, sp, p),
+                };
+                sp = sp.wrapping_sub(2);
+                store_word!(sp, value, Access::Stack);
+            }
+            Op::Pop(p) => {
+                let value = bus.read_word(sp, Access::Stack);
+                sp = sp.wrapping_add(2);
+                match p {
+                    PSW => {
+                        r[A] = (value >> 8) as u8;
+                        f = Flags::from_byte(value as u8);
+                    }
+                    _ => set_pair(&mut r, &mut sp, p, value),
+                }
+            }
+            Op::Xthl => {
+                let hl = hl!();
+                let top = bus.read_word(sp, Access::Stack);
+                [r[4], r[5]] = top.to_be_bytes();
+                store_word!(sp, hl, Access::Stack);
+            }
+            Op::Xchg => {
+                r.swap(2, 4);
+                r.swap(3, 5);
+            }
+            Op::Sphl => sp = hl!(),
+        }
+    }
+
+    // the exit runs last, a store there only needs to be reported
+    macro_rules! push {
+        ($value:expr) => {{
+            sp = sp.wrapping_sub(2);
+            bus.write_word(sp, $value, Access::Stack);
+            if covered[sp as usize] != 0 || covered[sp.wrapping_add(1) as usize] != 0 {
+                hit = Some(sp);
+            }
+        }};
+    }
+
+    if hit.is_none() {
+        ops += 1;
+        let next = match block.exit {
+            Exit::Next(pc) => {
+                ops -= 1;
+                pc
+            }
+            Exit::Jump(target) => {
+                cycles += 10;
+                target
+            }
+            Exit::Branch(cc, target, next) => {
+                cycles += 10;
+                if f.condition(cc) { target } else { next }
+            }
+            Exit::Call(target, next, call_cycles) => {
+                cycles += call_cycles;
+                push!(next);
+                target
+            }
+            Exit::CallIf(cc, target, next) => {
+                if f.condition(cc) {
+                    cycles += 17;
+                    push!(next);
+                    target
+                } else {
+                    cycles += 11;
+                    next
+                }
+            }
+            Exit::Ret => {
+                cycles += 10;
+                let target = bus.read_word(sp, Access::Stack);
+                sp = sp.wrapping_add(2);
+                target
+            }
+            Exit::RetIf(cc, next) => {
+                if f.condition(cc) {
+                    cycles += 11;
+                    let target = bus.read_word(sp, Access::Stack);
+                    sp = sp.wrapping_add(2);
+                    target
+                } else {
+                    cycles += 5;
+                    next
+                }
+            }
+            Exit::Pchl => {
+                cycles += 5;
+                u16::from_be_bytes([r[4], r[5]])
+            }
+        };
+        cpu.pc = next;
+    }
+
+    [cpu.b, cpu.c, cpu.d, cpu.e, cpu.h, cpu.l] = [r[0], r[1], r[2], r[3], r[4], r[5]];
+    cpu.a = r[A];
+    cpu.flags = f;
+    cpu.sp = sp;
+    Run { ops, cycles, hit }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::machine::SimpleMachine;
+
+    #[test]
+    fn test_self_modifying() {
+        // the loop bumps the operand of its own MVI C, so its block is
+        // invalidated from inside until it is left to the interpreter
+        #[rustfmt::skip]
+        let program = [
+            0x06, 0x40,       // 0000 MVI B,40h
+            0x21, 0x0A, 0x00, // 0002 LXI H,000Ah
+            0x34,             // 0005 INR M
+            0x00, 0x00, 0x00, // 0006 NOP x3
+            0x0E, 0x00,       // 0009 MVI C,00h
+            0x79,             // 000B MOV A,C
+            0x83,             // 000C ADD E
+            0x5F,             // 000D MOV E,A
+            0x05,             // 000E DCR B
+            0xC2, 0x05, 0x00, // 000F JNZ 0005
+            0x76,             // 0012 HLT
+        ];
+        let run = |cache: bool| {
+            let mut machine = SimpleMachine::new();
+            machine.set_block_cache(cache);
+            machine.load(0x0000, &program);
+            let stop = machine.run_until_halt();
+            let stats = machine.block_cache().map(|cache| cache.stats());
+            (machine.cpu, stop, stats)
+        };
+
+        let (cpu, stop, _) = run(false);
+        let (cached_cpu, cached_stop, stats) = run(true);
+        assert_eq!((cached_cpu, cached_stop), (cpu, stop));
+        assert_eq!(cpu.e, 0x20); // 1 + 2 + ... + 64, modulo 256
+        let stats = stats.unwrap();
+        assert!(stats.translated > 0);
+        assert!(stats.invalidated >= UNSTABLE as u64);
+    }
+}
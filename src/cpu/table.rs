@@ -0,0 +1,416 @@
+use std::marker::PhantomData;
+
+use super::{Cpu, Cycles, State};
+use crate::bus::{Access, Bus};
+
+// One handler per opcode, each monomorphized over its register, pair or
+// condition operand so nothing is decoded at run time.
+
+pub(super) type Handler<T> = fn(&mut Cpu, &mut T) -> Cycles;
+
+// a table per bus type, so memory access can be inlined into the handlers
+pub(super) struct Handlers<T: ?Sized>(PhantomData<T>);
+
+// register codes as encoded in the opcode, M is memory at HL
+const B: u8 = 0;
+const C: u8 = 1;
+const D: u8 = 2;
+const E: u8 = 3;
+const H: u8 = 4;
+const L: u8 = 5;
+const M: u8 = 6;
+const A: u8 = 7;
+
+// register pairs; PSW takes the place of SP for PUSH and POP
+const BC: u8 = 0;
+const DE: u8 = 1;
+const HL: u8 = 2;
+const SP: u8 = 3;
+const PSW: u8 = 3;
+
+// conditions
+const NZ: u8 = 0;
+const Z: u8 = 1;
+const NC: u8 = 2;
+const CY: u8 = 3;
+const PO: u8 = 4;
+const PE: u8 = 5;
+const P: u8 = 6;
+const MI: u8 = 7;
+
+// accumulator operations
+const ADD: u8 = 0;
+const ADC: u8 = 1;
+const SUB: u8 = 2;
+const SBB: u8 = 3;
+const ANA: u8 = 4;
+const XRA: u8 = 5;
+const ORA: u8 = 6;
+const CMP: u8 = 7;
+
+impl<T: Bus + ?Sized> Handlers<T> {
+    #[rustfmt::skip]
+    pub(super) const TABLE: [Handler<T>; 256] = [
+        // 0x00
+        nop, lxi::<BC, _>, stax::<BC, _>, inx::<BC, _>, inr::<B, _>, dcr::<B, _>, mvi::<B, _>, rlc,
+        nop, dad::<BC, _>, ldax::<BC, _>, dcx::<BC, _>, inr::<C, _>, dcr::<C, _>, mvi::<C, _>, rrc,
+        // 0x10
+        nop, lxi::<DE, _>, stax::<DE, _>, inx::<DE, _>, inr::<D, _>, dcr::<D, _>, mvi::<D, _>, ral,
+        nop, dad::<DE, _>, ldax::<DE, _>, dcx::<DE, _>, inr::<E, _>, dcr::<E, _>, mvi::<E, _>, rar,
+        // 0x20
+        nop, lxi::<HL, _>, shld, inx::<HL, _>, inr::<H, _>, dcr::<H, _>, mvi::<H, _>, daa,
+        nop, dad::<HL, _>, lhld, dcx::<HL, _>, inr::<L, _>, dcr::<L, _>, mvi::<L, _>, cma,
+        // 0x30
+        nop, lxi::<SP, _>, sta, inx::<SP, _>, inr::<M, _>, dcr::<M, _>, mvi::<M, _>, stc,
+        nop, dad::<SP, _>, lda, dcx::<SP, _>, inr::<A, _>, dcr::<A, _>, mvi::<A, _>, cmc,
+        // 0x40
+        mov::<B, B, _>, mov::<B, C, _>, mov::<B, D, _>, mov::<B, E, _>, mov::<B, H, _>, mov::<B, L, _>, mov::<B, M, _>, mov::<B, A, _>,
+        mov::<C, B, _>, mov::<C, C, _>, mov::<C, D, _>, mov::<C, E, _>, mov::<C, H, _>, mov::<C, L, _>, mov::<C, M, _>, mov::<C, A, _>,
+        // 0x50
+        mov::<D, B, _>, mov::<D, C, _>, mov::<D, D, _>, mov::<D, E, _>, mov::<D, H, _>, mov::<D, L, _>, mov::<D, M, _>, mov::<D, A, _>,
+        mov::<E, B, _>, mov::<E, C, _>, mov::<E, D, _>, mov::<E, E, _>, mov::<E, H, _>, mov::<E, L, _>, mov::<E, M, _>, mov::<E, A, _>,
+        // 0x60
+        mov::<H, B, _>, mov::<H, C, _>, mov::<H, D, _>, mov::<H, E, _>, mov::<H, H, _>, mov::<H, L, _>, mov::<H, M, _>, mov::<H, A, _>,
+        mov::<L, B, _>, mov::<L, C, _>, mov::<L, D, _>, mov::<L, E, _>, mov::<L, H, _>, mov::<L, L, _>, mov::<L, M, _>, mov::<L, A, _>,
+        // 0x70
+        mov::<M, B, _>, mov::<M, C, _>, mov::<M, D, _>, mov::<M, E, _>, mov::<M, H, _>, mov::<M, L, _>, hlt,            mov::<M, A, _>,
+        mov::<A, B, _>, mov::<A, C, _>, mov::<A, D, _>, mov::<A, E, _>, mov::<A, H, _>, mov::<A, L, _>, mov::<A, M, _>, mov::<A, A, _>,
+        // 0x80
+        alu::<ADD, B, _>, alu::<ADD, C, _>, alu::<ADD, D, _>, alu::<ADD, E, _>, alu::<ADD, H, _>, alu::<ADD, L, _>, alu::<ADD, M, _>, alu::<ADD, A, _>,
+        alu::<ADC, B, _>, alu::<ADC, C, _>, alu::<ADC, D, _>, alu::<ADC, E, _>, alu::<ADC, H, _>, alu::<ADC, L, _>, alu::<ADC, M, _>, alu::<ADC, A, _>,
+        // 0x90
+        alu::<SUB, B, _>, alu::<SUB, C, _>, alu::<SUB, D, _>, alu::<SUB, E, _>, alu::<SUB, H, _>, alu::<SUB, L, _>, alu::<SUB, M, _>, alu::<SUB, A, _>,
+        alu::<SBB, B, _>, alu::<SBB, C, _>, alu::<SBB, D, _>, alu::<SBB, E, _>, alu::<SBB, H, _>, alu::<SBB, L, _>, alu::<SBB, M, _>, alu::<SBB, A, _>,
+        // 0xA0
+        alu::<ANA, B, _>, alu::<ANA, C, _>, alu::<ANA, D, _>, alu::<ANA, E, _>, alu::<ANA, H, _>, alu::<ANA, L, _>, alu::<ANA, M, _>, alu::<ANA, A, _>,
+        alu::<XRA, B, _>, alu::<XRA, C, _>, alu::<XRA, D, _>, alu::<XRA, E, _>, alu::<XRA, H, _>, alu::<XRA, L, _>, alu::<XRA, M, _>, alu::<XRA, A, _>,
+        // 0xB0
+        alu::<ORA, B, _>, alu::<ORA, C, _>, alu::<ORA, D, _>, alu::<ORA, E, _>, alu::<ORA, H, _>, alu::<ORA, L, _>, alu::<ORA, M, _>, alu::<ORA, A, _>,
+        alu::<CMP, B, _>, alu::<CMP, C, _>, alu::<CMP, D, _>, alu::<CMP, E, _>, alu::<CMP, H, _>, alu::<CMP, L, _>, alu::<CMP, M, _>, alu::<CMP, A, _>,
+        // 0xC0
+        rcc::<NZ, _>, pop::<BC, _>,  jcc::<NZ, _>, jmp,  ccc::<NZ, _>, push::<BC, _>,  alui::<ADD, _>, rst::<0, _>,
+        rcc::<Z, _>,  ret,           jcc::<Z, _>,  jmp,  ccc::<Z, _>,  call,           alui::<ADC, _>, rst::<1, _>,
+        // 0xD0
+        rcc::<NC, _>, pop::<DE, _>,  jcc::<NC, _>, out,  ccc::<NC, _>, push::<DE, _>,  alui::<SUB, _>, rst::<2, _>,
+        rcc::<CY, _>, ret,           jcc::<CY, _>, inp,  ccc::<CY, _>, call,           alui::<SBB, _>, rst::<3, _>,
+        // 0xE0
+        rcc::<PO, _>, pop::<HL, _>,  jcc::<PO, _>, xthl, ccc::<PO, _>, push::<HL, _>,  alui::<ANA, _>, rst::<4, _>,
+        rcc::<PE, _>, pchl,          jcc::<PE, _>, xchg, ccc::<PE, _>, call,           alui::<XRA, _>, rst::<5, _>,
+        // 0xF0
+        rcc::<P, _>,  pop::<PSW, _>, jcc::<P, _>,  di,   ccc::<P, _>,  push::<PSW, _>, alui::<ORA, _>, rst::<6, _>,
+        rcc::<MI, _>, sphl,          jcc::<MI, _>, ei,   ccc::<MI, _>, call,           alui::<CMP, _>, rst::<7, _>,
+    ];
+}
+
+#[inline(always)]
+fn reg<const R: u8, T: Bus + ?Sized>(cpu: &Cpu, bus: &mut T) -> u8 {
+    match R {
+        B => cpu.b,
+        C => cpu.c,
+        D => cpu.d,
+        E => cpu.e,
+        H => cpu.h,
+        L => cpu.l,
+        M => bus.read_access(cpu.hl(), Access::Data),
+        _ => cpu.a,
+    }
+}
+
+#[inline(always)]
+fn set_reg<const R: u8, T: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut T, value: u8) {
+    match R {
+        B => cpu.b = value,
+        C => cpu.c = value,
+        D => cpu.d = value,
+        E => cpu.e = value,
+        H => cpu.h = value,
+        L => cpu.l = value,
+        M => bus.write_access(cpu.hl(), value, Access::Data),
+        _ => cpu.a = value,
+    }
+}
+
+#[inline(always)]
+fn pair<const P: u8>(cpu: &Cpu) -> u16 {
+    match P {
+        BC => cpu.bc(),
+        DE => cpu.de(),
+        HL => cpu.hl(),
+        _ => cpu.sp,
+    }
+}
+
+#[inline(always)]
+fn set_pair<const P: u8>(cpu: &mut Cpu, value: u16) {
+    match P {
+        BC => cpu.set_bc(value),
+        DE => cpu.set_de(value),
+        HL => cpu.set_hl(value),
+        _ => cpu.sp = value,
+    }
+}
+
+#[inline(always)]
+fn accumulate<const OP: u8>(cpu: &mut Cpu, value: u8) {
+    match OP {
+        ADD => cpu.op_add(value),
+        ADC => cpu.op_adc(value),
+        SUB => cpu.op_sub(value),
+        SBB => cpu.op_sbc(value),
+        ANA => cpu.op_and(value),
+        XRA => cpu.op_xor(value),
+        ORA => cpu.op_or(value),
+        _ => cpu.op_cp(value),
+    }
+}
+
+fn nop<T: Bus + ?Sized>(_: &mut Cpu, _: &mut T) -> Cycles {
+    4
+}
+
+fn hlt<T: Bus + ?Sized>(cpu: &mut Cpu, _: &mut T) -> Cycles {
+    cpu.state = State::Halted;
+    7
+}
+
+fn mov<const DST: u8, const SRC: u8, T: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut T) -> Cycles {
+    let value = reg::<SRC, _>(cpu, bus);
+    set_reg::<DST, _>(cpu, bus, value);
+    if DST == M || SRC == M { 7 } else { 5 }
+}
+
+fn mvi<const DST: u8, T: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut T) -> Cycles {
+    let value = cpu.fetch_byte(bus);
+    set_reg::<DST, _>(cpu, bus, value);
+    if DST == M { 10 } else { 7 }
+}
+
+fn ldax<const P: u8, T: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut T) -> Cycles {
+    cpu.a = bus.read_access(pair::<P>(cpu), Access::Data);
+    7
+}
+
+fn stax<const P: u8, T: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut T) -> Cycles {
+    bus.write_access(pair::<P>(cpu), cpu.a, Access::Data);
+    7
+}
+
+fn lda<T: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut T) -> Cycles {
+    let addr = cpu.fetch_word(bus);
+    cpu.a = bus.read_access(addr, Access::Data);
+    13
+}
+
+fn sta<T: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut T) -> Cycles {
+    let addr = cpu.fetch_word(bus);
+    bus.write_access(addr, cpu.a, Access::Data);
+    13
+}
+
+fn lxi<const P: u8, T: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut T) -> Cycles {
+    let value = cpu.fetch_word(bus);
+    set_pair::<P>(cpu, value);
+    10
+}
+
+fn lhld<T: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut T) -> Cycles {
+    let addr = cpu.fetch_word(bus);
+    let value = bus.read_word(addr, Access::Data);
+    cpu.set_hl(value);
+    16
+}
+
+fn shld<T: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut T) -> Cycles {
+    let addr = cpu.fetch_word(bus);
+    bus.write_word(addr, cpu.hl(), Access::Data);
+    16
+}
+
+fn sphl<T: Bus + ?Sized>(cpu: &mut Cpu, _: &mut T) -> Cycles {
+    cpu.sp = cpu.hl();
+    6
+}
+
+fn xthl<T: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut T) -> Cycles {
+    let hl_old = cpu.hl();
+    let sp_old = bus.read_word(cpu.sp, Access::Stack);
+    cpu.set_hl(sp_old);
+    bus.write_word(cpu.sp, hl_old, Access::Stack);
+    18
+}
+
+fn xchg<T: Bus + ?Sized>(cpu: &mut Cpu, _: &mut T) -> Cycles {
+    let de_old = cpu.de();
+    let hl_old = cpu.hl();
+    cpu.set_de(hl_old);
+    cpu.set_hl(de_old);
+    5
+}
+
+fn push<const P: u8, T: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut T) -> Cycles {
+    let value = if P == PSW { cpu.af() } else { pair::<P>(cpu) };
+    cpu.op_push(bus, value);
+    11
+}
+
+fn pop<const P: u8, T: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut T) -> Cycles {
+    let value = cpu.op_pop(bus);
+    if P == PSW {
+        cpu.set_af(value);
+    } else {
+        set_pair::<P>(cpu, value);
+    }
+    10
+}
+
+fn alu<const OP: u8, const SRC: u8, T: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut T) -> Cycles {
+    let value = reg::<SRC, _>(cpu, bus);
+    accumulate::<OP>(cpu, value);
+    if SRC == M { 7 } else { 4 }
+}
+
+fn alui<const OP: u8, T: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut T) -> Cycles {
+    let value = cpu.fetch_byte(bus);
+    accumulate::<OP>(cpu, value);
+    7
+}
+
+fn inr<const DST: u8, T: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut T) -> Cycles {
+    let value = reg::<DST, _>(cpu, bus);
+    let result = cpu.op_inc(value);
+    set_reg::<DST, _>(cpu, bus, result);
+    if DST == M { 10 } else { 5 }
+}
+
+fn dcr<const DST: u8, T: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut T) -> Cycles {
+    let value = reg::<DST, _>(cpu, bus);
+    let result = cpu.op_dec(value);
+    set_reg::<DST, _>(cpu, bus, result);
+    if DST == M { 10 } else { 5 }
+}
+
+fn dad<const P: u8, T: Bus + ?Sized>(cpu: &mut Cpu, _: &mut T) -> Cycles {
+    cpu.op_add16(pair::<P>(cpu));
+    10
+}
+
+fn inx<const P: u8, T: Bus + ?Sized>(cpu: &mut Cpu, _: &mut T) -> Cycles {
+    set_pair::<P>(cpu, pair::<P>(cpu).wrapping_add(1));
+    5
+}
+
+fn dcx<const P: u8, T: Bus + ?Sized>(cpu: &mut Cpu, _: &mut T) -> Cycles {
+    set_pair::<P>(cpu, pair::<P>(cpu).wrapping_sub(1));
+    5
+}
+
+fn rlc<T: Bus + ?Sized>(cpu: &mut Cpu, _: &mut T) -> Cycles {
+    cpu.flags.carry = cpu.a & 0x80 != 0;
+    cpu.a = cpu.a.rotate_left(1);
+    4
+}
+
+fn rrc<T: Bus + ?Sized>(cpu: &mut Cpu, _: &mut T) -> Cycles {
+    cpu.flags.carry = cpu.a & 0x01 != 0;
+    cpu.a = cpu.a.rotate_right(1);
+    4
+}
+
+fn ral<T: Bus + ?Sized>(cpu: &mut Cpu, _: &mut T) -> Cycles {
+    let msb = cpu.a & 0x80;
+    cpu.a = (cpu.a << 1) | cpu.flags.carry as u8;
+    cpu.flags.carry = msb != 0;
+    4
+}
+
+fn rar<T: Bus + ?Sized>(cpu: &mut Cpu, _: &mut T) -> Cycles {
+    let lsb = cpu.a & 0x01;
+    cpu.a = (cpu.a >> 1) | if cpu.flags.carry { 0x80 } else { 0 };
+    cpu.flags.carry = lsb != 0;
+    4
+}
+
+fn daa<T: Bus + ?Sized>(cpu: &mut Cpu, _: &mut T) -> Cycles {
+    cpu.daa();
+    4
+}
+
+fn cma<T: Bus + ?Sized>(cpu: &mut Cpu, _: &mut T) -> Cycles {
+    cpu.a = !cpu.a;
+    4
+}
+
+fn stc<T: Bus + ?Sized>(cpu: &mut Cpu, _: &mut T) -> Cycles {
+    cpu.flags.carry = true;
+    4
+}
+
+fn cmc<T: Bus + ?Sized>(cpu: &mut Cpu, _: &mut T) -> Cycles {
+    cpu.flags.carry = !cpu.flags.carry;
+    4
+}
+
+fn jmp<T: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut T) -> Cycles {
+    cpu.op_jp(bus, true);
+    10
+}
+
+fn jcc<const CC: u8, T: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut T) -> Cycles {
+    cpu.op_jp(bus, cpu.condition(CC));
+    10
+}
+
+fn ret<T: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut T) -> Cycles {
+    cpu.op_ret(bus, true);
+    10
+}
+
+fn rcc<const CC: u8, T: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut T) -> Cycles {
+    let condition = cpu.condition(CC);
+    cpu.op_ret(bus, condition);
+    if condition { 11 } else { 5 }
+}
+
+fn call<T: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut T) -> Cycles {
+    cpu.op_call(bus, true);
+    17
+}
+
+fn ccc<const CC: u8, T: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut T) -> Cycles {
+    let condition = cpu.condition(CC);
+    cpu.op_call(bus, condition);
+    if condition { 17 } else { 11 }
+}
+
+fn rst<const N: u16, T: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut T) -> Cycles {
+    cpu.op_push(bus, cpu.pc);
+    cpu.pc = N * 8;
+    11
+}
+
+fn pchl<T: Bus + ?Sized>(cpu: &mut Cpu, _: &mut T) -> Cycles {
+    cpu.pc = cpu.hl();
+    5
+}
+
+fn ei<T: Bus + ?Sized>(cpu: &mut Cpu, _: &mut T) -> Cycles {
+    cpu.iff = true;
+    4
+}
+
+fn di<T: Bus + ?Sized>(cpu: &mut Cpu, _: &mut T) -> Cycles {
+    cpu.iff = false;
+    4
+}
+
+fn inp<T: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut T) -> Cycles {
+    let port = cpu.fetch_byte(bus);
+    cpu.a = bus.input(port);
+    10
+}
+
+fn out<T: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut T) -> Cycles {
+    let port = cpu.fetch_byte(bus);
+    bus.output(port, cpu.a);
+    10
+}
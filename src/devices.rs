@@ -0,0 +1,6 @@
+pub mod i8253;
+pub mod i8255;
+pub mod i8259;
+pub mod terminal;
+pub mod upd765;
+pub mod wd179x;
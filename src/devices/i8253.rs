@@ -0,0 +1,621 @@
+use crate::bus::Device;
+use crate::signal::Line;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Mode {
+    InterruptOnTerminalCount,
+    OneShot,
+    RateGenerator,
+    SquareWave,
+    SoftwareStrobe,
+    HardwareStrobe,
+}
+
+impl Mode {
+    fn from_bits(bits: u8) -> Self {
+        match bits & 0x07 {
+            0 => Mode::InterruptOnTerminalCount,
+            1 => Mode::OneShot,
+            2 | 6 => Mode::RateGenerator,
+            3 | 7 => Mode::SquareWave,
+            4 => Mode::SoftwareStrobe,
+            5 => Mode::HardwareStrobe,
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Access {
+    Lsb,
+    Msb,
+    Word,
+}
+
+pub struct Counter {
+    // the access, mode and BCD bits as programmed, for read-back status;
+    // modes 6 and 7 read back as such
+    control: u8,
+    mode: Mode,
+    access: Access,
+    bcd: bool,
+    reload: u16,
+    count: u16,
+    latch: Option<u16>,
+    status: Option<u8>,
+    write_msb: bool,
+    read_msb: bool,
+    null_count: bool,
+    written: bool,
+    load: bool,
+    running: bool,
+    armed: bool,
+    odd_extra: bool,
+    gate: bool,
+    out: bool,
+    line: Line,
+}
+
+impl Counter {
+    fn new() -> Self {
+        Counter {
+            control: 0x30,
+            mode: Mode::InterruptOnTerminalCount,
+            access: Access::Word,
+            bcd: false,
+            reload: 0,
+            count: 0,
+            latch: None,
+            status: None,
+            write_msb: false,
+            read_msb: false,
+            null_count: true,
+            written: false,
+            load: false,
+            running: false,
+            armed: false,
+            odd_extra: false,
+            gate: true,
+            out: false,
+            line: Line::new(false),
+        }
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    pub fn count(&self) -> u16 {
+        self.count
+    }
+
+    pub fn out(&self) -> bool {
+        self.out
+    }
+
+    pub fn out_line(&self) -> Line {
+        self.line.clone()
+    }
+
+    fn set_out(&mut self, level: bool) {
+        self.out = level;
+        self.line.set(level);
+    }
+
+    fn program(&mut self, control: u8) {
+        self.control = control & 0x3F;
+        self.access = match (control >> 4) & 0x03 {
+            1 => Access::Lsb,
+            2 => Access::Msb,
+            _ => Access::Word,
+        };
+        self.mode = Mode::from_bits(control >> 1);
+        self.bcd = control & 0x01 != 0;
+
+        self.latch = None;
+        self.status = None;
+        self.write_msb = false;
+        self.read_msb = false;
+        self.null_count = true;
+        self.written = false;
+        self.load = false;
+        self.running = false;
+        self.armed = false;
+        self.odd_extra = false;
+        self.set_out(self.mode != Mode::InterruptOnTerminalCount);
+    }
+
+    fn latch_count(&mut self) {
+        if self.latch.is_none() {
+            self.latch = Some(self.count);
+        }
+    }
+
+    fn latch_status(&mut self) {
+        if self.status.is_none() {
+            self.status = Some(
+                (if self.out { 0x80 } else { 0 })
+                    | (if self.null_count { 0x40 } else { 0 })
+                    | self.control,
+            );
+        }
+    }
+
+    fn read(&mut self) -> u8 {
+        if let Some(status) = self.status.take() {
+            return status;
+        }
+
+        let value = self.latch.unwrap_or(self.count);
+        match self.access {
+            Access::Lsb => {
+                self.latch = None;
+                value as u8
+            }
+            Access::Msb => {
+                self.latch = None;
+                (value >> 8) as u8
+            }
+            Access::Word => {
+                if self.read_msb {
+                    self.read_msb = false;
+                    self.latch = None;
+                    (value >> 8) as u8
+                } else {
+                    self.read_msb = true;
+                    value as u8
+                }
+            }
+        }
+    }
+
+    fn write(&mut self, value: u8) {
+        match self.access {
+            Access::Lsb => self.reload = value as u16,
+            Access::Msb => self.reload = (value as u16) << 8,
+            Access::Word => {
+                if !self.write_msb {
+                    self.reload = (self.reload & 0xFF00) | value as u16;
+                    self.write_msb = true;
+                    if self.mode == Mode::InterruptOnTerminalCount {
+                        // writing the first byte stops the count
+                        self.running = false;
+                    }
+                    return;
+                }
+                self.reload = (self.reload & 0x00FF) | ((value as u16) << 8);
+                self.write_msb = false;
+            }
+        }
+
+        self.null_count = true;
+        self.written = true;
+
+        match self.mode {
+            Mode::InterruptOnTerminalCount => {
+                self.set_out(false);
+                self.load = true;
+            }
+            Mode::SoftwareStrobe => self.load = true,
+            Mode::RateGenerator | Mode::SquareWave => {
+                // a new count written while counting takes effect at the
+                // end of the current period
+                if !self.running {
+                    self.load = true;
+                }
+            }
+            Mode::OneShot | Mode::HardwareStrobe => {}
+        }
+    }
+
+    fn set_gate(&mut self, level: bool) {
+        let rising = level && !self.gate;
+        self.gate = level;
+
+        match self.mode {
+            Mode::OneShot | Mode::HardwareStrobe => {
+                if rising && self.written {
+                    self.load = true;
+                }
+            }
+            Mode::RateGenerator | Mode::SquareWave => {
+                if !level {
+                    self.set_out(true);
+                }
+                if rising && self.written {
+                    self.load = true;
+                }
+            }
+            Mode::InterruptOnTerminalCount | Mode::SoftwareStrobe => {}
+        }
+    }
+
+    fn clock(&mut self) {
+        if self.load {
+            self.load = false;
+            self.null_count = false;
+            self.running = true;
+            self.odd_extra = false;
+            match self.mode {
+                Mode::SquareWave => {
+                    self.count = self.reload & !1;
+                    self.set_out(true);
+                }
+                Mode::OneShot => {
+                    self.count = self.reload;
+                    self.set_out(false);
+                }
+                Mode::SoftwareStrobe | Mode::HardwareStrobe => {
+                    self.count = self.reload;
+                    self.armed = true;
+                }
+                Mode::InterruptOnTerminalCount | Mode::RateGenerator => {
+                    self.count = self.reload;
+                }
+            }
+            return;
+        }
+
+        if !self.running {
+            return;
+        }
+
+        match self.mode {
+            Mode::InterruptOnTerminalCount => {
+                if self.gate {
+                    self.count = self.decrement(self.count);
+                    if self.count == 0 {
+                        self.set_out(true);
+                    }
+                }
+            }
+            Mode::OneShot => {
+                self.count = self.decrement(self.count);
+                if self.count == 0 {
+                    self.set_out(true);
+                }
+            }
+            Mode::RateGenerator => {
+                if self.gate {
+                    if self.count == 1 {
+                        self.count = self.reload;
+                        self.null_count = false;
+                        self.set_out(true);
+                    } else {
+                        self.count = self.decrement(self.count);
+                        if self.count == 1 {
+                            self.set_out(false);
+                        }
+                    }
+                }
+            }
+            Mode::SquareWave => {
+                if self.gate {
+                    if self.odd_extra {
+                        // odd counts keep OUT high for one extra clock
+                        self.odd_extra = false;
+                        self.count = self.reload & !1;
+                        self.null_count = false;
+                        self.set_out(false);
+                        return;
+                    }
+                    self.count = self.decrement(self.decrement(self.count));
+                    if self.count == 0 {
+                        if self.out && self.reload & 1 != 0 {
+                            self.odd_extra = true;
+                        } else {
+                            self.count = self.reload & !1;
+                            self.null_count = false;
+                            self.set_out(!self.out);
+                        }
+                    }
+                }
+            }
+            Mode::SoftwareStrobe | Mode::HardwareStrobe => {
+                if self.gate || self.mode == Mode::HardwareStrobe {
+                    if !self.out {
+                        self.set_out(true);
+                    }
+                    self.count = self.decrement(self.count);
+                    if self.count == 0 && self.armed {
+                        self.armed = false;
+                        self.set_out(false);
+                    }
+                }
+            }
+        }
+    }
+
+    fn decrement(&self, value: u16) -> u16 {
+        if self.bcd {
+            bcd_decrement(value)
+        } else {
+            value.wrapping_sub(1)
+        }
+    }
+}
+
+fn bcd_decrement(value: u16) -> u16 {
+    if value == 0 {
+        return 0x9999;
+    }
+    let mut r = value;
+    for shift in (0..16).step_by(4) {
+        if (r >> shift) & 0x0F != 0 {
+            r -= 1 << shift;
+            break;
+        }
+        r |= 0x09 << shift;
+    }
+    r
+}
+
+// Intel 8253/8254 programmable interval timer; ports 0-2 address the
+// counters, port 3 is the control word register
+pub struct Pit {
+    counters: [Counter; 3],
+}
+
+impl Pit {
+    pub fn new() -> Self {
+        Pit {
+            counters: [Counter::new(), Counter::new(), Counter::new()],
+        }
+    }
+
+    pub fn counter(&self, index: usize) -> &Counter {
+        &self.counters[index]
+    }
+
+    pub fn out(&self, index: usize) -> bool {
+        self.counters[index].out()
+    }
+
+    pub fn out_line(&self, index: usize) -> Line {
+        self.counters[index].out_line()
+    }
+
+    pub fn set_gate(&mut self, index: usize, level: bool) {
+        self.counters[index].set_gate(level);
+    }
+
+    // one CLK pulse on a single counter
+    pub fn clock(&mut self, index: usize) {
+        self.counters[index].clock();
+    }
+
+    // CLK pulses on all counters, for boards feeding them a common clock
+    pub fn tick(&mut self, pulses: u64) {
+        for _ in 0..pulses {
+            for counter in &mut self.counters {
+                counter.clock();
+            }
+        }
+    }
+
+    pub fn read(&mut self, port: u8) -> u8 {
+        match port & 0x03 {
+            3 => 0xFF,
+            index => self.counters[index as usize].read(),
+        }
+    }
+
+    pub fn write(&mut self, port: u8, value: u8) {
+        match port & 0x03 {
+            3 => self.control(value),
+            index => self.counters[index as usize].write(value),
+        }
+    }
+
+    fn control(&mut self, value: u8) {
+        let select = (value >> 6) as usize;
+        if select == 3 {
+            // 8254 read-back command
+            for (index, counter) in self.counters.iter_mut().enumerate() {
+                if value & (0x02 << index) == 0 {
+                    continue;
+                }
+                if value & 0x20 == 0 {
+                    counter.latch_count();
+                }
+                if value & 0x10 == 0 {
+                    counter.latch_status();
+                }
+            }
+            return;
+        }
+
+        let counter = &mut self.counters[select];
+        if value & 0x30 == 0 {
+            counter.latch_count();
+        } else {
+            counter.program(value);
+        }
+    }
+}
+
+impl Device for Pit {
+    fn input(&mut self, port: u8) -> u8 {
+        self.read(port)
+    }
+
+    fn output(&mut self, port: u8, value: u8) {
+        self.write(port, value);
+    }
+}
+
+impl Default for Pit {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup(mode: u8, count: u16) -> Pit {
+        let mut pit = Pit::new();
+        pit.write(3, 0x30 | (mode << 1));
+        pit.write(0, count as u8);
+        pit.write(0, (count >> 8) as u8);
+        pit
+    }
+
+    fn wave(pit: &mut Pit, pulses: usize) -> String {
+        (0..pulses)
+            .map(|_| {
+                pit.clock(0);
+                if pit.out(0) { 'H' } else { 'L' }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_mode0_interrupt_on_terminal_count() {
+        let mut pit = setup(0, 4);
+        assert!(!pit.out(0));
+        assert_eq!(wave(&mut pit, 7), "LLLLHHH");
+
+        // gate low suspends counting
+        let mut pit = setup(0, 3);
+        pit.set_gate(0, false);
+        assert_eq!(wave(&mut pit, 4), "LLLL");
+        pit.set_gate(0, true);
+        assert_eq!(wave(&mut pit, 4), "LLHH");
+    }
+
+    #[test]
+    fn test_mode1_one_shot() {
+        let mut pit = setup(1, 3);
+        assert_eq!(wave(&mut pit, 3), "HHH");
+        pit.set_gate(0, false);
+        pit.set_gate(0, true);
+        assert_eq!(wave(&mut pit, 6), "LLLHHH");
+
+        // retriggering restarts the pulse
+        pit.set_gate(0, false);
+        pit.set_gate(0, true);
+        assert_eq!(wave(&mut pit, 2), "LL");
+        pit.set_gate(0, false);
+        pit.set_gate(0, true);
+        assert_eq!(wave(&mut pit, 4), "LLLH");
+    }
+
+    #[test]
+    fn test_mode2_rate_generator() {
+        let mut pit = setup(2, 3);
+        assert_eq!(wave(&mut pit, 10), "HHLHHLHHLH");
+
+        // a new count takes effect on the next period
+        pit.write(0, 4);
+        pit.write(0, 0);
+        assert_eq!(wave(&mut pit, 6), "HLHHHL");
+    }
+
+    #[test]
+    fn test_mode3_square_wave() {
+        let mut pit = setup(3, 4);
+        assert_eq!(wave(&mut pit, 9), "HHLLHHLLH");
+
+        let mut pit = setup(3, 5);
+        assert_eq!(wave(&mut pit, 11), "HHHLLHHHLLH");
+
+        // gate low forces OUT high
+        let mut pit = setup(3, 4);
+        assert_eq!(wave(&mut pit, 3), "HHL");
+        pit.set_gate(0, false);
+        assert!(pit.out(0));
+        pit.set_gate(0, true);
+        assert_eq!(wave(&mut pit, 5), "HHLLH");
+    }
+
+    #[test]
+    fn test_mode4_software_strobe() {
+        let mut pit = setup(4, 3);
+        assert_eq!(wave(&mut pit, 8), "HHHLHHHH");
+    }
+
+    #[test]
+    fn test_mode5_hardware_strobe() {
+        let mut pit = setup(5, 3);
+        assert_eq!(wave(&mut pit, 3), "HHH");
+        pit.set_gate(0, false);
+        pit.set_gate(0, true);
+        assert_eq!(wave(&mut pit, 6), "HHHLHH");
+    }
+
+    #[test]
+    fn test_latch_and_read_back() {
+        let mut pit = setup(2, 0x1234);
+        pit.tick(3);
+
+        // counter latch command freezes the value until it is read
+        pit.write(3, 0x00);
+        pit.tick(5);
+        assert_eq!(pit.read(0), 0x32);
+        assert_eq!(pit.read(0), 0x12);
+        assert_eq!(pit.read(0), 0x2D);
+        assert_eq!(pit.read(0), 0x12);
+
+        // read-back of status then count
+        pit.write(3, 0xC2);
+        assert_eq!(pit.read(0), 0b1011_0100);
+        assert_eq!(pit.read(0), 0x2D);
+        assert_eq!(pit.read(0), 0x12);
+
+        // null count is reported until the count is loaded
+        pit.write(0, 0x00);
+        pit.write(0, 0x10);
+        pit.write(3, 0xE2);
+        assert_eq!(pit.read(0) & 0x40, 0x40);
+
+        // mode 6 runs as mode 2 but reads back as programmed
+        pit.write(3, 0x3C);
+        pit.write(3, 0xE2);
+        assert_eq!(pit.read(0) & 0x3F, 0x3C);
+        assert_eq!(pit.counter(0).mode(), Mode::RateGenerator);
+    }
+
+    #[test]
+    fn test_bcd_count() {
+        let mut pit = Pit::new();
+        pit.write(3, 0x51); // counter 1, LSB only, mode 0, BCD
+        pit.write(1, 0x10);
+        pit.clock(1);
+        pit.clock(1);
+        assert_eq!(pit.read(1), 0x09);
+        for _ in 0..9 {
+            pit.clock(1);
+        }
+        assert!(pit.out(1));
+        pit.clock(1);
+        assert_eq!(pit.counter(1).count(), 0x9999);
+    }
+
+    #[test]
+    fn test_out_drives_interrupt() {
+        use crate::machine::SimpleMachine;
+
+        let mut pit = setup(2, 4);
+        let irq = pit.out_line(0);
+
+        let mut machine = SimpleMachine::new();
+        machine.cpu.sp = 0x1000;
+        machine.load(0x0000, &[0xFB, 0x00, 0x00, 0x00, 0x76]); // EI; NOP; NOP; NOP; HLT
+
+        let mut serviced = false;
+        for _ in 0..8 {
+            machine.step();
+            pit.clock(0);
+            if !irq.get() {
+                serviced = machine.interrupt(&[0xFF]).is_some(); // RST 7
+                break;
+            }
+        }
+
+        assert!(serviced);
+        assert_eq!(machine.cpu.pc, 0x0038);
+        assert!(!machine.cpu.iff);
+    }
+}
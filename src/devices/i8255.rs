@@ -0,0 +1,467 @@
+use crate::bus::Device;
+use crate::signal::Line;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Mode {
+    Basic,
+    Strobed,
+    Bidirectional,
+}
+
+// handshake state of a strobed port (mode 1 or 2)
+#[derive(Clone, Copy, Default)]
+struct Handshake {
+    input_latch: u8,
+    ibf: bool,
+    obf: bool,
+    inte_in: bool,
+    inte_out: bool,
+}
+
+// Intel 8255 programmable peripheral interface; ports 0-2 are A, B and C,
+// port 3 is the control word register. The pins are driven by host code
+// through the `port_*`/`set_port_*` accessors and the strobe/acknowledge
+// handshakes.
+pub struct Ppi {
+    mode_a: Mode,
+    mode_b: Mode,
+    a_input: bool,
+    b_input: bool,
+    c_upper_input: bool,
+    c_lower_input: bool,
+
+    a_latch: u8,
+    b_latch: u8,
+    c_latch: u8,
+
+    a_pins: u8,
+    b_pins: u8,
+    c_pins: u8,
+
+    hs_a: Handshake,
+    hs_b: Handshake,
+
+    intr_a: Line,
+    intr_b: Line,
+}
+
+impl Ppi {
+    pub fn new() -> Self {
+        let mut ppi = Ppi {
+            mode_a: Mode::Basic,
+            mode_b: Mode::Basic,
+            a_input: true,
+            b_input: true,
+            c_upper_input: true,
+            c_lower_input: true,
+            a_latch: 0,
+            b_latch: 0,
+            c_latch: 0,
+            a_pins: 0,
+            b_pins: 0,
+            c_pins: 0,
+            hs_a: Handshake::default(),
+            hs_b: Handshake::default(),
+            intr_a: Line::new(false),
+            intr_b: Line::new(false),
+        };
+        ppi.control(0x9B); // all ports input after reset
+        ppi
+    }
+
+    pub fn mode_a(&self) -> Mode {
+        self.mode_a
+    }
+
+    pub fn mode_b(&self) -> Mode {
+        self.mode_b
+    }
+
+    pub fn intr_a(&self) -> Line {
+        self.intr_a.clone()
+    }
+
+    pub fn intr_b(&self) -> Line {
+        self.intr_b.clone()
+    }
+
+    // the levels a peripheral sees on the port pins
+    pub fn port_a(&self) -> u8 {
+        match self.mode_a {
+            Mode::Basic if self.a_input => self.a_pins,
+            Mode::Strobed if self.a_input => self.a_pins,
+            _ => self.a_latch,
+        }
+    }
+
+    pub fn port_b(&self) -> u8 {
+        if self.b_input {
+            self.b_pins
+        } else {
+            self.b_latch
+        }
+    }
+
+    pub fn port_c(&self) -> u8 {
+        let mut value = self.c_basic(self.c_pins);
+
+        // handshake outputs: IBF is active high, OBF is active low
+        if self.mode_a != Mode::Basic {
+            value = set_bit(value, 3, self.intr_a.get());
+            if self.mode_a == Mode::Bidirectional || self.a_input {
+                value = set_bit(value, 5, self.hs_a.ibf);
+            }
+            if self.mode_a == Mode::Bidirectional || !self.a_input {
+                value = set_bit(value, 7, !self.hs_a.obf);
+            }
+        }
+        if self.mode_b != Mode::Basic {
+            value = set_bit(value, 0, self.intr_b.get());
+            value = set_bit(
+                value,
+                1,
+                if self.b_input {
+                    self.hs_b.ibf
+                } else {
+                    !self.hs_b.obf
+                },
+            );
+        }
+        value
+    }
+
+    pub fn set_port_a(&mut self, value: u8) {
+        self.a_pins = value;
+    }
+
+    pub fn set_port_b(&mut self, value: u8) {
+        self.b_pins = value;
+    }
+
+    pub fn set_port_c(&mut self, value: u8) {
+        self.c_pins = value;
+    }
+
+    pub fn ibf_a(&self) -> bool {
+        self.hs_a.ibf
+    }
+
+    pub fn obf_a(&self) -> bool {
+        self.hs_a.obf
+    }
+
+    pub fn ibf_b(&self) -> bool {
+        self.hs_b.ibf
+    }
+
+    pub fn obf_b(&self) -> bool {
+        self.hs_b.obf
+    }
+
+    // peripheral pulses STB with data on the port A pins
+    pub fn strobe_a(&mut self, value: u8) {
+        self.a_pins = value;
+        if self.mode_a == Mode::Bidirectional || (self.mode_a == Mode::Strobed && self.a_input) {
+            self.hs_a.input_latch = value;
+            self.hs_a.ibf = true;
+            self.update_intr();
+        }
+    }
+
+    pub fn strobe_b(&mut self, value: u8) {
+        self.b_pins = value;
+        if self.mode_b == Mode::Strobed && self.b_input {
+            self.hs_b.input_latch = value;
+            self.hs_b.ibf = true;
+            self.update_intr();
+        }
+    }
+
+    // peripheral pulses ACK and takes the byte the CPU wrote
+    pub fn acknowledge_a(&mut self) -> u8 {
+        if self.mode_a == Mode::Bidirectional || (self.mode_a == Mode::Strobed && !self.a_input) {
+            self.hs_a.obf = false;
+            self.update_intr();
+        }
+        self.a_latch
+    }
+
+    pub fn acknowledge_b(&mut self) -> u8 {
+        if self.mode_b == Mode::Strobed && !self.b_input {
+            self.hs_b.obf = false;
+            self.update_intr();
+        }
+        self.b_latch
+    }
+
+    pub fn read(&mut self, port: u8) -> u8 {
+        match port & 0x03 {
+            0 => match self.mode_a {
+                Mode::Basic => self.port_a(),
+                Mode::Strobed if !self.a_input => self.a_latch,
+                _ => {
+                    self.hs_a.ibf = false;
+                    self.update_intr();
+                    self.hs_a.input_latch
+                }
+            },
+            1 => match self.mode_b {
+                Mode::Strobed if self.b_input => {
+                    self.hs_b.ibf = false;
+                    self.update_intr();
+                    self.hs_b.input_latch
+                }
+                _ => self.port_b(),
+            },
+            2 => self.status(),
+            _ => 0xFF,
+        }
+    }
+
+    pub fn write(&mut self, port: u8, value: u8) {
+        match port & 0x03 {
+            0 => {
+                self.a_latch = value;
+                if self.mode_a == Mode::Bidirectional
+                    || (self.mode_a == Mode::Strobed && !self.a_input)
+                {
+                    self.hs_a.obf = true;
+                    self.update_intr();
+                }
+            }
+            1 => {
+                self.b_latch = value;
+                if self.mode_b == Mode::Strobed && !self.b_input {
+                    self.hs_b.obf = true;
+                    self.update_intr();
+                }
+            }
+            2 => self.c_latch = value,
+            _ => {
+                if value & 0x80 != 0 {
+                    self.control(value);
+                } else {
+                    self.bit_set_reset(value);
+                }
+            }
+        }
+    }
+
+    fn control(&mut self, value: u8) {
+        self.mode_a = match (value >> 5) & 0x03 {
+            0 => Mode::Basic,
+            1 => Mode::Strobed,
+            _ => Mode::Bidirectional,
+        };
+        self.mode_b = if value & 0x04 != 0 {
+            Mode::Strobed
+        } else {
+            Mode::Basic
+        };
+        self.a_input = value & 0x10 != 0;
+        self.c_upper_input = value & 0x08 != 0;
+        self.b_input = value & 0x02 != 0;
+        self.c_lower_input = value & 0x01 != 0;
+
+        // a mode change clears all output latches and handshake state
+        self.a_latch = 0;
+        self.b_latch = 0;
+        self.c_latch = 0;
+        self.hs_a = Handshake::default();
+        self.hs_b = Handshake::default();
+        self.update_intr();
+    }
+
+    fn bit_set_reset(&mut self, value: u8) {
+        let bit = (value >> 1) & 0x07;
+        let level = value & 0x01 != 0;
+
+        // in the strobed modes the STB/ACK bit positions hold the INTE flags
+        match (bit, self.mode_a, self.mode_b) {
+            (4, Mode::Strobed, _) if self.a_input => self.hs_a.inte_in = level,
+            (6, Mode::Strobed, _) if !self.a_input => self.hs_a.inte_out = level,
+            (4, Mode::Bidirectional, _) => self.hs_a.inte_in = level,
+            (6, Mode::Bidirectional, _) => self.hs_a.inte_out = level,
+            (2, _, Mode::Strobed) => {
+                self.hs_b.inte_in = level;
+                self.hs_b.inte_out = level;
+            }
+            _ => self.c_latch = set_bit(self.c_latch, bit, level),
+        }
+        self.update_intr();
+    }
+
+    // port C bits in basic mode: outputs come from the latch, inputs from
+    // the pins
+    fn c_basic(&self, pins: u8) -> u8 {
+        let mut mask = 0;
+        if !self.c_upper_input {
+            mask |= 0xF0;
+        }
+        if !self.c_lower_input {
+            mask |= 0x0F;
+        }
+        (self.c_latch & mask) | (pins & !mask)
+    }
+
+    // port C as read by the CPU, with the handshake status in place of the
+    // control bits
+    fn status(&self) -> u8 {
+        let mut value = self.c_basic(self.c_pins);
+
+        match self.mode_a {
+            Mode::Basic => {}
+            Mode::Strobed if self.a_input => {
+                value = set_bit(value, 3, self.intr_a.get());
+                value = set_bit(value, 4, self.hs_a.inte_in);
+                value = set_bit(value, 5, self.hs_a.ibf);
+            }
+            Mode::Strobed => {
+                value = set_bit(value, 3, self.intr_a.get());
+                value = set_bit(value, 6, self.hs_a.inte_out);
+                value = set_bit(value, 7, !self.hs_a.obf);
+            }
+            Mode::Bidirectional => {
+                value = set_bit(value, 3, self.intr_a.get());
+                value = set_bit(value, 4, self.hs_a.inte_in);
+                value = set_bit(value, 5, self.hs_a.ibf);
+                value = set_bit(value, 6, self.hs_a.inte_out);
+                value = set_bit(value, 7, !self.hs_a.obf);
+            }
+        }
+
+        if self.mode_b == Mode::Strobed {
+            value = set_bit(value, 0, self.intr_b.get());
+            value = set_bit(
+                value,
+                1,
+                if self.b_input {
+                    self.hs_b.ibf
+                } else {
+                    !self.hs_b.obf
+                },
+            );
+            value = set_bit(value, 2, self.hs_b.inte_in);
+        }
+
+        value
+    }
+
+    fn update_intr(&mut self) {
+        let input_a = self.hs_a.inte_in && self.hs_a.ibf;
+        let output_a = self.hs_a.inte_out && !self.hs_a.obf;
+        let intr_a = match self.mode_a {
+            Mode::Basic => false,
+            Mode::Strobed if self.a_input => input_a,
+            Mode::Strobed => output_a,
+            Mode::Bidirectional => input_a || output_a,
+        };
+
+        let intr_b = match self.mode_b {
+            Mode::Strobed if self.b_input => self.hs_b.inte_in && self.hs_b.ibf,
+            Mode::Strobed => self.hs_b.inte_out && !self.hs_b.obf,
+            _ => false,
+        };
+
+        self.intr_a.set(intr_a);
+        self.intr_b.set(intr_b);
+    }
+}
+
+fn set_bit(value: u8, bit: u8, level: bool) -> u8 {
+    if level {
+        value | (1 << bit)
+    } else {
+        value & !(1 << bit)
+    }
+}
+
+impl Device for Ppi {
+    fn input(&mut self, port: u8) -> u8 {
+        self.read(port)
+    }
+
+    fn output(&mut self, port: u8, value: u8) {
+        self.write(port, value);
+    }
+}
+
+impl Default for Ppi {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mode0() {
+        let mut ppi = Ppi::new();
+        ppi.write(3, 0x91); // A in, B out, C upper out, C lower in
+        ppi.set_port_a(0x5A);
+        ppi.set_port_c(0xFF);
+        ppi.write(1, 0xC3);
+        ppi.write(2, 0xA0);
+
+        assert_eq!(ppi.read(0), 0x5A);
+        assert_eq!(ppi.port_b(), 0xC3);
+        assert_eq!(ppi.read(2), 0xAF);
+
+        // bit set/reset only touches the addressed bit
+        ppi.write(3, 0x0D); // set PC6
+        ppi.write(3, 0x0E); // reset PC7
+        assert_eq!(ppi.port_c() & 0xF0, 0x60);
+    }
+
+    #[test]
+    fn test_mode1_input() {
+        let mut ppi = Ppi::new();
+        ppi.write(3, 0xB0); // A mode 1 input
+        ppi.write(3, 0x09); // INTE A (PC4)
+        assert!(!ppi.intr_a().get());
+
+        ppi.strobe_a(0x42);
+        assert!(ppi.ibf_a());
+        assert!(ppi.intr_a().get());
+        assert_eq!(ppi.read(2) & 0x38, 0x38);
+
+        assert_eq!(ppi.read(0), 0x42);
+        assert!(!ppi.ibf_a());
+        assert!(!ppi.intr_a().get());
+    }
+
+    #[test]
+    fn test_mode1_output() {
+        let mut ppi = Ppi::new();
+        ppi.write(3, 0x84); // B mode 1 output
+        ppi.write(3, 0x05); // INTE B (PC2)
+        assert!(ppi.intr_b().get());
+
+        ppi.write(1, 0x99);
+        assert!(ppi.obf_b());
+        assert!(!ppi.intr_b().get());
+        assert_eq!(ppi.port_c() & 0x02, 0x00);
+
+        assert_eq!(ppi.acknowledge_b(), 0x99);
+        assert!(!ppi.obf_b());
+        assert!(ppi.intr_b().get());
+    }
+
+    #[test]
+    fn test_mode2() {
+        let mut ppi = Ppi::new();
+        ppi.write(3, 0xC0); // A mode 2
+        ppi.write(3, 0x09); // INTE 2 (PC4)
+
+        ppi.write(0, 0x11);
+        assert!(ppi.obf_a());
+        assert_eq!(ppi.acknowledge_a(), 0x11);
+
+        ppi.strobe_a(0x22);
+        assert!(ppi.intr_a().get());
+        assert_eq!(ppi.read(0), 0x22);
+        assert!(!ppi.intr_a().get());
+    }
+}
@@ -0,0 +1,483 @@
+use crate::bus::Device;
+use crate::signal::Line;
+
+// what the PIC drives onto the data bus during an interrupt acknowledge
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Response {
+    // complete CALL instruction for the CPU
+    Call([u8; 3]),
+    // the CALL opcode was sent, the address bytes come from a slave
+    Cascade(u8),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Init {
+    Ready,
+    Icw2,
+    Icw3,
+    Icw4,
+}
+
+// Intel 8259A programmable interrupt controller in 8080 mode; port 0 is A0=0
+// (ICW1, OCW2, OCW3, IRR/ISR/poll), port 1 is A0=1 (ICW2-4, OCW1/IMR)
+pub struct Pic {
+    init: Init,
+    icw1: u8,
+    vector_hi: u8,
+    cascade: u8,
+    icw4: u8,
+
+    irr: u8,
+    isr: u8,
+    imr: u8,
+    lowest: u8,
+
+    read_isr: bool,
+    poll: bool,
+    special_mask: bool,
+    rotate_aeoi: bool,
+
+    master: bool,
+    levels: u8,
+    inputs: [Option<Line>; 8],
+    int: Line,
+}
+
+impl Pic {
+    pub fn new() -> Self {
+        Pic {
+            init: Init::Ready,
+            icw1: 0,
+            vector_hi: 0,
+            cascade: 0,
+            icw4: 0,
+            irr: 0,
+            isr: 0,
+            imr: 0,
+            lowest: 7,
+            read_isr: false,
+            poll: false,
+            special_mask: false,
+            rotate_aeoi: false,
+            master: true,
+            levels: 0,
+            inputs: Default::default(),
+            int: Line::new(false),
+        }
+    }
+
+    pub fn int_line(&self) -> Line {
+        self.int.clone()
+    }
+
+    pub fn irr(&self) -> u8 {
+        self.irr
+    }
+
+    pub fn isr(&self) -> u8 {
+        self.isr
+    }
+
+    pub fn imr(&self) -> u8 {
+        self.imr
+    }
+
+    // level of the SP/EN pin, which selects master or slave when not in
+    // buffered mode
+    pub fn set_master(&mut self, master: bool) {
+        self.master = master;
+    }
+
+    // wire an IR input to a line driven by another device
+    pub fn connect(&mut self, ir: u8, line: Line) {
+        self.inputs[ir as usize] = Some(line);
+    }
+
+    // sample the connected IR lines
+    pub fn update(&mut self) {
+        for ir in 0..8 {
+            if let Some(level) = self.inputs[ir as usize].as_ref().map(Line::get) {
+                self.set_ir(ir, level);
+            }
+        }
+    }
+
+    pub fn set_ir(&mut self, ir: u8, level: bool) {
+        let bit = 1 << ir;
+        let rising = level && self.levels & bit == 0;
+
+        if level {
+            self.levels |= bit;
+        } else {
+            self.levels &= !bit;
+        }
+
+        if self.level_triggered() {
+            if level {
+                self.irr |= bit;
+            } else {
+                self.irr &= !bit;
+            }
+        } else if rising {
+            self.irr |= bit;
+        }
+
+        self.update_int();
+    }
+
+    // INTA sequence: resolves the highest priority request and returns the
+    // CALL instruction, or defers the address bytes to a cascaded slave
+    pub fn acknowledge(&mut self) -> Option<Response> {
+        let ir = self.resolve()?;
+        self.service(ir);
+
+        if self.is_master() && self.cascade & (1 << ir) != 0 {
+            Some(Response::Cascade(ir))
+        } else {
+            Some(Response::Call(self.call(ir)))
+        }
+    }
+
+    // second and third INTA cycles for a slave selected on the cascade lines
+    pub fn acknowledge_slave(&mut self, id: u8) -> Option<[u8; 3]> {
+        if self.is_master() || self.cascade & 0x07 != id {
+            return None;
+        }
+        let ir = self.resolve().unwrap_or(7);
+        self.service(ir);
+        Some(self.call(ir))
+    }
+
+    pub fn read(&mut self, port: u8) -> u8 {
+        if port & 0x01 != 0 {
+            return self.imr;
+        }
+
+        if self.poll {
+            self.poll = false;
+            return match self.resolve() {
+                Some(ir) => {
+                    self.service(ir);
+                    0x80 | ir
+                }
+                None => 0,
+            };
+        }
+
+        if self.read_isr { self.isr } else { self.irr }
+    }
+
+    pub fn write(&mut self, port: u8, value: u8) {
+        if port & 0x01 == 0 {
+            if value & 0x10 != 0 {
+                self.icw1(value);
+            } else if value & 0x08 != 0 {
+                self.ocw3(value);
+            } else {
+                self.ocw2(value);
+            }
+            self.update_int();
+            return;
+        }
+
+        match self.init {
+            Init::Icw2 => {
+                self.vector_hi = value;
+                self.init = if self.icw1 & 0x02 == 0 {
+                    Init::Icw3
+                } else if self.icw1 & 0x01 != 0 {
+                    Init::Icw4
+                } else {
+                    Init::Ready
+                };
+            }
+            Init::Icw3 => {
+                self.cascade = value;
+                self.init = if self.icw1 & 0x01 != 0 {
+                    Init::Icw4
+                } else {
+                    Init::Ready
+                };
+            }
+            Init::Icw4 => {
+                self.icw4 = value;
+                self.init = Init::Ready;
+            }
+            Init::Ready => self.imr = value,
+        }
+        self.update_int();
+    }
+
+    fn icw1(&mut self, value: u8) {
+        self.icw1 = value;
+        self.icw4 = 0;
+        self.cascade = 0;
+        self.imr = 0;
+        self.isr = 0;
+        self.irr = 0;
+        self.lowest = 7;
+        self.read_isr = false;
+        self.poll = false;
+        self.special_mask = false;
+        self.rotate_aeoi = false;
+        self.init = Init::Icw2;
+    }
+
+    fn ocw2(&mut self, value: u8) {
+        let level = value & 0x07;
+        match value >> 5 {
+            // non-specific EOI
+            0b001 => {
+                if let Some(ir) = self.highest_in_service() {
+                    self.isr &= !(1 << ir);
+                }
+            }
+            // specific EOI
+            0b011 => self.isr &= !(1 << level),
+            // rotate on non-specific EOI
+            0b101 => {
+                if let Some(ir) = self.highest_in_service() {
+                    self.isr &= !(1 << ir);
+                    self.lowest = ir;
+                }
+            }
+            // rotate in automatic EOI mode
+            0b100 => self.rotate_aeoi = true,
+            0b000 => self.rotate_aeoi = false,
+            // rotate on specific EOI
+            0b111 => {
+                self.isr &= !(1 << level);
+                self.lowest = level;
+            }
+            // set priority
+            0b110 => self.lowest = level,
+            _ => {}
+        }
+    }
+
+    fn ocw3(&mut self, value: u8) {
+        if value & 0x40 != 0 {
+            self.special_mask = value & 0x20 != 0;
+        }
+        if value & 0x02 != 0 {
+            self.read_isr = value & 0x01 != 0;
+        }
+        self.poll = value & 0x04 != 0;
+    }
+
+    fn level_triggered(&self) -> bool {
+        self.icw1 & 0x08 != 0
+    }
+
+    fn auto_eoi(&self) -> bool {
+        self.icw4 & 0x02 != 0
+    }
+
+    fn special_fully_nested(&self) -> bool {
+        self.icw4 & 0x10 != 0
+    }
+
+    fn is_master(&self) -> bool {
+        if self.icw1 & 0x02 != 0 {
+            return true;
+        }
+        // in buffered mode M/S selects the role, otherwise the SP/EN pin
+        if self.icw4 & 0x08 != 0 {
+            self.icw4 & 0x04 != 0
+        } else {
+            self.master
+        }
+    }
+
+    // IR levels in priority order, highest first
+    fn priority(&self) -> impl Iterator<Item = u8> + use<> {
+        let highest = (self.lowest + 1) & 0x07;
+        (0..8).map(move |n| (highest + n) & 0x07)
+    }
+
+    fn highest_in_service(&self) -> Option<u8> {
+        self.priority().find(|ir| self.isr & (1 << ir) != 0)
+    }
+
+    fn resolve(&self) -> Option<u8> {
+        let requests = self.irr & !self.imr;
+
+        for ir in self.priority() {
+            let bit = 1 << ir;
+            if self.special_mask {
+                if requests & bit != 0 && self.isr & bit == 0 {
+                    return Some(ir);
+                }
+                continue;
+            }
+            if self.isr & bit != 0 {
+                // an in-service level blocks itself and everything below,
+                // except a slave input in special fully nested mode
+                let nested =
+                    self.special_fully_nested() && self.is_master() && self.cascade & bit != 0;
+                return if nested && requests & bit != 0 {
+                    Some(ir)
+                } else {
+                    None
+                };
+            }
+            if requests & bit != 0 {
+                return Some(ir);
+            }
+        }
+        None
+    }
+
+    fn service(&mut self, ir: u8) {
+        let bit = 1 << ir;
+        if !self.level_triggered() {
+            self.irr &= !bit;
+        }
+        if self.auto_eoi() {
+            if self.rotate_aeoi {
+                self.lowest = ir;
+            }
+        } else {
+            self.isr |= bit;
+        }
+        self.update_int();
+    }
+
+    fn call(&self, ir: u8) -> [u8; 3] {
+        let lo = if self.icw1 & 0x04 != 0 {
+            (self.icw1 & 0xE0) | (ir << 2)
+        } else {
+            (self.icw1 & 0xC0) | (ir << 3)
+        };
+        [0xCD, lo, self.vector_hi]
+    }
+
+    fn update_int(&mut self) {
+        let pending = self.init == Init::Ready && self.resolve().is_some();
+        self.int.set(pending);
+    }
+}
+
+impl Device for Pic {
+    fn input(&mut self, port: u8) -> u8 {
+        self.read(port)
+    }
+
+    fn output(&mut self, port: u8, value: u8) {
+        self.write(port, value);
+    }
+}
+
+impl Default for Pic {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // single PIC, edge triggered, call interval 4, vectors at 0x1000
+    fn setup() -> Pic {
+        let mut pic = Pic::new();
+        pic.write(0, 0x16);
+        pic.write(1, 0x10);
+        pic
+    }
+
+    #[test]
+    fn test_priority_and_eoi() {
+        let mut pic = setup();
+        pic.set_ir(5, true);
+        pic.set_ir(2, true);
+        assert!(pic.int_line().get());
+
+        assert_eq!(pic.acknowledge(), Some(Response::Call([0xCD, 0x08, 0x10])));
+        assert_eq!(pic.isr(), 0x04);
+
+        // IR5 is blocked until IR2 is finished
+        assert!(!pic.int_line().get());
+        pic.write(0, 0x20); // non-specific EOI
+        assert!(pic.int_line().get());
+        assert_eq!(pic.acknowledge(), Some(Response::Call([0xCD, 0x14, 0x10])));
+    }
+
+    #[test]
+    fn test_mask_and_read_registers() {
+        let mut pic = setup();
+        pic.write(1, 0x08); // mask IR3
+        pic.set_ir(3, true);
+        assert!(!pic.int_line().get());
+        assert_eq!(pic.read(0), 0x08);
+        assert_eq!(pic.read(1), 0x08);
+
+        pic.write(1, 0x00);
+        assert!(pic.int_line().get());
+        pic.acknowledge();
+        pic.write(0, 0x0B); // read ISR
+        assert_eq!(pic.read(0), 0x08);
+    }
+
+    #[test]
+    fn test_rotation_and_poll() {
+        let mut pic = setup();
+        pic.write(0, 0xC4); // set priority: IR4 lowest, IR5 highest
+        pic.set_ir(1, true);
+        pic.set_ir(6, true);
+
+        pic.write(0, 0x0C); // poll
+        assert_eq!(pic.read(0), 0x86);
+        pic.write(0, 0xA0); // rotate on non-specific EOI
+        pic.write(0, 0x0C);
+        assert_eq!(pic.read(0), 0x81);
+    }
+
+    #[test]
+    fn test_cascade() {
+        let mut master = Pic::new();
+        master.write(0, 0x15); // edge, cascade, interval 4, ICW4
+        master.write(1, 0x20);
+        master.write(1, 0x04); // slave on IR2
+        master.write(1, 0x00);
+
+        let mut slave = Pic::new();
+        slave.set_master(false);
+        slave.write(0, 0x15);
+        slave.write(1, 0x30);
+        slave.write(1, 0x02); // slave id 2
+        slave.write(1, 0x00);
+
+        master.connect(2, slave.int_line());
+        slave.set_ir(3, true);
+        master.update();
+        assert!(master.int_line().get());
+
+        let response = master.acknowledge();
+        assert_eq!(response, Some(Response::Cascade(2)));
+        assert_eq!(slave.acknowledge_slave(1), None);
+        assert_eq!(slave.acknowledge_slave(2), Some([0xCD, 0x0C, 0x30]));
+    }
+
+    #[test]
+    fn test_interrupt_sequence() {
+        use crate::bus::{Access, Bus};
+        use crate::machine::SimpleMachine;
+
+        let mut pic = setup();
+        let mut machine = SimpleMachine::new();
+        machine.cpu.sp = 0x2000;
+        machine.load(0x0000, &[0xFB, 0x76]); // EI; HLT
+        machine.step();
+        machine.step();
+
+        pic.set_ir(1, true);
+        assert!(pic.int_line().get() && machine.cpu.iff);
+        let Some(Response::Call(call)) = pic.acknowledge() else {
+            panic!("expected CALL");
+        };
+        assert_eq!(machine.interrupt(&call), Some(17));
+        assert_eq!(machine.cpu.pc, 0x1004);
+        assert_eq!(machine.bus.read_word(machine.cpu.sp, Access::Stack), 0x0002);
+    }
+}
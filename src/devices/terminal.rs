@@ -0,0 +1,519 @@
+use std::collections::VecDeque;
+use std::io::{self, Write};
+
+use crate::bus::Device;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Emulation {
+    Adm3a,
+    Vt52,
+    Vt100,
+}
+
+impl Emulation {
+    pub fn parse(name: &str) -> Option<Emulation> {
+        match name.to_ascii_lowercase().as_str() {
+            "adm3a" | "adm-3a" => Some(Emulation::Adm3a),
+            "vt52" => Some(Emulation::Vt52),
+            "vt100" => Some(Emulation::Vt100),
+            _ => None,
+        }
+    }
+}
+
+// keys that send different codes depending on the terminal
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Key {
+    Char(u8),
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Parse {
+    Ground,
+    Escape,
+    // ADM-3A ESC = and VT52 ESC Y take a row, then a column, offset by 32
+    Address(Option<u8>),
+    // VT100 ESC [ with parameters
+    Csi,
+}
+
+const MAX_PARAMS: usize = 8;
+
+// A video terminal: output bytes are interpreted as ADM-3A, VT52 or a
+// VT100 subset into a character grid, which can be read back as text or
+// redrawn on the host terminal. Keys typed into it queue up for the
+// machine. As a device, port 0 is status (bit 0 a key is waiting, bit 1
+// ready to send) and port 1 is data.
+pub struct Terminal {
+    emulation: Emulation,
+    rows: usize,
+    cols: usize,
+    cells: Vec<u8>,
+    row: usize,
+    col: usize,
+    // a VT100 writing into the last column only wraps on the next character
+    wrap_pending: bool,
+    saved: (usize, usize),
+    // VT100 scrolling region, inclusive
+    top: usize,
+    bottom: usize,
+    parse: Parse,
+    params: [u16; MAX_PARAMS],
+    param_count: usize,
+    private: bool,
+    keys: VecDeque<u8>,
+    dirty: Vec<bool>,
+    bells: usize,
+}
+
+impl Terminal {
+    pub fn new(emulation: Emulation) -> Self {
+        Self::with_size(emulation, 24, 80)
+    }
+
+    pub fn with_size(emulation: Emulation, rows: usize, cols: usize) -> Self {
+        assert!(rows > 0 && cols > 0, "terminal needs at least one cell");
+        Terminal {
+            emulation,
+            rows,
+            cols,
+            cells: vec![b' '; rows * cols],
+            row: 0,
+            col: 0,
+            wrap_pending: false,
+            saved: (0, 0),
+            top: 0,
+            bottom: rows - 1,
+            parse: Parse::Ground,
+            params: [0; MAX_PARAMS],
+            param_count: 0,
+            private: false,
+            keys: VecDeque::new(),
+            dirty: vec![true; rows],
+            bells: 0,
+        }
+    }
+
+    pub fn emulation(&self) -> Emulation {
+        self.emulation
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        (self.rows, self.cols)
+    }
+
+    // (row, column) from 0
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.row, self.col)
+    }
+
+    pub fn bells(&self) -> usize {
+        self.bells
+    }
+
+    // a row without trailing blanks
+    pub fn line(&self, row: usize) -> String {
+        let cells = &self.cells[row * self.cols..(row + 1) * self.cols];
+        let text: String = cells.iter().map(|&c| c as char).collect();
+        text.trim_end().to_string()
+    }
+
+    // the screen as text, without trailing blank lines
+    pub fn snapshot(&self) -> String {
+        let lines: Vec<String> = (0..self.rows).map(|row| self.line(row)).collect();
+        let used = lines
+            .iter()
+            .rposition(|line| !line.is_empty())
+            .map_or(0, |i| i + 1);
+        lines[..used].join("\n")
+    }
+
+    // redraw the rows changed since the last render with ANSI sequences
+    pub fn render(&mut self, out: &mut dyn Write) -> io::Result<()> {
+        for row in 0..self.rows {
+            if self.dirty[row] {
+                write!(out, "\x1b[{};1H{}\x1b[K", row + 1, self.line(row))?;
+                self.dirty[row] = false;
+            }
+        }
+        write!(out, "\x1b[{};{}H", self.row + 1, self.col + 1)?;
+        out.flush()
+    }
+
+    pub fn send(&mut self, key: Key) {
+        let sequence: &[u8] = match (key, self.emulation) {
+            (Key::Char(c), _) => return self.keys.push_back(c),
+            (Key::Up, Emulation::Adm3a) => b"\x0b",
+            (Key::Down, Emulation::Adm3a) => b"\x0a",
+            (Key::Left, Emulation::Adm3a) => b"\x08",
+            (Key::Right, Emulation::Adm3a) => b"\x0c",
+            (Key::Up, Emulation::Vt52) => b"\x1bA",
+            (Key::Down, Emulation::Vt52) => b"\x1bB",
+            (Key::Right, Emulation::Vt52) => b"\x1bC",
+            (Key::Left, Emulation::Vt52) => b"\x1bD",
+            (Key::Up, Emulation::Vt100) => b"\x1b[A",
+            (Key::Down, Emulation::Vt100) => b"\x1b[B",
+            (Key::Right, Emulation::Vt100) => b"\x1b[C",
+            (Key::Left, Emulation::Vt100) => b"\x1b[D",
+        };
+        self.keys.extend(sequence);
+    }
+
+    pub fn send_bytes(&mut self, bytes: &[u8]) {
+        self.keys.extend(bytes);
+    }
+
+    pub fn key_ready(&self) -> bool {
+        !self.keys.is_empty()
+    }
+
+    pub fn take_key(&mut self) -> Option<u8> {
+        self.keys.pop_front()
+    }
+
+    pub fn put_all(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.put(byte);
+        }
+    }
+
+    // interpret one byte sent to the terminal
+    pub fn put(&mut self, byte: u8) {
+        let byte = byte & 0x7F;
+        match self.parse {
+            Parse::Ground => self.ground(byte),
+            Parse::Escape => {
+                self.parse = Parse::Ground;
+                match self.emulation {
+                    Emulation::Adm3a => self.adm3a_escape(byte),
+                    Emulation::Vt52 => self.vt52_escape(byte),
+                    Emulation::Vt100 => self.vt100_escape(byte),
+                }
+            }
+            Parse::Address(None) => self.parse = Parse::Address(Some(byte)),
+            Parse::Address(Some(row)) => {
+                self.parse = Parse::Ground;
+                let row = (row.saturating_sub(32) as usize).min(self.rows - 1);
+                let col = (byte.saturating_sub(32) as usize).min(self.cols - 1);
+                self.move_to(row, col);
+            }
+            Parse::Csi => self.csi(byte),
+        }
+    }
+
+    fn ground(&mut self, byte: u8) {
+        match byte {
+            0x07 => self.bells += 1,
+            0x08 => self.move_to(self.row, self.col.saturating_sub(1)),
+            0x09 => self.move_to(self.row, ((self.col / 8 + 1) * 8).min(self.cols - 1)),
+            0x0A => self.line_feed(),
+            0x0D => self.move_to(self.row, 0),
+            0x1B => self.parse = Parse::Escape,
+            _ if self.emulation == Emulation::Adm3a => match byte {
+                0x0B => self.move_to(self.row.saturating_sub(1), self.col),
+                0x0C => self.move_to(self.row, (self.col + 1).min(self.cols - 1)),
+                0x1A => {
+                    self.clear(0, self.rows * self.cols);
+                    self.move_to(0, 0);
+                }
+                0x1E => self.move_to(0, 0),
+                _ => self.print(byte),
+            },
+            _ => self.print(byte),
+        }
+    }
+
+    fn print(&mut self, byte: u8) {
+        if byte < 0x20 || byte == 0x7F {
+            return;
+        }
+        if self.wrap_pending {
+            self.wrap_pending = false;
+            self.col = 0;
+            self.line_feed();
+        }
+        self.cells[self.row * self.cols + self.col] = byte;
+        self.dirty[self.row] = true;
+        if self.col + 1 < self.cols {
+            self.col += 1;
+        } else {
+            match self.emulation {
+                // the ADM-3A wraps at once, the VT52 stays in the last column
+                Emulation::Adm3a => {
+                    self.col = 0;
+                    self.line_feed();
+                }
+                Emulation::Vt52 => {}
+                Emulation::Vt100 => self.wrap_pending = true,
+            }
+        }
+    }
+
+    // ADM-3A ESC = row col, plus the clear-to-end sequences of later models
+    fn adm3a_escape(&mut self, byte: u8) {
+        match byte {
+            b'=' => self.parse = Parse::Address(None),
+            b'T' | b't' => self.clear_line(self.col, self.cols),
+            b'Y' | b'y' => self.clear_screen_from_cursor(),
+            _ => {}
+        }
+    }
+
+    fn vt52_escape(&mut self, byte: u8) {
+        match byte {
+            b'A' => self.move_to(self.row.saturating_sub(1), self.col),
+            b'B' => self.move_to((self.row + 1).min(self.rows - 1), self.col),
+            b'C' => self.move_to(self.row, (self.col + 1).min(self.cols - 1)),
+            b'D' => self.move_to(self.row, self.col.saturating_sub(1)),
+            b'H' => self.move_to(0, 0),
+            b'I' => self.reverse_line_feed(),
+            b'J' => self.clear_screen_from_cursor(),
+            b'K' => self.clear_line(self.col, self.cols),
+            b'Y' => self.parse = Parse::Address(None),
+            b'Z' => self.keys.extend(b"\x1b/Z"),
+            _ => {}
+        }
+    }
+
+    fn vt100_escape(&mut self, byte: u8) {
+        match byte {
+            b'[' => {
+                self.parse = Parse::Csi;
+                self.params = [0; MAX_PARAMS];
+                self.param_count = 0;
+                self.private = false;
+            }
+            b'D' => self.line_feed(),
+            b'E' => {
+                self.move_to(self.row, 0);
+                self.line_feed();
+            }
+            b'M' => self.reverse_line_feed(),
+            b'7' => self.saved = (self.row, self.col),
+            b'8' => self.move_to(self.saved.0, self.saved.1),
+            b'c' => {
+                let keys = std::mem::take(&mut self.keys);
+                *self = Terminal::with_size(self.emulation, self.rows, self.cols);
+                self.keys = keys;
+            }
+            _ => {}
+        }
+    }
+
+    fn csi(&mut self, byte: u8) {
+        match byte {
+            b'0'..=b'9' => {
+                let count = self.param_count.max(1);
+                if count <= MAX_PARAMS {
+                    let param = &mut self.params[count - 1];
+                    *param = param
+                        .saturating_mul(10)
+                        .saturating_add((byte - b'0') as u16);
+                }
+                self.param_count = count;
+            }
+            b';' => self.param_count = self.param_count.max(1) + 1,
+            b'?' => self.private = true,
+            0x40..=0x7E => {
+                self.parse = Parse::Ground;
+                if !self.private {
+                    self.csi_final(byte);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn param(&self, index: usize, default: u16) -> usize {
+        match self.params.get(index) {
+            Some(&value) if index < self.param_count && value != 0 => value as usize,
+            _ => default as usize,
+        }
+    }
+
+    fn csi_final(&mut self, byte: u8) {
+        let n = self.param(0, 1);
+        match byte {
+            b'A' => {
+                let limit = if self.row >= self.top { self.top } else { 0 };
+                self.move_to(self.row.saturating_sub(n).max(limit), self.col);
+            }
+            b'B' => {
+                let limit = if self.row <= self.bottom {
+                    self.bottom
+                } else {
+                    self.rows - 1
+                };
+                self.move_to((self.row + n).min(limit), self.col);
+            }
+            b'C' => self.move_to(self.row, (self.col + n).min(self.cols - 1)),
+            b'D' => self.move_to(self.row, self.col.saturating_sub(n)),
+            b'H' | b'f' => {
+                let row = (self.param(0, 1) - 1).min(self.rows - 1);
+                let col = (self.param(1, 1) - 1).min(self.cols - 1);
+                self.move_to(row, col);
+            }
+            b'J' => match self.param(0, 0) {
+                0 => self.clear_screen_from_cursor(),
+                1 => {
+                    self.clear(0, self.row * self.cols);
+                    self.clear_line(0, self.col + 1);
+                }
+                _ => self.clear(0, self.rows * self.cols),
+            },
+            b'K' => match self.param(0, 0) {
+                0 => self.clear_line(self.col, self.cols),
+                1 => self.clear_line(0, self.col + 1),
+                _ => self.clear_line(0, self.cols),
+            },
+            b'r' => {
+                let top = self.param(0, 1) - 1;
+                let bottom = self.param(1, self.rows as u16).min(self.rows) - 1;
+                if top < bottom {
+                    (self.top, self.bottom) = (top, bottom);
+                    self.move_to(0, 0);
+                }
+            }
+            b's' => self.saved = (self.row, self.col),
+            b'u' => self.move_to(self.saved.0, self.saved.1),
+            b'n' if n == 6 => {
+                let report = format!("\x1b[{};{}R", self.row + 1, self.col + 1);
+                self.keys.extend(report.as_bytes());
+            }
+            b'c' => self.keys.extend(b"\x1b[?1;0c"),
+            // attributes are not kept, the grid holds characters only
+            _ => {}
+        }
+    }
+
+    fn move_to(&mut self, row: usize, col: usize) {
+        self.row = row;
+        self.col = col;
+        self.wrap_pending = false;
+    }
+
+    fn line_feed(&mut self) {
+        self.wrap_pending = false;
+        if self.row == self.bottom {
+            self.scroll_up();
+        } else if self.row + 1 < self.rows {
+            self.row += 1;
+        }
+    }
+
+    fn reverse_line_feed(&mut self) {
+        self.wrap_pending = false;
+        if self.row == self.top {
+            self.scroll_down();
+        } else if self.row > 0 {
+            self.row -= 1;
+        }
+    }
+
+    fn scroll_up(&mut self) {
+        let (start, end) = (self.top * self.cols, (self.bottom + 1) * self.cols);
+        self.cells.copy_within(start + self.cols..end, start);
+        self.clear(end - self.cols, end);
+        self.touch(self.top, self.bottom);
+    }
+
+    fn scroll_down(&mut self) {
+        let (start, end) = (self.top * self.cols, (self.bottom + 1) * self.cols);
+        self.cells
+            .copy_within(start..end - self.cols, start + self.cols);
+        self.clear(start, start + self.cols);
+        self.touch(self.top, self.bottom);
+    }
+
+    fn clear_line(&mut self, from: usize, to: usize) {
+        let base = self.row * self.cols;
+        self.clear(base + from, base + to.min(self.cols));
+    }
+
+    fn clear_screen_from_cursor(&mut self) {
+        self.clear(self.row * self.cols + self.col, self.rows * self.cols);
+    }
+
+    // blank a range of cells
+    fn clear(&mut self, start: usize, end: usize) {
+        if start >= end {
+            return;
+        }
+        self.cells[start..end].fill(b' ');
+        self.touch(start / self.cols, (end - 1) / self.cols);
+    }
+
+    fn touch(&mut self, first: usize, last: usize) {
+        self.dirty[first..=last].fill(true);
+    }
+}
+
+impl Device for Terminal {
+    fn input(&mut self, port: u8) -> u8 {
+        match port {
+            0 => 0x02 | self.key_ready() as u8,
+            _ => self.take_key().unwrap_or(0),
+        }
+    }
+
+    fn output(&mut self, port: u8, value: u8) {
+        if port == 1 {
+            self.put(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adm3a_vt52() {
+        let mut adm = Terminal::with_size(Emulation::Adm3a, 4, 10);
+        adm.put_all(b"junk\x1a\x1b=\x21\x23AB\x08C\x1e*");
+        assert_eq!(adm.snapshot(), "*\n   AC");
+        adm.put_all(b"\x1b=\x22\x27wrapped\x0b!");
+        assert_eq!(adm.snapshot(), "*\n   AC\n    !  wra\npped");
+        adm.put_all(b"\r\n\n\n");
+        assert_eq!(adm.snapshot(), "    !  wra\npped");
+        adm.send(Key::Up);
+        assert_eq!(
+            (adm.input(0), adm.input(1), adm.input(0)),
+            (0x03, 0x0B, 0x02)
+        );
+
+        let mut vt52 = Terminal::with_size(Emulation::Vt52, 3, 8);
+        vt52.put_all(b"12345678\x1bY\x20\x22ab\x1bH\x1bK\x1bB\x1bC\x1bCxy\x1bZ");
+        assert_eq!(vt52.snapshot(), "\n  xy");
+        assert_eq!(vt52.cursor(), (1, 4));
+        let reply: Vec<u8> = std::iter::from_fn(|| vt52.take_key()).collect();
+        assert_eq!(reply, b"\x1b/Z");
+    }
+
+    #[test]
+    fn test_vt100() {
+        let mut vt = Terminal::with_size(Emulation::Vt100, 4, 10);
+        vt.put_all(b"\x1b[2J\x1b[1;1Htop\x1b[4;8Hlas\x1b[1m");
+        assert_eq!(vt.snapshot(), "top\n\n\n       las");
+        // the pending wrap moves to the next line and scrolls
+        vt.put_all(b"t");
+        assert_eq!(vt.snapshot(), "\n\n       las\nt");
+        // only the scrolling region moves
+        vt.put_all(b"\x1b[2;3r\x1b[2;1Hone\r\ntwo\r\nthree\x1b[?25l");
+        assert_eq!(vt.snapshot(), "\ntwo    las\nthree\nt");
+        vt.put_all(b"\x1b[2;2H\x1b[1K\x1b[6n");
+        assert_eq!(vt.line(1), "  o    las");
+        let reply: Vec<u8> = std::iter::from_fn(|| vt.take_key()).collect();
+        assert_eq!(reply, b"\x1b[2;2R");
+
+        let mut out = Vec::new();
+        vt.render(&mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.starts_with("\x1b[1;1H\x1b[K\x1b[2;1H  o    las\x1b[K"));
+        assert!(text.ends_with("\x1b[2;2H"));
+    }
+}
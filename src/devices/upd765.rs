@@ -0,0 +1,736 @@
+use crate::bus::Device;
+use crate::cpu::Cycles;
+use crate::disk::{Disk, Drive, SectorId};
+use crate::signal::Line;
+
+// main status register
+const RQM: u8 = 0x80;
+const DIO: u8 = 0x40;
+const NDM: u8 = 0x20;
+const CB: u8 = 0x10;
+
+// ST0
+const ABNORMAL: u8 = 0x40;
+const INVALID: u8 = 0x80;
+const SEEK_END: u8 = 0x20;
+const NOT_READY: u8 = 0x08;
+
+// ST1
+const END_OF_CYLINDER: u8 = 0x80;
+const NO_DATA: u8 = 0x04;
+const NOT_WRITABLE: u8 = 0x02;
+const MISSING_ADDRESS_MARK: u8 = 0x01;
+
+// ST2
+const SCAN_HIT: u8 = 0x08;
+const SCAN_NOT_SATISFIED: u8 = 0x04;
+
+// ST3
+const ST3_WRITE_PROTECT: u8 = 0x40;
+const ST3_READY: u8 = 0x20;
+const ST3_TRACK_0: u8 = 0x10;
+const ST3_TWO_SIDE: u8 = 0x08;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Phase {
+    Command,
+    Execution,
+    Result,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Scan {
+    Equal,
+    LowOrEqual,
+    HighOrEqual,
+}
+
+// NEC uPD765 floppy disk controller; port 0 is the main status register,
+// port 1 the data register. Data is transferred in non-DMA mode through the
+// data register, and the terminal count input is driven by host code.
+pub struct Upd765 {
+    clock: u64,
+    drives: [Drive; 4],
+    seeking: [Option<(u8, Cycles)>; 4],
+    interrupts: Vec<(u8, u8)>,
+
+    phase: Phase,
+    command: Vec<u8>,
+    result: Vec<u8>,
+    result_pos: usize,
+
+    buffer: Vec<u8>,
+    pos: usize,
+    reading: bool,
+    terminal_count: bool,
+    st0: u8,
+    st1: u8,
+    st2: u8,
+
+    step_rate: u8,
+    non_dma: bool,
+
+    int: Line,
+}
+
+impl Upd765 {
+    // `clock` is the CPU clock in Hz, used to convert step times into cycles
+    pub fn new(clock: u64) -> Self {
+        Upd765 {
+            clock,
+            drives: Default::default(),
+            seeking: [None; 4],
+            interrupts: Vec::new(),
+            phase: Phase::Command,
+            command: Vec::new(),
+            result: Vec::new(),
+            result_pos: 0,
+            buffer: Vec::new(),
+            pos: 0,
+            reading: false,
+            terminal_count: false,
+            st0: 0,
+            st1: 0,
+            st2: 0,
+            step_rate: 16,
+            non_dma: true,
+            int: Line::new(false),
+        }
+    }
+
+    pub fn int_line(&self) -> Line {
+        self.int.clone()
+    }
+
+    pub fn drive(&mut self, index: usize) -> &mut Drive {
+        &mut self.drives[index]
+    }
+
+    pub fn insert(&mut self, index: usize, disk: Box<dyn Disk>) {
+        self.drives[index].disk = Some(disk);
+    }
+
+    pub fn eject(&mut self, index: usize) -> Option<Box<dyn Disk>> {
+        self.drives[index].disk.take()
+    }
+
+    pub fn non_dma(&self) -> bool {
+        self.non_dma
+    }
+
+    // TC input: ends the data transfer after the current sector
+    pub fn terminal_count(&mut self) {
+        if self.phase == Phase::Execution {
+            self.terminal_count = true;
+            // at a sector boundary nothing of the next sector has moved yet
+            if self.reading && self.pos == 0 || !self.reading && self.buffer.is_empty() {
+                self.result_phase(self.command_id());
+            }
+        }
+    }
+
+    pub fn tick(&mut self, cycles: Cycles) {
+        for unit in 0..4 {
+            if let Some((target, timer)) = self.seeking[unit] {
+                let timer = timer.saturating_sub(cycles);
+                if timer > 0 {
+                    self.seeking[unit] = Some((target, timer));
+                    continue;
+                }
+                self.seeking[unit] = None;
+                self.drives[unit].cylinder = target;
+                let st0 = SEEK_END | unit as u8;
+                self.interrupts.push((st0, target));
+            }
+        }
+        self.update_int();
+    }
+
+    pub fn status(&self) -> u8 {
+        let mut msr = 0;
+        for (unit, seek) in self.seeking.iter().enumerate() {
+            if seek.is_some() {
+                msr |= 1 << unit;
+            }
+        }
+        match self.phase {
+            Phase::Command => {
+                msr |= RQM;
+                if !self.command.is_empty() {
+                    msr |= CB;
+                }
+            }
+            Phase::Execution => {
+                msr |= RQM | CB;
+                if self.non_dma {
+                    msr |= NDM;
+                }
+                if self.reading {
+                    msr |= DIO;
+                }
+            }
+            Phase::Result => msr |= RQM | DIO | CB,
+        }
+        msr
+    }
+
+    pub fn read(&mut self, port: u8) -> u8 {
+        if port & 0x01 == 0 {
+            return self.status();
+        }
+
+        match self.phase {
+            Phase::Execution if self.reading => {
+                let value = self.buffer.get(self.pos).copied().unwrap_or(0xFF);
+                self.pos += 1;
+                if self.pos >= self.buffer.len() {
+                    self.end_of_sector();
+                }
+                value
+            }
+            Phase::Result => {
+                let value = self.result[self.result_pos];
+                self.result_pos += 1;
+                if self.result_pos == self.result.len() {
+                    self.phase = Phase::Command;
+                }
+                self.update_int();
+                value
+            }
+            _ => 0xFF,
+        }
+    }
+
+    pub fn write(&mut self, port: u8, value: u8) {
+        if port & 0x01 == 0 {
+            return;
+        }
+
+        match self.phase {
+            Phase::Command => {
+                self.command.push(value);
+                if self.command.len() == command_length(self.command[0]) {
+                    self.execute();
+                }
+            }
+            Phase::Execution if !self.reading => {
+                self.buffer.push(value);
+                let full = match self.opcode() {
+                    0x0D => self.buffer.len() == 4 * self.command[3] as usize,
+                    _ => self.buffer.len() == self.sector_length(),
+                };
+                if full {
+                    self.end_of_sector();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn opcode(&self) -> u8 {
+        self.command[0] & 0x1F
+    }
+
+    fn unit(&self) -> usize {
+        (self.command[1] & 0x03) as usize
+    }
+
+    fn head(&self) -> u8 {
+        (self.command[1] >> 2) & 0x01
+    }
+
+    fn ms(&self, ms: u64) -> Cycles {
+        (self.clock / 1000 * ms).max(1)
+    }
+
+    // N = 0 transfers DTL bytes, which the chip takes as 1 to 128
+    fn sector_length(&self) -> usize {
+        match self.command[5] {
+            0 => (self.command[8] as usize).clamp(1, 128),
+            n => 128 << n.min(7),
+        }
+    }
+
+    fn execute(&mut self) {
+        self.st0 = if self.command.len() > 1 {
+            (self.head() << 2) | self.unit() as u8
+        } else {
+            0
+        };
+        self.st1 = 0;
+        self.st2 = 0;
+        self.terminal_count = false;
+
+        match self.opcode() {
+            // specify
+            0x03 => {
+                self.step_rate = 16 - (self.command[1] >> 4);
+                self.non_dma = self.command[2] & 0x01 != 0;
+                self.finish(Vec::new());
+            }
+            // sense drive status
+            0x04 => {
+                let drive = &self.drives[self.unit()];
+                let mut st3 = self.command[1] & 0x07;
+                if drive.write_protected() {
+                    st3 |= ST3_WRITE_PROTECT;
+                }
+                if drive.ready() {
+                    st3 |= ST3_READY;
+                }
+                if drive.cylinder == 0 {
+                    st3 |= ST3_TRACK_0;
+                }
+                if drive.disk.as_ref().is_some_and(|disk| disk.heads() > 1) {
+                    st3 |= ST3_TWO_SIDE;
+                }
+                self.finish(vec![st3]);
+            }
+            // recalibrate, seek
+            0x07 | 0x0F => {
+                let unit = self.unit();
+                let target = if self.opcode() == 0x07 {
+                    0
+                } else {
+                    self.command[2]
+                };
+                let steps = self.drives[unit].cylinder.abs_diff(target) as u64;
+                let time = self.ms(steps * self.step_rate as u64);
+                self.seeking[unit] = Some((target, time));
+                self.command.clear();
+            }
+            // sense interrupt status
+            0x08 => {
+                let result = if self.interrupts.is_empty() {
+                    vec![INVALID]
+                } else {
+                    let (st0, pcn) = self.interrupts.remove(0);
+                    vec![st0, pcn]
+                };
+                self.finish(result);
+            }
+            // read ID
+            0x0A => {
+                if let Some(status) = self.check_drive(false) {
+                    self.st0 |= status;
+                    self.result_phase(self.command_id());
+                    return;
+                }
+                let (unit, head) = (self.unit(), self.head());
+                match self.drives[unit].next_id(head) {
+                    Some(id) => self.result_phase(id),
+                    None => {
+                        self.st0 |= ABNORMAL;
+                        self.st1 |= MISSING_ADDRESS_MARK;
+                        self.result_phase(self.command_id());
+                    }
+                }
+            }
+            // format track
+            0x0D => {
+                if let Some(status) = self.check_drive(true) {
+                    self.st0 |= status;
+                    self.result_phase(self.command_id());
+                    return;
+                }
+                // no sectors to format, so no IDs to wait for
+                if self.command[3] == 0 {
+                    self.result_phase(self.command_id());
+                    return;
+                }
+                self.begin(false);
+            }
+            // read/write (deleted) data, read track, scans
+            0x02 | 0x05 | 0x06 | 0x09 | 0x0C | 0x11 | 0x19 | 0x1D => {
+                let writing = matches!(self.opcode(), 0x05 | 0x09);
+                if let Some(status) = self.check_drive(writing) {
+                    self.st0 |= status;
+                    self.result_phase(self.command_id());
+                    return;
+                }
+                self.load_sector();
+            }
+            _ => {
+                self.st0 = INVALID;
+                self.finish(vec![INVALID]);
+            }
+        }
+    }
+
+    fn check_drive(&mut self, writing: bool) -> Option<u8> {
+        let drive = &self.drives[self.unit()];
+        if !drive.ready() {
+            return Some(ABNORMAL | NOT_READY);
+        }
+        if writing && drive.write_protected() {
+            self.st1 |= NOT_WRITABLE;
+            return Some(ABNORMAL);
+        }
+        None
+    }
+
+    fn command_id(&self) -> SectorId {
+        if self.command.len() >= 6 {
+            SectorId {
+                cylinder: self.command[2],
+                head: self.command[3],
+                sector: self.command[4],
+                size: self.command[5],
+            }
+        } else {
+            SectorId {
+                cylinder: self.drives[self.unit()].cylinder,
+                head: self.head(),
+                sector: 0,
+                size: 0,
+            }
+        }
+    }
+
+    fn begin(&mut self, reading: bool) {
+        self.phase = Phase::Execution;
+        self.reading = reading;
+        self.pos = 0;
+        if !reading {
+            self.buffer.clear();
+        }
+        self.update_int();
+    }
+
+    // locate the sector addressed by C, H, R, N and start transferring it
+    fn load_sector(&mut self) {
+        let (unit, head) = (self.unit(), self.head());
+        let want = self.command_id();
+        let read_track = self.opcode() == 0x02;
+
+        let found = if read_track {
+            // read track takes sectors in physical order, ignoring R
+            self.drives[unit]
+                .disk
+                .as_ref()
+                .map(|disk| disk.sector_ids(self.drives[unit].cylinder, head))
+                .and_then(|ids| ids.get(want.sector.saturating_sub(1) as usize).copied())
+        } else {
+            self.drives[unit].find(head, |id| *id == want)
+        };
+
+        let Some(id) = found else {
+            self.st0 |= ABNORMAL;
+            self.st1 |= NO_DATA;
+            self.result_phase(want);
+            return;
+        };
+
+        match self.opcode() {
+            0x05 | 0x09 => self.begin(false),
+            0x11 | 0x19 | 0x1D => self.begin(false),
+            _ => {
+                let cylinder = self.drives[unit].cylinder;
+                let disk = self.drives[unit].disk.as_mut().unwrap();
+                match disk.read_sector(cylinder, head, id.sector) {
+                    Ok(mut data) => {
+                        data.truncate(self.sector_length());
+                        self.buffer = data;
+                        self.begin(true);
+                    }
+                    Err(_) => {
+                        self.st0 |= ABNORMAL;
+                        self.st1 |= NO_DATA;
+                        self.result_phase(want);
+                    }
+                }
+            }
+        }
+    }
+
+    fn end_of_sector(&mut self) {
+        let (unit, head) = (self.unit(), self.head());
+        let cylinder = self.drives[unit].cylinder;
+
+        match self.opcode() {
+            0x05 | 0x09 => {
+                let buffer = std::mem::take(&mut self.buffer);
+                let disk = self.drives[unit].disk.as_mut().unwrap();
+                if disk
+                    .write_sector(cylinder, head, self.command[4], &buffer)
+                    .is_err()
+                {
+                    self.st0 |= ABNORMAL;
+                    self.st1 |= NOT_WRITABLE;
+                    self.result_phase(self.command_id());
+                    return;
+                }
+            }
+            0x0D => {
+                let ids: Vec<SectorId> = self
+                    .buffer
+                    .chunks(4)
+                    .map(|id| SectorId {
+                        cylinder: id[0],
+                        head: id[1],
+                        sector: id[2],
+                        size: id[3],
+                    })
+                    .collect();
+                let fill = self.command[5];
+                let disk = self.drives[unit].disk.as_mut().unwrap();
+                if disk.format_track(cylinder, head, &ids, fill).is_err() {
+                    self.st0 |= ABNORMAL;
+                    self.st1 |= NOT_WRITABLE;
+                }
+                let last = ids.last().copied().unwrap_or(self.command_id());
+                self.result_phase(last);
+                return;
+            }
+            0x11 | 0x19 | 0x1D if self.scan_sector() => {
+                self.st2 |= SCAN_HIT;
+                self.result_phase(self.command_id());
+                return;
+            }
+            _ => {}
+        }
+
+        // advance to the next sector, crossing to head 1 in multi-track mode
+        let multi_track = self.command[0] & 0x80 != 0;
+        let step = if matches!(self.opcode(), 0x11 | 0x19 | 0x1D) {
+            self.command[8].max(1)
+        } else {
+            1
+        };
+        let eot = self.command[6];
+        let end_of_track = self.command[4] >= eot;
+
+        if end_of_track {
+            if multi_track && self.command[3] == 0 {
+                self.command[1] |= 0x04;
+                self.command[3] = 1;
+                self.command[4] = 1;
+            } else {
+                self.command[2] = self.command[2].wrapping_add(1);
+                self.command[4] = 1;
+                if !self.terminal_count {
+                    if matches!(self.opcode(), 0x11 | 0x19 | 0x1D) {
+                        self.st2 |= SCAN_NOT_SATISFIED;
+                    } else {
+                        self.st0 |= ABNORMAL;
+                        self.st1 |= END_OF_CYLINDER;
+                    }
+                }
+                self.result_phase(self.command_id());
+                return;
+            }
+        } else {
+            self.command[4] = self.command[4].wrapping_add(step);
+        }
+
+        if self.terminal_count {
+            self.result_phase(self.command_id());
+        } else {
+            self.load_sector();
+        }
+    }
+
+    fn scan_sector(&mut self) -> bool {
+        let (unit, head) = (self.unit(), self.head());
+        let cylinder = self.drives[unit].cylinder;
+        let disk = self.drives[unit].disk.as_mut().unwrap();
+        let Ok(data) = disk.read_sector(cylinder, head, self.command[4]) else {
+            return false;
+        };
+
+        let scan = match self.opcode() {
+            0x11 => Scan::Equal,
+            0x19 => Scan::LowOrEqual,
+            _ => Scan::HighOrEqual,
+        };
+        // 0xFF from the CPU matches anything
+        let mut pairs = data
+            .iter()
+            .zip(&self.buffer)
+            .filter(|(_, cpu)| **cpu != 0xFF);
+        let hit = match scan {
+            Scan::Equal => pairs.all(|(disk, cpu)| disk == cpu),
+            Scan::LowOrEqual => pairs.all(|(disk, cpu)| disk <= cpu),
+            Scan::HighOrEqual => pairs.all(|(disk, cpu)| disk >= cpu),
+        };
+        self.buffer.clear();
+        hit
+    }
+
+    fn result_phase(&mut self, id: SectorId) {
+        let result = vec![
+            self.st0,
+            self.st1,
+            self.st2,
+            id.cylinder,
+            id.head,
+            id.sector,
+            id.size,
+        ];
+        self.finish(result);
+    }
+
+    fn finish(&mut self, result: Vec<u8>) {
+        self.command.clear();
+        self.buffer.clear();
+        self.reading = false;
+        self.result_pos = 0;
+        self.phase = if result.is_empty() {
+            Phase::Command
+        } else {
+            Phase::Result
+        };
+        self.result = result;
+        self.update_int();
+    }
+
+    fn update_int(&mut self) {
+        let result = self.phase == Phase::Result && self.result.len() == 7 && self.result_pos == 0;
+        let data = self.phase == Phase::Execution && self.non_dma;
+        self.int.set(result || data || !self.interrupts.is_empty());
+    }
+}
+
+fn command_length(command: u8) -> usize {
+    match command & 0x1F {
+        0x02 | 0x05 | 0x06 | 0x09 | 0x0C | 0x11 | 0x19 | 0x1D => 9,
+        0x0D => 6,
+        0x03 | 0x0F => 3,
+        0x04 | 0x07 | 0x0A => 2,
+        _ => 1,
+    }
+}
+
+impl Device for Upd765 {
+    fn input(&mut self, port: u8) -> u8 {
+        self.read(port)
+    }
+
+    fn output(&mut self, port: u8, value: u8) {
+        self.write(port, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::disk::{Geometry, RawDisk};
+
+    const CLOCK: u64 = 4_000_000;
+
+    fn setup() -> Upd765 {
+        let geometry = Geometry {
+            cylinders: 40,
+            heads: 2,
+            sectors: 9,
+            sector_size: 512,
+            first_sector: 1,
+        };
+        let mut disk = RawDisk::new(geometry);
+        disk.write_sector(3, 1, 9, &[0x11; 512]).unwrap();
+        disk.write_sector(3, 1, 1, &[0x22; 512]).unwrap();
+        let mut fdc = Upd765::new(CLOCK);
+        fdc.insert(0, Box::new(disk));
+        fdc
+    }
+
+    fn command(fdc: &mut Upd765, bytes: &[u8]) {
+        for &byte in bytes {
+            assert_eq!(fdc.status() & (RQM | DIO), RQM);
+            fdc.write(1, byte);
+        }
+    }
+
+    fn result(fdc: &mut Upd765) -> Vec<u8> {
+        let mut result = Vec::new();
+        while fdc.status() & (RQM | DIO) == RQM | DIO && fdc.status() & NDM == 0 {
+            result.push(fdc.read(1));
+        }
+        result
+    }
+
+    #[test]
+    fn test_seek_and_sense_interrupt() {
+        let mut fdc = setup();
+        command(&mut fdc, &[0x03, 0xDF, 0x03]); // specify: 3ms steps, non-DMA
+        command(&mut fdc, &[0x0F, 0x00, 3]);
+        assert_eq!(fdc.status() & 0x01, 0x01);
+
+        fdc.tick(CLOCK / 1000 * 9);
+        assert!(fdc.int_line().get());
+        command(&mut fdc, &[0x08]);
+        assert_eq!(result(&mut fdc), vec![0x20, 3]);
+        assert!(!fdc.int_line().get());
+
+        command(&mut fdc, &[0x04, 0x00]);
+        assert_eq!(result(&mut fdc), vec![0x28]);
+    }
+
+    #[test]
+    fn test_read_multi_track() {
+        let mut fdc = setup();
+        fdc.drive(0).cylinder = 3;
+        command(&mut fdc, &[0x03, 0xDF, 0x03]);
+
+        // C=3 H=1 R=9 N=2 EOT=9, then terminal count partway into R=1 of C=4
+        command(&mut fdc, &[0xC6, 0x04, 3, 1, 9, 2, 9, 0x1B, 0xFF]);
+        let data: Vec<u8> = (0..512).map(|_| fdc.read(1)).collect();
+        assert_eq!(data, vec![0x11; 512]);
+        assert_eq!(result(&mut fdc), vec![0x44, END_OF_CYLINDER, 0, 4, 1, 1, 2]);
+
+        command(&mut fdc, &[0x46, 0x04, 3, 1, 1, 2, 9, 0x1B, 0xFF]);
+        assert_eq!(fdc.read(1), 0x22);
+        fdc.terminal_count();
+        for _ in 1..512 {
+            fdc.read(1);
+        }
+        assert_eq!(result(&mut fdc), vec![0x04, 0, 0, 3, 1, 2, 2]);
+    }
+
+    #[test]
+    fn test_write_and_protect() {
+        let mut fdc = setup();
+        command(&mut fdc, &[0x45, 0x00, 0, 0, 1, 2, 1, 0x1B, 0xFF]);
+        for n in 0..511 {
+            fdc.write(1, n as u8);
+        }
+        fdc.terminal_count();
+        fdc.write(1, 0xFF);
+        assert_eq!(result(&mut fdc), vec![0x00, 0, 0, 1, 0, 1, 2]);
+        let disk = fdc.drive(0).disk.as_mut().unwrap();
+        assert_eq!(disk.read_sector(0, 0, 1).unwrap()[511], 0xFF);
+
+        let mut disk = RawDisk::new(Geometry::IBM_3740);
+        disk.set_write_protected(true);
+        fdc.insert(1, Box::new(disk));
+        command(&mut fdc, &[0x45, 0x01, 0, 0, 1, 0, 26, 0x07, 128]);
+        let result = result(&mut fdc);
+        assert_eq!(result[0], 0x41);
+        assert_eq!(result[1], NOT_WRITABLE);
+    }
+
+    #[test]
+    fn test_degenerate_commands() {
+        let mut fdc = setup();
+        fdc.insert(1, Box::new(RawDisk::new(Geometry::IBM_3740)));
+        // N=0 with DTL=0 transfers a single byte
+        command(&mut fdc, &[0x46, 0x01, 0, 0, 1, 0, 1, 0x07, 0]);
+        assert_eq!(fdc.read(1), 0xE5);
+        assert_eq!(result(&mut fdc)[..3], [0x41, END_OF_CYLINDER, 0]);
+
+        // formatting no sectors ends at once
+        command(&mut fdc, &[0x4D, 0x00, 2, 0, 0x1B, 0xE5]);
+        assert_eq!(result(&mut fdc)[0], 0x00);
+
+        // read ID with no disk in the drive
+        command(&mut fdc, &[0x4A, 0x02]);
+        assert_eq!(result(&mut fdc)[..2], [ABNORMAL | NOT_READY | 0x02, 0]);
+    }
+
+    #[test]
+    fn test_invalid_command() {
+        let mut fdc = setup();
+        command(&mut fdc, &[0x1F]);
+        assert_eq!(result(&mut fdc), vec![0x80]);
+    }
+}
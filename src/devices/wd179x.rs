@@ -0,0 +1,763 @@
+use crate::bus::Device;
+use crate::cpu::Cycles;
+use crate::disk::{Disk, Drive, SectorId, crc16};
+use crate::signal::Line;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Variant {
+    // single density only
+    Wd1771,
+    // inverted data bus
+    Wd1791,
+    Wd1793,
+}
+
+const NOT_READY: u8 = 0x80;
+const WRITE_PROTECT: u8 = 0x40;
+const HEAD_LOADED: u8 = 0x20;
+const WRITE_FAULT: u8 = 0x20;
+const SEEK_ERROR: u8 = 0x10;
+const RECORD_NOT_FOUND: u8 = 0x10;
+const TRACK_0: u8 = 0x04;
+const INDEX: u8 = 0x02;
+const DRQ: u8 = 0x02;
+const BUSY: u8 = 0x01;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Action {
+    Restore,
+    Seek,
+    Step,
+    ReadSector,
+    WriteSector,
+    ReadAddress,
+    ReadTrack,
+    WriteTrack,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Transfer {
+    None,
+    Read,
+    Write,
+}
+
+// Western Digital WD1771/WD179x floppy disk controller; ports 0-3 are the
+// status/command, track, sector and data registers. Drive and side select
+// are board-specific latches, so host code drives them through `select`.
+pub struct Wd179x {
+    variant: Variant,
+    clock: u64,
+
+    drives: [Drive; 4],
+    drive: usize,
+    side: u8,
+
+    command: u8,
+    status: u8,
+    track: u8,
+    sector: u8,
+    data: u8,
+    step_in: bool,
+    head_loaded: bool,
+    type1: bool,
+
+    action: Option<Action>,
+    timer: Cycles,
+    transfer: Transfer,
+    buffer: Vec<u8>,
+    pos: usize,
+    length: usize,
+    target: Option<SectorId>,
+
+    elapsed: Cycles,
+    interrupt_on_index: bool,
+    interrupt_on_ready: bool,
+    interrupt_on_not_ready: bool,
+
+    intrq: Line,
+    drq: Line,
+}
+
+impl Wd179x {
+    // `clock` is the CPU clock in Hz, used to convert the mechanical delays
+    // into cycles for `tick`
+    pub fn new(variant: Variant, clock: u64) -> Self {
+        Wd179x {
+            variant,
+            clock,
+            drives: Default::default(),
+            drive: 0,
+            side: 0,
+            command: 0,
+            status: 0,
+            track: 0,
+            sector: 1,
+            data: 0,
+            step_in: true,
+            head_loaded: false,
+            type1: true,
+            action: None,
+            timer: 0,
+            transfer: Transfer::None,
+            buffer: Vec::new(),
+            pos: 0,
+            length: 0,
+            target: None,
+            elapsed: 0,
+            interrupt_on_index: false,
+            interrupt_on_ready: false,
+            interrupt_on_not_ready: false,
+            intrq: Line::new(false),
+            drq: Line::new(false),
+        }
+    }
+
+    pub fn intrq(&self) -> Line {
+        self.intrq.clone()
+    }
+
+    pub fn drq(&self) -> Line {
+        self.drq.clone()
+    }
+
+    pub fn busy(&self) -> bool {
+        self.action.is_some() || self.transfer != Transfer::None
+    }
+
+    pub fn track(&self) -> u8 {
+        self.track
+    }
+
+    pub fn sector(&self) -> u8 {
+        self.sector
+    }
+
+    pub fn drive(&mut self, index: usize) -> &mut Drive {
+        &mut self.drives[index]
+    }
+
+    pub fn insert(&mut self, index: usize, disk: Box<dyn Disk>) {
+        self.drives[index].disk = Some(disk);
+        if index == self.drive && self.interrupt_on_ready {
+            self.intrq.set(true);
+        }
+    }
+
+    pub fn eject(&mut self, index: usize) -> Option<Box<dyn Disk>> {
+        let disk = self.drives[index].disk.take();
+        if index == self.drive && self.interrupt_on_not_ready {
+            self.intrq.set(true);
+        }
+        disk
+    }
+
+    pub fn select(&mut self, drive: usize, side: u8) {
+        self.drive = drive;
+        self.side = side;
+    }
+
+    pub fn tick(&mut self, cycles: Cycles) {
+        let revolution = self.ms(200);
+        let before = self.elapsed / revolution;
+        self.elapsed += cycles;
+        if self.interrupt_on_index && self.elapsed / revolution != before {
+            self.intrq.set(true);
+        }
+
+        if self.action.is_some() {
+            self.timer = self.timer.saturating_sub(cycles);
+            if self.timer == 0 {
+                self.run();
+            }
+        }
+    }
+
+    pub fn read(&mut self, port: u8) -> u8 {
+        let value = match port & 0x03 {
+            0 => {
+                self.intrq.set(false);
+                self.status()
+            }
+            1 => self.track,
+            2 => self.sector,
+            _ => {
+                if self.transfer == Transfer::Read {
+                    self.data = self.buffer[self.pos];
+                    self.pos += 1;
+                    if self.pos == self.buffer.len() {
+                        self.end_of_record();
+                    }
+                }
+                self.data
+            }
+        };
+        self.bus(value)
+    }
+
+    pub fn write(&mut self, port: u8, value: u8) {
+        let value = self.bus(value);
+        match port & 0x03 {
+            0 => self.command(value),
+            1 => self.track = value,
+            2 => self.sector = value,
+            _ => {
+                self.data = value;
+                if self.transfer == Transfer::Write {
+                    self.buffer.push(value);
+                    if self.buffer.len() == self.length {
+                        self.end_of_record();
+                    }
+                }
+            }
+        }
+    }
+
+    fn bus(&self, value: u8) -> u8 {
+        if self.variant == Variant::Wd1791 {
+            !value
+        } else {
+            value
+        }
+    }
+
+    fn ms(&self, ms: u64) -> Cycles {
+        (self.clock / 1000 * ms).max(1)
+    }
+
+    fn current(&mut self) -> &mut Drive {
+        &mut self.drives[self.drive]
+    }
+
+    fn status(&self) -> u8 {
+        let drive = &self.drives[self.drive];
+        let mut status = self.status;
+
+        if !drive.ready() {
+            status |= NOT_READY;
+        }
+        if self.busy() {
+            status |= BUSY;
+        }
+        if self.type1 {
+            if drive.write_protected() {
+                status |= WRITE_PROTECT;
+            }
+            if self.head_loaded {
+                status |= HEAD_LOADED;
+            }
+            if drive.cylinder == 0 {
+                status |= TRACK_0;
+            }
+            if drive.ready() && self.elapsed % self.ms(200) < self.ms(4) {
+                status |= INDEX;
+            }
+        } else if self.drq.get() {
+            status |= DRQ;
+        }
+        status
+    }
+
+    fn command(&mut self, command: u8) {
+        if command & 0xF0 == 0xD0 {
+            self.force_interrupt(command);
+            return;
+        }
+        if self.busy() {
+            return;
+        }
+
+        self.command = command;
+        self.status = 0;
+        self.intrq.set(false);
+        self.drq.set(false);
+        self.type1 = command & 0x80 == 0;
+
+        if self.type1 {
+            let rates: [u64; 4] = match self.variant {
+                Variant::Wd1771 => [6, 6, 10, 20],
+                _ => [3, 6, 10, 15],
+            };
+            let rate = self.ms(rates[(command & 0x03) as usize]);
+            let verify = if command & 0x04 != 0 { self.ms(15) } else { 0 };
+            self.head_loaded = command & 0x0C != 0;
+
+            let (action, steps) = match command >> 4 {
+                0x0 => (Action::Restore, self.drives[self.drive].cylinder as u64),
+                0x1 => (Action::Seek, self.track.abs_diff(self.data) as u64),
+                0x2 | 0x3 => (Action::Step, 1),
+                0x4 | 0x5 => {
+                    self.step_in = true;
+                    (Action::Step, 1)
+                }
+                _ => {
+                    self.step_in = false;
+                    (Action::Step, 1)
+                }
+            };
+            self.start(action, steps * rate + verify);
+            return;
+        }
+
+        if !self.current().ready() {
+            self.finish();
+            return;
+        }
+
+        let delay = if command & 0x04 != 0 { self.ms(15) } else { 1 };
+        let action = match command >> 4 {
+            0x8 | 0x9 => Action::ReadSector,
+            0xA | 0xB => Action::WriteSector,
+            0xC => Action::ReadAddress,
+            0xE => Action::ReadTrack,
+            _ => Action::WriteTrack,
+        };
+        if matches!(action, Action::WriteSector | Action::WriteTrack)
+            && self.current().write_protected()
+        {
+            self.status |= WRITE_PROTECT;
+            self.finish();
+            return;
+        }
+        self.start(action, delay);
+    }
+
+    fn force_interrupt(&mut self, command: u8) {
+        if !self.busy() {
+            self.type1 = true;
+        }
+        self.action = None;
+        self.transfer = Transfer::None;
+        self.drq.set(false);
+
+        self.interrupt_on_not_ready = command & 0x01 != 0;
+        self.interrupt_on_ready = command & 0x02 != 0;
+        self.interrupt_on_index = command & 0x04 != 0;
+        self.intrq.set(command & 0x08 != 0);
+    }
+
+    fn start(&mut self, action: Action, delay: Cycles) {
+        self.action = Some(action);
+        self.timer = delay.max(1);
+    }
+
+    fn finish(&mut self) {
+        self.action = None;
+        self.transfer = Transfer::None;
+        self.drq.set(false);
+        self.intrq.set(true);
+    }
+
+    fn run(&mut self) {
+        let Some(action) = self.action.take() else {
+            return;
+        };
+
+        match action {
+            Action::Restore => {
+                self.current().cylinder = 0;
+                self.track = 0;
+                self.verify();
+            }
+            Action::Seek => {
+                let inward = self.data > self.track;
+                for _ in 0..self.track.abs_diff(self.data) {
+                    let drive = self.drive;
+                    self.drives[drive].step(inward);
+                }
+                self.step_in = inward;
+                self.track = self.data;
+                self.verify();
+            }
+            Action::Step => {
+                let inward = self.step_in;
+                self.current().step(inward);
+                if self.command & 0x10 != 0 {
+                    self.track = if inward {
+                        self.track.wrapping_add(1)
+                    } else {
+                        self.track.wrapping_sub(1)
+                    };
+                }
+                self.verify();
+            }
+            Action::ReadSector | Action::WriteSector => self.locate(action),
+            Action::ReadAddress => {
+                let side = self.side;
+                match self.current().next_id(side) {
+                    Some(id) => {
+                        let crc = crc16(&[0xFE, id.cylinder, id.head, id.sector, id.size]);
+                        self.buffer = vec![
+                            id.cylinder,
+                            id.head,
+                            id.sector,
+                            id.size,
+                            (crc >> 8) as u8,
+                            crc as u8,
+                        ];
+                        self.sector = id.cylinder;
+                        self.begin_read();
+                    }
+                    None => {
+                        self.status |= RECORD_NOT_FOUND;
+                        self.finish();
+                    }
+                }
+            }
+            Action::ReadTrack => {
+                self.buffer = self.raw_track();
+                self.begin_read();
+            }
+            Action::WriteTrack => {
+                self.buffer.clear();
+                self.length = self.track_length();
+                self.transfer = Transfer::Write;
+                self.drq.set(true);
+            }
+        }
+    }
+
+    fn verify(&mut self) {
+        if self.command & 0x04 != 0 {
+            let (track, side) = (self.track, self.side);
+            if self
+                .current()
+                .find(side, |id| id.cylinder == track)
+                .is_none()
+            {
+                self.status |= SEEK_ERROR;
+            }
+        }
+        self.finish();
+    }
+
+    fn locate(&mut self, action: Action) {
+        let (track, sector, side) = (self.track, self.sector, self.side);
+        let side_compare = self.variant != Variant::Wd1771 && self.command & 0x02 != 0;
+        let expected_side = (self.command >> 3) & 0x01;
+
+        let id = self.current().find(side, |id| {
+            id.cylinder == track
+                && id.sector == sector
+                && (!side_compare || id.head == expected_side)
+        });
+        let Some(id) = id else {
+            self.status |= RECORD_NOT_FOUND;
+            self.finish();
+            return;
+        };
+
+        self.target = Some(id);
+        if action == Action::ReadSector {
+            let cylinder = self.current().cylinder;
+            let disk = self.current().disk.as_mut().unwrap();
+            match disk.read_sector(cylinder, side, id.sector) {
+                Ok(data) => {
+                    self.buffer = data;
+                    self.begin_read();
+                }
+                Err(_) => {
+                    self.status |= RECORD_NOT_FOUND;
+                    self.finish();
+                }
+            }
+        } else {
+            self.buffer.clear();
+            self.length = id.data_len();
+            self.transfer = Transfer::Write;
+            self.drq.set(true);
+        }
+    }
+
+    fn begin_read(&mut self) {
+        self.pos = 0;
+        self.transfer = Transfer::Read;
+        self.drq.set(true);
+    }
+
+    // the buffer for the current record has been drained or filled
+    fn end_of_record(&mut self) {
+        self.transfer = Transfer::None;
+        self.drq.set(false);
+
+        let command = self.command >> 4;
+        match command {
+            0xA | 0xB => {
+                let (cylinder, side) = (self.current().cylinder, self.side);
+                let sector = self.target.map(|id| id.sector).unwrap_or(self.sector);
+                let buffer = std::mem::take(&mut self.buffer);
+                let disk = self.current().disk.as_mut().unwrap();
+                if disk.write_sector(cylinder, side, sector, &buffer).is_err() {
+                    self.status |= WRITE_FAULT;
+                    self.finish();
+                    return;
+                }
+            }
+            0xF => {
+                self.format();
+                self.finish();
+                return;
+            }
+            _ => {}
+        }
+
+        // multiple record commands continue with the next sector
+        if matches!(command, 0x9 | 0xB) {
+            self.sector = self.sector.wrapping_add(1);
+            let action = if command == 0x9 {
+                Action::ReadSector
+            } else {
+                Action::WriteSector
+            };
+            self.start(action, 1);
+        } else {
+            self.finish();
+        }
+    }
+
+    fn track_length(&self) -> usize {
+        if self.variant == Variant::Wd1771 {
+            3125
+        } else {
+            6250
+        }
+    }
+
+    // IBM track layout as the controller would see it on the medium
+    fn raw_track(&mut self) -> Vec<u8> {
+        let (cylinder, side) = (self.current().cylinder, self.side);
+        let length = self.track_length();
+        let mut raw = vec![0x4E; 80];
+        let Some(disk) = self.current().disk.as_mut() else {
+            return raw;
+        };
+
+        for id in disk.sector_ids(cylinder, side) {
+            raw.extend([0x00; 12]);
+            let header = [0xFE, id.cylinder, id.head, id.sector, id.size];
+            raw.extend(header);
+            raw.extend(crc16(&header).to_be_bytes());
+            raw.extend([0x4E; 22]);
+            raw.extend([0x00; 12]);
+            let data = disk
+                .read_sector(cylinder, side, id.sector)
+                .unwrap_or_else(|_| vec![0; id.data_len()]);
+            let mut field = vec![0xFB];
+            field.extend(&data);
+            raw.extend(&field);
+            raw.extend(crc16(&field).to_be_bytes());
+            raw.extend([0x4E; 54]);
+        }
+        raw.resize(length.max(raw.len()), 0x4E);
+        raw
+    }
+
+    // decode a Write Track stream: ID fields follow 0xFE, data fields follow
+    // 0xFB/0xF8 and 0xF7 stands for the two CRC bytes
+    fn format(&mut self) {
+        let stream = std::mem::take(&mut self.buffer);
+        let mut ids: Vec<(SectorId, Vec<u8>)> = Vec::new();
+        let mut i = 0;
+
+        while i < stream.len() {
+            match stream[i] {
+                0xFE if i + 4 < stream.len() => {
+                    let id = SectorId {
+                        cylinder: stream[i + 1],
+                        head: stream[i + 2],
+                        sector: stream[i + 3],
+                        size: stream[i + 4] & 0x03,
+                    };
+                    ids.push((id, Vec::new()));
+                    i += 5;
+                }
+                0xFB | 0xF8 if ids.last().is_some_and(|(_, data)| data.is_empty()) => {
+                    let (id, data) = ids.last_mut().unwrap();
+                    let end = (i + 1 + id.data_len()).min(stream.len());
+                    data.extend(&stream[i + 1..end]);
+                    i = end;
+                }
+                _ => i += 1,
+            }
+        }
+
+        let (cylinder, side) = (self.current().cylinder, self.side);
+        let Some(disk) = self.current().disk.as_mut() else {
+            return;
+        };
+        let layout: Vec<SectorId> = ids.iter().map(|(id, _)| *id).collect();
+        let fill = ids
+            .first()
+            .and_then(|(_, data)| data.first().copied())
+            .unwrap_or(0xE5);
+        if disk.format_track(cylinder, side, &layout, fill).is_err() {
+            self.status |= WRITE_FAULT;
+            return;
+        }
+        for (id, data) in &ids {
+            if !data.is_empty() && data.iter().any(|&b| b != fill) {
+                let _ = disk.write_sector(cylinder, side, id.sector, data);
+            }
+        }
+    }
+}
+
+impl Device for Wd179x {
+    fn input(&mut self, port: u8) -> u8 {
+        self.read(port)
+    }
+
+    fn output(&mut self, port: u8, value: u8) {
+        self.write(port, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::disk::{self, Disk, Geometry, RawDisk};
+
+    const CLOCK: u64 = 2_000_000;
+
+    // a disk that takes no writes without being write protected
+    struct Faulty(RawDisk);
+
+    impl Disk for Faulty {
+        fn cylinders(&self) -> u8 {
+            self.0.cylinders()
+        }
+
+        fn heads(&self) -> u8 {
+            self.0.heads()
+        }
+
+        fn sector_ids(&self, cylinder: u8, head: u8) -> Vec<SectorId> {
+            self.0.sector_ids(cylinder, head)
+        }
+
+        fn read_sector(&mut self, cylinder: u8, head: u8, sector: u8) -> disk::Result<Vec<u8>> {
+            self.0.read_sector(cylinder, head, sector)
+        }
+
+        fn write_sector(&mut self, _: u8, _: u8, _: u8, _: &[u8]) -> disk::Result<()> {
+            Err(disk::Error::Unsupported)
+        }
+
+        fn format_track(&mut self, _: u8, _: u8, _: &[SectorId], _: u8) -> disk::Result<()> {
+            Err(disk::Error::Unsupported)
+        }
+
+        fn write_protected(&self) -> bool {
+            false
+        }
+    }
+
+    fn setup() -> Wd179x {
+        let mut disk = RawDisk::new(Geometry::IBM_3740);
+        disk.write_sector(2, 0, 5, &[0x42; 128]).unwrap();
+        let mut fdc = Wd179x::new(Variant::Wd1793, CLOCK);
+        fdc.insert(0, Box::new(disk));
+        fdc
+    }
+
+    fn wait(fdc: &mut Wd179x) {
+        while fdc.busy() && !fdc.drq().get() {
+            fdc.tick(100);
+        }
+    }
+
+    #[test]
+    fn test_seek_timing() {
+        let mut fdc = setup();
+        fdc.write(3, 2);
+        fdc.write(0, 0x17); // seek, verify, 15ms steps
+        assert!(fdc.busy());
+
+        fdc.tick(CLOCK / 1000 * 44);
+        assert!(fdc.busy());
+        fdc.tick(CLOCK / 1000);
+        assert!(!fdc.busy());
+        assert!(fdc.intrq().get());
+        assert_eq!(fdc.read(0) & 0x1F, 0x00);
+        assert!(!fdc.intrq().get());
+        assert_eq!(fdc.drive(0).cylinder, 2);
+
+        fdc.write(0, 0x00); // restore
+        wait(&mut fdc);
+        assert_eq!(fdc.read(0) & TRACK_0, TRACK_0);
+    }
+
+    #[test]
+    fn test_read_write_sector() {
+        let mut fdc = setup();
+        fdc.write(3, 2);
+        fdc.write(0, 0x10);
+        wait(&mut fdc);
+
+        fdc.write(2, 5);
+        fdc.write(0, 0x80);
+        wait(&mut fdc);
+        let data: Vec<u8> = (0..128).map(|_| fdc.read(3)).collect();
+        assert_eq!(data, vec![0x42; 128]);
+        assert!(fdc.intrq().get());
+
+        fdc.write(2, 6);
+        fdc.write(0, 0xA0);
+        wait(&mut fdc);
+        for n in 0..128 {
+            assert!(fdc.drq().get());
+            fdc.write(3, n);
+        }
+        assert!(!fdc.busy());
+        let disk = fdc.drive(0).disk.as_mut().unwrap();
+        assert_eq!(disk.read_sector(2, 0, 6).unwrap()[127], 127);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut fdc = setup();
+        fdc.write(2, 30);
+        fdc.write(0, 0x80);
+        wait(&mut fdc);
+        assert_eq!(fdc.read(0) & RECORD_NOT_FOUND, RECORD_NOT_FOUND);
+
+        let mut disk = RawDisk::new(Geometry::IBM_3740);
+        disk.set_write_protected(true);
+        fdc.insert(1, Box::new(disk));
+        fdc.select(1, 0);
+        fdc.write(0, 0xA0);
+        assert!(!fdc.busy());
+        assert_eq!(fdc.read(0) & WRITE_PROTECT, WRITE_PROTECT);
+
+        fdc.select(2, 0);
+        fdc.write(0, 0x80);
+        assert_eq!(fdc.read(0) & NOT_READY, NOT_READY);
+
+        fdc.insert(3, Box::new(Faulty(RawDisk::new(Geometry::IBM_3740))));
+        fdc.select(3, 0);
+        fdc.write(2, 1);
+        fdc.write(0, 0xA0);
+        wait(&mut fdc);
+        for _ in 0..128 {
+            fdc.write(3, 0);
+        }
+        assert_eq!(fdc.read(0) & (WRITE_FAULT | RECORD_NOT_FOUND), WRITE_FAULT);
+    }
+
+    #[test]
+    fn test_read_address_and_force_interrupt() {
+        let mut fdc = setup();
+        fdc.write(0, 0xC0);
+        wait(&mut fdc);
+        let id: Vec<u8> = (0..6).map(|_| fdc.read(3)).collect();
+        assert_eq!(&id[..4], &[0, 0, 2, 0]);
+
+        fdc.write(2, 1);
+        fdc.write(0, 0x90); // read multiple
+        wait(&mut fdc);
+        fdc.read(3);
+        fdc.write(0, 0xD8);
+        assert!(!fdc.busy());
+        assert!(fdc.intrq().get());
+    }
+}
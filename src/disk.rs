@@ -0,0 +1,411 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub mod diskdef;
+pub mod imd;
+pub mod simh;
+pub mod volume;
+
+pub use diskdef::DiskDef;
+pub use imd::ImdDisk;
+pub use simh::SimhDisk;
+pub use volume::Volume;
+
+#[derive(Debug)]
+pub enum Error {
+    SectorNotFound,
+    WriteProtected,
+    Unsupported,
+    Io(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::SectorNotFound => write!(f, "sector not found"),
+            Error::WriteProtected => write!(f, "disk is write protected"),
+            Error::Unsupported => write!(f, "operation not supported by this image"),
+            Error::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+// sector address as recorded in its ID field; `size` is the length code N,
+// the sector holds 128 << N bytes
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SectorId {
+    pub cylinder: u8,
+    pub head: u8,
+    pub sector: u8,
+    pub size: u8,
+}
+
+impl SectorId {
+    pub fn data_len(&self) -> usize {
+        128 << self.size
+    }
+}
+
+pub fn size_code(len: usize) -> u8 {
+    (len / 128).trailing_zeros() as u8
+}
+
+// a sector-addressed medium; tracks are located by physical cylinder and
+// head, sectors within a track by the R value of their ID field
+pub trait Disk {
+    fn cylinders(&self) -> u8;
+    fn heads(&self) -> u8;
+    fn sector_ids(&self, cylinder: u8, head: u8) -> Vec<SectorId>;
+
+    fn read_sector(&mut self, cylinder: u8, head: u8, sector: u8) -> Result<Vec<u8>>;
+    fn write_sector(&mut self, cylinder: u8, head: u8, sector: u8, data: &[u8]) -> Result<()>;
+    fn format_track(&mut self, cylinder: u8, head: u8, ids: &[SectorId], fill: u8) -> Result<()>;
+
+    fn write_protected(&self) -> bool;
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Geometry {
+    pub cylinders: u8,
+    pub heads: u8,
+    pub sectors: u8,
+    pub sector_size: usize,
+    pub first_sector: u8,
+}
+
+impl Geometry {
+    // 8" single sided single density, the CP/M distribution format
+    pub const IBM_3740: Geometry = Geometry {
+        cylinders: 77,
+        heads: 1,
+        sectors: 26,
+        sector_size: 128,
+        first_sector: 1,
+    };
+
+    pub fn track_size(&self) -> usize {
+        self.sectors as usize * self.sector_size
+    }
+
+    pub fn size(&self) -> usize {
+        self.cylinders as usize * self.heads as usize * self.track_size()
+    }
+}
+
+// raw sector dump in cylinder, head, sector order, optionally behind a
+// fixed size header; the header and anything past the last sector are
+// preserved as is
+pub struct RawDisk {
+    geometry: Geometry,
+    header: Vec<u8>,
+    data: Vec<u8>,
+    trailer: Vec<u8>,
+    path: Option<PathBuf>,
+    write_protected: bool,
+    dirty: bool,
+}
+
+impl RawDisk {
+    pub fn new(geometry: Geometry) -> Self {
+        RawDisk {
+            geometry,
+            header: Vec::new(),
+            data: vec![0xE5; geometry.size()],
+            trailer: Vec::new(),
+            path: None,
+            write_protected: false,
+            dirty: false,
+        }
+    }
+
+    // images shorter than the geometry are padded and longer ones keep the
+    // rest, read-only files are mounted write protected
+    pub fn open(path: impl AsRef<Path>, geometry: Geometry) -> Result<Self> {
+        Self::open_with_header(path, geometry, 0)
+    }
+
+    pub fn open_with_header(
+        path: impl AsRef<Path>,
+        geometry: Geometry,
+        header_len: usize,
+    ) -> Result<Self> {
+        let path = path.as_ref();
+        let mut data = fs::read(path)?;
+        if data.len() < header_len {
+            data.resize(header_len, 0);
+        }
+        let header = data.drain(..header_len).collect();
+        let trailer = data.split_off(geometry.size().min(data.len()));
+        data.resize(geometry.size(), 0xE5);
+        let write_protected = fs::metadata(path)?.permissions().readonly();
+
+        Ok(RawDisk {
+            geometry,
+            header,
+            data,
+            trailer,
+            path: Some(path.to_path_buf()),
+            write_protected,
+            dirty: false,
+        })
+    }
+
+    pub fn geometry(&self) -> Geometry {
+        self.geometry
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn set_write_protected(&mut self, write_protected: bool) {
+        self.write_protected = write_protected;
+    }
+
+    fn offset(&self, cylinder: u8, head: u8, sector: u8) -> Result<usize> {
+        let g = &self.geometry;
+        if cylinder >= g.cylinders
+            || head >= g.heads
+            || sector < g.first_sector
+            || sector - g.first_sector >= g.sectors
+        {
+            return Err(Error::SectorNotFound);
+        }
+        let track = cylinder as usize * g.heads as usize + head as usize;
+        Ok(track * g.track_size() + (sector - g.first_sector) as usize * g.sector_size)
+    }
+}
+
+impl Disk for RawDisk {
+    fn cylinders(&self) -> u8 {
+        self.geometry.cylinders
+    }
+
+    fn heads(&self) -> u8 {
+        self.geometry.heads
+    }
+
+    fn sector_ids(&self, cylinder: u8, head: u8) -> Vec<SectorId> {
+        if cylinder >= self.geometry.cylinders || head >= self.geometry.heads {
+            return Vec::new();
+        }
+        let size = size_code(self.geometry.sector_size);
+        (0..self.geometry.sectors)
+            .map(|n| SectorId {
+                cylinder,
+                head,
+                sector: self.geometry.first_sector + n,
+                size,
+            })
+            .collect()
+    }
+
+    fn read_sector(&mut self, cylinder: u8, head: u8, sector: u8) -> Result<Vec<u8>> {
+        let offset = self.offset(cylinder, head, sector)?;
+        Ok(self.data[offset..offset + self.geometry.sector_size].to_vec())
+    }
+
+    fn write_sector(&mut self, cylinder: u8, head: u8, sector: u8, data: &[u8]) -> Result<()> {
+        if self.write_protected {
+            return Err(Error::WriteProtected);
+        }
+        let offset = self.offset(cylinder, head, sector)?;
+        let len = data.len().min(self.geometry.sector_size);
+        self.data[offset..offset + len].copy_from_slice(&data[..len]);
+        self.dirty = true;
+        Ok(())
+    }
+
+    fn format_track(&mut self, cylinder: u8, head: u8, ids: &[SectorId], fill: u8) -> Result<()> {
+        if self.write_protected {
+            return Err(Error::WriteProtected);
+        }
+        // a raw image can only be reformatted with its own layout
+        let expected = self.sector_ids(cylinder, head);
+        let mut sectors: Vec<u8> = ids.iter().map(|id| id.sector).collect();
+        sectors.sort_unstable();
+        let layout_matches = ids.len() == expected.len()
+            && ids
+                .iter()
+                .all(|id| id.data_len() == self.geometry.sector_size)
+            && sectors.iter().zip(&expected).all(|(s, e)| *s == e.sector);
+        if !layout_matches {
+            return Err(Error::Unsupported);
+        }
+
+        let start = self.offset(cylinder, head, self.geometry.first_sector)?;
+        self.data[start..start + self.geometry.track_size()].fill(fill);
+        self.dirty = true;
+        Ok(())
+    }
+
+    fn write_protected(&self) -> bool {
+        self.write_protected
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        if let (true, Some(path)) = (self.dirty, &self.path) {
+            fs::write(path, [&self.header[..], &self.data, &self.trailer].concat())?;
+            self.dirty = false;
+        }
+        Ok(())
+    }
+}
+
+// open an image, picking the container format from the extension and size;
+// anything unrecognised is taken as a raw dump laid out as `def` describes
+pub fn open(path: impl AsRef<Path>, def: &DiskDef) -> Result<Box<dyn Disk>> {
+    let path = path.as_ref();
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+    let len = fs::metadata(path)?.len() as usize;
+
+    Ok(match extension.as_deref() {
+        Some("imd") => Box::new(ImdDisk::open(path)?),
+        Some("dsk") if len == simh::IMAGE_SIZE => Box::new(SimhDisk::open(path)?),
+        _ => Box::new(RawDisk::open_with_header(path, def.geometry(), def.offset)?),
+    })
+}
+
+// write a blank, formatted image in the format `open` would pick for the
+// path, then open it
+pub fn create(path: impl AsRef<Path>, def: &DiskDef) -> Result<Box<dyn Disk>> {
+    let path = path.as_ref();
+    let geometry = def.geometry();
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+
+    let bytes = match extension.as_deref() {
+        Some("imd") => {
+            let mut disk = ImdDisk::new(&def.name);
+            let layout = RawDisk::new(geometry);
+            for cylinder in 0..geometry.cylinders {
+                for head in 0..geometry.heads {
+                    let ids = layout.sector_ids(cylinder, head);
+                    disk.format_track(cylinder, head, &ids, 0xE5)?;
+                }
+            }
+            disk.to_bytes()
+        }
+        Some("dsk") if geometry.size() == simh::TRACKS as usize * simh::SECTORS as usize * 128 => {
+            SimhDisk::new().data().to_vec()
+        }
+        _ => [vec![0; def.offset], vec![0xE5; geometry.size()]].concat(),
+    };
+    fs::write(path, bytes)?;
+    open(path, def)
+}
+
+// a drive mechanism: the inserted medium and the physical head position
+#[derive(Default)]
+pub struct Drive {
+    pub disk: Option<Box<dyn Disk>>,
+    pub cylinder: u8,
+    rotation: usize,
+}
+
+impl Drive {
+    pub fn ready(&self) -> bool {
+        self.disk.is_some()
+    }
+
+    pub fn write_protected(&self) -> bool {
+        self.disk
+            .as_ref()
+            .is_some_and(|disk| disk.write_protected())
+    }
+
+    // IDs come past the head in order, one per call
+    pub fn next_id(&mut self, head: u8) -> Option<SectorId> {
+        let ids = self.disk.as_ref()?.sector_ids(self.cylinder, head);
+        if ids.is_empty() {
+            return None;
+        }
+        self.rotation = (self.rotation + 1) % ids.len();
+        Some(ids[self.rotation])
+    }
+
+    pub fn find(&self, head: u8, matches: impl Fn(&SectorId) -> bool) -> Option<SectorId> {
+        let disk = self.disk.as_ref()?;
+        disk.sector_ids(self.cylinder, head)
+            .into_iter()
+            .find(matches)
+    }
+
+    pub fn step(&mut self, inward: bool) {
+        if inward {
+            self.cylinder = self.cylinder.saturating_add(1).min(MAX_CYLINDER);
+        } else {
+            self.cylinder = self.cylinder.saturating_sub(1);
+        }
+    }
+}
+
+// the furthest a head stepper can travel
+const MAX_CYLINDER: u8 = 83;
+
+// CRC-CCITT as used on ID and data fields
+pub fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_raw_extra_bytes() {
+        let path = std::env::temp_dir().join(format!("remu-raw-{}.img", std::process::id()));
+        let geometry = Geometry {
+            cylinders: 1,
+            heads: 1,
+            sectors: 2,
+            sector_size: 128,
+            first_sector: 1,
+        };
+        let mut image = vec![b'h'; 4];
+        image.extend_from_slice(&[0; 256]);
+        image.extend_from_slice(b"trailer");
+        fs::write(&path, &image).unwrap();
+
+        let mut disk = RawDisk::open_with_header(&path, geometry, 4).unwrap();
+        disk.write_sector(0, 0, 2, &[1; 128]).unwrap();
+        disk.flush().unwrap();
+        let saved = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        // the header and the bytes past the last sector come back untouched
+        image[4 + 128..4 + 256].fill(1);
+        assert_eq!(saved, image);
+    }
+}
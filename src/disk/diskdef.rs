@@ -0,0 +1,399 @@
+use std::fmt;
+
+use super::Geometry;
+
+// built-in definitions, in the same syntax as a cpmtools diskdefs file
+const BUILTIN: &str = "
+diskdef ibm-3740
+  seclen 128
+  tracks 77
+  sectrk 26
+  blocksize 1024
+  maxdir 64
+  skew 6
+  boottrk 2
+  os 2.2
+end
+
+diskdef 4mb-hd
+  seclen 128
+  tracks 1024
+  sectrk 32
+  blocksize 2048
+  maxdir 256
+  skew 1
+  boottrk 0
+  os 2.2
+end
+";
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Os {
+    Cpm22,
+    Cpm3,
+    P2dos,
+    Zsys,
+}
+
+#[derive(Debug)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "diskdefs line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// CP/M disk parameter block as the BIOS would hold it
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Dpb {
+    pub spt: u16,
+    pub bsh: u8,
+    pub blm: u8,
+    pub exm: u8,
+    pub dsm: u16,
+    pub drm: u16,
+    pub al0: u8,
+    pub al1: u8,
+    pub cks: u16,
+    pub off: u16,
+    pub psh: u8,
+    pub phm: u8,
+}
+
+impl Dpb {
+    // the 15 byte (CP/M 2.2) or 17 byte (CP/M 3) in-memory layout
+    pub fn to_bytes(&self, os: Os) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(17);
+        bytes.extend(self.spt.to_le_bytes());
+        bytes.extend([self.bsh, self.blm, self.exm]);
+        bytes.extend(self.dsm.to_le_bytes());
+        bytes.extend(self.drm.to_le_bytes());
+        bytes.extend([self.al0, self.al1]);
+        bytes.extend(self.cks.to_le_bytes());
+        bytes.extend(self.off.to_le_bytes());
+        if os == Os::Cpm3 {
+            bytes.extend([self.psh, self.phm]);
+        }
+        bytes
+    }
+}
+
+// a cpmtools-style description of a CP/M file system layout
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DiskDef {
+    pub name: String,
+    pub seclen: usize,
+    pub tracks: u16,
+    pub sectrk: u16,
+    pub blocksize: usize,
+    pub maxdir: usize,
+    pub skewtab: Vec<u16>,
+    pub boottrk: u16,
+    pub offset: usize,
+    pub os: Os,
+}
+
+impl DiskDef {
+    pub fn builtin(name: &str) -> Option<DiskDef> {
+        parse(BUILTIN)
+            .expect("built-in diskdefs are valid")
+            .into_iter()
+            .find(|def| def.name == name)
+    }
+
+    // look a definition up in a diskdefs file, falling back to the
+    // built-in ones
+    pub fn find(text: Option<&str>, name: &str) -> Result<Option<DiskDef>, ParseError> {
+        if let Some(text) = text
+            && let Some(def) = parse(text)?.into_iter().find(|def| def.name == name)
+        {
+            return Ok(Some(def));
+        }
+        Ok(Self::builtin(name))
+    }
+
+    // logical sector number within a track to physical sector index
+    pub fn translate(&self, sector: u16) -> u16 {
+        self.skewtab.get(sector as usize).copied().unwrap_or(sector)
+    }
+
+    pub fn records_per_sector(&self) -> usize {
+        self.seclen / 128
+    }
+
+    pub fn records_per_block(&self) -> usize {
+        self.blocksize / 128
+    }
+
+    pub fn blocks(&self) -> usize {
+        (self.tracks - self.boottrk) as usize * self.sectrk as usize * self.seclen / self.blocksize
+    }
+
+    pub fn directory_blocks(&self) -> usize {
+        (self.maxdir * 32).div_ceil(self.blocksize)
+    }
+
+    // physical layout of a raw dump in this format; tracks are spread over
+    // as many heads as needed to keep cylinder numbers within a byte
+    pub fn geometry(&self) -> Geometry {
+        let mut heads = 1;
+        while self.tracks.div_ceil(heads) > 255 {
+            heads *= 2;
+        }
+        Geometry {
+            cylinders: self.tracks.div_ceil(heads) as u8,
+            heads: heads as u8,
+            sectors: self.sectrk as u8,
+            sector_size: self.seclen,
+            first_sector: 1,
+        }
+    }
+
+    pub fn dpb(&self) -> Dpb {
+        let records = self.records_per_block();
+        let dsm = self.blocks() - 1;
+        let exm = if dsm < 256 {
+            self.blocksize / 1024 - 1
+        } else {
+            self.blocksize / 2048 - 1
+        };
+        let al = (0xFFFFu32 << (16 - self.directory_blocks().min(16))) as u16;
+
+        Dpb {
+            spt: self.sectrk * self.records_per_sector() as u16,
+            bsh: records.trailing_zeros() as u8,
+            blm: (records - 1) as u8,
+            exm: exm as u8,
+            dsm: dsm as u16,
+            drm: (self.maxdir - 1) as u16,
+            al0: (al >> 8) as u8,
+            al1: al as u8,
+            cks: (self.maxdir / 4) as u16,
+            off: self.boottrk,
+            psh: self.records_per_sector().trailing_zeros() as u8,
+            phm: (self.records_per_sector() - 1) as u8,
+        }
+    }
+}
+
+pub fn skew_table(sectrk: u16, skew: u16) -> Vec<u16> {
+    let mut table = Vec::with_capacity(sectrk as usize);
+    let mut used = vec![false; sectrk as usize];
+    let mut j = 0;
+    for _ in 0..sectrk {
+        while used[j as usize] {
+            j = (j + 1) % sectrk;
+        }
+        table.push(j);
+        used[j as usize] = true;
+        j = (j + skew) % sectrk;
+    }
+    table
+}
+
+pub fn parse(text: &str) -> Result<Vec<DiskDef>, ParseError> {
+    let mut defs = Vec::new();
+    let mut current: Option<(usize, Vec<(String, String)>)> = None;
+
+    for (index, raw) in text.lines().enumerate() {
+        let line = index + 1;
+        let content = raw.split('#').next().unwrap_or("").trim();
+        if content.is_empty() {
+            continue;
+        }
+        let mut words = content.split_whitespace();
+        let key = words.next().unwrap_or("");
+        let value = words.collect::<Vec<_>>().join(" ");
+
+        match (&mut current, key) {
+            (None, "diskdef") => current = Some((line, vec![("name".to_string(), value)])),
+            (None, _) => {
+                return Err(ParseError {
+                    line,
+                    message: format!("expected diskdef, found {}", key),
+                });
+            }
+            (Some(_), "end") => {
+                let (start, fields) = current.take().unwrap();
+                defs.push(build(start, &fields)?);
+            }
+            (Some((_, fields)), _) => fields.push((key.to_string(), value)),
+        }
+    }
+
+    if let Some((line, _)) = current {
+        return Err(ParseError {
+            line,
+            message: "diskdef without end".to_string(),
+        });
+    }
+    Ok(defs)
+}
+
+fn build(line: usize, fields: &[(String, String)]) -> Result<DiskDef, ParseError> {
+    let error = |message: String| ParseError { line, message };
+    let get = |key: &str| {
+        fields
+            .iter()
+            .rev()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    };
+    let number = |key: &str| -> Result<Option<usize>, ParseError> {
+        match get(key) {
+            None => Ok(None),
+            Some(value) => parse_number(value)
+                .map(Some)
+                .ok_or_else(|| error(format!("invalid {} value {}", key, value))),
+        }
+    };
+    let required = |key: &str| -> Result<usize, ParseError> {
+        number(key)?.ok_or_else(|| error(format!("missing {}", key)))
+    };
+
+    let name = get("name").unwrap_or("").to_string();
+    let seclen = required("seclen")?;
+    let tracks = required("tracks")? as u16;
+    let sectrk = required("sectrk")? as u16;
+    let blocksize = required("blocksize")?;
+    let maxdir = required("maxdir")?;
+    let boottrk = number("boottrk")?.unwrap_or(0) as u16;
+    let offset = number("offset")?.unwrap_or(0);
+
+    if seclen < 128 || !seclen.is_power_of_two() {
+        return Err(error(format!("unsupported seclen {}", seclen)));
+    }
+    if blocksize < 1024 || !blocksize.is_power_of_two() {
+        return Err(error(format!("unsupported blocksize {}", blocksize)));
+    }
+    if boottrk >= tracks || sectrk == 0 {
+        return Err(error("no data tracks".to_string()));
+    }
+    if maxdir == 0 {
+        return Err(error("no directory entries".to_string()));
+    }
+
+    let skewtab = match get("skewtab") {
+        Some(list) => list
+            .split(',')
+            .map(|n| parse_number(n.trim()).map(|n| n as u16))
+            .collect::<Option<Vec<_>>>()
+            .filter(|table| table.len() == sectrk as usize)
+            .ok_or_else(|| error(format!("invalid skewtab {}", list)))?,
+        None => skew_table(sectrk, number("skew")?.unwrap_or(1).max(1) as u16),
+    };
+
+    let os = match get("os").unwrap_or("2.2") {
+        "2.2" => Os::Cpm22,
+        "3" => Os::Cpm3,
+        "p2dos" => Os::P2dos,
+        "zsys" => Os::Zsys,
+        other => return Err(error(format!("unknown os {}", other))),
+    };
+
+    let def = DiskDef {
+        name,
+        seclen,
+        tracks,
+        sectrk,
+        blocksize,
+        maxdir,
+        skewtab,
+        boottrk,
+        offset,
+        os,
+    };
+    // the DPB has to be able to describe the disk: DSM and DRM fit 16
+    // bits, and with 1K blocks EXM only works for up to 256 of them
+    let blocks = def.blocks();
+    if blocks <= def.directory_blocks() {
+        return Err(error("no room for data blocks".to_string()));
+    }
+    if blocks > 0x10000 || maxdir > 0x10000 {
+        return Err(error(format!(
+            "{} blocks and {} entries is too big",
+            blocks, maxdir
+        )));
+    }
+    if blocksize == 1024 && blocks > 256 {
+        return Err(error(format!("{} 1K blocks, at most 256 allowed", blocks)));
+    }
+    Ok(def)
+}
+
+fn parse_number(value: &str) -> Option<usize> {
+    if let Some(hex) = value.strip_prefix("0x") {
+        usize::from_str_radix(hex, 16).ok()
+    } else {
+        value.parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ibm_3740() {
+        let def = DiskDef::builtin("ibm-3740").unwrap();
+        assert_eq!(&def.skewtab[..6], &[0, 6, 12, 18, 24, 4]);
+        assert_eq!(
+            def.dpb(),
+            Dpb {
+                spt: 26,
+                bsh: 3,
+                blm: 7,
+                exm: 0,
+                dsm: 242,
+                drm: 63,
+                al0: 0xC0,
+                al1: 0x00,
+                cks: 16,
+                off: 2,
+                psh: 0,
+                phm: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        let text = "
+# comment
+diskdef test
+  seclen 512
+  tracks 160
+  sectrk 9
+  blocksize 2048
+  maxdir 128
+  skewtab 0,2,4,6,8,1,3,5,7
+  boottrk 2
+  os 3
+end
+";
+        let defs = parse(text).unwrap();
+        assert_eq!(defs[0].name, "test");
+        assert_eq!(defs[0].translate(5), 1);
+        let dpb = defs[0].dpb();
+        assert_eq!((dpb.spt, dpb.dsm, dpb.exm, dpb.al0), (36, 354, 0, 0xC0));
+        assert_eq!((dpb.psh, dpb.phm), (2, 3));
+
+        let err = parse("diskdef bad\n  seclen 100\nend\n").unwrap_err();
+        assert_eq!(err.line, 1);
+
+        // 1K blocks cannot describe more than 256 of them, and every
+        // layout needs room for data
+        let big =
+            "diskdef big\n seclen 512\n tracks 160\n sectrk 9\n blocksize 1024\n maxdir 64\nend\n";
+        assert!(parse(big).unwrap_err().message.contains("1K blocks"));
+        let tiny =
+            "diskdef tiny\n seclen 128\n tracks 1\n sectrk 4\n blocksize 1024\n maxdir 64\nend\n";
+        assert!(parse(tiny).is_err());
+    }
+}
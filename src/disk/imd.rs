@@ -0,0 +1,423 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::{Disk, Error, Result, SectorId, size_code};
+
+// data record types
+const UNAVAILABLE: u8 = 0;
+const NORMAL: u8 = 1;
+const DELETED: u8 = 3;
+
+struct Sector {
+    id: SectorId,
+    data: Option<Vec<u8>>,
+    // record type with the compression bit cleared: 1, 3, 5 or 7
+    kind: u8,
+}
+
+struct Track {
+    mode: u8,
+    cylinder: u8,
+    head: u8,
+    sectors: Vec<Sector>,
+}
+
+// ImageDisk (.IMD) image: per-track recording mode, sector numbering,
+// optional cylinder/head maps and compressed sectors
+pub struct ImdDisk {
+    header: Vec<u8>,
+    tracks: Vec<Track>,
+    path: Option<PathBuf>,
+    write_protected: bool,
+    dirty: bool,
+}
+
+impl ImdDisk {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let mut disk = Self::parse(&fs::read(path)?)?;
+        disk.path = Some(path.to_path_buf());
+        disk.write_protected = fs::metadata(path)?.permissions().readonly();
+        Ok(disk)
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        if !bytes.starts_with(b"IMD ") {
+            return Err(invalid("missing IMD signature"));
+        }
+        let end = bytes
+            .iter()
+            .position(|&b| b == 0x1A)
+            .ok_or_else(|| invalid("unterminated comment"))?;
+        let header = bytes[..end].to_vec();
+
+        let mut tracks = Vec::new();
+        let mut reader = Reader {
+            bytes,
+            pos: end + 1,
+        };
+        while !reader.done() {
+            let mode = reader.byte()?;
+            let cylinder = reader.byte()?;
+            let flags = reader.byte()?;
+            let count = reader.byte()? as usize;
+            let size = reader.byte()?;
+            let head = flags & 0x01;
+
+            let numbers = reader.take(count)?.to_vec();
+            let cylinders = if flags & 0x80 != 0 {
+                reader.take(count)?.to_vec()
+            } else {
+                vec![cylinder; count]
+            };
+            let heads = if flags & 0x40 != 0 {
+                reader.take(count)?.to_vec()
+            } else {
+                vec![head; count]
+            };
+            let sizes: Vec<usize> = if size == 0xFF {
+                reader
+                    .take(count * 2)?
+                    .chunks(2)
+                    .map(|b| u16::from_le_bytes([b[0], b[1]]) as usize)
+                    .collect()
+            } else if size <= 6 {
+                vec![128 << size; count]
+            } else {
+                return Err(invalid("bad sector size"));
+            };
+
+            // a sector has at least its ID's 128 bytes
+            if sizes.iter().any(|&size| size < 128) {
+                return Err(invalid("bad sector size"));
+            }
+
+            let mut sectors = Vec::with_capacity(count);
+            for n in 0..count {
+                let kind = reader.byte()?;
+                let data = match kind {
+                    UNAVAILABLE => None,
+                    1 | 3 | 5 | 7 => Some(reader.take(sizes[n])?.to_vec()),
+                    2 | 4 | 6 | 8 => Some(vec![reader.byte()?; sizes[n]]),
+                    _ => return Err(invalid("bad sector record type")),
+                };
+                let kind = if kind != UNAVAILABLE && kind & 1 == 0 {
+                    kind - 1
+                } else {
+                    kind
+                };
+                sectors.push(Sector {
+                    id: SectorId {
+                        cylinder: cylinders[n],
+                        head: heads[n],
+                        sector: numbers[n],
+                        size: size_code(sizes[n]),
+                    },
+                    data,
+                    kind,
+                });
+            }
+
+            tracks.push(Track {
+                mode,
+                cylinder,
+                head,
+                sectors,
+            });
+        }
+
+        Ok(ImdDisk {
+            header,
+            tracks,
+            path: None,
+            write_protected: false,
+            dirty: false,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = self.header.clone();
+        out.push(0x1A);
+
+        for track in &self.tracks {
+            let count = track.sectors.len();
+            let lengths: Vec<usize> = track
+                .sectors
+                .iter()
+                .map(|s| s.data.as_ref().map_or(s.id.data_len(), Vec::len))
+                .collect();
+            let uniform = lengths.windows(2).all(|w| w[0] == w[1]);
+            let cylinder_map = track
+                .sectors
+                .iter()
+                .any(|s| s.id.cylinder != track.cylinder);
+            let head_map = track.sectors.iter().any(|s| s.id.head != track.head);
+
+            let mut flags = track.head;
+            if cylinder_map {
+                flags |= 0x80;
+            }
+            if head_map {
+                flags |= 0x40;
+            }
+            let size = match lengths.first() {
+                Some(&len) if uniform => size_code(len),
+                Some(_) => 0xFF,
+                None => 0,
+            };
+
+            out.extend([track.mode, track.cylinder, flags, count as u8, size]);
+            out.extend(track.sectors.iter().map(|s| s.id.sector));
+            if cylinder_map {
+                out.extend(track.sectors.iter().map(|s| s.id.cylinder));
+            }
+            if head_map {
+                out.extend(track.sectors.iter().map(|s| s.id.head));
+            }
+            if size == 0xFF {
+                for len in &lengths {
+                    out.extend((*len as u16).to_le_bytes());
+                }
+            }
+
+            for sector in &track.sectors {
+                match &sector.data {
+                    None => out.push(UNAVAILABLE),
+                    Some(data)
+                        if data
+                            .first()
+                            .is_some_and(|&first| data.iter().all(|&b| b == first)) =>
+                    {
+                        out.push(sector.kind + 1);
+                        out.push(data[0]);
+                    }
+                    Some(data) => {
+                        out.push(sector.kind);
+                        out.extend(data);
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    pub fn set_write_protected(&mut self, write_protected: bool) {
+        self.write_protected = write_protected;
+    }
+
+    fn track(&self, cylinder: u8, head: u8) -> Option<&Track> {
+        self.tracks
+            .iter()
+            .find(|t| t.cylinder == cylinder && t.head == head)
+    }
+
+    fn sector_mut(&mut self, cylinder: u8, head: u8, sector: u8) -> Result<&mut Sector> {
+        self.tracks
+            .iter_mut()
+            .find(|t| t.cylinder == cylinder && t.head == head)
+            .and_then(|t| t.sectors.iter_mut().find(|s| s.id.sector == sector))
+            .ok_or(Error::SectorNotFound)
+    }
+}
+
+impl Disk for ImdDisk {
+    fn cylinders(&self) -> u8 {
+        // a track on cylinder 255 would make 256, which a u8 cannot count
+        self.tracks
+            .iter()
+            .map(|t| (t.cylinder as u16 + 1).min(u8::MAX as u16) as u8)
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn heads(&self) -> u8 {
+        self.tracks.iter().map(|t| t.head + 1).max().unwrap_or(0)
+    }
+
+    fn sector_ids(&self, cylinder: u8, head: u8) -> Vec<SectorId> {
+        self.track(cylinder, head)
+            .map(|t| t.sectors.iter().map(|s| s.id).collect())
+            .unwrap_or_default()
+    }
+
+    fn read_sector(&mut self, cylinder: u8, head: u8, sector: u8) -> Result<Vec<u8>> {
+        self.sector_mut(cylinder, head, sector)?
+            .data
+            .clone()
+            .ok_or(Error::SectorNotFound)
+    }
+
+    fn write_sector(&mut self, cylinder: u8, head: u8, sector: u8, data: &[u8]) -> Result<()> {
+        if self.write_protected {
+            return Err(Error::WriteProtected);
+        }
+        let target = self.sector_mut(cylinder, head, sector)?;
+        let mut contents = vec![0; target.id.data_len()];
+        let len = data.len().min(contents.len());
+        contents[..len].copy_from_slice(&data[..len]);
+        target.data = Some(contents);
+        target.kind = NORMAL;
+        self.dirty = true;
+        Ok(())
+    }
+
+    fn format_track(&mut self, cylinder: u8, head: u8, ids: &[SectorId], fill: u8) -> Result<()> {
+        if self.write_protected {
+            return Err(Error::WriteProtected);
+        }
+        let sectors = ids
+            .iter()
+            .map(|&id| Sector {
+                id,
+                data: Some(vec![fill; id.data_len()]),
+                kind: NORMAL,
+            })
+            .collect();
+
+        // keep the recording mode of the track being replaced; otherwise
+        // assume FM for 128 byte sectors and MFM for anything larger
+        let position = self
+            .tracks
+            .iter()
+            .position(|t| t.cylinder == cylinder && t.head == head);
+        let mode = match position {
+            Some(index) => self.tracks[index].mode,
+            None if ids.iter().all(|id| id.size == 0) => 0,
+            None => 3,
+        };
+        let track = Track {
+            mode,
+            cylinder,
+            head,
+            sectors,
+        };
+        match position {
+            Some(index) => self.tracks[index] = track,
+            None => {
+                let index = self
+                    .tracks
+                    .iter()
+                    .position(|t| (t.cylinder, t.head) > (cylinder, head))
+                    .unwrap_or(self.tracks.len());
+                self.tracks.insert(index, track);
+            }
+        }
+        self.dirty = true;
+        Ok(())
+    }
+
+    fn write_protected(&self) -> bool {
+        self.write_protected
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        if let (true, Some(path)) = (self.dirty, &self.path) {
+            fs::write(path, self.to_bytes())?;
+            self.dirty = false;
+        }
+        Ok(())
+    }
+}
+
+impl ImdDisk {
+    // blank image with a comment line, to be filled by format_track
+    pub fn new(comment: &str) -> Self {
+        ImdDisk {
+            header: format!("IMD 1.18: {}\r\n", comment).into_bytes(),
+            tracks: Vec::new(),
+            path: None,
+            write_protected: false,
+            dirty: false,
+        }
+    }
+
+    // records written with a deleted data address mark
+    pub fn is_deleted(&self, cylinder: u8, head: u8, sector: u8) -> bool {
+        self.track(cylinder, head)
+            .and_then(|t| t.sectors.iter().find(|s| s.id.sector == sector))
+            .is_some_and(|s| s.kind & DELETED == DELETED)
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn done(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+
+    fn byte(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self.pos + len;
+        if end > self.bytes.len() {
+            return Err(invalid("truncated image"));
+        }
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+}
+
+fn invalid(message: &str) -> Error {
+    Error::Io(std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        format!("IMD: {}", message),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut disk = ImdDisk::new("test");
+        let ids: Vec<SectorId> = [1, 3, 2]
+            .iter()
+            .map(|&sector| SectorId {
+                cylinder: 0,
+                head: 0,
+                sector,
+                size: 1,
+            })
+            .collect();
+        disk.format_track(0, 0, &ids, 0xE5).unwrap();
+        let mut odd = ids.clone();
+        odd[0].cylinder = 9;
+        disk.format_track(1, 0, &odd, 0x00).unwrap();
+        let data: Vec<u8> = (0..=255).collect();
+        disk.write_sector(0, 0, 3, &data).unwrap();
+
+        let bytes = disk.to_bytes();
+        let mut copy = ImdDisk::parse(&bytes).unwrap();
+        assert_eq!(copy.to_bytes(), bytes);
+        assert_eq!(copy.read_sector(0, 0, 3).unwrap(), data);
+        assert_eq!(copy.read_sector(0, 0, 2).unwrap(), vec![0xE5; 256]);
+        assert_eq!(copy.sector_ids(1, 0)[0].cylinder, 9);
+        assert_eq!((copy.cylinders(), copy.heads()), (2, 1));
+        assert!(matches!(
+            copy.read_sector(0, 0, 4),
+            Err(Error::SectorNotFound)
+        ));
+    }
+
+    #[test]
+    fn test_odd_tracks() {
+        // cylinder 255, with a size table
+        let mut bytes = b"IMD 1.18: test\r\n\x1A".to_vec();
+        bytes.extend([0, 255, 0, 2, 0xFF, 1, 2, 0x80, 0, 0, 1, 2, 0xAA, 2, 0xBB]);
+        let disk = ImdDisk::parse(&bytes).unwrap();
+        assert_eq!(disk.to_bytes(), bytes);
+        assert_eq!(disk.cylinders(), 255);
+
+        // an empty sector in the size table
+        bytes[24..26].copy_from_slice(&[0, 0]);
+        assert!(ImdDisk::parse(&bytes).is_err());
+    }
+}
@@ -0,0 +1,166 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::{Disk, Error, Result, SectorId};
+
+pub const TRACKS: u8 = 77;
+pub const SECTORS: u8 = 32;
+pub const SECTOR_SIZE: usize = 137;
+pub const IMAGE_SIZE: usize = TRACKS as usize * SECTORS as usize * SECTOR_SIZE;
+
+// Altair 8" image as used by SIMH: every sector is stored as the 137 bytes
+// the MITS controller sees; the 128 byte payload sits behind a short header
+// whose layout differs between the system tracks and the rest
+pub struct SimhDisk {
+    data: Vec<u8>,
+    path: Option<PathBuf>,
+    write_protected: bool,
+    dirty: bool,
+}
+
+impl SimhDisk {
+    pub fn new() -> Self {
+        let mut disk = SimhDisk {
+            data: vec![0xE5; IMAGE_SIZE],
+            path: None,
+            write_protected: false,
+            dirty: false,
+        };
+        for track in 0..TRACKS {
+            for sector in 0..SECTORS {
+                disk.seal(track, sector);
+            }
+        }
+        disk
+    }
+
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let mut data = fs::read(path)?;
+        data.resize(IMAGE_SIZE, 0xE5);
+        let write_protected = fs::metadata(path)?.permissions().readonly();
+
+        Ok(SimhDisk {
+            data,
+            path: Some(path.to_path_buf()),
+            write_protected,
+            dirty: false,
+        })
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn set_write_protected(&mut self, write_protected: bool) {
+        self.write_protected = write_protected;
+    }
+
+    fn start(track: u8, sector: u8) -> Result<usize> {
+        if track >= TRACKS || sector >= SECTORS {
+            return Err(Error::SectorNotFound);
+        }
+        Ok((track as usize * SECTORS as usize + sector as usize) * SECTOR_SIZE)
+    }
+
+    // payload offset and checksum position within a raw sector
+    fn layout(track: u8) -> (usize, usize) {
+        if track < 6 { (3, 132) } else { (7, 4) }
+    }
+
+    // rewrite the header, stop byte and checksum around the payload
+    fn seal(&mut self, track: u8, sector: u8) {
+        let start = Self::start(track, sector).unwrap();
+        let (payload, checksum) = Self::layout(track);
+        let raw = &mut self.data[start..start + SECTOR_SIZE];
+        let sum = raw[payload..payload + 128]
+            .iter()
+            .fold(0u8, |sum, &b| sum.wrapping_add(b));
+
+        raw[0] = track | 0x80;
+        raw[checksum] = sum;
+        raw[payload + 128] = 0xFF;
+        if track >= 6 {
+            raw[1] = sector.wrapping_mul(17) & 0x1F;
+            raw[3] = 0x80;
+        }
+    }
+}
+
+impl Default for SimhDisk {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Disk for SimhDisk {
+    fn cylinders(&self) -> u8 {
+        TRACKS
+    }
+
+    fn heads(&self) -> u8 {
+        1
+    }
+
+    fn sector_ids(&self, cylinder: u8, head: u8) -> Vec<SectorId> {
+        if cylinder >= TRACKS || head != 0 {
+            return Vec::new();
+        }
+        (0..SECTORS)
+            .map(|sector| SectorId {
+                cylinder,
+                head,
+                sector,
+                size: 0,
+            })
+            .collect()
+    }
+
+    fn read_sector(&mut self, cylinder: u8, head: u8, sector: u8) -> Result<Vec<u8>> {
+        if head != 0 {
+            return Err(Error::SectorNotFound);
+        }
+        let start = Self::start(cylinder, sector)? + Self::layout(cylinder).0;
+        Ok(self.data[start..start + 128].to_vec())
+    }
+
+    fn write_sector(&mut self, cylinder: u8, head: u8, sector: u8, data: &[u8]) -> Result<()> {
+        if self.write_protected {
+            return Err(Error::WriteProtected);
+        }
+        if head != 0 {
+            return Err(Error::SectorNotFound);
+        }
+        let start = Self::start(cylinder, sector)? + Self::layout(cylinder).0;
+        let len = data.len().min(128);
+        self.data[start..start + len].copy_from_slice(&data[..len]);
+        self.seal(cylinder, sector);
+        self.dirty = true;
+        Ok(())
+    }
+
+    fn format_track(&mut self, cylinder: u8, head: u8, ids: &[SectorId], fill: u8) -> Result<()> {
+        if self.write_protected {
+            return Err(Error::WriteProtected);
+        }
+        if ids != self.sector_ids(cylinder, head).as_slice() {
+            return Err(Error::Unsupported);
+        }
+        for sector in 0..SECTORS {
+            self.write_sector(cylinder, head, sector, &[fill; 128])?;
+        }
+        Ok(())
+    }
+
+    fn write_protected(&self) -> bool {
+        self.write_protected
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        if let (true, Some(path)) = (self.dirty, &self.path) {
+            fs::write(path, &self.data)?;
+            self.dirty = false;
+        }
+        Ok(())
+    }
+}
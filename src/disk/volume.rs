@@ -0,0 +1,153 @@
+use super::{Disk, DiskDef, Error, Result, SectorId};
+
+// the logical view of a medium that CP/M has: numbered tracks of 128 byte
+// records, and allocation blocks counted from the first track after the
+// system area
+pub struct Volume {
+    def: DiskDef,
+    disk: Box<dyn Disk>,
+}
+
+impl Volume {
+    pub fn new(def: DiskDef, disk: Box<dyn Disk>) -> Self {
+        Volume { def, disk }
+    }
+
+    pub fn def(&self) -> &DiskDef {
+        &self.def
+    }
+
+    pub fn disk(&mut self) -> &mut dyn Disk {
+        self.disk.as_mut()
+    }
+
+    pub fn into_disk(self) -> Box<dyn Disk> {
+        self.disk
+    }
+
+    // logical track and unskewed record number to the physical sector
+    // holding it, plus the record's offset within that sector
+    fn locate(&self, track: u16, record: u16) -> Result<(SectorId, usize)> {
+        let rps = self.def.records_per_sector() as u16;
+        if track >= self.def.tracks || record >= self.def.sectrk * rps {
+            return Err(Error::SectorNotFound);
+        }
+        let heads = self.disk.heads().max(1) as u16;
+        let cylinder = (track / heads) as u8;
+        let head = (track % heads) as u8;
+
+        let mut ids = self.disk.sector_ids(cylinder, head);
+        ids.sort_unstable_by_key(|id| id.sector);
+        let index = self.def.translate(record / rps) as usize;
+        let id = *ids.get(index).ok_or(Error::SectorNotFound)?;
+        Ok((id, (record % rps) as usize * 128))
+    }
+
+    pub fn read_record(&mut self, track: u16, record: u16) -> Result<Vec<u8>> {
+        let (id, offset) = self.locate(track, record)?;
+        let data = self.disk.read_sector(id.cylinder, id.head, id.sector)?;
+        // a sector shorter than the diskdef says has no such record
+        let record = data
+            .get(offset..offset + 128)
+            .ok_or(Error::SectorNotFound)?;
+        Ok(record.to_vec())
+    }
+
+    pub fn write_record(&mut self, track: u16, record: u16, data: &[u8]) -> Result<()> {
+        let (id, offset) = self.locate(track, record)?;
+        let mut sector = self.disk.read_sector(id.cylinder, id.head, id.sector)?;
+        let len = data.len().min(128);
+        sector
+            .get_mut(offset..offset + len)
+            .ok_or(Error::SectorNotFound)?
+            .copy_from_slice(&data[..len]);
+        self.disk
+            .write_sector(id.cylinder, id.head, id.sector, &sector)
+    }
+
+    // track and record of the n-th record of the data area
+    fn position(&self, n: usize) -> (u16, u16) {
+        let spt = self.def.sectrk as usize * self.def.records_per_sector();
+        (self.def.boottrk + (n / spt) as u16, (n % spt) as u16)
+    }
+
+    pub fn read_block(&mut self, block: usize) -> Result<Vec<u8>> {
+        if block >= self.def.blocks() {
+            return Err(Error::SectorNotFound);
+        }
+        let records = self.def.records_per_block();
+        let mut data = Vec::with_capacity(self.def.blocksize);
+        for n in block * records..(block + 1) * records {
+            let (track, record) = self.position(n);
+            data.extend(self.read_record(track, record)?);
+        }
+        Ok(data)
+    }
+
+    pub fn write_block(&mut self, block: usize, data: &[u8]) -> Result<()> {
+        if block >= self.def.blocks() {
+            return Err(Error::SectorNotFound);
+        }
+        let records = self.def.records_per_block();
+        let mut padded = data.to_vec();
+        padded.resize(self.def.blocksize, 0xE5);
+        for (i, chunk) in padded.chunks(128).enumerate() {
+            let (track, record) = self.position(block * records + i);
+            self.write_record(track, record, chunk)?;
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        self.disk.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::disk::{RawDisk, SimhDisk};
+
+    #[test]
+    fn test_skew() {
+        let def = DiskDef::builtin("ibm-3740").unwrap();
+        let mut volume = Volume::new(def.clone(), Box::new(RawDisk::new(def.geometry())));
+        volume.write_record(2, 1, &[0x11; 128]).unwrap();
+        volume.write_block(1, &[0x22; 1024]).unwrap();
+
+        let mut raw = volume.into_disk();
+        // record 1 of track 2 lands in the seventh physical sector
+        assert_eq!(raw.read_sector(2, 0, 7).unwrap(), vec![0x11; 128]);
+        // block 1 starts with the ninth record of the data area
+        assert_eq!(
+            raw.read_sector(2, 0, def.translate(8) as u8 + 1).unwrap()[0],
+            0x22
+        );
+
+        // sectors shorter than the diskdef says are an error, not a panic
+        let long = DiskDef {
+            seclen: 512,
+            skewtab: Vec::new(),
+            ..def.clone()
+        };
+        let mut volume = Volume::new(long, Box::new(RawDisk::new(def.geometry())));
+        assert!(matches!(
+            volume.read_record(2, 1),
+            Err(Error::SectorNotFound)
+        ));
+        let err = volume.write_record(2, 1, &[0; 128]).unwrap_err();
+        assert!(matches!(err, Error::SectorNotFound));
+    }
+
+    #[test]
+    fn test_simh() {
+        let def = DiskDef::builtin("ibm-3740").unwrap();
+        let mut disk = SimhDisk::new();
+        disk.write_sector(10, 0, 31, &[0x01; 128]).unwrap();
+        let raw = &disk.data()[(10 * 32 + 31) * 137..][..137];
+        assert_eq!((raw[0], raw[4], raw[7], raw[135]), (0x8A, 0x80, 0x01, 0xFF));
+
+        let mut volume = Volume::new(def, Box::new(disk));
+        assert_eq!(volume.read_record(10, 0).unwrap(), vec![0xE5; 128]);
+    }
+}
@@ -0,0 +1,408 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::bus::{Access, Bus};
+use crate::cpu::{Cpu, State};
+
+pub mod reference;
+
+use reference::Reference;
+
+// xorshift64*, enough to spread test cases around
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed.max(1))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    pub fn byte(&mut self) -> u8 {
+        (self.next_u64() >> 56) as u8
+    }
+}
+
+// a starting state and an instruction stream; memory outside the program
+// holds bytes derived from `fill` so loads see varied data
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Case {
+    pub af: u16,
+    pub bc: u16,
+    pub de: u16,
+    pub hl: u16,
+    pub sp: u16,
+    pub pc: u16,
+    pub iff: bool,
+    pub fill: u64,
+    pub program: Vec<u8>,
+    pub steps: usize,
+}
+
+impl Case {
+    pub fn random(rng: &mut Rng, len: usize) -> Case {
+        let mut word = || rng.next_u64() as u16;
+        let (af, bc, de, hl, sp, pc) = (word(), word(), word(), word(), word(), word());
+        Case {
+            // flag bits that do not exist on the 8080 are normalized by
+            // the CPU, start from the normalized form
+            af: af & 0xFFD7 | 0x0002,
+            bc,
+            de,
+            hl,
+            sp,
+            pc,
+            iff: rng.next_u64() & 1 != 0,
+            fill: rng.next_u64(),
+            program: (0..len).map(|_| rng.byte()).collect(),
+            steps: len,
+        }
+    }
+
+    // the inverse of Display
+    pub fn parse(text: &str) -> Option<Case> {
+        let mut case = Case {
+            af: 0x0002,
+            bc: 0,
+            de: 0,
+            hl: 0,
+            sp: 0,
+            pc: 0,
+            iff: false,
+            fill: 0,
+            program: Vec::new(),
+            steps: 0,
+        };
+        for field in text.split_whitespace() {
+            let (key, value) = field.split_once('=')?;
+            let word = || u16::from_str_radix(value, 16).ok();
+            match key {
+                "af" => case.af = word()?,
+                "bc" => case.bc = word()?,
+                "de" => case.de = word()?,
+                "hl" => case.hl = word()?,
+                "sp" => case.sp = word()?,
+                "pc" => case.pc = word()?,
+                "iff" => case.iff = value == "1",
+                "fill" => case.fill = u64::from_str_radix(value, 16).ok()?,
+                "steps" => case.steps = value.parse().ok()?,
+                "program" => {
+                    case.program = (0..value.len())
+                        .step_by(2)
+                        .map(|i| u8::from_str_radix(value.get(i..i + 2)?, 16).ok())
+                        .collect::<Option<_>>()?;
+                }
+                _ => return None,
+            }
+        }
+        Some(case)
+    }
+}
+
+impl fmt::Display for Case {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "af={:04X} bc={:04X} de={:04X} hl={:04X} sp={:04X} pc={:04X} iff={} fill={:X} steps={} program=",
+            self.af,
+            self.bc,
+            self.de,
+            self.hl,
+            self.sp,
+            self.pc,
+            self.iff as u8,
+            self.fill,
+            self.steps
+        )?;
+        for byte in &self.program {
+            write!(f, "{:02X}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+enum Event {
+    Read(u16, Access),
+    Write(u16, u8, Access),
+    Input(u8),
+    Output(u8, u8),
+}
+
+// memory the program was written into, over a pseudo-random background;
+// port reads return a value derived from the port
+struct FuzzBus {
+    fill: u64,
+    memory: HashMap<u16, u8>,
+    log: Vec<Event>,
+}
+
+impl FuzzBus {
+    fn new(case: &Case) -> Self {
+        let mut memory = HashMap::new();
+        for (i, &byte) in case.program.iter().enumerate() {
+            memory.insert(case.pc.wrapping_add(i as u16), byte);
+        }
+        FuzzBus {
+            fill: case.fill,
+            memory,
+            log: Vec::new(),
+        }
+    }
+}
+
+impl Bus for FuzzBus {
+    fn read(&mut self, addr: u16) -> u8 {
+        match self.memory.get(&addr) {
+            Some(&value) => value,
+            None => (self.fill.rotate_left(addr as u32 % 64) ^ (addr as u64 * 0x9E37)) as u8,
+        }
+    }
+
+    fn write(&mut self, addr: u16, value: u8) {
+        self.memory.insert(addr, value);
+    }
+
+    fn input(&mut self, port: u8) -> u8 {
+        self.log.push(Event::Input(port));
+        port.rotate_left(3) ^ self.fill as u8
+    }
+
+    fn output(&mut self, port: u8, value: u8) {
+        self.log.push(Event::Output(port, value));
+    }
+
+    // only what the interpreters do is logged, not the harness peeking
+    fn read_access(&mut self, addr: u16, access: Access) -> u8 {
+        self.log.push(Event::Read(addr, access));
+        self.read(addr)
+    }
+
+    fn write_access(&mut self, addr: u16, value: u8, access: Access) {
+        self.log.push(Event::Write(addr, value, access));
+        self.write(addr, value);
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Divergence {
+    pub step: usize,
+    pub pc: u16,
+    pub opcode: u8,
+    pub differences: Vec<String>,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "step {} at {:04X} (opcode {:02X}): {}",
+            self.step,
+            self.pc,
+            self.opcode,
+            self.differences.join(", ")
+        )
+    }
+}
+
+fn compare(cpu: &Cpu, reference: &Reference) -> Vec<String> {
+    let pairs = [
+        ("a", cpu.a as u16, reference.a() as u16),
+        ("f", cpu.af() & 0xFF, reference.f as u16),
+        ("b", cpu.b as u16, reference.regs[0] as u16),
+        ("c", cpu.c as u16, reference.regs[1] as u16),
+        ("d", cpu.d as u16, reference.regs[2] as u16),
+        ("e", cpu.e as u16, reference.regs[3] as u16),
+        ("h", cpu.h as u16, reference.regs[4] as u16),
+        ("l", cpu.l as u16, reference.regs[5] as u16),
+        ("sp", cpu.sp, reference.sp),
+        ("pc", cpu.pc, reference.pc),
+        ("iff", cpu.iff as u16, reference.iff as u16),
+        (
+            "halted",
+            (cpu.state == State::Halted) as u16,
+            reference.halted as u16,
+        ),
+    ];
+    pairs
+        .iter()
+        .filter(|(_, got, want)| got != want)
+        .map(|(name, got, want)| format!("{}: cpu {:X}, reference {:X}", name, got, want))
+        .collect()
+}
+
+// the chip pushes the high byte first where Cpu writes words low byte
+// first; which byte of an instruction's writes goes out first is not
+// something a program can see, so writes are compared as a set and
+// everything else in order
+fn comparable(log: &[Event]) -> (Vec<Event>, Vec<Event>) {
+    let (mut writes, rest): (Vec<Event>, Vec<Event>) = log
+        .iter()
+        .partition(|event| matches!(event, Event::Write(..)));
+    writes.sort_unstable();
+    (writes, rest)
+}
+
+// run the case on both interpreters in lock step and report the first
+// instruction after which they disagree
+pub fn run(case: &Case) -> Option<Divergence> {
+    let mut cpu = Cpu::new();
+    cpu.set_af(case.af);
+    cpu.set_bc(case.bc);
+    cpu.set_de(case.de);
+    cpu.set_hl(case.hl);
+    cpu.sp = case.sp;
+    cpu.pc = case.pc;
+    cpu.iff = case.iff;
+
+    let mut reference = Reference {
+        f: (cpu.af() & 0xFF) as u8,
+        sp: case.sp,
+        pc: case.pc,
+        iff: case.iff,
+        ..Reference::default()
+    };
+    reference.regs = [cpu.b, cpu.c, cpu.d, cpu.e, cpu.h, cpu.l, 0, cpu.a];
+
+    let mut cpu_bus = FuzzBus::new(case);
+    let mut reference_bus = FuzzBus::new(case);
+
+    for step in 0..case.steps {
+        let pc = cpu.pc;
+        let opcode = cpu_bus.read(pc);
+        let cpu_cycles = cpu.step(&mut cpu_bus);
+        let reference_cycles = reference.step(&mut reference_bus);
+
+        let mut differences = compare(&cpu, &reference);
+        if cpu_cycles != reference_cycles {
+            differences.push(format!(
+                "cycles: cpu {}, reference {}",
+                cpu_cycles, reference_cycles
+            ));
+        }
+        if comparable(&cpu_bus.log) != comparable(&reference_bus.log) {
+            differences.push(format!(
+                "bus: cpu {:X?}, reference {:X?}",
+                cpu_bus.log, reference_bus.log
+            ));
+        }
+        if !differences.is_empty() {
+            return Some(Divergence {
+                step,
+                pc,
+                opcode,
+                differences,
+            });
+        }
+        cpu_bus.log.clear();
+        reference_bus.log.clear();
+    }
+    None
+}
+
+// shrink a case while `fails` still holds for it: cut the run short, drop
+// program bytes, then simplify what is left
+pub fn minimize(case: &Case, fails: impl Fn(&Case) -> bool) -> Case {
+    let mut best = case.clone();
+    loop {
+        let mut candidates = Vec::new();
+        if best.steps > 1 {
+            candidates.push(Case {
+                steps: best.steps - 1,
+                ..best.clone()
+            });
+        }
+        for i in (0..best.program.len()).rev() {
+            let mut shorter = best.clone();
+            shorter.program.remove(i);
+            candidates.push(shorter);
+        }
+        for i in 0..best.program.len() {
+            if best.program[i] != 0 {
+                let mut simpler = best.clone();
+                simpler.program[i] = 0;
+                candidates.push(simpler);
+            }
+        }
+        let simplified = [
+            Case {
+                bc: 0,
+                ..best.clone()
+            },
+            Case {
+                de: 0,
+                ..best.clone()
+            },
+            Case {
+                hl: 0,
+                ..best.clone()
+            },
+            Case {
+                af: 0x0002,
+                ..best.clone()
+            },
+            Case {
+                fill: 0,
+                ..best.clone()
+            },
+        ];
+        candidates.extend(simplified.into_iter().filter(|c| *c != best));
+
+        match candidates.into_iter().find(|c| fails(c)) {
+            Some(smaller) => best = smaller,
+            None => return best,
+        }
+    }
+}
+
+// what differed, without the values
+fn kind(divergence: &Divergence) -> Vec<&str> {
+    divergence
+        .differences
+        .iter()
+        .map(|difference| difference.split(':').next().unwrap_or(""))
+        .collect()
+}
+
+// minimize a case that diverges, keeping the same kind of divergence: the
+// smaller case must differ in the same things at the same opcode
+pub fn minimize_divergence(case: &Case) -> Case {
+    let Some(original) = run(case) else {
+        return case.clone();
+    };
+    minimize(case, |c| {
+        run(c).is_some_and(|d| d.opcode == original.opcode && kind(&d) == kind(&original))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_agreement() {
+        let mut rng = Rng::new(1);
+        for _ in 0..500 {
+            let case = Case::random(&mut rng, 32);
+            if let Some(divergence) = run(&case) {
+                panic!("{}\n{}", divergence, minimize_divergence(&case));
+            }
+        }
+    }
+
+    #[test]
+    fn test_minimize() {
+        let mut rng = Rng::new(7);
+        let mut case = Case::random(&mut rng, 16);
+        case.program[9] = 0x27;
+        let fails = |c: &Case| c.program[..c.steps.min(c.program.len())].contains(&0x27);
+        let small = minimize(&case, fails);
+        assert_eq!((small.program.as_slice(), small.steps), (&[0x27][..], 1));
+        assert_eq!(Case::parse(&small.to_string()), Some(small));
+    }
+}
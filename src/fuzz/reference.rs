@@ -0,0 +1,544 @@
+use std::sync::OnceLock;
+
+use crate::bus::{Access, Bus};
+
+// A second 8080 interpreter, written independently of `Cpu` for
+// differential testing: opcodes are decoded once into a table of
+// operations, flags live in a packed F byte and are derived with plain
+// nibble arithmetic instead of the shared adder `Cpu` uses.
+
+const S: u8 = 0x80;
+const Z: u8 = 0x40;
+const AC: u8 = 0x10;
+const P: u8 = 0x04;
+const CY: u8 = 0x01;
+// bit 1 always reads as one, bits 3 and 5 as zero
+const FIXED: u8 = 0x02;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Alu {
+    Add,
+    Adc,
+    Sub,
+    Sbb,
+    Ana,
+    Xra,
+    Ora,
+    Cmp,
+}
+
+const ALU: [Alu; 8] = [
+    Alu::Add,
+    Alu::Adc,
+    Alu::Sub,
+    Alu::Sbb,
+    Alu::Ana,
+    Alu::Xra,
+    Alu::Ora,
+    Alu::Cmp,
+];
+
+// register codes as encoded in opcodes: B C D E H L M A; register pairs
+// BC DE HL SP (or PSW for PUSH/POP)
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Op {
+    Nop,
+    Hlt,
+    Mov(u8, u8),
+    Mvi(u8),
+    Lxi(u8),
+    Ldax(u8),
+    Stax(u8),
+    Lda,
+    Sta,
+    Lhld,
+    Shld,
+    Sphl,
+    Xthl,
+    Xchg,
+    Push(u8),
+    Pop(u8),
+    Alu(Alu, u8),
+    AluImm(Alu),
+    Inr(u8),
+    Dcr(u8),
+    Dad(u8),
+    Inx(u8),
+    Dcx(u8),
+    Rlc,
+    Rrc,
+    Ral,
+    Rar,
+    Daa,
+    Cma,
+    Stc,
+    Cmc,
+    Jmp(Option<u8>),
+    Call(Option<u8>),
+    Ret(Option<u8>),
+    Rst(u8),
+    Pchl,
+    Ei,
+    Di,
+    In,
+    Out,
+}
+
+fn decode(opcode: u8) -> Op {
+    let x = opcode >> 6;
+    let y = (opcode >> 3) & 7;
+    let z = opcode & 7;
+    let rp = y >> 1;
+
+    match (x, z) {
+        (1, _) if opcode == 0x76 => Op::Hlt,
+        (1, _) => Op::Mov(y, z),
+        (2, _) => Op::Alu(ALU[y as usize], z),
+        (0, 0) => Op::Nop,
+        (0, 1) if y & 1 == 0 => Op::Lxi(rp),
+        (0, 1) => Op::Dad(rp),
+        (0, 2) => match y {
+            0 | 2 => Op::Stax(rp),
+            1 | 3 => Op::Ldax(rp),
+            4 => Op::Shld,
+            5 => Op::Lhld,
+            6 => Op::Sta,
+            _ => Op::Lda,
+        },
+        (0, 3) if y & 1 == 0 => Op::Inx(rp),
+        (0, 3) => Op::Dcx(rp),
+        (0, 4) => Op::Inr(y),
+        (0, 5) => Op::Dcr(y),
+        (0, 6) => Op::Mvi(y),
+        (0, _) => [
+            Op::Rlc,
+            Op::Rrc,
+            Op::Ral,
+            Op::Rar,
+            Op::Daa,
+            Op::Cma,
+            Op::Stc,
+            Op::Cmc,
+        ][y as usize],
+        (_, 0) => Op::Ret(Some(y)),
+        (_, 1) => match y {
+            1 | 3 => Op::Ret(None),
+            5 => Op::Pchl,
+            7 => Op::Sphl,
+            _ => Op::Pop(rp),
+        },
+        (_, 2) => Op::Jmp(Some(y)),
+        (_, 3) => match y {
+            0 | 1 => Op::Jmp(None),
+            2 => Op::Out,
+            3 => Op::In,
+            4 => Op::Xthl,
+            5 => Op::Xchg,
+            6 => Op::Di,
+            _ => Op::Ei,
+        },
+        (_, 4) => Op::Call(Some(y)),
+        (_, 5) if y & 1 == 0 => Op::Push(rp),
+        (_, 5) => Op::Call(None),
+        (_, 6) => Op::AluImm(ALU[y as usize]),
+        _ => Op::Rst(y),
+    }
+}
+
+fn table() -> &'static [Op; 256] {
+    static TABLE: OnceLock<[Op; 256]> = OnceLock::new();
+    TABLE.get_or_init(|| std::array::from_fn(|opcode| decode(opcode as u8)))
+}
+
+fn parity(value: u8) -> bool {
+    let mut even = true;
+    for bit in 0..8 {
+        if value >> bit & 1 != 0 {
+            even = !even;
+        }
+    }
+    even
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Reference {
+    // B C D E H L - A, indexed by register code; slot 6 is unused
+    pub regs: [u8; 8],
+    pub f: u8,
+    pub sp: u16,
+    pub pc: u16,
+    pub iff: bool,
+    pub halted: bool,
+}
+
+impl Reference {
+    pub fn a(&self) -> u8 {
+        self.regs[7]
+    }
+
+    fn pair(&self, rp: u8) -> u16 {
+        match rp {
+            3 => self.sp,
+            _ => u16::from_be_bytes([self.regs[rp as usize * 2], self.regs[rp as usize * 2 + 1]]),
+        }
+    }
+
+    fn set_pair(&mut self, rp: u8, value: u16) {
+        match rp {
+            3 => self.sp = value,
+            _ => {
+                let [hi, lo] = value.to_be_bytes();
+                self.regs[rp as usize * 2] = hi;
+                self.regs[rp as usize * 2 + 1] = lo;
+            }
+        }
+    }
+
+    fn get(&self, r: u8, bus: &mut dyn Bus) -> u8 {
+        if r == 6 {
+            bus.read_access(self.pair(2), Access::Data)
+        } else {
+            self.regs[r as usize]
+        }
+    }
+
+    fn set(&mut self, r: u8, value: u8, bus: &mut dyn Bus) {
+        if r == 6 {
+            bus.write_access(self.pair(2), value, Access::Data);
+        } else {
+            self.regs[r as usize] = value;
+        }
+    }
+
+    fn next(&mut self, bus: &mut dyn Bus) -> u8 {
+        let byte = bus.read_access(self.pc, Access::Operand);
+        self.pc = self.pc.wrapping_add(1);
+        byte
+    }
+
+    fn next_word(&mut self, bus: &mut dyn Bus) -> u16 {
+        let lo = self.next(bus);
+        let hi = self.next(bus);
+        u16::from_le_bytes([lo, hi])
+    }
+
+    fn push(&mut self, value: u16, bus: &mut dyn Bus) {
+        let [hi, lo] = value.to_be_bytes();
+        self.sp = self.sp.wrapping_sub(1);
+        bus.write_access(self.sp, hi, Access::Stack);
+        self.sp = self.sp.wrapping_sub(1);
+        bus.write_access(self.sp, lo, Access::Stack);
+    }
+
+    fn pop(&mut self, bus: &mut dyn Bus) -> u16 {
+        let lo = bus.read_access(self.sp, Access::Stack);
+        self.sp = self.sp.wrapping_add(1);
+        let hi = bus.read_access(self.sp, Access::Stack);
+        self.sp = self.sp.wrapping_add(1);
+        u16::from_le_bytes([lo, hi])
+    }
+
+    fn flag(&self, mask: u8) -> bool {
+        self.f & mask != 0
+    }
+
+    fn set_flag(&mut self, mask: u8, on: bool) {
+        if on {
+            self.f |= mask;
+        } else {
+            self.f &= !mask;
+        }
+    }
+
+    fn szp(&mut self, value: u8) {
+        self.set_flag(S, value & 0x80 != 0);
+        self.set_flag(Z, value == 0);
+        self.set_flag(P, parity(value));
+    }
+
+    fn condition(&self, cc: u8) -> bool {
+        let flag = [Z, CY, P, S][cc as usize >> 1];
+        self.flag(flag) == (cc & 1 != 0)
+    }
+
+    fn alu(&mut self, op: Alu, value: u8) {
+        let a = self.a();
+        let carry = self.flag(CY) as u8;
+        let result = match op {
+            Alu::Add | Alu::Adc => {
+                let cin = if op == Alu::Adc { carry } else { 0 };
+                let sum = a as u16 + value as u16 + cin as u16;
+                self.set_flag(AC, (a & 0xF) + (value & 0xF) + cin > 0xF);
+                self.set_flag(CY, sum > 0xFF);
+                sum as u8
+            }
+            Alu::Sub | Alu::Sbb | Alu::Cmp => {
+                let borrow = if op == Alu::Sbb { carry } else { 0 };
+                // the 8080 subtracts by adding the complement, and AC is
+                // the carry out of that addition's low nibble
+                self.set_flag(AC, (a & 0xF) + (!value & 0xF) + (1 - borrow) > 0xF);
+                self.set_flag(CY, (a as u16) < value as u16 + borrow as u16);
+                a.wrapping_sub(value).wrapping_sub(borrow)
+            }
+            Alu::Ana => {
+                self.set_flag(AC, (a | value) & 0x08 != 0);
+                self.set_flag(CY, false);
+                a & value
+            }
+            Alu::Xra | Alu::Ora => {
+                self.set_flag(AC, false);
+                self.set_flag(CY, false);
+                if op == Alu::Xra { a ^ value } else { a | value }
+            }
+        };
+        self.szp(result);
+        if op != Alu::Cmp {
+            self.regs[7] = result;
+        }
+    }
+
+    fn daa(&mut self) {
+        let mut a = self.a();
+        let mut carry = self.flag(CY);
+        let mut aux = false;
+        if a & 0x0F > 9 || self.flag(AC) {
+            aux = (a & 0x0F) + 6 > 0x0F;
+            let (sum, overflow) = a.overflowing_add(6);
+            a = sum;
+            carry |= overflow;
+        }
+        if a >> 4 > 9 || carry {
+            a = a.wrapping_add(0x60);
+            carry = true;
+        }
+        self.regs[7] = a;
+        self.szp(a);
+        self.set_flag(AC, aux);
+        self.set_flag(CY, carry);
+    }
+
+    // executes one instruction and returns its duration in states
+    pub fn step(&mut self, bus: &mut dyn Bus) -> u64 {
+        if self.halted {
+            return 4;
+        }
+        let opcode = bus.read_access(self.pc, Access::Fetch);
+        self.pc = self.pc.wrapping_add(1);
+        let op = table()[opcode as usize];
+        let memory = |r: u8| r == 6;
+
+        match op {
+            Op::Nop => 4,
+            Op::Hlt => {
+                self.halted = true;
+                7
+            }
+            Op::Mov(dst, src) => {
+                let value = self.get(src, bus);
+                self.set(dst, value, bus);
+                if memory(dst) || memory(src) { 7 } else { 5 }
+            }
+            Op::Mvi(dst) => {
+                let value = self.next(bus);
+                self.set(dst, value, bus);
+                if memory(dst) { 10 } else { 7 }
+            }
+            Op::Lxi(rp) => {
+                let value = self.next_word(bus);
+                self.set_pair(rp, value);
+                10
+            }
+            Op::Ldax(rp) => {
+                self.regs[7] = bus.read_access(self.pair(rp), Access::Data);
+                7
+            }
+            Op::Stax(rp) => {
+                bus.write_access(self.pair(rp), self.a(), Access::Data);
+                7
+            }
+            Op::Lda => {
+                let addr = self.next_word(bus);
+                self.regs[7] = bus.read_access(addr, Access::Data);
+                13
+            }
+            Op::Sta => {
+                let addr = self.next_word(bus);
+                bus.write_access(addr, self.a(), Access::Data);
+                13
+            }
+            Op::Lhld => {
+                let addr = self.next_word(bus);
+                self.regs[5] = bus.read_access(addr, Access::Data);
+                self.regs[4] = bus.read_access(addr.wrapping_add(1), Access::Data);
+                16
+            }
+            Op::Shld => {
+                let addr = self.next_word(bus);
+                bus.write_access(addr, self.regs[5], Access::Data);
+                bus.write_access(addr.wrapping_add(1), self.regs[4], Access::Data);
+                16
+            }
+            Op::Sphl => {
+                self.sp = self.pair(2);
+                6
+            }
+            Op::Xthl => {
+                let lo = bus.read_access(self.sp, Access::Stack);
+                let hi = bus.read_access(self.sp.wrapping_add(1), Access::Stack);
+                bus.write_access(self.sp, self.regs[5], Access::Stack);
+                bus.write_access(self.sp.wrapping_add(1), self.regs[4], Access::Stack);
+                self.regs[5] = lo;
+                self.regs[4] = hi;
+                18
+            }
+            Op::Xchg => {
+                self.regs.swap(2, 4);
+                self.regs.swap(3, 5);
+                5
+            }
+            Op::Push(rp) => {
+                let value = if rp == 3 {
+                    u16::from_be_bytes([self.a(), self.f])
+                } else {
+                    self.pair(rp)
+                };
+                self.push(value, bus);
+                11
+            }
+            Op::Pop(rp) => {
+                let value = self.pop(bus);
+                if rp == 3 {
+                    let [a, f] = value.to_be_bytes();
+                    self.regs[7] = a;
+                    self.f = f & (S | Z | AC | P | CY) | FIXED;
+                } else {
+                    self.set_pair(rp, value);
+                }
+                10
+            }
+            Op::Alu(op, src) => {
+                let value = self.get(src, bus);
+                self.alu(op, value);
+                if memory(src) { 7 } else { 4 }
+            }
+            Op::AluImm(op) => {
+                let value = self.next(bus);
+                self.alu(op, value);
+                7
+            }
+            Op::Inr(r) => {
+                let value = self.get(r, bus).wrapping_add(1);
+                self.set(r, value, bus);
+                self.szp(value);
+                self.set_flag(AC, value & 0x0F == 0);
+                if memory(r) { 10 } else { 5 }
+            }
+            Op::Dcr(r) => {
+                let old = self.get(r, bus);
+                let value = old.wrapping_sub(1);
+                self.set(r, value, bus);
+                self.szp(value);
+                self.set_flag(AC, old & 0x0F != 0);
+                if memory(r) { 10 } else { 5 }
+            }
+            Op::Dad(rp) => {
+                let sum = self.pair(2) as u32 + self.pair(rp) as u32;
+                self.set_pair(2, sum as u16);
+                self.set_flag(CY, sum > 0xFFFF);
+                10
+            }
+            Op::Inx(rp) => {
+                self.set_pair(rp, self.pair(rp).wrapping_add(1));
+                5
+            }
+            Op::Dcx(rp) => {
+                self.set_pair(rp, self.pair(rp).wrapping_sub(1));
+                5
+            }
+            Op::Rlc | Op::Rrc | Op::Ral | Op::Rar => {
+                let a = self.a();
+                let carry = self.flag(CY) as u8;
+                let (result, out) = match op {
+                    Op::Rlc => (a.rotate_left(1), a >> 7),
+                    Op::Rrc => (a.rotate_right(1), a & 1),
+                    Op::Ral => (a << 1 | carry, a >> 7),
+                    _ => (a >> 1 | carry << 7, a & 1),
+                };
+                self.regs[7] = result;
+                self.set_flag(CY, out != 0);
+                4
+            }
+            Op::Daa => {
+                self.daa();
+                4
+            }
+            Op::Cma => {
+                self.regs[7] = !self.a();
+                4
+            }
+            Op::Stc => {
+                self.set_flag(CY, true);
+                4
+            }
+            Op::Cmc => {
+                self.f ^= CY;
+                4
+            }
+            Op::Jmp(cc) => {
+                let addr = self.next_word(bus);
+                if cc.is_none_or(|cc| self.condition(cc)) {
+                    self.pc = addr;
+                }
+                10
+            }
+            Op::Call(cc) => {
+                let addr = self.next_word(bus);
+                if cc.is_none_or(|cc| self.condition(cc)) {
+                    self.push(self.pc, bus);
+                    self.pc = addr;
+                    17
+                } else {
+                    11
+                }
+            }
+            Op::Ret(cc) => {
+                let taken = cc.is_none_or(|cc| self.condition(cc));
+                if taken {
+                    self.pc = self.pop(bus);
+                }
+                match (cc, taken) {
+                    (None, _) => 10,
+                    (Some(_), true) => 11,
+                    (Some(_), false) => 5,
+                }
+            }
+            Op::Rst(n) => {
+                self.push(self.pc, bus);
+                self.pc = n as u16 * 8;
+                11
+            }
+            Op::Pchl => {
+                self.pc = self.pair(2);
+                5
+            }
+            Op::Ei => {
+                self.iff = true;
+                4
+            }
+            Op::Di => {
+                self.iff = false;
+                4
+            }
+            Op::In => {
+                let port = self.next(bus);
+                self.regs[7] = bus.input(port);
+                10
+            }
+            Op::Out => {
+                let port = self.next(bus);
+                bus.output(port, self.a());
+                10
+            }
+        }
+    }
+}
@@ -0,0 +1,14 @@
+pub mod bus;
+pub mod cpm;
+pub mod cpu;
+pub mod devices;
+pub mod disk;
+pub mod fuzz;
+pub mod json;
+pub mod loader;
+pub mod machine;
+pub mod recompile;
+pub mod regex;
+pub mod signal;
+pub mod single_step;
+pub mod video;
@@ -0,0 +1,333 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(Debug)]
+pub enum Error {
+    Syntax { line: usize, message: String },
+    Checksum { line: usize },
+    Io(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+            Error::Checksum { line } => write!(f, "line {}: checksum mismatch", line),
+            Error::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Segment {
+    pub address: u16,
+    pub data: Vec<u8>,
+}
+
+// memory contents to load, plus the entry point if the file named one
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Image {
+    pub segments: Vec<Segment>,
+    pub start: Option<u16>,
+}
+
+impl Image {
+    // append data, extending the previous segment when contiguous
+    fn add(&mut self, address: u32, data: &[u8], line: usize) -> Result<()> {
+        if address as usize + data.len() > 0x10000 {
+            return Err(syntax(line, "address out of range"));
+        }
+        let address = address as u16;
+        match self.segments.last_mut() {
+            Some(last) if last.address as usize + last.data.len() == address as usize => {
+                last.data.extend_from_slice(data);
+            }
+            _ => self.segments.push(Segment {
+                address,
+                data: data.to_vec(),
+            }),
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Format {
+    Binary,
+    IntelHex,
+    SRecord,
+}
+
+impl Format {
+    pub fn from_path(path: impl AsRef<Path>) -> Format {
+        let extension = path
+            .as_ref()
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("hex" | "ihx" | "ihex") => Format::IntelHex,
+            Some("s19" | "s28" | "s37" | "srec" | "mot") => Format::SRecord,
+            _ => Format::Binary,
+        }
+    }
+
+    // binary files are taken to be CP/M programs loaded at 0x0100
+    pub fn parse(self, bytes: &[u8]) -> Result<Image> {
+        match self {
+            Format::Binary => Ok(Image {
+                segments: vec![Segment {
+                    address: 0x0100,
+                    data: bytes.to_vec(),
+                }],
+                start: None,
+            }),
+            Format::IntelHex => parse_ihex(&String::from_utf8_lossy(bytes)),
+            Format::SRecord => parse_srec(&String::from_utf8_lossy(bytes)),
+        }
+    }
+
+    pub fn write(self, address: u16, data: &[u8], start: Option<u16>) -> Vec<u8> {
+        match self {
+            Format::Binary => data.to_vec(),
+            Format::IntelHex => write_ihex(address, data, start).into_bytes(),
+            Format::SRecord => write_srec(address, data, start).into_bytes(),
+        }
+    }
+}
+
+pub fn read(path: impl AsRef<Path>) -> Result<Image> {
+    let path = path.as_ref();
+    Format::from_path(path).parse(&fs::read(path)?)
+}
+
+pub fn write(path: impl AsRef<Path>, address: u16, data: &[u8], start: Option<u16>) -> Result<()> {
+    let path = path.as_ref();
+    fs::write(path, Format::from_path(path).write(address, data, start))?;
+    Ok(())
+}
+
+fn syntax(line: usize, message: &str) -> Error {
+    Error::Syntax {
+        line,
+        message: message.to_string(),
+    }
+}
+
+fn decode_hex(line: usize, text: &str) -> Result<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return Err(syntax(line, "odd number of hex digits"));
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| {
+            text.get(i..i + 2)
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(|| syntax(line, "invalid hex digit"))
+        })
+        .collect()
+}
+
+fn be(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0, |value, &b| value << 8 | b as u32)
+}
+
+pub fn parse_ihex(text: &str) -> Result<Image> {
+    let mut image = Image::default();
+    let mut base: u32 = 0;
+
+    for (index, raw) in text.lines().enumerate() {
+        let line = index + 1;
+        let record = raw.trim();
+        if record.is_empty() {
+            continue;
+        }
+        let Some(hex) = record.strip_prefix(':') else {
+            return Err(syntax(line, "record does not start with ':'"));
+        };
+        let bytes = decode_hex(line, hex)?;
+        if bytes.len() < 5 || bytes.len() != bytes[0] as usize + 5 {
+            return Err(syntax(line, "bad record length"));
+        }
+        if bytes.iter().fold(0u8, |sum, &b| sum.wrapping_add(b)) != 0 {
+            return Err(Error::Checksum { line });
+        }
+
+        let offset = be(&bytes[1..3]);
+        let data = &bytes[4..bytes.len() - 1];
+        match (bytes[3], data.len()) {
+            (0x00, _) => image.add(base + offset, data, line)?,
+            (0x01, _) => break,
+            (0x02, 2) => base = be(data) << 4,
+            (0x04, 2) => base = be(data) << 16,
+            // CS:IP and EIP both end up as a 16 bit entry point
+            (0x03, 4) => image.start = Some(((be(&data[..2]) << 4) + be(&data[2..])) as u16),
+            (0x05, 4) => image.start = Some(be(data) as u16),
+            (kind, _) => {
+                return Err(syntax(
+                    line,
+                    &format!("unsupported record type {:02X}", kind),
+                ));
+            }
+        }
+    }
+    Ok(image)
+}
+
+pub fn parse_srec(text: &str) -> Result<Image> {
+    let mut image = Image::default();
+
+    for (index, raw) in text.lines().enumerate() {
+        let line = index + 1;
+        let record = raw.trim();
+        if record.is_empty() {
+            continue;
+        }
+        let (Some('S'), Some(kind)) = (record.chars().next(), record.chars().nth(1)) else {
+            return Err(syntax(line, "record does not start with 'S'"));
+        };
+        let bytes = decode_hex(line, &record[1 + kind.len_utf8()..])?;
+        if bytes.is_empty() || bytes.len() != bytes[0] as usize + 1 {
+            return Err(syntax(line, "bad record length"));
+        }
+        if bytes.iter().fold(0u8, |sum, &b| sum.wrapping_add(b)) != 0xFF {
+            return Err(Error::Checksum { line });
+        }
+
+        let address_len = match kind {
+            '0' | '1' | '5' | '9' => 2,
+            '2' | '6' | '8' => 3,
+            '3' | '7' => 4,
+            _ => return Err(syntax(line, &format!("unsupported record type S{}", kind))),
+        };
+        if bytes.len() < address_len + 2 {
+            return Err(syntax(line, "bad record length"));
+        }
+        let address = be(&bytes[1..1 + address_len]);
+        let data = &bytes[1 + address_len..bytes.len() - 1];
+        match kind {
+            '1' | '2' | '3' => image.add(address, data, line)?,
+            // the termination record is mandatory, so tools write an
+            // address of zero when there is no entry point
+            '7' | '8' | '9' if address != 0 => image.start = Some(address as u16),
+            // header and record counts carry nothing to load
+            _ => {}
+        }
+    }
+    Ok(image)
+}
+
+pub fn write_ihex(address: u16, data: &[u8], start: Option<u16>) -> String {
+    let mut out = String::new();
+    let mut record = |kind: u8, offset: u16, payload: &[u8]| {
+        let mut bytes = vec![payload.len() as u8];
+        bytes.extend(offset.to_be_bytes());
+        bytes.push(kind);
+        bytes.extend(payload);
+        let sum = bytes.iter().fold(0u8, |sum, &b| sum.wrapping_add(b));
+        bytes.push(sum.wrapping_neg());
+        out.push(':');
+        out.extend(bytes.iter().map(|b| format!("{:02X}", b)));
+        out.push('\n');
+    };
+
+    for (n, chunk) in data.chunks(16).enumerate() {
+        record(0x00, address.wrapping_add(n as u16 * 16), chunk);
+    }
+    if let Some(start) = start {
+        record(0x03, 0, &[0, 0, (start >> 8) as u8, start as u8]);
+    }
+    record(0x01, 0, &[]);
+    out
+}
+
+pub fn write_srec(address: u16, data: &[u8], start: Option<u16>) -> String {
+    let mut out = String::new();
+    let mut record = |kind: char, address: u16, payload: &[u8]| {
+        let mut bytes = vec![payload.len() as u8 + 3];
+        bytes.extend(address.to_be_bytes());
+        bytes.extend(payload);
+        let sum = bytes.iter().fold(0u8, |sum, &b| sum.wrapping_add(b));
+        bytes.push(!sum);
+        out.push('S');
+        out.push(kind);
+        out.extend(bytes.iter().map(|b| format!("{:02X}", b)));
+        out.push('\n');
+    };
+
+    record('0', 0, b"remu");
+    let chunks = data.chunks(16);
+    let count = chunks.len() as u16;
+    for (n, chunk) in chunks.enumerate() {
+        record('1', address.wrapping_add(n as u16 * 16), chunk);
+    }
+    record('5', count, &[]);
+    record('9', start.unwrap_or(0), &[]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ihex() {
+        let text = ":0300300002337A1E\n\
+                    :03003300C3000007\n\
+                    :020000020010EC\n\
+                    :010000007689\n\
+                    :0400000300000100F8\n\
+                    :00000001FF\n";
+        let image = parse_ihex(text).unwrap();
+        assert_eq!(
+            image.segments,
+            vec![
+                Segment {
+                    address: 0x0030,
+                    data: vec![0x02, 0x33, 0x7A, 0xC3, 0x00, 0x00],
+                },
+                Segment {
+                    address: 0x0100,
+                    data: vec![0x76],
+                },
+            ]
+        );
+        assert_eq!(image.start, Some(0x0100));
+
+        let bad = ":0300300002337A1F\n";
+        assert!(matches!(parse_ihex(bad), Err(Error::Checksum { line: 1 })));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let data: Vec<u8> = (0..40).collect();
+        for format in [Format::IntelHex, Format::SRecord] {
+            let bytes = format.write(0xF000, &data, Some(0xF000));
+            let image = format.parse(&bytes).unwrap();
+            assert_eq!(image.start, Some(0xF000));
+            assert_eq!(
+                image.segments,
+                vec![Segment {
+                    address: 0xF000,
+                    data: data.clone(),
+                }]
+            );
+        }
+        let text = "S00600004844521B\nS1130000285F245F2212226A000424290008237C2A\nS9030000FC\n";
+        let image = parse_srec(text).unwrap();
+        assert_eq!(image.segments[0].data.len(), 16);
+        assert_eq!(image.start, None);
+    }
+}
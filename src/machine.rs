@@ -1,25 +1,75 @@
-use crate::bus::Bus;
-use crate::cpu::{Cpu, Cycles};
+use crate::bus::{Bus, Ports};
+use crate::cpu::cache::BlockCache;
+use crate::cpu::{Cpu, Cycles, State};
+use crate::loader::Image;
 
 pub struct SimpleMachine {
     pub cpu: Cpu,
     pub bus: SimpleBus,
+    // per-address BREAKPOINT and TRAP bits, and trapped I/O ports
+    stops: Box<[u8; 0x10000]>,
+    io_traps: [bool; 256],
+    io_trapping: bool,
+    translation: Option<Translation>,
+    cache: Option<Box<BlockCache>>,
+    banks: Option<Banks>,
+}
+
+// Bank switched memory below `common`, shared above it. The selected bank
+// is the one in `bus.memory`; the others are kept here.
+struct Banks {
+    common: u16,
+    selected: usize,
+    saved: Vec<Box<[u8]>>,
+}
+
+// a recompiled `step` (see recompile.rs) that runs whole blocks at a time
+pub type Translation = fn(&mut Cpu, &mut SimpleBus) -> Cycles;
+
+const BREAKPOINT: u8 = 0x01;
+const TRAP: u8 = 0x02;
+
+// why a run stopped; breakpoints, traps and I/O traps stop before the
+// instruction at PC is executed
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StopReason {
+    CycleBudget,
+    Halted,
+    Breakpoint(u16),
+    Trap(u16),
+    IoTrap { port: u8, output: bool },
+    Condition,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Stop {
+    pub reason: StopReason,
+    pub ops: u64,
+    pub cycles: Cycles,
 }
 
 pub struct SimpleBus {
     pub memory: [u8; 0x10000],
+    pub ports: Ports,
 }
 
 impl SimpleBus {
     pub fn new() -> Self {
         SimpleBus {
             memory: [0; 0x10000],
+            ports: Ports::new(),
         }
     }
 }
 
+impl Default for SimpleBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Bus for SimpleBus {
-    fn read(&self, addr: u16) -> u8 {
+    fn read(&mut self, addr: u16) -> u8 {
         self.memory[addr as usize]
     }
 
@@ -27,13 +77,13 @@ impl Bus for SimpleBus {
         self.memory[addr as usize] = value;
     }
 
-    fn input(&self, _port: u8) -> u8 {
-        // no operation
-        0
+    fn input(&mut self, port: u8) -> u8 {
+        // unmapped ports read as zero
+        self.ports.input(port).unwrap_or(0)
     }
 
-    fn output(&mut self, _port: u8, _value: u8) {
-        // no operation
+    fn output(&mut self, port: u8, value: u8) {
+        self.ports.output(port, value);
     }
 }
 
@@ -42,16 +92,302 @@ impl SimpleMachine {
         SimpleMachine {
             cpu: Cpu::new(),
             bus: SimpleBus::new(),
+            stops: Box::new([0; 0x10000]),
+            io_traps: [false; 256],
+            io_trapping: false,
+            translation: None,
+            cache: None,
+            banks: None,
         }
     }
 
     pub fn step(&mut self) -> Cycles {
-        self.cpu.step(&mut self.bus)
+        match &mut self.cache {
+            Some(cache) => cache.interpret(&mut self.cpu, &mut self.bus),
+            None => self.cpu.step(&mut self.bus),
+        }
+    }
+
+    pub fn set_breakpoint(&mut self, addr: u16, enabled: bool) {
+        self.set_stop(addr, BREAKPOINT, enabled);
+    }
+
+    // a trap marks an entry point the host implements, such as the BDOS
+    pub fn set_trap(&mut self, addr: u16, enabled: bool) {
+        self.set_stop(addr, TRAP, enabled);
+    }
+
+    // stop before IN or OUT instructions addressing `port`
+    pub fn set_io_trap(&mut self, port: u8, enabled: bool) {
+        self.io_traps[port as usize] = enabled;
+        self.io_trapping = self.io_traps.contains(&true);
+    }
+
+    fn set_stop(&mut self, addr: u16, bit: u8, enabled: bool) {
+        let stop = &mut self.stops[addr as usize];
+        if enabled {
+            *stop |= bit;
+        } else {
+            *stop &= !bit;
+        }
+        // blocks may run across the new stop
+        if let Some(cache) = &mut self.cache {
+            cache.clear();
+        }
+    }
+
+    // With a translation the run loops execute a block per op, so stops are
+    // only seen at block boundaries: jump targets and code after calls.
+    pub fn set_translation(&mut self, translation: Option<Translation>) {
+        self.translation = translation;
+    }
+
+    // Run through a block cache (see cpu/cache.rs). Blocks end before
+    // breakpoints and traps, but `run_until` predicates are only checked
+    // between blocks. Writes to `bus.memory` other than through `load` must
+    // be followed by `invalidate`.
+    pub fn set_block_cache(&mut self, enabled: bool) {
+        self.cache = enabled.then(|| Box::new(BlockCache::new()));
+    }
+
+    pub fn block_cache(&self) -> Option<&BlockCache> {
+        self.cache.as_deref()
+    }
+
+    pub fn invalidate(&mut self, addr: u16, len: usize) {
+        if let Some(cache) = &mut self.cache {
+            cache.invalidate_range(addr, len);
+        }
+    }
+
+    // run until at least `budget` cycles have passed or something stops the
+    // run earlier
+    pub fn run_for_cycles(&mut self, budget: Cycles) -> Stop {
+        self.run(budget, |_, _| false)
+    }
+
+    // run until `predicate` holds before an instruction
+    pub fn run_until(&mut self, predicate: impl FnMut(&Cpu, &SimpleBus) -> bool) -> Stop {
+        self.run(Cycles::MAX, predicate)
+    }
+
+    pub fn run_until_halt(&mut self) -> Stop {
+        self.run(Cycles::MAX, |_, _| false)
+    }
+
+    // Stop conditions are not checked before the first instruction, so
+    // resuming after a breakpoint or trap moves past it.
+    fn run(&mut self, budget: Cycles, mut predicate: impl FnMut(&Cpu, &SimpleBus) -> bool) -> Stop {
+        let mut ops = 0;
+        let mut cycles = 0;
+        let reason = loop {
+            if self.cpu.state == State::Halted {
+                break StopReason::Halted;
+            }
+            if cycles >= budget {
+                break StopReason::CycleBudget;
+            }
+            if ops > 0 {
+                if let Some(reason) = self.stop_at(self.cpu.pc) {
+                    break reason;
+                }
+                if predicate(&self.cpu, &self.bus) {
+                    break StopReason::Condition;
+                }
+            }
+            if let Some(step) = self.translation {
+                cycles += step(&mut self.cpu, &mut self.bus);
+                ops += 1;
+            } else if let Some(cache) = &mut self.cache {
+                let stops = &self.stops;
+                let (block_ops, block_cycles) = cache.step(&mut self.cpu, &mut self.bus, |addr| {
+                    stops[addr as usize] != 0
+                });
+                ops += block_ops;
+                cycles += block_cycles;
+            } else {
+                cycles += self.cpu.step(&mut self.bus);
+                ops += 1;
+            }
+        };
+        Stop {
+            reason,
+            ops,
+            cycles,
+        }
+    }
+
+    #[inline]
+    fn stop_at(&self, pc: u16) -> Option<StopReason> {
+        let stop = self.stops[pc as usize];
+        if stop & BREAKPOINT != 0 {
+            return Some(StopReason::Breakpoint(pc));
+        }
+        if stop & TRAP != 0 {
+            return Some(StopReason::Trap(pc));
+        }
+        if self.io_trapping {
+            let opcode = self.bus.memory[pc as usize];
+            if opcode == 0xDB || opcode == 0xD3 {
+                let port = self.bus.memory[pc.wrapping_add(1) as usize];
+                if self.io_traps[port as usize] {
+                    let output = opcode == 0xD3;
+                    return Some(StopReason::IoTrap { port, output });
+                }
+            }
+        }
+        None
+    }
+
+    pub fn interrupt(&mut self, instruction: &[u8]) -> Option<Cycles> {
+        match &mut self.cache {
+            Some(cache) => cache.interrupt(&mut self.cpu, &mut self.bus, instruction),
+            None => self.cpu.interrupt(&mut self.bus, instruction),
+        }
+    }
+
+    // `count` banks of memory below `common`; what is in memory now becomes
+    // bank 0, and the other banks start out zeroed
+    pub fn set_banks(&mut self, count: usize, common: u16) {
+        self.banks = Some(Banks {
+            common,
+            selected: 0,
+            saved: (0..count)
+                .map(|_| vec![0; common as usize].into_boxed_slice())
+                .collect(),
+        });
+    }
+
+    // 1 without banking
+    pub fn bank_count(&self) -> usize {
+        self.banks.as_ref().map_or(1, |banks| banks.saved.len())
+    }
+
+    pub fn selected_bank(&self) -> usize {
+        self.banks.as_ref().map_or(0, |banks| banks.selected)
+    }
+
+    pub fn select_bank(&mut self, bank: usize) {
+        let Some(banks) = &mut self.banks else {
+            return;
+        };
+        if bank == banks.selected || bank >= banks.saved.len() {
+            return;
+        }
+        let common = banks.common as usize;
+        banks.saved[banks.selected].copy_from_slice(&self.bus.memory[..common]);
+        self.bus.memory[..common].copy_from_slice(&banks.saved[bank]);
+        banks.selected = bank;
+        self.invalidate(0, common);
+    }
+
+    // memory in `bank` whether or not it is selected; a bank that does not
+    // exist reads as FF and ignores writes, the way select_bank ignores it
+    pub fn read_bank(&self, bank: usize, addr: u16) -> u8 {
+        if bank >= self.bank_count() {
+            return 0xFF;
+        }
+        match &self.banks {
+            Some(banks) if bank != banks.selected && addr < banks.common => {
+                banks.saved[bank][addr as usize]
+            }
+            _ => self.bus.memory[addr as usize],
+        }
+    }
+
+    pub fn write_bank(&mut self, bank: usize, addr: u16, value: u8) {
+        if bank >= self.bank_count() {
+            return;
+        }
+        match &mut self.banks {
+            Some(banks) if bank != banks.selected && addr < banks.common => {
+                banks.saved[bank][addr as usize] = value;
+            }
+            _ => {
+                self.bus.memory[addr as usize] = value;
+                self.invalidate(addr, 1);
+            }
+        }
     }
 
     pub fn load(&mut self, addr: u16, data: &[u8]) {
         let start = addr as usize;
         let end = start + data.len();
         self.bus.memory[start..end].copy_from_slice(data);
+        self.invalidate(addr, data.len());
+    }
+
+    pub fn load_image(&mut self, image: &Image) {
+        for segment in &image.segments {
+            self.load(segment.address, &segment.data);
+        }
+    }
+}
+
+impl Default for SimpleMachine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run() {
+        let mut machine = SimpleMachine::new();
+        // loop: INR A; OUT 10h; JMP loop
+        machine.load(0x0000, &[0x3C, 0xD3, 0x10, 0xC3, 0x00, 0x00]);
+
+        let stop = machine.run_for_cycles(100);
+        assert_eq!(stop.reason, StopReason::CycleBudget);
+        assert_eq!((stop.ops, stop.cycles), (12, 100));
+
+        machine.set_io_trap(0x10, true);
+        let stop = machine.run_until_halt();
+        assert_eq!(
+            stop.reason,
+            StopReason::IoTrap {
+                port: 0x10,
+                output: true
+            }
+        );
+        assert_eq!(machine.cpu.pc, 0x0001);
+        machine.set_io_trap(0x10, false);
+
+        machine.set_breakpoint(0x0003, true);
+        assert_eq!(
+            machine.run_until_halt().reason,
+            StopReason::Breakpoint(0x0003)
+        );
+        machine.set_breakpoint(0x0003, false);
+
+        let stop = machine.run_until(|cpu, _| cpu.a == 0x10);
+        assert_eq!((stop.reason, machine.cpu.a), (StopReason::Condition, 0x10));
+
+        machine.load(0x0003, &[0x76]); // HLT
+        assert_eq!(machine.run_until_halt().reason, StopReason::Halted);
+    }
+
+    #[test]
+    fn test_banks() {
+        let mut machine = SimpleMachine::new();
+        machine.load(0x0100, &[1]);
+        machine.load(0xC000, &[2]);
+        machine.set_banks(2, 0xC000);
+        machine.select_bank(1);
+        assert_eq!(machine.bus.memory[0x0100..0x0101], [0]);
+        assert_eq!(machine.bus.memory[0xC000], 2);
+        machine.write_bank(0, 0x0101, 3);
+        machine.write_bank(0, 0xC001, 4);
+        machine.select_bank(0);
+        assert_eq!(machine.bus.memory[0x0100..0x0102], [1, 3]);
+        assert_eq!(machine.read_bank(1, 0xC001), 4);
+
+        // banks that do not exist are refused
+        machine.write_bank(5, 0x0100, 9);
+        assert_eq!(machine.read_bank(5, 0x0100), 0xFF);
+        assert_eq!(machine.bus.memory[0x0100], 1);
     }
 }
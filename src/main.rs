@@ -1,12 +1,64 @@
-use crate::bus::Bus;
-use crate::cpu::Cycles;
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, ErrorKind, IsTerminal, Write};
+use std::path::Path;
 
-mod bus;
-mod cpu;
-mod machine;
+use remu::cpm::bdos;
+use remu::cpm::bdos::{Bdos, Console, Status};
+use remu::cpm::console::{HostConsole, TcpConsole};
+use remu::cpm::drive::Drive;
+use remu::cpm::expect::{self, Script};
+use remu::cpm::fs::{FileName, FileSystem};
+use remu::cpm::hostdrive::HostDrive;
+use remu::cpm::imagedrive::ImageDrive;
+use remu::cpm::mpm::{self, Mpm};
+use remu::cpm::peripherals::{Peripherals, Physical};
+use remu::cpu::Cycles;
+use remu::devices::terminal::{Emulation, Terminal};
+use remu::disk::{self, DiskDef, Volume};
+use remu::fuzz::{self, Case, Rng};
+use remu::loader;
+use remu::machine;
+use remu::recompile;
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("recompile") {
+        if let Err(err) = run_recompiler(&args[2..]) {
+            eprintln!("recompile: {}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("fuzz") {
+        run_fuzzer(&args[2..]);
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("cpm") {
+        if let Err(err) = run_cpm_tool(&args[2..]) {
+            eprintln!("cpm: {}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("console") {
+        if let Err(err) = run_console(&args[2..]) {
+            eprintln!("console: {}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("mpm") {
+        if let Err(err) = run_mpm(&args[2..]) {
+            eprintln!("mpm: {}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("terminal") {
+        run_terminal(&args[2..]);
+        return;
+    }
     match args.len() {
         1 => {
             // run all tests
@@ -24,75 +76,420 @@ fn main() {
             // run single test
             run_test(&args[1]);
         }
+        4 => {
+            // run a program, then save a memory range in the format the
+            // output file's extension selects
+            let machine = run_test(&args[1]);
+            let Some((from, to)) = parse_range(&args[2]) else {
+                eprintln!("invalid range {}, expected <from>-<to> in hex", args[2]);
+                std::process::exit(1);
+            };
+            let data = &machine.bus.memory[from as usize..=to as usize];
+            if let Err(err) = loader::write(&args[3], from, data, None) {
+                eprintln!("{}: {}", args[3], err);
+                std::process::exit(1);
+            }
+        }
         _ => {
-            eprintln!("usage: {} [<test>]", args[0]);
+            eprintln!("usage: {} [<test> [<from>-<to> <dump file>]]", args[0]);
+            eprintln!("       {} terminal <adm3a|vt52|vt100> <program>", args[0]);
+            eprintln!("       {} console {}", args[0], CONSOLE_USAGE);
+            eprintln!("       {} cpm {}", args[0], CPM_USAGE);
+            eprintln!("       {} fuzz [<iterations> [<seed>]]", args[0]);
+            eprintln!("       {} mpm {}", args[0], MPM_USAGE);
+            eprintln!("       {} recompile {}", args[0], RECOMPILE_USAGE);
             std::process::exit(1);
         }
     }
 }
 
-fn run_test(path: &str) {
+// .COM, Intel HEX or S-record, picked by extension
+fn run_test(path: &str) -> machine::SimpleMachine {
     println!("test: {}", path);
-    let program = std::fs::read(path).expect("failed to read test file");
-    let (ops, cycles) = run_program(&program);
+    let image = loader::read(path).unwrap_or_else(|err| {
+        eprintln!("{}: {}", path, err);
+        std::process::exit(1);
+    });
+    let mut machine = machine::SimpleMachine::new();
+    let (ops, cycles) =
+        bdos::run(&mut machine, &image, &mut std::io::stdout()).expect("console write failed");
     println!("\nops: {}, cycles: {}\n", ops, cycles);
+    machine
+}
+
+// run a program on an emulated terminal and print the final screen
+fn run_terminal(args: &[String]) {
+    let (Some(emulation), Some(path)) = (
+        args.first().and_then(|name| Emulation::parse(name)),
+        args.get(1),
+    ) else {
+        eprintln!("usage: terminal <adm3a|vt52|vt100> <program>");
+        std::process::exit(1);
+    };
+    let image = loader::read(path).unwrap_or_else(|err| {
+        eprintln!("{}: {}", path, err);
+        std::process::exit(1);
+    });
+    let mut terminal = Terminal::new(emulation);
+    let mut machine = machine::SimpleMachine::new();
+    let (ops, cycles) =
+        bdos::run(&mut machine, &image, &mut terminal).expect("console write failed");
+    println!("{}", terminal.snapshot());
+    println!("\nops: {}, cycles: {}", ops, cycles);
 }
 
-fn run_program(program: &[u8]) -> (u64, Cycles) {
+const CONSOLE_USAGE: &str = "[-i <input file> | -e <expect script>] \
+[-v <2.2|3>] [-t <adm3a|vt52|vt100>] [-d <device>=<file>]... [-m|-r <drive>=<dir|image>]... <program> [<arg>...]";
+
+// Run a program on the host console as the CCP would, with the arguments
+// as its command tail and default FCBs. The exit status is 0 unless the
+// program sets a failing return code or is ended by Ctrl-C (130) or a
+// BDOS error (1). Keys come from the keyboard in raw mode, or from the
+// input file or a pipe for scripted runs; -t draws the output on an
+// emulated terminal. Ctrl-] breaks into the emulator. With
+// -e the program runs under a send/expect script instead, and the
+// transcript is printed when it passes. -d attaches a physical device
+// like LPT or PTR to a host file or pipe for LST:, PUN: and RDR:. -m
+// mounts a host directory or an ibm-3740 disk image as a drive, -r the
+// same write protected. -v 3 runs the program under CP/M 3 on a banked
+// machine.
+fn run_console(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let mut peripherals = Peripherals::new();
+    let mut drives = Vec::new();
+    let mut input = None;
+    let mut script = None;
+    let mut emulation = None;
+    let mut cpm3 = false;
+    let mut rest = args;
+    while let [flag, value, tail @ ..] = rest {
+        match flag.as_str() {
+            "-i" => input = Some(value.clone()),
+            "-e" => script = Some(Script::parse(&std::fs::read_to_string(value)?)?),
+            "-d" => {
+                let (name, file) = value
+                    .split_once('=')
+                    .ok_or_else(|| format!("expected <device>=<file>, got {}", value))?;
+                let device = Physical::parse(name)
+                    .filter(|&device| !matches!(device, Physical::Tty | Physical::Crt))
+                    .ok_or_else(|| format!("unknown device {}", name))?;
+                if device.is_input() {
+                    peripherals.attach_input(device, BufReader::new(File::open(file)?));
+                } else {
+                    peripherals.attach_output(device, BufWriter::new(File::create(file)?));
+                }
+            }
+            "-m" | "-r" => drives.push(parse_mount(flag, value)?),
+            "-v" => {
+                cpm3 = match value.as_str() {
+                    "2.2" => false,
+                    "3" => true,
+                    _ => return Err(format!("unknown CP/M version {}", value).into()),
+                }
+            }
+            "-t" => {
+                emulation = Some(
+                    Emulation::parse(value).ok_or_else(|| format!("unknown terminal {}", value))?,
+                )
+            }
+            _ => break,
+        }
+        rest = tail;
+    }
+    let [path, args @ ..] = rest else {
+        return Err(format!("usage: console {}", CONSOLE_USAGE).into());
+    };
+    let image = loader::read(path)?;
     let mut machine = machine::SimpleMachine::new();
+    let mut bdos = if cpm3 {
+        Bdos::boot_cpm3(&mut machine, &image)
+    } else {
+        Bdos::boot(&mut machine, &image)
+    };
+    bdos.set_command(&mut machine, &args.join(" "));
+    *bdos.peripherals() = peripherals;
+    for (disk, path, read_only) in drives {
+        bdos.mount(disk, mount_drive(&path, read_only)?);
+    }
 
-    let mut ops: u64 = 0;
-    let mut cycles: Cycles = 0;
+    if let Some(script) = script {
+        let transcript = expect::run_session(&mut machine, &mut bdos, &script)?;
+        std::io::stdout().write_all(&transcript)?;
+        return Ok(());
+    }
 
-    machine.load(0x0100, &program);
-    machine.cpu.pc = 0x0100;
+    let output = std::io::stdout();
+    let mut console = match input {
+        Some(file) => HostConsole::script(BufReader::new(File::open(file)?), output),
+        None if !std::io::stdin().is_terminal() => {
+            HostConsole::script(std::io::stdin().lock(), output)
+        }
+        None => HostConsole::keyboard(output),
+    };
+    if let Some(emulation) = emulation {
+        console = console.with_screen(Terminal::new(emulation));
+    }
 
-    machine.load(0x0000, &[0x76]); // HLT
-    machine.load(0x0005, &[0xC9]); // RET
+    let mut run = || {
+        while bdos.run(&mut machine, &mut console, Cycles::MAX)? == Status::Running {}
+        io::Result::Ok(())
+    };
+    match run() {
+        Ok(()) => {
+            drop(console);
+            match bdos.return_code() {
+                code if code < bdos::FAILURE => Ok(()),
+                bdos::CTRL_C_ABORT => std::process::exit(130),
+                _ => std::process::exit(1),
+            }
+        }
+        Err(err) if err.kind() == ErrorKind::Interrupted => {
+            // leave raw mode before showing where the program stopped
+            drop(console);
+            println!("\r\nbreak\n{}", machine.cpu);
+            std::process::exit(130);
+        }
+        Err(err) => Err(err.into()),
+    }
+}
 
-    loop {
-        if machine.cpu.state == cpu::State::Halted {
-            break;
+// "-m B=dir" or "-r B:=dir" as the drive, directory and write protection
+fn parse_mount(flag: &str, value: &str) -> Result<(usize, String, bool), String> {
+    let (name, dir) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected <drive>=<dir>, got {}", value))?;
+    let disk = match name.strip_suffix(':').unwrap_or(name).as_bytes() {
+        [letter @ (b'A'..=b'P' | b'a'..=b'p')] => letter.to_ascii_uppercase() - b'A',
+        _ => return Err(format!("unknown drive {}", name)),
+    };
+    Ok((disk as usize, dir.to_string(), flag == "-r"))
+}
+
+fn host_drive(dir: &str, read_only: bool) -> Box<HostDrive> {
+    let mut drive = HostDrive::new(dir);
+    drive.set_read_only(read_only);
+    Box::new(drive)
+}
+
+// a disk image if `path` is a file, else a host directory
+fn mount_drive(path: &str, read_only: bool) -> Result<Box<dyn Drive>, Box<dyn Error>> {
+    if !Path::new(path).is_file() {
+        return Ok(host_drive(path, read_only));
+    }
+    let def = DiskDef::builtin("ibm-3740").expect("built-in format");
+    let disk = disk::open(path, &def)?;
+    let mut drive = ImageDrive::new(FileSystem::open(Volume::new(def, disk))?);
+    drive.set_read_only(read_only);
+    Ok(Box::new(drive))
+}
+
+const MPM_USAGE: &str = "[-n <consoles>] [-p <port>] [-m|-r <drive>=<dir>]... <program>";
+
+// Run a program as an MP/M II system with a process per console. The
+// consoles listen on loopback TCP ports from -p on, or on free ports,
+// for nc or telnet; the system runs until every process has exited.
+fn run_mpm(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let mut count = 2;
+    let mut port: u16 = 0;
+    let mut drives = Vec::new();
+    let mut rest = args;
+    while let [flag, value, tail @ ..] = rest {
+        match flag.as_str() {
+            "-n" => {
+                count = value
+                    .parse()
+                    .ok()
+                    .filter(|count| (1..=16).contains(count))
+                    .ok_or_else(|| format!("invalid console count {}", value))?
+            }
+            "-p" => {
+                port = value
+                    .parse()
+                    .map_err(|_| format!("invalid port {}", value))?
+            }
+            "-m" | "-r" => drives.push(parse_mount(flag, value)?),
+            _ => break,
         }
-        if machine.cpu.pc == 0x0000 {
-            break;
+        rest = tail;
+    }
+    let [path] = rest else {
+        return Err(format!("usage: mpm {}", MPM_USAGE).into());
+    };
+    let image = loader::read(path)?;
+
+    let mut consoles: Vec<Box<dyn Console>> = Vec::new();
+    for index in 0..count {
+        let port = match port {
+            0 => 0,
+            port => port
+                .checked_add(index)
+                .ok_or_else(|| format!("no port for console {} after {}", index, port))?,
+        };
+        let console = TcpConsole::bind(port)?;
+        println!("console {} on {}", index, console.address()?);
+        consoles.push(Box::new(console));
+    }
+    let mut machine = machine::SimpleMachine::new();
+    let mut mpm = Mpm::boot(&mut machine, &image, consoles);
+    for index in 0..count as usize {
+        for (disk, dir, read_only) in &drives {
+            mpm.bdos(index).mount(*disk, host_drive(dir, *read_only));
         }
-        if machine.cpu.pc == 0x0005 {
-            process_cpm_call(&mut machine);
+    }
+    loop {
+        match mpm.run(&mut machine, mpm::DEFAULT_TICK)? {
+            Status::Exited => break,
+            Status::Waiting => std::thread::sleep(std::time::Duration::from_millis(10)),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn parse_range(text: &str) -> Option<(u16, u16)> {
+    let (from, to) = text.split_once('-')?;
+    let from = u16::from_str_radix(from, 16).ok()?;
+    let to = u16::from_str_radix(to, 16).ok()?;
+    (from <= to).then_some((from, to))
+}
+
+// compare Cpu against the reference interpreter on random programs; each
+// divergence is printed with a minimized case for tests/fuzz.rs
+fn run_fuzzer(args: &[String]) {
+    let iterations = args.first().and_then(|n| n.parse().ok()).unwrap_or(100_000);
+    let seed = args.get(1).and_then(|n| n.parse().ok()).unwrap_or(0x8080);
+    let mut rng = Rng::new(seed);
+    let mut failures = 0;
+    for _ in 0..iterations {
+        let case = Case::random(&mut rng, 64);
+        if let Some(divergence) = fuzz::run(&case) {
+            let minimized = fuzz::minimize_divergence(&case);
+            println!("{}", divergence);
+            println!("  {}", minimized);
+            failures += 1;
         }
+    }
+    println!("{} cases, {} divergences", iterations, failures);
+    if failures > 0 {
+        std::process::exit(1);
+    }
+}
+
+const RECOMPILE_USAGE: &str = "[-e <entry>]... <image> <output.rs>";
 
-        ops += 1;
-        cycles += machine.step();
+// translate an image to Rust; entry points default to the image start or
+// 0100h and are given in hex
+fn run_recompiler(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let mut entries = Vec::new();
+    let mut rest = args;
+    while let [flag, value, tail @ ..] = rest
+        && flag == "-e"
+    {
+        entries.push(u16::from_str_radix(value, 16)?);
+        rest = tail;
+    }
+    let [image_path, output] = rest else {
+        return Err(format!("usage: recompile {}", RECOMPILE_USAGE).into());
+    };
+    let image = loader::read(image_path)?;
+    if entries.is_empty() {
+        entries.push(image.start.unwrap_or(0x0100));
     }
 
-    (ops, cycles)
+    let program = recompile::analyze(&image, &entries);
+    let name = Path::new(image_path)
+        .file_name()
+        .map_or(image_path.clone(), |name| {
+            name.to_string_lossy().into_owned()
+        });
+    std::fs::write(output, recompile::emit(&program, &name))?;
+    eprintln!(
+        "{} blocks, {} instructions",
+        program.blocks.len(),
+        program.instructions()
+    );
+    Ok(())
 }
 
-fn process_cpm_call(machine: &mut machine::SimpleMachine) {
-    match machine.cpu.c {
-        0x02 => {
-            // character output
-            let char = machine.cpu.e;
-            if char >= b' ' || char == b'\n' {
-                print!("{}", char as char);
+const CPM_USAGE: &str = "[-f <diskdef>] [-d <diskdefs file>] <image> \
+ls | get <name> [<file>] | put <file> [<name>] | rm <name>... | format";
+
+// file transfer to and from CP/M disk images; names are [user:]NAME.EXT
+fn run_cpm_tool(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let mut format = "ibm-3740".to_string();
+    let mut diskdefs = None;
+    let mut rest = args;
+    while let [flag, value, tail @ ..] = rest {
+        match flag.as_str() {
+            "-f" => format = value.clone(),
+            "-d" => diskdefs = Some(std::fs::read_to_string(value)?),
+            _ => break,
+        }
+        rest = tail;
+    }
+    let [image, command, operands @ ..] = rest else {
+        return Err(format!("usage: cpm {}", CPM_USAGE).into());
+    };
+    let def = DiskDef::find(diskdefs.as_deref(), &format)?
+        .ok_or_else(|| format!("unknown disk format {}", format))?;
+
+    if command == "format" {
+        let disk = if Path::new(image).exists() {
+            disk::open(image, &def)?
+        } else {
+            disk::create(image, &def)?
+        };
+        FileSystem::format(Volume::new(def, disk))?.flush()?;
+        return Ok(());
+    }
+
+    let disk = disk::open(image, &def)?;
+    let mut fs = FileSystem::open(Volume::new(def, disk))?;
+    match (command.as_str(), operands) {
+        ("ls", []) => {
+            for file in fs.list() {
+                let flags = match (file.read_only, file.system) {
+                    (true, true) => " r/o sys",
+                    (true, false) => " r/o",
+                    (false, true) => " sys",
+                    (false, false) => "",
+                };
+                println!(
+                    "{:>2}: {:<12} {:>8}{}",
+                    file.name.user,
+                    file.name.file_name(),
+                    file.size,
+                    flags
+                );
             }
         }
-        0x09 => {
-            // string output
-            let mut addr = machine.cpu.de();
-            loop {
-                let char = machine.bus.read(addr);
-                if char == b'$' {
-                    break;
-                }
-                if char >= b' ' || char == b'\n' {
-                    print!("{}", char as char);
+        ("get", [name] | [name, _]) => {
+            let name = FileName::parse(name)?;
+            let target = operands
+                .get(1)
+                .cloned()
+                .unwrap_or_else(|| name.file_name().to_ascii_lowercase());
+            std::fs::write(target, fs.read(&name)?)?;
+        }
+        ("put", [file] | [file, _]) => {
+            let name = match operands.get(1) {
+                Some(name) => FileName::parse(name)?,
+                None => {
+                    let base = Path::new(file)
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .ok_or_else(|| format!("{}: no file name", file))?;
+                    FileName::parse(base)?
                 }
-                addr = addr.wrapping_add(1);
-            }
+            };
+            fs.write(&name, &std::fs::read(file)?)?;
         }
-        _ => {
-            println!("unsupported {:02X} CP/M call", machine.cpu.c);
+        ("rm", names) if !names.is_empty() => {
+            for name in names {
+                fs.remove(&FileName::parse(name)?)?;
+            }
         }
+        _ => return Err(format!("usage: cpm {}", CPM_USAGE).into()),
     }
+    fs.flush()?;
+    Ok(())
 }
@@ -0,0 +1,759 @@
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+use crate::bus::Bus;
+use crate::loader::Image;
+
+// Static translation of 8080 images to Rust. Control flow is followed from
+// the entry points through jumps, calls and returns into basic blocks; each
+// block becomes a function that checks its code is still in memory, so self
+// modifying and unreached code fall back to the interpreter.
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Instruction {
+    pub addr: u16,
+    pub bytes: Vec<u8>,
+}
+
+impl Instruction {
+    fn opcode(&self) -> u8 {
+        self.bytes[0]
+    }
+
+    fn byte(&self) -> u8 {
+        self.bytes[1]
+    }
+
+    fn word(&self) -> u16 {
+        u16::from_le_bytes([self.bytes[1], self.bytes[2]])
+    }
+
+    fn next(&self) -> u16 {
+        self.addr.wrapping_add(self.bytes.len() as u16)
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Block {
+    pub start: u16,
+    pub instructions: Vec<Instruction>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Program {
+    pub blocks: Vec<Block>,
+}
+
+impl Program {
+    pub fn instructions(&self) -> usize {
+        self.blocks
+            .iter()
+            .map(|block| block.instructions.len())
+            .sum()
+    }
+}
+
+// longer straight-line runs are split so generated functions stay small
+const MAX_BLOCK: usize = 64;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Flow {
+    Next,
+    Jump(u16),
+    Branch(u8, u16),
+    Call(u16),
+    CallIf(u8, u16),
+    Ret,
+    RetIf(u8),
+    Indirect,
+    Halt,
+}
+
+fn length(opcode: u8) -> u16 {
+    match opcode {
+        0x01 | 0x11 | 0x21 | 0x31 | 0x22 | 0x2A | 0x32 | 0x3A => 3,
+        _ if opcode & 0xC7 == 0xC2 || opcode & 0xC7 == 0xC4 => 3,
+        0xC3 | 0xCB | 0xCD | 0xDD | 0xED | 0xFD => 3,
+        _ if opcode & 0xC7 == 0x06 || opcode & 0xC7 == 0xC6 => 2,
+        0xD3 | 0xDB => 2,
+        _ => 1,
+    }
+}
+
+fn flow(ins: &Instruction) -> Flow {
+    let opcode = ins.opcode();
+    let cc = (opcode >> 3) & 0x07;
+    match opcode {
+        0xC3 | 0xCB => Flow::Jump(ins.word()),
+        0xCD | 0xDD | 0xED | 0xFD => Flow::Call(ins.word()),
+        0xC9 | 0xD9 => Flow::Ret,
+        0xE9 => Flow::Indirect,
+        0x76 => Flow::Halt,
+        _ if opcode & 0xC7 == 0xC2 => Flow::Branch(cc, ins.word()),
+        _ if opcode & 0xC7 == 0xC4 => Flow::CallIf(cc, ins.word()),
+        _ if opcode & 0xC7 == 0xC0 => Flow::RetIf(cc),
+        _ if opcode & 0xC7 == 0xC7 => Flow::Call((opcode & 0x38) as u16),
+        _ => Flow::Next,
+    }
+}
+
+// a block ends after a store, so code it overwrites is checked again before
+// it runs
+fn writes_memory(opcode: u8) -> bool {
+    matches!(opcode, 0x70..=0x75 | 0x77 | 0x34 | 0x35 | 0x36)
+        || matches!(opcode, 0x02 | 0x12 | 0x22 | 0x32 | 0xE3)
+        || opcode & 0xCF == 0xC5
+}
+
+pub fn analyze(image: &Image, entries: &[u16]) -> Program {
+    let mut memory = vec![None; 0x10000];
+    for segment in &image.segments {
+        for (i, &byte) in segment.data.iter().enumerate() {
+            memory[(segment.address as usize + i) & 0xFFFF] = Some(byte);
+        }
+    }
+    let decode = |addr: u16| {
+        let len = length(memory[addr as usize]?);
+        let bytes = (0..len)
+            .map(|i| memory[addr.wrapping_add(i) as usize])
+            .collect::<Option<Vec<u8>>>()?;
+        Some(Instruction { addr, bytes })
+    };
+
+    // every reachable instruction, and the addresses control arrives at
+    let mut leaders: BTreeSet<u16> = entries.iter().copied().collect();
+    let mut seen = BTreeSet::new();
+    let mut work = entries.to_vec();
+    while let Some(addr) = work.pop() {
+        if !seen.insert(addr) {
+            continue;
+        }
+        let Some(ins) = decode(addr) else {
+            continue;
+        };
+        let next = ins.next();
+        let targets = match flow(&ins) {
+            Flow::Next if writes_memory(ins.opcode()) => vec![next],
+            Flow::Next => {
+                work.push(next);
+                continue;
+            }
+            Flow::Jump(target) => vec![target],
+            Flow::Branch(_, target) | Flow::Call(target) | Flow::CallIf(_, target) => {
+                vec![target, next]
+            }
+            Flow::RetIf(_) => vec![next],
+            Flow::Ret | Flow::Indirect | Flow::Halt => vec![],
+        };
+        leaders.extend(&targets);
+        work.extend(targets);
+    }
+
+    let mut blocks = Vec::new();
+    let mut pending: Vec<u16> = leaders.iter().copied().collect();
+    let mut done = BTreeSet::new();
+    while let Some(start) = pending.pop() {
+        if !done.insert(start) {
+            continue;
+        }
+        let mut instructions = Vec::new();
+        let mut addr = start;
+        while let Some(ins) = decode(addr) {
+            let next = ins.next();
+            let end = flow(&ins) != Flow::Next || writes_memory(ins.opcode());
+            instructions.push(ins);
+            if end || leaders.contains(&next) || next == start {
+                break;
+            }
+            if instructions.len() == MAX_BLOCK {
+                leaders.insert(next);
+                pending.push(next);
+                break;
+            }
+            addr = next;
+        }
+        if !instructions.is_empty() {
+            blocks.push(Block {
+                start,
+                instructions,
+            });
+        }
+    }
+    blocks.sort_by_key(|block| block.start);
+    Program { blocks }
+}
+
+// a translated block is only valid while memory holds the code it was
+// translated from
+pub fn intact<B: Bus + ?Sized>(bus: &mut B, addr: u16, code: &[u8]) -> bool {
+    code.iter()
+        .enumerate()
+        .all(|(i, &byte)| bus.read(addr.wrapping_add(i as u16)) == byte)
+}
+
+const REGISTERS: [&str; 8] = ["B", "C", "D", "E", "H", "L", "M", "A"];
+const PAIRS: [&str; 4] = ["B", "D", "H", "SP"];
+const CONDITIONS: [&str; 8] = ["NZ", "Z", "NC", "C", "PO", "PE", "P", "M"];
+const ALU: [&str; 8] = ["ADD", "ADC", "SUB", "SBB", "ANA", "XRA", "ORA", "CMP"];
+const ALU_IMMEDIATE: [&str; 8] = ["ADI", "ACI", "SUI", "SBI", "ANI", "XRI", "ORI", "CPI"];
+
+pub fn mnemonic(ins: &Instruction) -> String {
+    let opcode = ins.opcode();
+    let r = REGISTERS[(opcode as usize >> 3) & 7];
+    let s = REGISTERS[opcode as usize & 7];
+    let rp = PAIRS[(opcode as usize >> 4) & 3];
+    let cc = CONDITIONS[(opcode as usize >> 3) & 7];
+    match opcode {
+        0x76 => "HLT".to_string(),
+        0x40..=0x7F => format!("MOV {},{}", r, s),
+        0x80..=0xBF => format!("{} {}", ALU[(opcode as usize >> 3) & 7], s),
+        0x01 | 0x11 | 0x21 | 0x31 => format!("LXI {},{:04X}H", rp, ins.word()),
+        0x02 | 0x12 => format!("STAX {}", rp),
+        0x0A | 0x1A => format!("LDAX {}", rp),
+        0x22 => format!("SHLD {:04X}H", ins.word()),
+        0x2A => format!("LHLD {:04X}H", ins.word()),
+        0x32 => format!("STA {:04X}H", ins.word()),
+        0x3A => format!("LDA {:04X}H", ins.word()),
+        0x07 => "RLC".to_string(),
+        0x0F => "RRC".to_string(),
+        0x17 => "RAL".to_string(),
+        0x1F => "RAR".to_string(),
+        0x27 => "DAA".to_string(),
+        0x2F => "CMA".to_string(),
+        0x37 => "STC".to_string(),
+        0x3F => "CMC".to_string(),
+        0xC3 | 0xCB => format!("JMP {:04X}H", ins.word()),
+        0xCD | 0xDD | 0xED | 0xFD => format!("CALL {:04X}H", ins.word()),
+        0xC9 | 0xD9 => "RET".to_string(),
+        0xE3 => "XTHL".to_string(),
+        0xE9 => "PCHL".to_string(),
+        0xEB => "XCHG".to_string(),
+        0xF9 => "SPHL".to_string(),
+        0xF3 => "DI".to_string(),
+        0xFB => "EI".to_string(),
+        0xD3 => format!("OUT {:02X}H", ins.byte()),
+        0xDB => format!("IN {:02X}H", ins.byte()),
+        0xF1 => "POP PSW".to_string(),
+        0xF5 => "PUSH PSW".to_string(),
+        _ => match opcode & 0xC7 {
+            0x00 => "NOP".to_string(),
+            0x04 => format!("INR {}", r),
+            0x05 => format!("DCR {}", r),
+            0x06 => format!("MVI {},{:02X}H", r, ins.byte()),
+            0xC0 => format!("R{}", cc),
+            0xC2 => format!("J{} {:04X}H", cc, ins.word()),
+            0xC4 => format!("C{} {:04X}H", cc, ins.word()),
+            0xC6 => format!(
+                "{} {:02X}H",
+                ALU_IMMEDIATE[(opcode as usize >> 3) & 7],
+                ins.byte()
+            ),
+            0xC7 => format!("RST {}", (opcode >> 3) & 7),
+            _ => match opcode & 0xCF {
+                0x03 => format!("INX {}", rp),
+                0x09 => format!("DAD {}", rp),
+                0x0B => format!("DCX {}", rp),
+                0xC1 => format!("POP {}", rp),
+                _ => format!("PUSH {}", rp),
+            },
+        },
+    }
+}
+
+// Rust source for a translation of `program`, exposing
+// `step(cpu, bus) -> Cycles` to use in place of `Cpu::step`
+pub fn emit(program: &Program, source: &str) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "// Translated by `remu recompile` from {}.", source);
+    out.push_str("#![allow(clippy::all)]\n\n");
+    out.push_str("use remu::bus::{Access, Bus};\n");
+    out.push_str("use remu::cpu::{Cpu, Cycles, State, arith};\n");
+    out.push_str("use remu::recompile::intact;\n\n");
+
+    out.push_str("pub fn step<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Cycles {\n");
+    out.push_str("    if cpu.state == State::Halted {\n");
+    out.push_str("        return cpu.step(bus);\n");
+    out.push_str("    }\n");
+    out.push_str("    let translated = match cpu.pc {\n");
+    for block in &program.blocks {
+        let _ = writeln!(
+            out,
+            "        0x{:04X} => block_{:04x}(cpu, bus),",
+            block.start, block.start
+        );
+    }
+    out.push_str("        _ => None,\n");
+    out.push_str("    };\n");
+    out.push_str("    translated.unwrap_or_else(|| cpu.step(bus))\n");
+    out.push_str("}\n");
+
+    for block in &program.blocks {
+        emit_block(&mut out, block);
+    }
+    out
+}
+
+fn emit_block(out: &mut String, block: &Block) {
+    let _ = writeln!(
+        out,
+        "\nfn block_{:04x}<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {{",
+        block.start
+    );
+    let code: Vec<u8> = block
+        .instructions
+        .iter()
+        .flat_map(|ins| ins.bytes.iter().copied())
+        .collect();
+    let bytes: Vec<String> = code.iter().map(|b| format!("0x{:02X}", b)).collect();
+    if bytes.len() <= 16 {
+        let _ = write!(
+            out,
+            "    if !intact(bus, 0x{:04X}, &[{}",
+            block.start,
+            bytes.join(", ")
+        );
+    } else {
+        let _ = write!(out, "    if !intact(bus, 0x{:04X}, &[", block.start);
+        for chunk in bytes.chunks(16) {
+            let _ = write!(out, "\n        {},", chunk.join(", "));
+        }
+        out.push_str("\n    ");
+    }
+    out.push_str("]) {\n");
+    out.push_str("        return None;\n");
+    out.push_str("    }\n");
+
+    let mut cycles = 0;
+    let (last, body) = block.instructions.split_last().unwrap();
+    for ins in body {
+        cycles += emit_instruction(out, ins);
+    }
+    let (fixed, conditional) = match flow(last) {
+        Flow::Next => {
+            let fixed = emit_instruction(out, last);
+            let _ = writeln!(out, "    cpu.pc = 0x{:04X};", last.next());
+            (fixed, false)
+        }
+        flow => emit_exit(out, last, flow),
+    };
+    cycles += fixed;
+    if conditional {
+        if cycles == 0 {
+            out.push_str("    Some(cycles)\n");
+        } else {
+            let _ = writeln!(out, "    Some({} + cycles)", cycles);
+        }
+    } else {
+        let _ = writeln!(out, "    Some({})", cycles);
+    }
+    out.push_str("}\n");
+}
+
+fn comment(out: &mut String, ins: &Instruction) {
+    let _ = writeln!(out, "    // {:04X}: {}", ins.addr, mnemonic(ins));
+}
+
+fn read(r: u8) -> String {
+    match r {
+        6 => "bus.read_access(cpu.hl(), Access::Data)".to_string(),
+        _ => format!("cpu.{}", REGISTERS[r as usize].to_lowercase()),
+    }
+}
+
+fn write(out: &mut String, r: u8, value: &str) {
+    let _ = match r {
+        6 => writeln!(
+            out,
+            "    bus.write_access(cpu.hl(), {}, Access::Data);",
+            value
+        ),
+        _ => writeln!(
+            out,
+            "    cpu.{} = {};",
+            REGISTERS[r as usize].to_lowercase(),
+            value
+        ),
+    };
+}
+
+fn pair(rp: u8) -> &'static str {
+    ["cpu.bc()", "cpu.de()", "cpu.hl()", "cpu.sp"][rp as usize]
+}
+
+fn set_pair(out: &mut String, rp: u8, value: &str) {
+    let _ = match rp {
+        3 => writeln!(out, "    cpu.sp = {};", value),
+        _ => writeln!(
+            out,
+            "    cpu.set_{}({});",
+            ["bc", "de", "hl"][rp as usize],
+            value
+        ),
+    };
+}
+
+fn push(out: &mut String, value: &str) {
+    out.push_str("    cpu.sp = cpu.sp.wrapping_sub(2);\n");
+    let _ = writeln!(out, "    bus.write_word(cpu.sp, {}, Access::Stack);", value);
+}
+
+fn pop(out: &mut String, indent: &str) {
+    let _ = writeln!(
+        out,
+        "{}cpu.pc = bus.read_word(cpu.sp, Access::Stack);",
+        indent
+    );
+    let _ = writeln!(out, "{}cpu.sp = cpu.sp.wrapping_add(2);", indent);
+}
+
+fn alu(out: &mut String, op: u8, value: &str) {
+    match op {
+        // ANA
+        4 => {
+            let _ = writeln!(out, "    let value = {};", value);
+            out.push_str("    let result = cpu.a & value;\n");
+            out.push_str("    cpu.set_zsp(result);\n");
+            out.push_str("    cpu.set_carry(false);\n");
+            out.push_str("    cpu.set_aux_carry((cpu.a | value) & 0x08 != 0);\n");
+            out.push_str("    cpu.a = result;\n");
+        }
+        // XRA, ORA
+        5 | 6 => {
+            let _ = writeln!(
+                out,
+                "    cpu.a {}= {};",
+                if op == 5 { "^" } else { "|" },
+                value
+            );
+            out.push_str("    cpu.set_zsp(cpu.a);\n");
+            out.push_str("    cpu.set_carry(false);\n");
+            out.push_str("    cpu.set_aux_carry(false);\n");
+        }
+        // ADD, ADC, SUB, SBB, CMP
+        _ => {
+            let carry = if op == 1 || op == 3 {
+                "cpu.carry()"
+            } else {
+                "false"
+            };
+            let complement = op >= 2;
+            let _ = writeln!(
+                out,
+                "    let (result, carry, aux_carry) = arith(cpu.a, {}, {}, {});",
+                value, carry, complement
+            );
+            if op != 7 {
+                out.push_str("    cpu.a = result;\n");
+            }
+            out.push_str("    cpu.set_zsp(result);\n");
+            out.push_str("    cpu.set_carry(carry);\n");
+            out.push_str("    cpu.set_aux_carry(aux_carry);\n");
+        }
+    }
+}
+
+// code for an instruction that continues with the next one; returns its
+// cycle count, matching cpu::table
+fn emit_instruction(out: &mut String, ins: &Instruction) -> u64 {
+    comment(out, ins);
+    let opcode = ins.opcode();
+    let dst = (opcode >> 3) & 0x07;
+    let src = opcode & 0x07;
+    let rp = (opcode >> 4) & 0x03;
+    let memory = |r: u8| r == 6;
+    match opcode {
+        0x40..=0x7F => {
+            if dst != src {
+                write(out, dst, &read(src));
+            }
+            if memory(dst) || memory(src) { 7 } else { 5 }
+        }
+        0x80..=0xBF => {
+            alu(out, dst, &read(src));
+            if memory(src) { 7 } else { 4 }
+        }
+        0x02 | 0x12 => {
+            let _ = writeln!(
+                out,
+                "    bus.write_access({}, cpu.a, Access::Data);",
+                pair(rp)
+            );
+            7
+        }
+        0x0A | 0x1A => {
+            let _ = writeln!(
+                out,
+                "    cpu.a = bus.read_access({}, Access::Data);",
+                pair(rp)
+            );
+            7
+        }
+        0x22 => {
+            let _ = writeln!(
+                out,
+                "    bus.write_word(0x{:04X}, cpu.hl(), Access::Data);",
+                ins.word()
+            );
+            16
+        }
+        0x2A => {
+            let _ = writeln!(
+                out,
+                "    let value = bus.read_word(0x{:04X}, Access::Data);",
+                ins.word()
+            );
+            out.push_str("    cpu.set_hl(value);\n");
+            16
+        }
+        0x32 => {
+            let _ = writeln!(
+                out,
+                "    bus.write_access(0x{:04X}, cpu.a, Access::Data);",
+                ins.word()
+            );
+            13
+        }
+        0x3A => {
+            let _ = writeln!(
+                out,
+                "    cpu.a = bus.read_access(0x{:04X}, Access::Data);",
+                ins.word()
+            );
+            13
+        }
+        0x07 => {
+            out.push_str("    cpu.set_carry(cpu.a & 0x80 != 0);\n");
+            out.push_str("    cpu.a = cpu.a.rotate_left(1);\n");
+            4
+        }
+        0x0F => {
+            out.push_str("    cpu.set_carry(cpu.a & 0x01 != 0);\n");
+            out.push_str("    cpu.a = cpu.a.rotate_right(1);\n");
+            4
+        }
+        0x17 => {
+            out.push_str("    let msb = cpu.a & 0x80;\n");
+            out.push_str("    cpu.a = (cpu.a << 1) | cpu.carry() as u8;\n");
+            out.push_str("    cpu.set_carry(msb != 0);\n");
+            4
+        }
+        0x1F => {
+            out.push_str("    let lsb = cpu.a & 0x01;\n");
+            out.push_str("    cpu.a = (cpu.a >> 1) | (cpu.carry() as u8) << 7;\n");
+            out.push_str("    cpu.set_carry(lsb != 0);\n");
+            4
+        }
+        0x27 => {
+            out.push_str("    cpu.daa();\n");
+            4
+        }
+        0x2F => {
+            out.push_str("    cpu.a = !cpu.a;\n");
+            4
+        }
+        0x37 => {
+            out.push_str("    cpu.set_carry(true);\n");
+            4
+        }
+        0x3F => {
+            out.push_str("    cpu.set_carry(!cpu.carry());\n");
+            4
+        }
+        0xE3 => {
+            out.push_str("    let value = bus.read_word(cpu.sp, Access::Stack);\n");
+            out.push_str("    bus.write_word(cpu.sp, cpu.hl(), Access::Stack);\n");
+            out.push_str("    cpu.set_hl(value);\n");
+            18
+        }
+        0xEB => {
+            out.push_str("    let value = cpu.de();\n");
+            out.push_str("    cpu.set_de(cpu.hl());\n");
+            out.push_str("    cpu.set_hl(value);\n");
+            5
+        }
+        0xF9 => {
+            out.push_str("    cpu.sp = cpu.hl();\n");
+            6
+        }
+        0xF3 | 0xFB => {
+            let _ = writeln!(out, "    cpu.iff = {};", opcode == 0xFB);
+            4
+        }
+        0xD3 => {
+            let _ = writeln!(out, "    bus.output(0x{:02X}, cpu.a);", ins.byte());
+            10
+        }
+        0xDB => {
+            let _ = writeln!(out, "    cpu.a = bus.input(0x{:02X});", ins.byte());
+            10
+        }
+        0xF5 => {
+            push(out, "cpu.af()");
+            11
+        }
+        0xF1 => {
+            out.push_str("    let value = bus.read_word(cpu.sp, Access::Stack);\n");
+            out.push_str("    cpu.sp = cpu.sp.wrapping_add(2);\n");
+            out.push_str("    cpu.set_af(value);\n");
+            10
+        }
+        _ => match opcode & 0xC7 {
+            0x00 => 4,
+            0x04 | 0x05 => {
+                let _ = writeln!(out, "    let value = {};", read(dst));
+                if opcode & 0x01 == 0 {
+                    out.push_str("    let result = value.wrapping_add(1);\n");
+                    out.push_str("    cpu.set_zsp(result);\n");
+                    out.push_str("    cpu.set_aux_carry(value & 0x0F == 0x0F);\n");
+                } else {
+                    out.push_str("    let result = value.wrapping_sub(1);\n");
+                    out.push_str("    cpu.set_zsp(result);\n");
+                    out.push_str("    cpu.set_aux_carry(result & 0x0F != 0x0F);\n");
+                }
+                write(out, dst, "result");
+                if memory(dst) { 10 } else { 5 }
+            }
+            0x06 => {
+                write(out, dst, &format!("0x{:02X}", ins.byte()));
+                if memory(dst) { 10 } else { 7 }
+            }
+            0xC6 => {
+                alu(out, dst, &format!("0x{:02X}", ins.byte()));
+                7
+            }
+            _ => match opcode & 0xCF {
+                0x01 => {
+                    set_pair(out, rp, &format!("0x{:04X}", ins.word()));
+                    10
+                }
+                0x03 => {
+                    set_pair(out, rp, &format!("{}.wrapping_add(1)", pair(rp)));
+                    5
+                }
+                0x0B => {
+                    set_pair(out, rp, &format!("{}.wrapping_sub(1)", pair(rp)));
+                    5
+                }
+                0x09 => {
+                    let _ = writeln!(
+                        out,
+                        "    let (value, carry) = cpu.hl().overflowing_add({});",
+                        pair(rp)
+                    );
+                    out.push_str("    cpu.set_hl(value);\n");
+                    out.push_str("    cpu.set_carry(carry);\n");
+                    10
+                }
+                0xC5 => {
+                    push(out, pair(rp));
+                    11
+                }
+                0xC1 => {
+                    out.push_str("    let value = bus.read_word(cpu.sp, Access::Stack);\n");
+                    out.push_str("    cpu.sp = cpu.sp.wrapping_add(2);\n");
+                    set_pair(out, rp, "value");
+                    10
+                }
+                _ => unreachable!("opcode {:02X} changes control flow", opcode),
+            },
+        },
+    }
+}
+
+// code for the control transfer ending a block; returns the fixed cycle
+// count, and whether a conditional part is left in `cycles`
+fn emit_exit(out: &mut String, ins: &Instruction, flow: Flow) -> (u64, bool) {
+    comment(out, ins);
+    let next = ins.next();
+    match flow {
+        Flow::Jump(target) => {
+            let _ = writeln!(out, "    cpu.pc = 0x{:04X};", target);
+            (10, false)
+        }
+        Flow::Branch(cc, target) => {
+            let _ = writeln!(
+                out,
+                "    cpu.pc = if cpu.condition({}) {{ 0x{:04X} }} else {{ 0x{:04X} }};",
+                cc, target, next
+            );
+            (10, false)
+        }
+        Flow::Call(target) => {
+            push(out, &format!("0x{:04X}", next));
+            let _ = writeln!(out, "    cpu.pc = 0x{:04X};", target);
+            // RST takes fewer cycles than CALL
+            (if ins.bytes.len() == 1 { 11 } else { 17 }, false)
+        }
+        Flow::CallIf(cc, target) => {
+            let _ = writeln!(out, "    let cycles = if cpu.condition({}) {{", cc);
+            out.push_str("        cpu.sp = cpu.sp.wrapping_sub(2);\n");
+            let _ = writeln!(
+                out,
+                "        bus.write_word(cpu.sp, 0x{:04X}, Access::Stack);",
+                next
+            );
+            let _ = writeln!(out, "        cpu.pc = 0x{:04X};", target);
+            out.push_str("        17\n");
+            out.push_str("    } else {\n");
+            let _ = writeln!(out, "        cpu.pc = 0x{:04X};", next);
+            out.push_str("        11\n");
+            out.push_str("    };\n");
+            (0, true)
+        }
+        Flow::Ret => {
+            pop(out, "    ");
+            (10, false)
+        }
+        Flow::RetIf(cc) => {
+            let _ = writeln!(out, "    let cycles = if cpu.condition({}) {{", cc);
+            pop(out, "        ");
+            out.push_str("        11\n");
+            out.push_str("    } else {\n");
+            let _ = writeln!(out, "        cpu.pc = 0x{:04X};", next);
+            out.push_str("        5\n");
+            out.push_str("    };\n");
+            (0, true)
+        }
+        Flow::Indirect => {
+            out.push_str("    cpu.pc = cpu.hl();\n");
+            (5, false)
+        }
+        Flow::Halt => {
+            out.push_str("    cpu.state = State::Halted;\n");
+            let _ = writeln!(out, "    cpu.pc = 0x{:04X};", next);
+            (7, false)
+        }
+        Flow::Next => unreachable!(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::Segment;
+
+    #[test]
+    fn test_analyze() {
+        let image = Image {
+            segments: vec![Segment {
+                address: 0x0100,
+                data: vec![
+                    0x3E, 0x05, // 0100: MVI A,05H
+                    0x3D, // 0102: DCR A
+                    0xC2, 0x02, 0x01, // 0103: JNZ 0102H
+                    0xCD, 0x0C, 0x01, // 0106: CALL 010CH
+                    0x76, // 0109: HLT
+                    0xFF, 0xFF, // data
+                    0x32, 0x00, 0x02, // 010C: STA 0200H
+                    0xC9, // 010F: RET
+                ],
+            }],
+            start: None,
+        };
+        let program = analyze(&image, &[0x0100]);
+        let starts: Vec<u16> = program.blocks.iter().map(|block| block.start).collect();
+        assert_eq!(starts, [0x0100, 0x0102, 0x0106, 0x0109, 0x010C, 0x010F]);
+        assert_eq!(program.instructions(), 7);
+        assert_eq!(mnemonic(&program.blocks[1].instructions[1]), "JNZ 0102H");
+    }
+}
@@ -0,0 +1,454 @@
+use std::fmt;
+use std::ops::Range;
+
+// Just enough regular expressions to match console output: literals, `.`,
+// [classes] with ranges and ^, \d \w \s and their negations, ^ and $ at
+// line boundaries, (groups) with |, and greedy *, + and ?. The pattern is
+// compiled to a little program that runs over the text once with all of its
+// threads in step (a Pike VM), so long text costs neither stack nor
+// backtracking. Threads keep the order a backtracker would try them in, so
+// the match is the same one.
+#[derive(Clone, Debug)]
+pub struct Regex {
+    pattern: String,
+    program: Vec<Inst>,
+}
+
+#[derive(Clone, Debug)]
+enum Node {
+    Byte(u8),
+    // anything but a newline
+    Any,
+    Class {
+        ranges: Vec<(u8, u8)>,
+        negated: bool,
+    },
+    LineStart,
+    LineEnd,
+    Group(Vec<Vec<Node>>),
+    Repeat {
+        node: Box<Node>,
+        min: usize,
+        max: Option<usize>,
+    },
+}
+
+#[derive(Clone, Debug)]
+enum Inst {
+    Byte(u8),
+    Any,
+    Class {
+        ranges: Vec<(u8, u8)>,
+        negated: bool,
+    },
+    LineStart,
+    LineEnd,
+    // try the first, then the second
+    Split(usize, usize),
+    Jump(usize),
+    Match,
+}
+
+#[derive(Debug)]
+pub struct Error {
+    pub offset: usize,
+    pub message: &'static str,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "regex error at byte {}: {}", self.offset, self.message)
+    }
+}
+
+impl std::error::Error for Error {}
+
+const DIGIT: &[(u8, u8)] = &[(b'0', b'9')];
+const WORD: &[(u8, u8)] = &[(b'0', b'9'), (b'A', b'Z'), (b'_', b'_'), (b'a', b'z')];
+const SPACE: &[(u8, u8)] = &[(b'\t', b'\r'), (b' ', b' ')];
+
+impl Regex {
+    pub fn new(pattern: &str) -> Result<Regex, Error> {
+        let mut parser = Parser {
+            bytes: pattern.as_bytes(),
+            pos: 0,
+        };
+        let root = Node::Group(parser.alternatives()?);
+        if parser.pos != parser.bytes.len() {
+            return Err(parser.error("unmatched )"));
+        }
+        let mut program = Vec::new();
+        compile(&root, &mut program);
+        program.push(Inst::Match);
+        Ok(Regex {
+            pattern: pattern.to_string(),
+            program,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    // the leftmost match, as long as the greedy repeats make it
+    pub fn find(&self, text: &[u8]) -> Option<Range<usize>> {
+        // threads as (instruction, where the match started), best first
+        let mut threads = Vec::new();
+        let mut stepped = Vec::new();
+        let mut seen = vec![usize::MAX; self.program.len()];
+        let mut found = None;
+        for pos in 0..=text.len() {
+            // a match starting here ranks below every one started earlier
+            if found.is_none() {
+                self.follow(text, pos, 0, pos, &mut threads, &mut seen);
+            }
+            if threads.is_empty() {
+                if found.is_some() {
+                    break;
+                }
+                continue;
+            }
+            for &(pc, start) in &threads {
+                let byte = text.get(pos).copied();
+                let step = match &self.program[pc] {
+                    Inst::Byte(want) => byte == Some(*want),
+                    Inst::Any => byte.is_some_and(|c| c != b'\n'),
+                    Inst::Class { ranges, negated } => byte.is_some_and(|c| {
+                        ranges.iter().any(|&(lo, hi)| (lo..=hi).contains(&c)) != *negated
+                    }),
+                    // the threads after this one would only find worse matches
+                    Inst::Match => {
+                        found = Some(start..pos);
+                        break;
+                    }
+                    _ => unreachable!("only consuming instructions are queued"),
+                };
+                if step {
+                    self.follow(text, pos + 1, pc + 1, start, &mut stepped, &mut seen);
+                }
+            }
+            std::mem::swap(&mut threads, &mut stepped);
+            stepped.clear();
+        }
+        found
+    }
+
+    // queue the instructions reachable from `pc` at `pos` without consuming
+    // a byte, in the order a backtracker would try them; an instruction
+    // already queued at `pos` has a better thread and is skipped, which also
+    // stops empty repeats from looping
+    fn follow(
+        &self,
+        text: &[u8],
+        pos: usize,
+        pc: usize,
+        start: usize,
+        threads: &mut Vec<(usize, usize)>,
+        seen: &mut [usize],
+    ) {
+        let mut stack = vec![pc];
+        while let Some(pc) = stack.pop() {
+            if seen[pc] == pos {
+                continue;
+            }
+            seen[pc] = pos;
+            match self.program[pc] {
+                Inst::Split(first, second) => stack.extend([second, first]),
+                Inst::Jump(to) => stack.push(to),
+                Inst::LineStart => {
+                    if pos == 0 || matches!(text[pos - 1], b'\r' | b'\n') {
+                        stack.push(pc + 1);
+                    }
+                }
+                Inst::LineEnd => {
+                    if pos == text.len() || matches!(text[pos], b'\r' | b'\n') {
+                        stack.push(pc + 1);
+                    }
+                }
+                _ => threads.push((pc, start)),
+            }
+        }
+    }
+
+    pub fn is_match(&self, text: &[u8]) -> bool {
+        self.find(text).is_some()
+    }
+}
+
+impl fmt::Display for Regex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "/{}/", self.pattern)
+    }
+}
+
+fn compile(node: &Node, program: &mut Vec<Inst>) {
+    match node {
+        Node::Byte(byte) => program.push(Inst::Byte(*byte)),
+        Node::Any => program.push(Inst::Any),
+        Node::Class { ranges, negated } => program.push(Inst::Class {
+            ranges: ranges.clone(),
+            negated: *negated,
+        }),
+        Node::LineStart => program.push(Inst::LineStart),
+        Node::LineEnd => program.push(Inst::LineEnd),
+        Node::Group(alternatives) => {
+            // each alternative but the last: split to it or on to the
+            // next, and jump past the rest at its end
+            let mut jumps = Vec::new();
+            for (i, sequence) in alternatives.iter().enumerate() {
+                let split = program.len();
+                let last = i + 1 == alternatives.len();
+                if !last {
+                    program.push(Inst::Split(split + 1, 0));
+                }
+                for node in sequence {
+                    compile(node, program);
+                }
+                if !last {
+                    jumps.push(program.len());
+                    program.push(Inst::Jump(0));
+                    program[split] = Inst::Split(split + 1, program.len());
+                }
+            }
+            let end = program.len();
+            for jump in jumps {
+                program[jump] = Inst::Jump(end);
+            }
+        }
+        Node::Repeat { node, min, max } => {
+            for _ in 0..*min {
+                compile(node, program);
+            }
+            match max {
+                // greedy: another one first, then stop
+                None => {
+                    let split = program.len();
+                    program.push(Inst::Split(split + 1, 0));
+                    compile(node, program);
+                    program.push(Inst::Jump(split));
+                    program[split] = Inst::Split(split + 1, program.len());
+                }
+                Some(max) => {
+                    let mut splits = Vec::new();
+                    for _ in *min..*max {
+                        splits.push(program.len());
+                        program.push(Inst::Split(program.len() + 1, 0));
+                        compile(node, program);
+                    }
+                    let end = program.len();
+                    for split in splits {
+                        program[split] = Inst::Split(split + 1, end);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// a byte, or one of the \d \w \s sets and whether it is negated
+enum Escape {
+    Byte(u8),
+    Set(&'static [(u8, u8)], bool),
+}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &'static str) -> Error {
+        Error {
+            offset: self.pos,
+            message,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn take(&mut self) -> Option<u8> {
+        let byte = self.peek()?;
+        self.pos += 1;
+        Some(byte)
+    }
+
+    fn alternatives(&mut self) -> Result<Vec<Vec<Node>>, Error> {
+        let mut alternatives = vec![self.sequence()?];
+        while self.peek() == Some(b'|') {
+            self.pos += 1;
+            alternatives.push(self.sequence()?);
+        }
+        Ok(alternatives)
+    }
+
+    fn sequence(&mut self) -> Result<Vec<Node>, Error> {
+        let mut sequence = Vec::new();
+        while let Some(byte) = self.peek() {
+            let (min, max) = match byte {
+                b'|' | b')' => break,
+                b'*' => (0, None),
+                b'+' => (1, None),
+                b'?' => (0, Some(1)),
+                _ => {
+                    sequence.push(self.atom()?);
+                    continue;
+                }
+            };
+            let node = match sequence.pop() {
+                Some(node @ (Node::Byte(_) | Node::Any | Node::Class { .. } | Node::Group(_))) => {
+                    node
+                }
+                _ => return Err(self.error("nothing to repeat")),
+            };
+            self.pos += 1;
+            sequence.push(Node::Repeat {
+                node: Box::new(node),
+                min,
+                max,
+            });
+        }
+        Ok(sequence)
+    }
+
+    fn atom(&mut self) -> Result<Node, Error> {
+        Ok(match self.take() {
+            Some(b'(') => {
+                let alternatives = self.alternatives()?;
+                if self.take() != Some(b')') {
+                    return Err(self.error("unclosed group"));
+                }
+                Node::Group(alternatives)
+            }
+            Some(b'[') => self.class()?,
+            Some(b'.') => Node::Any,
+            Some(b'^') => Node::LineStart,
+            Some(b'$') => Node::LineEnd,
+            Some(b'\\') => match self.escape()? {
+                Escape::Byte(byte) => Node::Byte(byte),
+                Escape::Set(ranges, negated) => Node::Class {
+                    ranges: ranges.to_vec(),
+                    negated,
+                },
+            },
+            Some(byte) => Node::Byte(byte),
+            None => return Err(self.error("unexpected end of pattern")),
+        })
+    }
+
+    fn class(&mut self) -> Result<Node, Error> {
+        let negated = self.peek() == Some(b'^');
+        if negated {
+            self.pos += 1;
+        }
+        let mut ranges = Vec::new();
+        let mut first = true;
+        loop {
+            let lo = match self.take() {
+                Some(b']') if !first => break,
+                Some(b'\\') => match self.escape()? {
+                    Escape::Byte(byte) => byte,
+                    Escape::Set(set, false) => {
+                        ranges.extend_from_slice(set);
+                        first = false;
+                        continue;
+                    }
+                    Escape::Set(_, true) => return Err(self.error("negated set in a class")),
+                },
+                Some(byte) => byte,
+                None => return Err(self.error("unclosed class")),
+            };
+            first = false;
+            let hi = if self.peek() == Some(b'-') && self.bytes.get(self.pos + 1) != Some(&b']') {
+                self.pos += 1;
+                match self.take() {
+                    Some(b'\\') => match self.escape()? {
+                        Escape::Byte(byte) => byte,
+                        Escape::Set(..) => return Err(self.error("set as a range end")),
+                    },
+                    Some(byte) => byte,
+                    None => return Err(self.error("unclosed class")),
+                }
+            } else {
+                lo
+            };
+            if hi < lo {
+                return Err(self.error("range out of order"));
+            }
+            ranges.push((lo, hi));
+        }
+        Ok(Node::Class { ranges, negated })
+    }
+
+    fn escape(&mut self) -> Result<Escape, Error> {
+        let Some(byte) = self.take() else {
+            return Err(self.error("unexpected end of pattern"));
+        };
+        Ok(match byte {
+            b'd' | b'D' => Escape::Set(DIGIT, byte == b'D'),
+            b'w' | b'W' => Escape::Set(WORD, byte == b'W'),
+            b's' | b'S' => Escape::Set(SPACE, byte == b'S'),
+            b'r' => Escape::Byte(b'\r'),
+            b'n' => Escape::Byte(b'\n'),
+            b't' => Escape::Byte(b'\t'),
+            b'e' => Escape::Byte(0x1B),
+            b'x' => {
+                let digits = self.bytes.get(self.pos..self.pos + 2);
+                let value = digits
+                    .and_then(|digits| std::str::from_utf8(digits).ok())
+                    .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                    .ok_or_else(|| self.error("invalid \\x escape"))?;
+                self.pos += 2;
+                Escape::Byte(value)
+            }
+            byte if byte.is_ascii_alphanumeric() => return Err(self.error("unknown escape")),
+            byte => Escape::Byte(byte),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find<'a>(pattern: &str, text: &'a str) -> Option<&'a str> {
+        let regex = Regex::new(pattern).unwrap();
+        regex.find(text.as_bytes()).map(|range| &text[range])
+    }
+
+    #[test]
+    fn test_find() {
+        assert_eq!(find("A>", "\r\nA>dir"), Some("A>"));
+        assert_eq!(find("^A>", "xA>\r\nA>"), Some("A>"));
+        assert_eq!(find("^A>$", "A>x"), None);
+        assert_eq!(find(r"\d+ bytes", "copied 1024 bytes"), Some("1024 bytes"));
+        assert_eq!(find("a.*c", "abcbc\ndc"), Some("abcbc"));
+        assert_eq!(find("(yes|no)+", "maybe nonoyes"), Some("nonoyes"));
+        assert_eq!(find("[^ ]+K", "SIZE 12K"), Some("12K"));
+        assert_eq!(find(r"[\d.-]+", "v-1.5 "), Some("-1.5"));
+        assert_eq!(find("colou?r", "color"), Some("color"));
+        assert_eq!(find("(a*)+b", "aab"), Some("aab"));
+        assert_eq!(find(r"\x1b\[H", "\x1b[H"), Some("\x1b[H"));
+        assert_eq!(find("", "abc"), Some(""));
+
+        for pattern in ["(ab", "ab)", "*a", "[a", "[z-a]", r"\q", r"\x4"] {
+            assert!(Regex::new(pattern).is_err(), "{}", pattern);
+        }
+    }
+
+    #[test]
+    fn test_long_text() {
+        // a long transcript must not run the matcher out of stack
+        let mut transcript = "A>dir\r\nNO FILE\r\n".repeat(8000);
+        assert!(transcript.len() > 100_000);
+        assert_eq!(find("[^>]*>", &transcript), Some("A>"));
+        assert_eq!(
+            find("(.|\r|\n)*FILE", &transcript).map(str::len),
+            Some(transcript.len() - 2)
+        );
+        assert_eq!(find("[^>]*>x", &transcript), None);
+        transcript.push_str("B>");
+        assert_eq!(find("^B>$", &transcript), Some("B>"));
+    }
+}
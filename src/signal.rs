@@ -0,0 +1,21 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+// a single wire shared between devices: one side drives it, any number of
+// other sides sample it
+#[derive(Clone, Default, Debug)]
+pub struct Line(Rc<Cell<bool>>);
+
+impl Line {
+    pub fn new(level: bool) -> Self {
+        Line(Rc::new(Cell::new(level)))
+    }
+
+    pub fn get(&self) -> bool {
+        self.0.get()
+    }
+
+    pub fn set(&self, level: bool) {
+        self.0.set(level);
+    }
+}
@@ -0,0 +1,305 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::bus::Bus;
+use crate::cpu::Cpu;
+use crate::json::{self, Value};
+
+// Runner for SingleStepTests-style vectors: each test gives the CPU and RAM
+// state before and after one instruction, plus the bus cycles it takes and,
+// optionally, the port accesses it makes.
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Json(json::Error),
+    Format(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "{}", err),
+            Error::Json(err) => write!(f, "{}", err),
+            Error::Format(message) => write!(f, "malformed test vector: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<json::Error> for Error {
+    fn from(err: json::Error) -> Self {
+        Error::Json(err)
+    }
+}
+
+#[derive(Debug)]
+pub struct Failure {
+    pub name: String,
+    pub mismatches: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct Report {
+    pub passed: usize,
+    pub failures: Vec<Failure>,
+}
+
+impl Report {
+    pub fn total(&self) -> usize {
+        self.passed + self.failures.len()
+    }
+}
+
+// sparse memory holding just the vector's RAM; port reads are answered with
+// the value the vector says the instruction saw
+struct RecordingBus {
+    memory: HashMap<u16, u8>,
+    input: u8,
+    outputs: Vec<(u8, u8)>,
+}
+
+impl Bus for RecordingBus {
+    fn read(&mut self, addr: u16) -> u8 {
+        self.memory.get(&addr).copied().unwrap_or(0)
+    }
+
+    fn write(&mut self, addr: u16, value: u8) {
+        self.memory.insert(addr, value);
+    }
+
+    fn input(&mut self, _port: u8) -> u8 {
+        self.input
+    }
+
+    fn output(&mut self, port: u8, value: u8) {
+        self.outputs.push((port, value));
+    }
+}
+
+fn format_error(message: &str) -> Error {
+    Error::Format(message.to_string())
+}
+
+fn field(state: &Value, key: &str) -> Result<u64, Error> {
+    state
+        .get(key)
+        .and_then(Value::as_u64)
+        .ok_or_else(|| format_error(&format!("missing {}", key)))
+}
+
+fn pairs(value: Option<&Value>) -> Result<Vec<(u16, u8)>, Error> {
+    let Some(items) = value else {
+        return Ok(Vec::new());
+    };
+    items
+        .as_array()
+        .ok_or_else(|| format_error("expected an array"))?
+        .iter()
+        .map(|item| {
+            let pair = item.as_array().unwrap_or(&[]);
+            match (
+                pair.first().and_then(Value::as_u64),
+                pair.get(1).and_then(Value::as_u64),
+            ) {
+                (Some(addr), Some(value)) => Ok((addr as u16, value as u8)),
+                _ => Err(format_error("expected [address, value]")),
+            }
+        })
+        .collect()
+}
+
+const REGISTERS: [&str; 10] = ["a", "b", "c", "d", "e", "f", "h", "l", "pc", "sp"];
+
+fn register(cpu: &Cpu, name: &str) -> u64 {
+    match name {
+        "a" => cpu.a as u64,
+        "b" => cpu.b as u64,
+        "c" => cpu.c as u64,
+        "d" => cpu.d as u64,
+        "e" => cpu.e as u64,
+        "f" => (cpu.af() & 0xFF) as u64,
+        "h" => cpu.h as u64,
+        "l" => cpu.l as u64,
+        "pc" => cpu.pc as u64,
+        "sp" => cpu.sp as u64,
+        _ => unreachable!(),
+    }
+}
+
+fn describe_flags(f: u64) -> String {
+    let names = [
+        (0x80, 'S'),
+        (0x40, 'Z'),
+        (0x10, 'A'),
+        (0x04, 'P'),
+        (0x01, 'C'),
+    ];
+    names
+        .iter()
+        .map(|&(bit, name)| if f & bit != 0 { name } else { '-' })
+        .collect()
+}
+
+pub fn run_test(test: &Value) -> Result<Vec<String>, Error> {
+    let initial = test
+        .get("initial")
+        .ok_or_else(|| format_error("missing initial"))?;
+    let expected = test
+        .get("final")
+        .ok_or_else(|| format_error("missing final"))?;
+
+    let mut cpu = Cpu::new();
+    cpu.set_af((field(initial, "a")? << 8 | field(initial, "f")?) as u16);
+    cpu.b = field(initial, "b")? as u8;
+    cpu.c = field(initial, "c")? as u8;
+    cpu.d = field(initial, "d")? as u8;
+    cpu.e = field(initial, "e")? as u8;
+    cpu.h = field(initial, "h")? as u8;
+    cpu.l = field(initial, "l")? as u8;
+    cpu.pc = field(initial, "pc")? as u16;
+    cpu.sp = field(initial, "sp")? as u16;
+    if let Some(iff) = initial.get("inte").and_then(Value::as_u64) {
+        cpu.iff = iff != 0;
+    }
+
+    let ports = test.get("ports").and_then(Value::as_array).unwrap_or(&[]);
+    let mut input = 0xFF;
+    let mut expected_outputs = Vec::new();
+    for port in ports {
+        let item = port.as_array().unwrap_or(&[]);
+        let number = item.first().and_then(Value::as_u64).unwrap_or(0) as u8;
+        let value = item.get(1).and_then(Value::as_u64).unwrap_or(0) as u8;
+        match item.get(2).and_then(Value::as_str) {
+            Some("r") => input = value,
+            Some("w") => expected_outputs.push((number, value)),
+            _ => return Err(format_error("expected [port, value, \"r\" | \"w\"]")),
+        }
+    }
+
+    let mut bus = RecordingBus {
+        memory: pairs(initial.get("ram"))?.into_iter().collect(),
+        input,
+        outputs: Vec::new(),
+    };
+    let cycles = cpu.step(&mut bus);
+
+    let mut mismatches = Vec::new();
+    for name in REGISTERS {
+        let want = field(expected, name)?;
+        let got = register(&cpu, name);
+        if want != got {
+            if name == "f" {
+                mismatches.push(format!(
+                    "f: expected {:02X} ({}), got {:02X} ({})",
+                    want,
+                    describe_flags(want),
+                    got,
+                    describe_flags(got)
+                ));
+            } else {
+                mismatches.push(format!("{}: expected {:X}, got {:X}", name, want, got));
+            }
+        }
+    }
+    if let Some(iff) = expected.get("inte").and_then(Value::as_u64)
+        && (iff != 0) != cpu.iff
+    {
+        mismatches.push(format!("inte: expected {}, got {}", iff != 0, cpu.iff));
+    }
+    for (addr, want) in pairs(expected.get("ram"))? {
+        let got = bus.read(addr);
+        if want != got {
+            mismatches.push(format!(
+                "ram[{:04X}]: expected {:02X}, got {:02X}",
+                addr, want, got
+            ));
+        }
+    }
+    if let Some(expected_cycles) = test.get("cycles").and_then(Value::as_array)
+        && expected_cycles.len() as u64 != cycles
+    {
+        mismatches.push(format!(
+            "cycles: expected {}, got {}",
+            expected_cycles.len(),
+            cycles
+        ));
+    }
+    if !ports.is_empty() && bus.outputs != expected_outputs {
+        mismatches.push(format!(
+            "ports: expected {:02X?}, got {:02X?}",
+            expected_outputs, bus.outputs
+        ));
+    }
+    Ok(mismatches)
+}
+
+pub fn run_vectors(text: &str) -> Result<Report, Error> {
+    let tests = json::parse(text)?;
+    let tests = tests
+        .as_array()
+        .ok_or_else(|| format_error("expected an array of tests"))?;
+
+    let mut report = Report::default();
+    for test in tests {
+        let mismatches = run_test(test)?;
+        if mismatches.is_empty() {
+            report.passed += 1;
+        } else {
+            let name = test.get("name").and_then(Value::as_str).unwrap_or("?");
+            report.failures.push(Failure {
+                name: name.to_string(),
+                mismatches,
+            });
+        }
+    }
+    Ok(report)
+}
+
+pub fn run_file(path: impl AsRef<Path>) -> Result<Report, Error> {
+    run_vectors(&fs::read_to_string(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vectors() {
+        // ADD B, one correct vector and one with a wrong carry and length
+        let text = r#"[
+            {"name": "80 0000",
+             "initial": {"pc": 256, "sp": 0, "a": 255, "b": 1, "c": 0, "d": 0, "e": 0,
+                         "f": 2, "h": 0, "l": 0, "ram": [[256, 128]]},
+             "final": {"pc": 257, "sp": 0, "a": 0, "b": 1, "c": 0, "d": 0, "e": 0,
+                       "f": 87, "h": 0, "l": 0, "ram": [[256, 128]]},
+             "cycles": [[256, 128, "r"], [null, null, "-"], [null, null, "-"], [null, null, "-"]]},
+            {"name": "80 0001",
+             "initial": {"pc": 256, "sp": 0, "a": 255, "b": 1, "c": 0, "d": 0, "e": 0,
+                         "f": 2, "h": 0, "l": 0, "ram": [[256, 128]]},
+             "final": {"pc": 257, "sp": 0, "a": 0, "b": 1, "c": 0, "d": 0, "e": 0,
+                       "f": 86, "h": 0, "l": 0, "ram": [[256, 128]]},
+             "cycles": [[256, 128, "r"]]}
+        ]"#;
+        let report = run_vectors(text).unwrap();
+        assert_eq!(report.passed, 1);
+        assert_eq!(report.failures[0].name, "80 0001");
+        assert_eq!(
+            report.failures[0].mismatches,
+            vec![
+                "f: expected 56 (-ZAP-), got 57 (-ZAPC)".to_string(),
+                "cycles: expected 1, got 4".to_string(),
+            ]
+        );
+    }
+}
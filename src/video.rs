@@ -0,0 +1,260 @@
+pub mod png;
+
+use std::io;
+use std::path::Path;
+
+use crate::cpu::Cycles;
+use crate::machine::{SimpleMachine, Stop, StopReason};
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Format {
+    // a byte per pixel, looked up in the palette
+    Indexed(Vec<[u8; 3]>),
+    // three bytes per pixel
+    Rgb,
+}
+
+// a picture in host memory, row after row from the top left
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Framebuffer {
+    pub width: usize,
+    pub height: usize,
+    pub format: Format,
+    pub pixels: Vec<u8>,
+}
+
+impl Framebuffer {
+    // all pixels color 0
+    pub fn indexed(width: usize, height: usize, palette: Vec<[u8; 3]>) -> Self {
+        Framebuffer {
+            width,
+            height,
+            format: Format::Indexed(palette),
+            pixels: vec![0; width * height],
+        }
+    }
+
+    // all pixels black
+    pub fn rgb(width: usize, height: usize) -> Self {
+        Framebuffer {
+            width,
+            height,
+            format: Format::Rgb,
+            pixels: vec![0; width * height * 3],
+        }
+    }
+
+    // bytes per row
+    pub fn stride(&self) -> usize {
+        match self.format {
+            Format::Indexed(_) => self.width,
+            Format::Rgb => self.width * 3,
+        }
+    }
+
+    // the color of a pixel; black for indexes past the palette
+    pub fn color(&self, x: usize, y: usize) -> [u8; 3] {
+        let offset = y * self.stride();
+        match &self.format {
+            Format::Indexed(palette) => {
+                let index = self.pixels[offset + x];
+                palette.get(index as usize).copied().unwrap_or([0; 3])
+            }
+            Format::Rgb => {
+                let pixel = &self.pixels[offset + x * 3..][..3];
+                [pixel[0], pixel[1], pixel[2]]
+            }
+        }
+    }
+
+    pub fn to_png(&self) -> Vec<u8> {
+        match &self.format {
+            Format::Indexed(palette) => {
+                // PNG wants an entry for every index used and at least one;
+                // the missing ones are black, as color() has them
+                let used = self.pixels.iter().max().map_or(1, |&max| max as usize + 1);
+                let mut palette = palette.clone();
+                palette.resize(palette.len().max(used), [0; 3]);
+                palette.truncate(256);
+                png::encode(
+                    self.width,
+                    self.height,
+                    png::INDEXED,
+                    &palette,
+                    &self.pixels,
+                )
+            }
+            Format::Rgb => png::encode(self.width, self.height, png::RGB, &[], &self.pixels),
+        }
+    }
+
+    pub fn write_png(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.to_png())
+    }
+}
+
+// A video device: what it shows is drawn from memory into its framebuffer
+// at the end of every frame, which lasts `frame_cycles` CPU cycles.
+pub trait Video {
+    fn frame_cycles(&self) -> Cycles;
+    fn render(&mut self, memory: &[u8]);
+    fn framebuffer(&self) -> &Framebuffer;
+}
+
+// A monochrome bitmap at `base`: a bit per pixel, leftmost pixel in the
+// top bit, and each row starting on a byte.
+pub struct Bitmap {
+    base: u16,
+    frame_cycles: Cycles,
+    framebuffer: Framebuffer,
+}
+
+impl Bitmap {
+    pub fn new(base: u16, width: usize, height: usize, frame_cycles: Cycles) -> Self {
+        let palette = vec![[0, 0, 0], [255, 255, 255]];
+        Bitmap {
+            base,
+            frame_cycles,
+            framebuffer: Framebuffer::indexed(width, height, palette),
+        }
+    }
+}
+
+impl Video for Bitmap {
+    fn frame_cycles(&self) -> Cycles {
+        self.frame_cycles
+    }
+
+    fn render(&mut self, memory: &[u8]) {
+        let frame = &mut self.framebuffer;
+        let row = frame.width.div_ceil(8);
+        for y in 0..frame.height {
+            for x in 0..frame.width {
+                let addr = self.base as usize + y * row + x / 8;
+                let byte = memory[addr % memory.len()];
+                frame.pixels[y * frame.width + x] = (byte >> (7 - x % 8)) & 1;
+            }
+        }
+    }
+
+    fn framebuffer(&self) -> &Framebuffer {
+        &self.framebuffer
+    }
+}
+
+// called with the number and picture of each frame
+pub type FrameCallback<'a> = Box<dyn FnMut(u64, &Framebuffer) + 'a>;
+
+// Runs a machine with a video device, rendering a frame whenever a frame's
+// worth of cycles has passed and handing it to the frame callbacks with
+// its number, counting from 1.
+pub struct Display<'a, V: Video> {
+    video: V,
+    // cycles into the current frame
+    elapsed: Cycles,
+    frames: u64,
+    callbacks: Vec<FrameCallback<'a>>,
+}
+
+impl<'a, V: Video> Display<'a, V> {
+    pub fn new(video: V) -> Self {
+        Display {
+            video,
+            elapsed: 0,
+            frames: 0,
+            callbacks: Vec::new(),
+        }
+    }
+
+    pub fn video(&self) -> &V {
+        &self.video
+    }
+
+    pub fn video_mut(&mut self) -> &mut V {
+        &mut self.video
+    }
+
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    pub fn on_frame(&mut self, callback: impl FnMut(u64, &Framebuffer) + 'a) {
+        self.callbacks.push(Box::new(callback));
+    }
+
+    // account for `cycles` run elsewhere, rendering the frames they end
+    pub fn advance(&mut self, cycles: Cycles, memory: &[u8]) {
+        let frame_cycles = self.video.frame_cycles().max(1);
+        self.elapsed += cycles;
+        while self.elapsed >= frame_cycles {
+            self.elapsed -= frame_cycles;
+            self.video.render(memory);
+            self.frames += 1;
+            for callback in &mut self.callbacks {
+                callback(self.frames, self.video.framebuffer());
+            }
+        }
+    }
+
+    // Run for at least `budget` cycles, a frame at a time. Anything else
+    // that stops the machine stops the run too; as with run_for_cycles,
+    // a breakpoint or trap right at the end of a frame is passed over.
+    pub fn run(&mut self, machine: &mut SimpleMachine, budget: Cycles) -> Stop {
+        let mut total = Stop {
+            reason: StopReason::CycleBudget,
+            ops: 0,
+            cycles: 0,
+        };
+        while total.cycles < budget {
+            let frame_cycles = self.video.frame_cycles().max(1);
+            // a video can shorten its frames, leaving more elapsed than a
+            // frame; advance renders what is owed
+            let slice = frame_cycles
+                .saturating_sub(self.elapsed)
+                .min(budget - total.cycles);
+            let stop = machine.run_for_cycles(slice);
+            total.ops += stop.ops;
+            total.cycles += stop.cycles;
+            total.reason = stop.reason;
+            self.advance(stop.cycles, &machine.bus.memory);
+            if stop.reason != StopReason::CycleBudget {
+                break;
+            }
+        }
+        total
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        // INR M at 8000h, over and over
+        let mut machine = SimpleMachine::new();
+        machine.load(0x0000, &[0x21, 0x00, 0x80, 0x34, 0xC3, 0x03, 0x00]);
+        let mut counts = Vec::new();
+        let mut display = Display::new(Bitmap::new(0x8000, 8, 1, 1000));
+        display.on_frame(|frame, framebuffer| counts.push((frame, framebuffer.pixels[7])));
+        let stop = display.run(&mut machine, 3500);
+        assert_eq!(stop.reason, StopReason::CycleBudget);
+        assert_eq!(display.frames(), 3);
+        // frames made shorter than what has already elapsed of one
+        display.video_mut().frame_cycles = 100;
+        display.run(&mut machine, 1000);
+        assert!(display.frames() >= 12, "{}", display.frames());
+        drop(display);
+        assert_eq!(counts[0].0, 1);
+    }
+
+    #[test]
+    fn test_empty_palette() {
+        // PNG needs a palette entry for each index used
+        let mut frame = Framebuffer::indexed(2, 1, Vec::new());
+        frame.pixels[1] = 2;
+        let png = frame.to_png();
+        let plte = png.windows(4).position(|w| w == b"PLTE").unwrap();
+        assert_eq!(png[plte - 4..plte], 9u32.to_be_bytes());
+    }
+}
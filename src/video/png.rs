@@ -0,0 +1,206 @@
+// A PNG encoder for frame dumps: one IDAT of zlib data compressed with
+// the fixed Huffman codes, matching runs and repeats of the row above.
+
+// color types
+pub const INDEXED: u8 = 3;
+pub const RGB: u8 = 2;
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+// `pixels` holds `height` rows of 8 bit samples, one per pixel for
+// INDEXED and three for RGB; `palette` is used for INDEXED only
+pub fn encode(
+    width: usize,
+    height: usize,
+    color: u8,
+    palette: &[[u8; 3]],
+    pixels: &[u8],
+) -> Vec<u8> {
+    let row = width * if color == RGB { 3 } else { 1 };
+    let mut header = Vec::new();
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(height as u32).to_be_bytes());
+    // 8 bits, deflate, no filtering per image, no interlacing
+    header.extend_from_slice(&[8, color, 0, 0, 0]);
+
+    // each row starts with filter type 0
+    let mut raw = Vec::with_capacity((row + 1) * height);
+    for line in pixels.chunks(row.max(1)).take(height) {
+        raw.push(0);
+        raw.extend_from_slice(line);
+    }
+
+    let mut png = SIGNATURE.to_vec();
+    chunk(&mut png, b"IHDR", &header);
+    if color == INDEXED {
+        chunk(&mut png, b"PLTE", palette.as_flattened());
+    }
+    chunk(&mut png, b"IDAT", &zlib(&raw, row + 1));
+    chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+const WINDOW: usize = 32768;
+const MAX_MATCH: usize = 258;
+
+// deflate's bit order: values least significant bit first, Huffman codes
+// most significant bit first
+struct Bits {
+    bytes: Vec<u8>,
+    buffer: u32,
+    count: u32,
+}
+
+impl Bits {
+    fn put(&mut self, value: u32, count: u32) {
+        self.buffer |= value << self.count;
+        self.count += count;
+        while self.count >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    fn code(&mut self, code: u32, count: u32) {
+        self.put(code.reverse_bits() >> (32 - count), count);
+    }
+
+    fn symbol(&mut self, symbol: u16) {
+        match symbol {
+            0..=143 => self.code(0x30 + symbol as u32, 8),
+            144..=255 => self.code(0x190 + symbol as u32 - 144, 9),
+            256..=279 => self.code(symbol as u32 - 256, 7),
+            _ => self.code(0xC0 + symbol as u32 - 280, 8),
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+// Compress `data` into a zlib stream with a single fixed Huffman block.
+// Matches are only looked for at distance 1 and `stride`, which is all a
+// framebuffer needs: runs of one color and rows like the one above.
+fn zlib(data: &[u8], stride: usize) -> Vec<u8> {
+    let mut bits = Bits {
+        bytes: vec![0x78, 0x01],
+        buffer: 0,
+        count: 0,
+    };
+    // final block, fixed codes
+    bits.put(0b011, 3);
+    let mut i = 0;
+    while i < data.len() {
+        let (length, distance) = [1, stride]
+            .into_iter()
+            .filter(|&distance| distance <= i && distance <= WINDOW)
+            .map(|distance| {
+                let length = (i..data.len().min(i + MAX_MATCH))
+                    .take_while(|&j| data[j] == data[j - distance])
+                    .count();
+                (length, distance)
+            })
+            .max_by_key(|&(length, _)| length)
+            .unwrap_or((0, 0));
+        if length < 3 {
+            bits.symbol(data[i] as u16);
+            i += 1;
+            continue;
+        }
+        let code = LENGTH_BASE
+            .iter()
+            .rposition(|&base| base as usize <= length)
+            .unwrap();
+        bits.symbol(257 + code as u16);
+        bits.put(
+            length as u32 - LENGTH_BASE[code] as u32,
+            LENGTH_EXTRA[code] as u32,
+        );
+        let code = DISTANCE_BASE
+            .iter()
+            .rposition(|&base| base as usize <= distance)
+            .unwrap();
+        bits.code(code as u32, 5);
+        bits.put(
+            distance as u32 - DISTANCE_BASE[code] as u32,
+            DISTANCE_EXTRA[code] as u32,
+        );
+        i += length;
+    }
+    bits.symbol(256);
+    let mut zlib = bits.finish();
+    zlib.extend_from_slice(&adler32(data).to_be_bytes());
+    zlib
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        let png = encode(2, 1, INDEXED, &[[0, 0, 0], [255, 255, 255]], &[0, 1]);
+        assert_eq!(png[..8], SIGNATURE);
+        assert_eq!(png[8..16], [0, 0, 0, 13, b'I', b'H', b'D', b'R']);
+        assert_eq!(png[16..29], [0, 0, 0, 2, 0, 0, 0, 1, 8, 3, 0, 0, 0]);
+        // every chunk ends with the CRC of its type and data
+        assert_eq!(
+            png[png.len() - 12..],
+            [0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xAE, 0x42, 0x60, 0x82]
+        );
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+}
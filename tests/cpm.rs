@@ -0,0 +1,448 @@
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use remu::cpm::bdos::{self, Bdos, Status};
+use remu::cpm::console::HostConsole;
+use remu::cpm::expect::{self, Error, Reason, Script};
+use remu::cpm::hostdrive::HostDrive;
+use remu::cpm::mpm::Mpm;
+use remu::cpm::peripherals::Physical;
+use remu::cpu::Cycles;
+use remu::devices::terminal::{Emulation, Terminal};
+use remu::loader::{Image, Segment};
+use remu::machine::SimpleMachine;
+
+fn program(code: Vec<u8>) -> Image {
+    Image {
+        segments: vec![Segment {
+            address: 0x0100,
+            data: code,
+        }],
+        start: None,
+    }
+}
+
+// print a string through BDOS function 9 and return to CP/M
+fn print(text: &[u8]) -> Image {
+    let mut code = vec![
+        0x11, 0x0B, 0x01, // LXI D,010Bh
+        0x0E, 0x09, //       MVI C,9
+        0xCD, 0x05, 0x00, // CALL 5
+        0xC3, 0x00, 0x00, // JMP 0
+    ];
+    code.extend_from_slice(text);
+    code.push(b'$');
+    program(code)
+}
+
+// read lines with BDOS 10 and print their lengths until Ctrl-C
+fn lines() -> Image {
+    program(vec![
+        0x11, 0x80, 0x00, // LXI D,0080h
+        0x3E, 0x10, //       MVI A,16
+        0x12, //             STAX D
+        0x0E, 0x0A, //       MVI C,10
+        0xCD, 0x05, 0x00, // CALL 5
+        0x3A, 0x81, 0x00, // LDA 0081h
+        0xC6, 0x30, //       ADI '0'
+        0x5F, //             MOV E,A
+        0x0E, 0x02, //       MVI C,2
+        0xCD, 0x05, 0x00, // CALL 5
+        0xC3, 0x00, 0x01, // JMP 0100h
+    ])
+}
+
+// an empty directory for host drives, removed when dropped
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> TempDir {
+        let path = std::env::temp_dir().join(format!("remu-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        TempDir(path)
+    }
+
+    fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn test_terminal_console() {
+    let image = print(b"\x1a\x1b=\x21\x25Hello\x1e*");
+
+    let mut terminal = Terminal::new(Emulation::Adm3a);
+    bdos::run(&mut SimpleMachine::new(), &image, &mut terminal).unwrap();
+    assert_eq!(terminal.snapshot(), "*\n     Hello");
+
+    // a plain writer only sees the printable characters
+    let mut text = Vec::new();
+    bdos::run(&mut SimpleMachine::new(), &image, &mut text).unwrap();
+    assert_eq!(text, b"=!%Hello*");
+}
+
+fn script(image: &Image, input: &'static [u8]) -> Vec<u8> {
+    let mut console = HostConsole::script(input, Vec::new());
+    bdos::run(&mut SimpleMachine::new(), image, &mut console).unwrap();
+    console.output().clone()
+}
+
+#[test]
+fn test_console_input() {
+    let lines = lines();
+    assert_eq!(
+        script(&lines, b"abc\x08d\nxy\r\n\x03never"),
+        b"abc\x08 \x08d\r3xy\r2"
+    );
+    // the end of the input ends the program too
+    assert_eq!(script(&lines, b"\x01\n"), b"^A\r1");
+
+    // copy BIOS CONIN to CONOUT until the Ctrl-Z that ends the input
+    let copy = program(vec![
+        0xCD, 0x09, 0xFE, // CALL CONIN
+        0xFE, 0x1A, //       CPI 1Ah
+        0xCA, 0x00, 0x00, // JZ 0
+        0x4F, //             MOV C,A
+        0xCD, 0x0C, 0xFE, // CALL CONOUT
+        0xC3, 0x00, 0x01, // JMP 0100h
+    ]);
+    assert_eq!(script(&copy, b"hi\nthere"), b"hi\rthere");
+}
+
+#[test]
+fn test_expect() {
+    let lines = lines();
+    let script = Script::parse("send hello\\r\nexpect \\r5\nsend ab\\r\nexpect 2$").unwrap();
+    let transcript = expect::run(&mut SimpleMachine::new(), &lines, &script).unwrap();
+    assert_eq!(transcript, b"hello\r5ab\r2");
+
+    // the program cannot print anything more without another line
+    let script = script.expect(r"\d").unwrap();
+    match expect::run(&mut SimpleMachine::new(), &lines, &script) {
+        Err(Error::Failed {
+            step: 5,
+            reason: Reason::Waiting,
+            transcript,
+            ..
+        }) => assert_eq!(transcript, b"hello\r5ab\r2"),
+        result => panic!("{:?}", result),
+    }
+
+    let spin = program(vec![0xC3, 0x00, 0x01]); // JMP 0100h
+    let script = Script::new().timeout(1000).expect("A>").unwrap();
+    let err = expect::run(&mut SimpleMachine::new(), &spin, &script).unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Failed {
+            reason: Reason::Timeout(1000),
+            ..
+        }
+    ));
+    assert_eq!(
+        err.to_string(),
+        "step 2: /A>/ not seen: timed out after 1000 cycles\n--- transcript ---\n"
+    );
+}
+
+// a writer the test can read back after the run
+#[derive(Clone, Default)]
+struct Shared(Rc<RefCell<Vec<u8>>>);
+
+impl Write for Shared {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_peripherals() {
+    let image = program(vec![
+        0x0E, 0x05, 0x1E, 0x41, 0xCD, 0x05, 0x00, // list 'A'
+        0x0E, 0x04, 0x1E, 0x42, 0xCD, 0x05, 0x00, // punch 'B'
+        0x0E, 0x03, 0xCD, 0x05, 0x00, //             read RDR:
+        0x5F, 0x0E, 0x02, 0xCD, 0x05, 0x00, //       and print it
+        0xCD, 0x15, 0xFE, //                         CALL READER
+        0x32, 0x80, 0x00, //                         STA 0080h
+        0x0E, 0x08, 0x1E, 0x15, 0xCD, 0x05, 0x00, // LST: = TTY:
+        0x0E, 0x05, 0x1E, 0x43, 0xCD, 0x05, 0x00, // list 'C'
+        0x0E, 0x07, 0xCD, 0x05, 0x00, //             get IOBYTE
+        0x32, 0x81, 0x00, //                         STA 0081h
+        0xC3, 0x00, 0x00, //                         JMP 0
+    ]);
+    let (list, punch) = (Shared::default(), Shared::default());
+    let mut machine = SimpleMachine::new();
+    let mut bdos = Bdos::boot(&mut machine, &image);
+    bdos.peripherals()
+        .attach_output(Physical::Lpt, list.clone());
+    bdos.peripherals()
+        .attach_output(Physical::Ptp, punch.clone());
+    bdos.peripherals().attach_input(Physical::Ptr, &b"x"[..]);
+
+    let mut console = Vec::new();
+    while bdos.run(&mut machine, &mut console, Cycles::MAX).unwrap() == Status::Running {}
+    assert_eq!(*list.0.borrow(), b"A");
+    assert_eq!(*punch.0.borrow(), b"B");
+    assert_eq!(console, b"xC");
+    // past the end of the file, and the IOBYTE as set
+    assert_eq!(machine.bus.memory[0x80..0x82], [0x1A, 0x15]);
+}
+
+#[test]
+fn test_host_drive() {
+    let dir = TempDir::new("cpm-drive");
+    let root = dir.path();
+    std::fs::write(root.join("in.txt"), [b'x'; 200]).unwrap();
+
+    // copy IN.TXT to OUT.TXT a record at a time, then size OUT.TXT
+    let mut code = vec![
+        0x11, 0x00, 0x03, 0x0E, 0x1A, 0xCD, 0x05, 0x00, // DMA = 0300h
+        0x11, 0x00, 0x02, 0x0E, 0x0F, 0xCD, 0x05, 0x00, // open IN.TXT
+        0x32, 0x80, 0x02, //                               STA 0280h
+        0x11, 0x30, 0x02, 0x0E, 0x16, 0xCD, 0x05, 0x00, // make OUT.TXT
+        0x11, 0x00, 0x02, 0x0E, 0x14, 0xCD, 0x05, 0x00, // read sequential
+        0xB7, //                                           ORA A
+        0xC2, 0x32, 0x01, //                               JNZ done
+        0x11, 0x30, 0x02, 0x0E, 0x15, 0xCD, 0x05, 0x00, // write sequential
+        0xC3, 0x1B, 0x01, //                               JMP read
+        0x32, 0x81, 0x02, //                               STA 0281h
+        0x11, 0x30, 0x02, 0x0E, 0x10, 0xCD, 0x05, 0x00, // close OUT.TXT
+        0x11, 0x30, 0x02, 0x0E, 0x23, 0xCD, 0x05, 0x00, // size OUT.TXT
+        0xC3, 0x00, 0x00, //                               JMP 0
+    ];
+    code.resize(0x100, 0);
+    code.extend_from_slice(b"\0IN      TXT");
+    code.resize(0x130, 0);
+    code.extend_from_slice(b"\0OUT     TXT");
+    code.resize(0x160, 0);
+
+    let mut machine = SimpleMachine::new();
+    let mut bdos = Bdos::boot(&mut machine, &program(code));
+    bdos.mount(0, Box::new(HostDrive::new(root)));
+    let mut console = Vec::new();
+    while bdos.run(&mut machine, &mut console, Cycles::MAX).unwrap() == Status::Running {}
+
+    // whole records are copied, the last one padded with Ctrl-Z
+    let copy = std::fs::read(root.join("out.txt")).unwrap();
+    assert_eq!(copy.len(), 256);
+    assert_eq!(copy[199..201], [b'x', 0x1A]);
+    assert_eq!(machine.bus.memory[0x280..0x282], [0, 1]);
+    assert_eq!(machine.bus.memory[0x251..0x254], [2, 0, 0]);
+    assert!(console.is_empty());
+}
+
+// LXI D,<de>; MVI C,<function>; CALL 5
+fn call(function: u8, de: u16) -> Vec<u8> {
+    let [lo, hi] = de.to_le_bytes();
+    vec![0x11, lo, hi, 0x0E, function, 0xCD, 0x05, 0x00]
+}
+
+// STA <addr>
+fn store(addr: u16) -> Vec<u8> {
+    let [lo, hi] = addr.to_le_bytes();
+    vec![0x32, lo, hi]
+}
+
+#[test]
+fn test_cpm3() {
+    let dir = TempDir::new("cpm3");
+    let root = dir.path();
+    std::fs::write(root.join("in.txt"), [b'x'; 200]).unwrap();
+    // LDA 005Dh; STA 0287h; JMP 0
+    std::fs::write(
+        root.join("next.com"),
+        [0x3A, 0x5D, 0x00, 0x32, 0x87, 0x02, 0xC3, 0x00, 0x00],
+    )
+    .unwrap();
+
+    let code = [
+        call(12, 0),
+        store(0x0280),
+        // print with # as the delimiter
+        call(110, b'#' as u16),
+        call(9, 0x0200),
+        // console mode, set and read back
+        call(109, 0x0002),
+        call(109, 0xFFFF),
+        vec![0x22, 0x81, 0x02], // SHLD 0281h
+        // set the clock and read it back
+        call(104, 0x0210),
+        call(105, 0x0290),
+        // return errors: making a file on a write protected drive
+        call(45, 0xFF),
+        call(28, 0),
+        call(22, 0x0250),
+        store(0x0283),
+        vec![0x7C], // MOV A,H
+        store(0x0284),
+        // two records at a time: the whole file, then the end of it
+        call(44, 2),
+        call(26, 0x0400),
+        call(15, 0x0220),
+        call(20, 0x0220),
+        store(0x0285),
+        call(20, 0x0220),
+        store(0x0286),
+        call(26, 0x0500),
+        call(46, 0),
+        // copy "hi" to bank 0
+        vec![0x06, 0x00, 0x0E, 0x01, 0xCD, 0x57, 0xFE], // XMOVE from 1 to 0
+        vec![0x11, 0x00, 0x02, 0x21, 0x00, 0x03, 0x01, 0x02, 0x00], // DE, HL, BC
+        vec![0xCD, 0x4B, 0xFE],                         // CALL MOVE
+        call(26, 0x0270),
+        call(47, 0),
+    ]
+    .concat();
+    let mut image = code;
+    image.resize(0x100, 0);
+    image.extend_from_slice(b"hi#");
+    image.resize(0x110, 0);
+    image.extend_from_slice(&[1, 0, 0x12, 0x34]);
+    image.resize(0x120, 0);
+    image.extend_from_slice(b"\0IN      TXT");
+    image.resize(0x150, 0);
+    image.extend_from_slice(b"\0NEW     TXT");
+    image.resize(0x170, 0);
+    image.extend_from_slice(b"next foo.txt\0");
+
+    let mut machine = SimpleMachine::new();
+    let mut bdos = Bdos::boot_cpm3(&mut machine, &program(image));
+    bdos.mount(0, Box::new(HostDrive::new(root)));
+    let mut console = Vec::new();
+    while bdos.run(&mut machine, &mut console, Cycles::MAX).unwrap() == Status::Running {}
+
+    assert_eq!(console, b"hi");
+    let memory = &machine.bus.memory;
+    // version 3.1, console mode 2, read/only disk error 2, then a full
+    // read and end of file
+    assert_eq!(memory[0x280..0x287], [0x31, 0x02, 0x00, 0xFF, 0x02, 0, 1]);
+    assert_eq!(memory[0x290..0x294], [1, 0, 0x12, 0x34]);
+    assert_eq!(memory[0x400 + 199..0x400 + 201], [b'x', 0x1A]);
+    // 256 1K blocks less one for IN.TXT and one for NEXT.COM
+    assert_eq!(memory[0x500..0x503], [0xF0, 0x07, 0]);
+    assert_eq!(memory[0x300], 0);
+    assert_eq!(machine.read_bank(0, 0x300), b'h');
+    // NEXT.COM ran with FOO.TXT in the FCB at 005Ch
+    assert_eq!(memory[0x287], b'F');
+}
+
+#[test]
+fn test_bad_bank() {
+    // XMOVE to bank 5 of 2, then MOVE "hi" from 0200h to 0300h
+    let mut code = [
+        vec![0x06, 0x05, 0x0E, 0x00, 0xCD, 0x57, 0xFE], // XMOVE from 0 to 5
+        vec![0x11, 0x00, 0x02, 0x21, 0x00, 0x03, 0x01, 0x02, 0x00], // DE, HL, BC
+        vec![0xCD, 0x4B, 0xFE, 0xC3, 0x00, 0x00],       // CALL MOVE; JMP 0
+    ]
+    .concat();
+    code.resize(0x100, 0);
+    code.extend_from_slice(b"hi");
+
+    let mut machine = SimpleMachine::new();
+    let mut bdos = Bdos::boot_cpm3(&mut machine, &program(code));
+    while bdos
+        .run(&mut machine, &mut Vec::new(), Cycles::MAX)
+        .unwrap()
+        == Status::Running
+    {}
+    // the bad bank is ignored and the copy stays in the selected bank
+    assert_eq!(machine.bus.memory[0x300..0x302], *b"hi");
+}
+
+#[test]
+fn test_run_program() {
+    let code = [
+        // the tail length, the names in both default FCBs, the top of
+        // memory and the stack
+        vec![0x3A, 0x80, 0x00],
+        store(0x0200),
+        vec![0x3A, 0x5C, 0x00],
+        store(0x0201),
+        vec![0x3A, 0x5D, 0x00],
+        store(0x0202),
+        vec![0x3A, 0x6D, 0x00],
+        store(0x0203),
+        vec![0x2A, 0x06, 0x00, 0x22, 0x04, 0x02], // LHLD 0006h; SHLD 0204h
+        vec![0x21, 0x00, 0x00, 0x39, 0x22, 0x06, 0x02], // LXI H,0; DAD SP; SHLD 0206h
+        // fail, and return to the CCP
+        call(108, 0xFF01),
+        vec![0xC9],
+    ]
+    .concat();
+
+    let mut machine = SimpleMachine::new();
+    let mut console = Vec::new();
+    let code = bdos::run_program(
+        &mut machine,
+        &program(code),
+        "b:foo.txt *.bas",
+        &mut console,
+    );
+    assert_eq!(code.unwrap(), 0xFF01);
+    let memory = &machine.bus.memory;
+    assert_eq!(memory[0x81..0x91], *b" B:FOO.TXT *.BAS");
+    assert_eq!(memory[0x200..0x204], [16, 2, b'F', b'?']);
+    assert_eq!(memory[0x6C..0x78], *b"\0????????BAS");
+    let top = u16::from_le_bytes([memory[0x204], memory[0x205]]);
+    let stack = u16::from_le_bytes([memory[0x206], memory[0x207]]);
+    assert!(top > 0x8000 && stack < top);
+    assert!(console.is_empty());
+}
+
+#[test]
+fn test_long_tail() {
+    // the tail is cut short rather than run into the program
+    let code = [call(108, 0x0042), vec![0xC9]].concat();
+    let mut machine = SimpleMachine::new();
+    let tail = "x".repeat(130);
+    let code = bdos::run_program(&mut machine, &program(code), &tail, &mut Vec::new());
+    assert_eq!(code.unwrap(), 0x0042);
+    let memory = &machine.bus.memory;
+    assert_eq!((memory[0x80], memory[0xFE], memory[0xFF]), (126, b'X', 0));
+    assert_eq!(memory[0x100], 0x11);
+}
+
+#[test]
+fn test_mpm() {
+    // print the console number, spin, print it again, sleep two ticks,
+    // print it a third time and terminate
+    let image = program(vec![
+        0x0E, 0x99, 0xCD, 0x05, 0x00, //             get console number
+        0xC6, 0x30, //                               ADI '0'
+        0x32, 0x00, 0x02, //                         STA 0200h
+        0x5F, 0x0E, 0x02, 0xCD, 0x05, 0x00, //       print it
+        0x01, 0x00, 0x10, //                         LXI B,1000h
+        0x0B, 0x78, 0xB1, 0xC2, 0x13, 0x01, //       spin until BC is 0
+        0x3A, 0x00, 0x02, 0x5F, 0x0E, 0x02, 0xCD, 0x05, 0x00, // print
+        0x11, 0x02, 0x00, 0x0E, 0x8D, 0xCD, 0x05, 0x00, //       delay 2
+        0x3A, 0x00, 0x02, 0x5F, 0x0E, 0x02, 0xCD, 0x05, 0x00, // print
+        0x0E, 0x8F, 0xCD, 0x05, 0x00, //             terminate
+    ]);
+    let output = Shared::default();
+    let consoles: Vec<Box<dyn bdos::Console>> =
+        vec![Box::new(output.clone()), Box::new(output.clone())];
+    let mut machine = SimpleMachine::new();
+    let mut mpm = Mpm::boot(&mut machine, &image, consoles);
+    mpm.set_tick(10_000);
+    while mpm.run(&mut machine, 100_000).unwrap() != Status::Exited {}
+
+    // the clock interrupt shares the processor while both spin
+    assert_eq!(*output.0.borrow(), b"010101");
+    assert!(mpm.ticks() > 10, "{}", mpm.ticks());
+    // each ran in its own bank
+    assert_eq!(machine.read_bank(0, 0x0200), b'0');
+    assert_eq!(machine.read_bank(1, 0x0200), b'1');
+}
@@ -0,0 +1,132 @@
+use remu::cpm::bdos;
+use remu::loader;
+use remu::machine::SimpleMachine;
+
+// line diff of expected against actual output, longest common subsequence
+fn diff(expected: &str, actual: &str) -> String {
+    let a: Vec<&str> = expected.lines().collect();
+    let b: Vec<&str> = actual.lines().collect();
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            out += &format!("  {}\n", a[i]);
+            i += 1;
+            j += 1;
+        } else if j < b.len() && (i == a.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            out += &format!("+ {}\n", b[j]);
+            j += 1;
+        } else {
+            out += &format!("- {}\n", a[i]);
+            i += 1;
+        }
+    }
+    out
+}
+
+fn check(program: &str, expected: &str, ops: u64, cycles: u64) {
+    run(program, false, expected, ops, cycles);
+}
+
+// the same run through the block cache has to be indistinguishable
+fn check_cached(program: &str, expected: &str, ops: u64, cycles: u64) {
+    run(program, true, expected, ops, cycles);
+}
+
+fn run(program: &str, cache: bool, expected: &str, ops: u64, cycles: u64) {
+    let image = loader::read(format!("data/{}", program)).unwrap();
+    let mut machine = SimpleMachine::new();
+    machine.set_block_cache(cache);
+    let mut console = Vec::new();
+    let counts = bdos::run(&mut machine, &image, &mut console).unwrap();
+
+    let output = String::from_utf8_lossy(&console);
+    assert!(
+        output == expected,
+        "{} output differs:\n{}",
+        program,
+        diff(expected, &output)
+    );
+    assert_eq!(counts, (ops, cycles), "{} op and cycle counts", program);
+}
+
+#[test]
+fn test_8080pre() {
+    check("8080PRE.COM", "8080 Preliminary tests complete", 1059, 7797);
+}
+
+#[test]
+fn test_tst8080() {
+    check(
+        "TST8080.COM",
+        "MICROCOSM ASSOCIATES 8080/8085 CPU DIAGNOSTIC\n VERSION 1.0  (C) 1980\n\n CPU IS OPERATIONAL",
+        648,
+        4899,
+    );
+}
+
+const CPUTEST: &str = "\nDIAGNOSTICS II V1.2 - CPU TEST\nCOPYRIGHT (C) 1981 - SUPERSOFT ASSOCIATES\n\n\
+                       ABCDEFGHIJKLMNOPQRSTUVWXYZ\nCPU IS 8080/8085\nBEGIN TIMING TEST\nEND TIMING TEST\n\
+                       CPU TESTS OK\n";
+
+#[test]
+fn test_cputest() {
+    check("CPUTEST.COM", CPUTEST, 33971128, 255663225);
+}
+
+#[test]
+fn test_cputest_block_cache() {
+    check_cached("CPUTEST.COM", CPUTEST, 33971128, 255663225);
+}
+
+const EXM: &str = "8080 instruction exerciser\n\
+         dad <b,d,h,sp>................  PASS! crc is:14474ba6\n\
+         aluop nn......................  PASS! crc is:9e922f9e\n\
+         aluop <b,c,d,e,h,l,m,a>.......  PASS! crc is:cf762c86\n\
+         <daa,cma,stc,cmc>.............  PASS! crc is:bb3f030c\n\
+         <inr,dcr> a...................  PASS! crc is:adb6460e\n\
+         <inr,dcr> b...................  PASS! crc is:83ed1345\n\
+         <inx,dcx> b...................  PASS! crc is:f79287cd\n\
+         <inr,dcr> c...................  PASS! crc is:e5f6721b\n\
+         <inr,dcr> d...................  PASS! crc is:15b5579a\n\
+         <inx,dcx> d...................  PASS! crc is:7f4e2501\n\
+         <inr,dcr> e...................  PASS! crc is:cf2ab396\n\
+         <inr,dcr> h...................  PASS! crc is:12b2952c\n\
+         <inx,dcx> h...................  PASS! crc is:9f2b23c0\n\
+         <inr,dcr> l...................  PASS! crc is:ff57d356\n\
+         <inr,dcr> m...................  PASS! crc is:92e963bd\n\
+         <inx,dcx> sp..................  PASS! crc is:d5702fab\n\
+         lhld nnnn.....................  PASS! crc is:a9c3d5cb\n\
+         shld nnnn.....................  PASS! crc is:e8864f26\n\
+         lxi <b,d,h,sp>,nnnn...........  PASS! crc is:fcf46e12\n\
+         ldax <b,d>....................  PASS! crc is:2b821d5f\n\
+         mvi <b,c,d,e,h,l,m,a>,nn......  PASS! crc is:eaa72044\n\
+         mov <bcdehla>,<bcdehla>.......  PASS! crc is:10b58cee\n\
+         sta nnnn / lda nnnn...........  PASS! crc is:ed57af72\n\
+         <rlc,rrc,ral,rar>.............  PASS! crc is:e0d89235\n\
+         stax <b,d>....................  PASS! crc is:2b0471e9\n\
+         Tests complete";
+
+// takes minutes in a debug build: cargo test --release -- --ignored
+#[test]
+#[ignore]
+fn test_8080exm() {
+    check("8080EXM.COM", EXM, 2919050420, 23837561269);
+}
+
+#[test]
+#[ignore]
+fn test_8080exm_block_cache() {
+    check_cached("8080EXM.COM", EXM, 2919050420, 23837561269);
+}
@@ -0,0 +1,41 @@
+use remu::fuzz::{self, Case, Rng};
+
+// minimized cases from past divergences, in the form `remu fuzz` prints
+const REGRESSIONS: &[&str] = &[
+    // a push with its two bytes swapped, through RST 6
+    "af=0002 bc=0000 de=0000 hl=0000 sp=F0B5 pc=D811 iff=1 fill=EDCA41B1BB1C2C54 steps=2 program=",
+    // the same through CM
+    "af=0002 bc=0000 de=0000 hl=337E sp=88AC pc=06DB iff=1 fill=B094C3E36A1D8C03 steps=25 program=35F1CBB8",
+];
+
+#[test]
+fn fuzz_regressions() {
+    for text in REGRESSIONS {
+        let case = Case::parse(text).expect("malformed regression case");
+        if let Some(divergence) = fuzz::run(&case) {
+            panic!("{}\n{}", text, divergence);
+        }
+    }
+}
+
+// FUZZ_ITERATIONS and FUZZ_SEED widen the search
+#[test]
+fn fuzz_random() {
+    let env = |name: &str, default: u64| {
+        std::env::var(name)
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(default)
+    };
+    let mut rng = Rng::new(env("FUZZ_SEED", 0x8080));
+    for _ in 0..env("FUZZ_ITERATIONS", 2000) {
+        let case = Case::random(&mut rng, 64);
+        if let Some(divergence) = fuzz::run(&case) {
+            panic!(
+                "{}\nminimized: {}",
+                divergence,
+                fuzz::minimize_divergence(&case)
+            );
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use remu::cpm::bdos;
+use remu::loader;
+use remu::machine::SimpleMachine;
+use remu::recompile;
+
+// regenerate with: remu recompile data/TST8080.COM tests/recompile/tst8080.rs
+#[path = "recompile/tst8080.rs"]
+#[rustfmt::skip]
+mod tst8080;
+
+#[test]
+fn test_translation_up_to_date() {
+    let image = loader::read("data/TST8080.COM").unwrap();
+    let program = recompile::analyze(&image, &[0x0100]);
+    assert!(
+        recompile::emit(&program, "TST8080.COM") == include_str!("recompile/tst8080.rs"),
+        "tests/recompile/tst8080.rs is stale"
+    );
+}
+
+#[test]
+fn test_tst8080() {
+    let image = loader::read("data/TST8080.COM").unwrap();
+    let mut machine = SimpleMachine::new();
+    machine.set_translation(Some(tst8080::step));
+    let mut console = Vec::new();
+    let (ops, cycles) = bdos::run(&mut machine, &image, &mut console).unwrap();
+
+    assert_eq!(
+        String::from_utf8_lossy(&console),
+        "MICROCOSM ASSOCIATES 8080/8085 CPU DIAGNOSTIC\n VERSION 1.0  (C) 1980\n\n CPU IS OPERATIONAL"
+    );
+    // blocks count as one op each, time is the same as interpreting
+    assert!(ops < 648);
+    assert_eq!(cycles, 4899);
+}
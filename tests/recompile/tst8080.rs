@@ -0,0 +1,5280 @@
+// Translated by `remu recompile` from TST8080.COM.
+#![allow(clippy::all)]
+
+use remu::bus::{Access, Bus};
+use remu::cpu::{Cpu, Cycles, State, arith};
+use remu::recompile::intact;
+
+pub fn step<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Cycles {
+    if cpu.state == State::Halted {
+        return cpu.step(bus);
+    }
+    let translated = match cpu.pc {
+        0x0100 => block_0100(cpu, bus),
+        0x014B => block_014b(cpu, bus),
+        0x014C => block_014c(cpu, bus),
+        0x0152 => block_0152(cpu, bus),
+        0x0154 => block_0154(cpu, bus),
+        0x0159 => block_0159(cpu, bus),
+        0x015A => block_015a(cpu, bus),
+        0x015B => block_015b(cpu, bus),
+        0x015E => block_015e(cpu, bus),
+        0x0162 => block_0162(cpu, bus),
+        0x0166 => block_0166(cpu, bus),
+        0x016A => block_016a(cpu, bus),
+        0x016E => block_016e(cpu, bus),
+        0x0175 => block_0175(cpu, bus),
+        0x0177 => block_0177(cpu, bus),
+        0x01B2 => block_01b2(cpu, bus),
+        0x01BB => block_01bb(cpu, bus),
+        0x01C0 => block_01c0(cpu, bus),
+        0x01C3 => block_01c3(cpu, bus),
+        0x01C6 => block_01c6(cpu, bus),
+        0x01C9 => block_01c9(cpu, bus),
+        0x01CC => block_01cc(cpu, bus),
+        0x01CF => block_01cf(cpu, bus),
+        0x01D2 => block_01d2(cpu, bus),
+        0x01D5 => block_01d5(cpu, bus),
+        0x01D8 => block_01d8(cpu, bus),
+        0x01DB => block_01db(cpu, bus),
+        0x01DE => block_01de(cpu, bus),
+        0x01E1 => block_01e1(cpu, bus),
+        0x01E4 => block_01e4(cpu, bus),
+        0x01E7 => block_01e7(cpu, bus),
+        0x01EC => block_01ec(cpu, bus),
+        0x01EF => block_01ef(cpu, bus),
+        0x01F2 => block_01f2(cpu, bus),
+        0x01F5 => block_01f5(cpu, bus),
+        0x01F8 => block_01f8(cpu, bus),
+        0x01FB => block_01fb(cpu, bus),
+        0x0200 => block_0200(cpu, bus),
+        0x0203 => block_0203(cpu, bus),
+        0x0206 => block_0206(cpu, bus),
+        0x0209 => block_0209(cpu, bus),
+        0x020C => block_020c(cpu, bus),
+        0x020F => block_020f(cpu, bus),
+        0x0214 => block_0214(cpu, bus),
+        0x0217 => block_0217(cpu, bus),
+        0x021A => block_021a(cpu, bus),
+        0x021D => block_021d(cpu, bus),
+        0x0220 => block_0220(cpu, bus),
+        0x0223 => block_0223(cpu, bus),
+        0x0228 => block_0228(cpu, bus),
+        0x022B => block_022b(cpu, bus),
+        0x022E => block_022e(cpu, bus),
+        0x0231 => block_0231(cpu, bus),
+        0x0234 => block_0234(cpu, bus),
+        0x0237 => block_0237(cpu, bus),
+        0x023C => block_023c(cpu, bus),
+        0x023F => block_023f(cpu, bus),
+        0x0244 => block_0244(cpu, bus),
+        0x0247 => block_0247(cpu, bus),
+        0x024C => block_024c(cpu, bus),
+        0x024F => block_024f(cpu, bus),
+        0x0252 => block_0252(cpu, bus),
+        0x025B => block_025b(cpu, bus),
+        0x025E => block_025e(cpu, bus),
+        0x0267 => block_0267(cpu, bus),
+        0x026A => block_026a(cpu, bus),
+        0x0273 => block_0273(cpu, bus),
+        0x0276 => block_0276(cpu, bus),
+        0x027D => block_027d(cpu, bus),
+        0x0280 => block_0280(cpu, bus),
+        0x0287 => block_0287(cpu, bus),
+        0x028A => block_028a(cpu, bus),
+        0x0291 => block_0291(cpu, bus),
+        0x0294 => block_0294(cpu, bus),
+        0x0299 => block_0299(cpu, bus),
+        0x029C => block_029c(cpu, bus),
+        0x029F => block_029f(cpu, bus),
+        0x02A2 => block_02a2(cpu, bus),
+        0x02A7 => block_02a7(cpu, bus),
+        0x02AA => block_02aa(cpu, bus),
+        0x02AF => block_02af(cpu, bus),
+        0x02B2 => block_02b2(cpu, bus),
+        0x02B5 => block_02b5(cpu, bus),
+        0x02B8 => block_02b8(cpu, bus),
+        0x02BD => block_02bd(cpu, bus),
+        0x02C0 => block_02c0(cpu, bus),
+        0x02C5 => block_02c5(cpu, bus),
+        0x02CA => block_02ca(cpu, bus),
+        0x02CD => block_02cd(cpu, bus),
+        0x02CE => block_02ce(cpu, bus),
+        0x02D3 => block_02d3(cpu, bus),
+        0x02D6 => block_02d6(cpu, bus),
+        0x02D9 => block_02d9(cpu, bus),
+        0x02DA => block_02da(cpu, bus),
+        0x02DF => block_02df(cpu, bus),
+        0x02E2 => block_02e2(cpu, bus),
+        0x02E5 => block_02e5(cpu, bus),
+        0x02E6 => block_02e6(cpu, bus),
+        0x02EB => block_02eb(cpu, bus),
+        0x02EE => block_02ee(cpu, bus),
+        0x02F1 => block_02f1(cpu, bus),
+        0x02F2 => block_02f2(cpu, bus),
+        0x02F7 => block_02f7(cpu, bus),
+        0x02FA => block_02fa(cpu, bus),
+        0x02FD => block_02fd(cpu, bus),
+        0x02FE => block_02fe(cpu, bus),
+        0x0303 => block_0303(cpu, bus),
+        0x0306 => block_0306(cpu, bus),
+        0x0309 => block_0309(cpu, bus),
+        0x030A => block_030a(cpu, bus),
+        0x030F => block_030f(cpu, bus),
+        0x0312 => block_0312(cpu, bus),
+        0x0315 => block_0315(cpu, bus),
+        0x0316 => block_0316(cpu, bus),
+        0x031B => block_031b(cpu, bus),
+        0x031E => block_031e(cpu, bus),
+        0x0321 => block_0321(cpu, bus),
+        0x0322 => block_0322(cpu, bus),
+        0x0327 => block_0327(cpu, bus),
+        0x032A => block_032a(cpu, bus),
+        0x0369 => block_0369(cpu, bus),
+        0x0382 => block_0382(cpu, bus),
+        0x038D => block_038d(cpu, bus),
+        0x0391 => block_0391(cpu, bus),
+        0x03BA => block_03ba(cpu, bus),
+        0x03D1 => block_03d1(cpu, bus),
+        0x03DA => block_03da(cpu, bus),
+        0x03F4 => block_03f4(cpu, bus),
+        0x040D => block_040d(cpu, bus),
+        0x041E => block_041e(cpu, bus),
+        0x0422 => block_0422(cpu, bus),
+        0x042F => block_042f(cpu, bus),
+        0x0438 => block_0438(cpu, bus),
+        0x0439 => block_0439(cpu, bus),
+        0x0442 => block_0442(cpu, bus),
+        0x0443 => block_0443(cpu, bus),
+        0x044C => block_044c(cpu, bus),
+        0x044D => block_044d(cpu, bus),
+        0x0456 => block_0456(cpu, bus),
+        0x0457 => block_0457(cpu, bus),
+        0x0462 => block_0462(cpu, bus),
+        0x0463 => block_0463(cpu, bus),
+        0x046E => block_046e(cpu, bus),
+        0x0475 => block_0475(cpu, bus),
+        0x0479 => block_0479(cpu, bus),
+        0x047F => block_047f(cpu, bus),
+        0x0486 => block_0486(cpu, bus),
+        0x048F => block_048f(cpu, bus),
+        0x0498 => block_0498(cpu, bus),
+        0x04A1 => block_04a1(cpu, bus),
+        0x04A5 => block_04a5(cpu, bus),
+        0x04AD => block_04ad(cpu, bus),
+        0x04B3 => block_04b3(cpu, bus),
+        0x04B5 => block_04b5(cpu, bus),
+        0x04B6 => block_04b6(cpu, bus),
+        0x04B7 => block_04b7(cpu, bus),
+        0x04BD => block_04bd(cpu, bus),
+        0x04CF => block_04cf(cpu, bus),
+        0x04D3 => block_04d3(cpu, bus),
+        0x04D7 => block_04d7(cpu, bus),
+        0x04DD => block_04dd(cpu, bus),
+        0x04E1 => block_04e1(cpu, bus),
+        0x04E5 => block_04e5(cpu, bus),
+        0x04EE => block_04ee(cpu, bus),
+        0x04F2 => block_04f2(cpu, bus),
+        0x04F6 => block_04f6(cpu, bus),
+        0x04FC => block_04fc(cpu, bus),
+        0x0500 => block_0500(cpu, bus),
+        0x0504 => block_0504(cpu, bus),
+        0x0507 => block_0507(cpu, bus),
+        0x0510 => block_0510(cpu, bus),
+        0x0516 => block_0516(cpu, bus),
+        0x0521 => block_0521(cpu, bus),
+        0x052C => block_052c(cpu, bus),
+        0x0531 => block_0531(cpu, bus),
+        0x053A => block_053a(cpu, bus),
+        0x053C => block_053c(cpu, bus),
+        0x0544 => block_0544(cpu, bus),
+        0x0549 => block_0549(cpu, bus),
+        0x0557 => block_0557(cpu, bus),
+        0x055D => block_055d(cpu, bus),
+        0x0560 => block_0560(cpu, bus),
+        0x0566 => block_0566(cpu, bus),
+        0x056E => block_056e(cpu, bus),
+        0x0578 => block_0578(cpu, bus),
+        0x057C => block_057c(cpu, bus),
+        0x0588 => block_0588(cpu, bus),
+        0x058C => block_058c(cpu, bus),
+        0x0592 => block_0592(cpu, bus),
+        0x059F => block_059f(cpu, bus),
+        0x05A3 => block_05a3(cpu, bus),
+        0x05A7 => block_05a7(cpu, bus),
+        0x05AB => block_05ab(cpu, bus),
+        0x05B3 => block_05b3(cpu, bus),
+        0x05BA => block_05ba(cpu, bus),
+        0x05C3 => block_05c3(cpu, bus),
+        0x05CA => block_05ca(cpu, bus),
+        0x05CF => block_05cf(cpu, bus),
+        0x05D6 => block_05d6(cpu, bus),
+        0x05D9 => block_05d9(cpu, bus),
+        0x05E0 => block_05e0(cpu, bus),
+        0x05E4 => block_05e4(cpu, bus),
+        0x05E9 => block_05e9(cpu, bus),
+        0x05ED => block_05ed(cpu, bus),
+        0x05F3 => block_05f3(cpu, bus),
+        0x05F8 => block_05f8(cpu, bus),
+        0x05FD => block_05fd(cpu, bus),
+        0x0602 => block_0602(cpu, bus),
+        0x0607 => block_0607(cpu, bus),
+        0x0612 => block_0612(cpu, bus),
+        0x0613 => block_0613(cpu, bus),
+        0x0614 => block_0614(cpu, bus),
+        0x0615 => block_0615(cpu, bus),
+        0x0629 => block_0629(cpu, bus),
+        0x062C => block_062c(cpu, bus),
+        0x062F => block_062f(cpu, bus),
+        0x0632 => block_0632(cpu, bus),
+        0x0638 => block_0638(cpu, bus),
+        0x063E => block_063e(cpu, bus),
+        0x0644 => block_0644(cpu, bus),
+        0x0648 => block_0648(cpu, bus),
+        0x064E => block_064e(cpu, bus),
+        0x0652 => block_0652(cpu, bus),
+        0x0659 => block_0659(cpu, bus),
+        0x0665 => block_0665(cpu, bus),
+        0x0669 => block_0669(cpu, bus),
+        0x066E => block_066e(cpu, bus),
+        0x0673 => block_0673(cpu, bus),
+        0x067D => block_067d(cpu, bus),
+        0x0685 => block_0685(cpu, bus),
+        0x068D => block_068d(cpu, bus),
+        0x0693 => block_0693(cpu, bus),
+        0x0698 => block_0698(cpu, bus),
+        0x06A0 => block_06a0(cpu, bus),
+        0x06A6 => block_06a6(cpu, bus),
+        0x06A8 => block_06a8(cpu, bus),
+        0x06AC => block_06ac(cpu, bus),
+        0x06B1 => block_06b1(cpu, bus),
+        _ => None,
+    };
+    translated.unwrap_or_else(|| cpu.step(bus))
+}
+
+fn block_0100<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0100, &[0xC3, 0xB2, 0x01]) {
+        return None;
+    }
+    // 0100: JMP 01B2H
+    cpu.pc = 0x01B2;
+    Some(10)
+}
+
+fn block_014b<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x014B, &[0xD5]) {
+        return None;
+    }
+    // 014B: PUSH D
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, cpu.de(), Access::Stack);
+    cpu.pc = 0x014C;
+    Some(11)
+}
+
+fn block_014c<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x014C, &[0xEB, 0x0E, 0x09, 0xCD, 0x05, 0x00]) {
+        return None;
+    }
+    // 014C: XCHG
+    let value = cpu.de();
+    cpu.set_de(cpu.hl());
+    cpu.set_hl(value);
+    // 014D: MVI C,09H
+    cpu.c = 0x09;
+    // 014F: CALL 0005H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x0152, Access::Stack);
+    cpu.pc = 0x0005;
+    Some(29)
+}
+
+fn block_0152<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0152, &[0xD1, 0xC9]) {
+        return None;
+    }
+    // 0152: POP D
+    let value = bus.read_word(cpu.sp, Access::Stack);
+    cpu.sp = cpu.sp.wrapping_add(2);
+    cpu.set_de(value);
+    // 0153: RET
+    cpu.pc = bus.read_word(cpu.sp, Access::Stack);
+    cpu.sp = cpu.sp.wrapping_add(2);
+    Some(20)
+}
+
+fn block_0154<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0154, &[0x0E, 0x02, 0xCD, 0x05, 0x00]) {
+        return None;
+    }
+    // 0154: MVI C,02H
+    cpu.c = 0x02;
+    // 0156: CALL 0005H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x0159, Access::Stack);
+    cpu.pc = 0x0005;
+    Some(24)
+}
+
+fn block_0159<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0159, &[0xC9]) {
+        return None;
+    }
+    // 0159: RET
+    cpu.pc = bus.read_word(cpu.sp, Access::Stack);
+    cpu.sp = cpu.sp.wrapping_add(2);
+    Some(10)
+}
+
+fn block_015a<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x015A, &[0xF5]) {
+        return None;
+    }
+    // 015A: PUSH PSW
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, cpu.af(), Access::Stack);
+    cpu.pc = 0x015B;
+    Some(11)
+}
+
+fn block_015b<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x015B, &[0xCD, 0x6A, 0x01]) {
+        return None;
+    }
+    // 015B: CALL 016AH
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x015E, Access::Stack);
+    cpu.pc = 0x016A;
+    Some(17)
+}
+
+fn block_015e<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x015E, &[0x5F, 0xCD, 0x54, 0x01]) {
+        return None;
+    }
+    // 015E: MOV E,A
+    cpu.e = cpu.a;
+    // 015F: CALL 0154H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x0162, Access::Stack);
+    cpu.pc = 0x0154;
+    Some(22)
+}
+
+fn block_0162<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0162, &[0xF1, 0xCD, 0x6E, 0x01]) {
+        return None;
+    }
+    // 0162: POP PSW
+    let value = bus.read_word(cpu.sp, Access::Stack);
+    cpu.sp = cpu.sp.wrapping_add(2);
+    cpu.set_af(value);
+    // 0163: CALL 016EH
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x0166, Access::Stack);
+    cpu.pc = 0x016E;
+    Some(27)
+}
+
+fn block_0166<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0166, &[0x5F, 0xC3, 0x54, 0x01]) {
+        return None;
+    }
+    // 0166: MOV E,A
+    cpu.e = cpu.a;
+    // 0167: JMP 0154H
+    cpu.pc = 0x0154;
+    Some(15)
+}
+
+fn block_016a<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x016A, &[0x0F, 0x0F, 0x0F, 0x0F]) {
+        return None;
+    }
+    // 016A: RRC
+    cpu.set_carry(cpu.a & 0x01 != 0);
+    cpu.a = cpu.a.rotate_right(1);
+    // 016B: RRC
+    cpu.set_carry(cpu.a & 0x01 != 0);
+    cpu.a = cpu.a.rotate_right(1);
+    // 016C: RRC
+    cpu.set_carry(cpu.a & 0x01 != 0);
+    cpu.a = cpu.a.rotate_right(1);
+    // 016D: RRC
+    cpu.set_carry(cpu.a & 0x01 != 0);
+    cpu.a = cpu.a.rotate_right(1);
+    cpu.pc = 0x016E;
+    Some(16)
+}
+
+fn block_016e<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x016E, &[0xE6, 0x0F, 0xFE, 0x0A, 0xFA, 0x77, 0x01]) {
+        return None;
+    }
+    // 016E: ANI 0FH
+    let value = 0x0F;
+    let result = cpu.a & value;
+    cpu.set_zsp(result);
+    cpu.set_carry(false);
+    cpu.set_aux_carry((cpu.a | value) & 0x08 != 0);
+    cpu.a = result;
+    // 0170: CPI 0AH
+    let (result, carry, aux_carry) = arith(cpu.a, 0x0A, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0172: JM 0177H
+    cpu.pc = if cpu.condition(7) { 0x0177 } else { 0x0175 };
+    Some(24)
+}
+
+fn block_0175<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0175, &[0xC6, 0x07]) {
+        return None;
+    }
+    // 0175: ADI 07H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x07, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    cpu.pc = 0x0177;
+    Some(7)
+}
+
+fn block_0177<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0177, &[0xC6, 0x30, 0xC9]) {
+        return None;
+    }
+    // 0177: ADI 30H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x30, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0179: RET
+    cpu.pc = bus.read_word(cpu.sp, Access::Stack);
+    cpu.sp = cpu.sp.wrapping_add(2);
+    Some(17)
+}
+
+fn block_01b2<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x01B2, &[0x31, 0xBD, 0x07, 0x21, 0x03, 0x01, 0xCD, 0x4B, 0x01]) {
+        return None;
+    }
+    // 01B2: LXI SP,07BDH
+    cpu.sp = 0x07BD;
+    // 01B5: LXI H,0103H
+    cpu.set_hl(0x0103);
+    // 01B8: CALL 014BH
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x01BB, Access::Stack);
+    cpu.pc = 0x014B;
+    Some(37)
+}
+
+fn block_01bb<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x01BB, &[0xE6, 0x00, 0xCA, 0xC3, 0x01]) {
+        return None;
+    }
+    // 01BB: ANI 00H
+    let value = 0x00;
+    let result = cpu.a & value;
+    cpu.set_zsp(result);
+    cpu.set_carry(false);
+    cpu.set_aux_carry((cpu.a | value) & 0x08 != 0);
+    cpu.a = result;
+    // 01BD: JZ 01C3H
+    cpu.pc = if cpu.condition(1) { 0x01C3 } else { 0x01C0 };
+    Some(17)
+}
+
+fn block_01c0<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x01C0, &[0xCD, 0xA0, 0x06]) {
+        return None;
+    }
+    // 01C0: CALL 06A0H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x01C3, Access::Stack);
+    cpu.pc = 0x06A0;
+    Some(17)
+}
+
+fn block_01c3<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x01C3, &[0xD2, 0xC9, 0x01]) {
+        return None;
+    }
+    // 01C3: JNC 01C9H
+    cpu.pc = if cpu.condition(2) { 0x01C9 } else { 0x01C6 };
+    Some(10)
+}
+
+fn block_01c6<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x01C6, &[0xCD, 0xA0, 0x06]) {
+        return None;
+    }
+    // 01C6: CALL 06A0H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x01C9, Access::Stack);
+    cpu.pc = 0x06A0;
+    Some(17)
+}
+
+fn block_01c9<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x01C9, &[0xEA, 0xCF, 0x01]) {
+        return None;
+    }
+    // 01C9: JPE 01CFH
+    cpu.pc = if cpu.condition(5) { 0x01CF } else { 0x01CC };
+    Some(10)
+}
+
+fn block_01cc<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x01CC, &[0xCD, 0xA0, 0x06]) {
+        return None;
+    }
+    // 01CC: CALL 06A0H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x01CF, Access::Stack);
+    cpu.pc = 0x06A0;
+    Some(17)
+}
+
+fn block_01cf<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x01CF, &[0xF2, 0xD5, 0x01]) {
+        return None;
+    }
+    // 01CF: JP 01D5H
+    cpu.pc = if cpu.condition(6) { 0x01D5 } else { 0x01D2 };
+    Some(10)
+}
+
+fn block_01d2<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x01D2, &[0xCD, 0xA0, 0x06]) {
+        return None;
+    }
+    // 01D2: CALL 06A0H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x01D5, Access::Stack);
+    cpu.pc = 0x06A0;
+    Some(17)
+}
+
+fn block_01d5<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x01D5, &[0xC2, 0xE4, 0x01]) {
+        return None;
+    }
+    // 01D5: JNZ 01E4H
+    cpu.pc = if cpu.condition(0) { 0x01E4 } else { 0x01D8 };
+    Some(10)
+}
+
+fn block_01d8<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x01D8, &[0xDA, 0xE4, 0x01]) {
+        return None;
+    }
+    // 01D8: JC 01E4H
+    cpu.pc = if cpu.condition(3) { 0x01E4 } else { 0x01DB };
+    Some(10)
+}
+
+fn block_01db<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x01DB, &[0xE2, 0xE4, 0x01]) {
+        return None;
+    }
+    // 01DB: JPO 01E4H
+    cpu.pc = if cpu.condition(4) { 0x01E4 } else { 0x01DE };
+    Some(10)
+}
+
+fn block_01de<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x01DE, &[0xFA, 0xE4, 0x01]) {
+        return None;
+    }
+    // 01DE: JM 01E4H
+    cpu.pc = if cpu.condition(7) { 0x01E4 } else { 0x01E1 };
+    Some(10)
+}
+
+fn block_01e1<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x01E1, &[0xC3, 0xE7, 0x01]) {
+        return None;
+    }
+    // 01E1: JMP 01E7H
+    cpu.pc = 0x01E7;
+    Some(10)
+}
+
+fn block_01e4<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x01E4, &[0xCD, 0xA0, 0x06]) {
+        return None;
+    }
+    // 01E4: CALL 06A0H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x01E7, Access::Stack);
+    cpu.pc = 0x06A0;
+    Some(17)
+}
+
+fn block_01e7<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x01E7, &[0xC6, 0x06, 0xC2, 0xEF, 0x01]) {
+        return None;
+    }
+    // 01E7: ADI 06H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x06, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 01E9: JNZ 01EFH
+    cpu.pc = if cpu.condition(0) { 0x01EF } else { 0x01EC };
+    Some(17)
+}
+
+fn block_01ec<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x01EC, &[0xCD, 0xA0, 0x06]) {
+        return None;
+    }
+    // 01EC: CALL 06A0H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x01EF, Access::Stack);
+    cpu.pc = 0x06A0;
+    Some(17)
+}
+
+fn block_01ef<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x01EF, &[0xDA, 0xF8, 0x01]) {
+        return None;
+    }
+    // 01EF: JC 01F8H
+    cpu.pc = if cpu.condition(3) { 0x01F8 } else { 0x01F2 };
+    Some(10)
+}
+
+fn block_01f2<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x01F2, &[0xE2, 0xF8, 0x01]) {
+        return None;
+    }
+    // 01F2: JPO 01F8H
+    cpu.pc = if cpu.condition(4) { 0x01F8 } else { 0x01F5 };
+    Some(10)
+}
+
+fn block_01f5<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x01F5, &[0xF2, 0xFB, 0x01]) {
+        return None;
+    }
+    // 01F5: JP 01FBH
+    cpu.pc = if cpu.condition(6) { 0x01FB } else { 0x01F8 };
+    Some(10)
+}
+
+fn block_01f8<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x01F8, &[0xCD, 0xA0, 0x06]) {
+        return None;
+    }
+    // 01F8: CALL 06A0H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x01FB, Access::Stack);
+    cpu.pc = 0x06A0;
+    Some(17)
+}
+
+fn block_01fb<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x01FB, &[0xC6, 0x70, 0xE2, 0x03, 0x02]) {
+        return None;
+    }
+    // 01FB: ADI 70H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x70, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 01FD: JPO 0203H
+    cpu.pc = if cpu.condition(4) { 0x0203 } else { 0x0200 };
+    Some(17)
+}
+
+fn block_0200<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0200, &[0xCD, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0200: CALL 06A0H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x0203, Access::Stack);
+    cpu.pc = 0x06A0;
+    Some(17)
+}
+
+fn block_0203<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0203, &[0xFA, 0x0C, 0x02]) {
+        return None;
+    }
+    // 0203: JM 020CH
+    cpu.pc = if cpu.condition(7) { 0x020C } else { 0x0206 };
+    Some(10)
+}
+
+fn block_0206<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0206, &[0xCA, 0x0C, 0x02]) {
+        return None;
+    }
+    // 0206: JZ 020CH
+    cpu.pc = if cpu.condition(1) { 0x020C } else { 0x0209 };
+    Some(10)
+}
+
+fn block_0209<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0209, &[0xD2, 0x0F, 0x02]) {
+        return None;
+    }
+    // 0209: JNC 020FH
+    cpu.pc = if cpu.condition(2) { 0x020F } else { 0x020C };
+    Some(10)
+}
+
+fn block_020c<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x020C, &[0xCD, 0xA0, 0x06]) {
+        return None;
+    }
+    // 020C: CALL 06A0H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x020F, Access::Stack);
+    cpu.pc = 0x06A0;
+    Some(17)
+}
+
+fn block_020f<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x020F, &[0xC6, 0x81, 0xFA, 0x17, 0x02]) {
+        return None;
+    }
+    // 020F: ADI 81H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x81, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0211: JM 0217H
+    cpu.pc = if cpu.condition(7) { 0x0217 } else { 0x0214 };
+    Some(17)
+}
+
+fn block_0214<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0214, &[0xCD, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0214: CALL 06A0H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x0217, Access::Stack);
+    cpu.pc = 0x06A0;
+    Some(17)
+}
+
+fn block_0217<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0217, &[0xCA, 0x20, 0x02]) {
+        return None;
+    }
+    // 0217: JZ 0220H
+    cpu.pc = if cpu.condition(1) { 0x0220 } else { 0x021A };
+    Some(10)
+}
+
+fn block_021a<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x021A, &[0xDA, 0x20, 0x02]) {
+        return None;
+    }
+    // 021A: JC 0220H
+    cpu.pc = if cpu.condition(3) { 0x0220 } else { 0x021D };
+    Some(10)
+}
+
+fn block_021d<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x021D, &[0xE2, 0x23, 0x02]) {
+        return None;
+    }
+    // 021D: JPO 0223H
+    cpu.pc = if cpu.condition(4) { 0x0223 } else { 0x0220 };
+    Some(10)
+}
+
+fn block_0220<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0220, &[0xCD, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0220: CALL 06A0H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x0223, Access::Stack);
+    cpu.pc = 0x06A0;
+    Some(17)
+}
+
+fn block_0223<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0223, &[0xC6, 0xFE, 0xDA, 0x2B, 0x02]) {
+        return None;
+    }
+    // 0223: ADI FEH
+    let (result, carry, aux_carry) = arith(cpu.a, 0xFE, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0225: JC 022BH
+    cpu.pc = if cpu.condition(3) { 0x022B } else { 0x0228 };
+    Some(17)
+}
+
+fn block_0228<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0228, &[0xCD, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0228: CALL 06A0H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x022B, Access::Stack);
+    cpu.pc = 0x06A0;
+    Some(17)
+}
+
+fn block_022b<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x022B, &[0xCA, 0x34, 0x02]) {
+        return None;
+    }
+    // 022B: JZ 0234H
+    cpu.pc = if cpu.condition(1) { 0x0234 } else { 0x022E };
+    Some(10)
+}
+
+fn block_022e<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x022E, &[0xE2, 0x34, 0x02]) {
+        return None;
+    }
+    // 022E: JPO 0234H
+    cpu.pc = if cpu.condition(4) { 0x0234 } else { 0x0231 };
+    Some(10)
+}
+
+fn block_0231<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0231, &[0xFA, 0x37, 0x02]) {
+        return None;
+    }
+    // 0231: JM 0237H
+    cpu.pc = if cpu.condition(7) { 0x0237 } else { 0x0234 };
+    Some(10)
+}
+
+fn block_0234<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0234, &[0xCD, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0234: CALL 06A0H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x0237, Access::Stack);
+    cpu.pc = 0x06A0;
+    Some(17)
+}
+
+fn block_0237<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0237, &[0xFE, 0x00, 0xDA, 0x4F, 0x02]) {
+        return None;
+    }
+    // 0237: CPI 00H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x00, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0239: JC 024FH
+    cpu.pc = if cpu.condition(3) { 0x024F } else { 0x023C };
+    Some(17)
+}
+
+fn block_023c<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x023C, &[0xCA, 0x4F, 0x02]) {
+        return None;
+    }
+    // 023C: JZ 024FH
+    cpu.pc = if cpu.condition(1) { 0x024F } else { 0x023F };
+    Some(10)
+}
+
+fn block_023f<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x023F, &[0xFE, 0xF5, 0xDA, 0x4F, 0x02]) {
+        return None;
+    }
+    // 023F: CPI F5H
+    let (result, carry, aux_carry) = arith(cpu.a, 0xF5, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0241: JC 024FH
+    cpu.pc = if cpu.condition(3) { 0x024F } else { 0x0244 };
+    Some(17)
+}
+
+fn block_0244<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0244, &[0xC2, 0x4F, 0x02]) {
+        return None;
+    }
+    // 0244: JNZ 024FH
+    cpu.pc = if cpu.condition(0) { 0x024F } else { 0x0247 };
+    Some(10)
+}
+
+fn block_0247<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0247, &[0xFE, 0xFF, 0xCA, 0x4F, 0x02]) {
+        return None;
+    }
+    // 0247: CPI FFH
+    let (result, carry, aux_carry) = arith(cpu.a, 0xFF, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0249: JZ 024FH
+    cpu.pc = if cpu.condition(1) { 0x024F } else { 0x024C };
+    Some(17)
+}
+
+fn block_024c<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x024C, &[0xDA, 0x52, 0x02]) {
+        return None;
+    }
+    // 024C: JC 0252H
+    cpu.pc = if cpu.condition(3) { 0x0252 } else { 0x024F };
+    Some(10)
+}
+
+fn block_024f<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x024F, &[0xCD, 0xA0, 0x06]) {
+        return None;
+    }
+    // 024F: CALL 06A0H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x0252, Access::Stack);
+    cpu.pc = 0x06A0;
+    Some(17)
+}
+
+fn block_0252<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0252, &[0xCE, 0x0A, 0xCE, 0x0A, 0xFE, 0x0B, 0xCA, 0x5E, 0x02]) {
+        return None;
+    }
+    // 0252: ACI 0AH
+    let (result, carry, aux_carry) = arith(cpu.a, 0x0A, cpu.carry(), false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0254: ACI 0AH
+    let (result, carry, aux_carry) = arith(cpu.a, 0x0A, cpu.carry(), false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0256: CPI 0BH
+    let (result, carry, aux_carry) = arith(cpu.a, 0x0B, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0258: JZ 025EH
+    cpu.pc = if cpu.condition(1) { 0x025E } else { 0x025B };
+    Some(31)
+}
+
+fn block_025b<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x025B, &[0xCD, 0xA0, 0x06]) {
+        return None;
+    }
+    // 025B: CALL 06A0H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x025E, Access::Stack);
+    cpu.pc = 0x06A0;
+    Some(17)
+}
+
+fn block_025e<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x025E, &[0xD6, 0x0C, 0xD6, 0x0F, 0xFE, 0xF0, 0xCA, 0x6A, 0x02]) {
+        return None;
+    }
+    // 025E: SUI 0CH
+    let (result, carry, aux_carry) = arith(cpu.a, 0x0C, false, true);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0260: SUI 0FH
+    let (result, carry, aux_carry) = arith(cpu.a, 0x0F, false, true);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0262: CPI F0H
+    let (result, carry, aux_carry) = arith(cpu.a, 0xF0, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0264: JZ 026AH
+    cpu.pc = if cpu.condition(1) { 0x026A } else { 0x0267 };
+    Some(31)
+}
+
+fn block_0267<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0267, &[0xCD, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0267: CALL 06A0H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x026A, Access::Stack);
+    cpu.pc = 0x06A0;
+    Some(17)
+}
+
+fn block_026a<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x026A, &[0xDE, 0xF1, 0xDE, 0x0E, 0xFE, 0xF0, 0xCA, 0x76, 0x02]) {
+        return None;
+    }
+    // 026A: SBI F1H
+    let (result, carry, aux_carry) = arith(cpu.a, 0xF1, cpu.carry(), true);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 026C: SBI 0EH
+    let (result, carry, aux_carry) = arith(cpu.a, 0x0E, cpu.carry(), true);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 026E: CPI F0H
+    let (result, carry, aux_carry) = arith(cpu.a, 0xF0, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0270: JZ 0276H
+    cpu.pc = if cpu.condition(1) { 0x0276 } else { 0x0273 };
+    Some(31)
+}
+
+fn block_0273<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0273, &[0xCD, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0273: CALL 06A0H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x0276, Access::Stack);
+    cpu.pc = 0x06A0;
+    Some(17)
+}
+
+fn block_0276<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0276, &[0xE6, 0x55, 0xFE, 0x50, 0xCA, 0x80, 0x02]) {
+        return None;
+    }
+    // 0276: ANI 55H
+    let value = 0x55;
+    let result = cpu.a & value;
+    cpu.set_zsp(result);
+    cpu.set_carry(false);
+    cpu.set_aux_carry((cpu.a | value) & 0x08 != 0);
+    cpu.a = result;
+    // 0278: CPI 50H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x50, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 027A: JZ 0280H
+    cpu.pc = if cpu.condition(1) { 0x0280 } else { 0x027D };
+    Some(24)
+}
+
+fn block_027d<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x027D, &[0xCD, 0xA0, 0x06]) {
+        return None;
+    }
+    // 027D: CALL 06A0H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x0280, Access::Stack);
+    cpu.pc = 0x06A0;
+    Some(17)
+}
+
+fn block_0280<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0280, &[0xF6, 0x3A, 0xFE, 0x7A, 0xCA, 0x8A, 0x02]) {
+        return None;
+    }
+    // 0280: ORI 3AH
+    cpu.a |= 0x3A;
+    cpu.set_zsp(cpu.a);
+    cpu.set_carry(false);
+    cpu.set_aux_carry(false);
+    // 0282: CPI 7AH
+    let (result, carry, aux_carry) = arith(cpu.a, 0x7A, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0284: JZ 028AH
+    cpu.pc = if cpu.condition(1) { 0x028A } else { 0x0287 };
+    Some(24)
+}
+
+fn block_0287<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0287, &[0xCD, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0287: CALL 06A0H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x028A, Access::Stack);
+    cpu.pc = 0x06A0;
+    Some(17)
+}
+
+fn block_028a<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x028A, &[0xEE, 0x0F, 0xFE, 0x75, 0xCA, 0x94, 0x02]) {
+        return None;
+    }
+    // 028A: XRI 0FH
+    cpu.a ^= 0x0F;
+    cpu.set_zsp(cpu.a);
+    cpu.set_carry(false);
+    cpu.set_aux_carry(false);
+    // 028C: CPI 75H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x75, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 028E: JZ 0294H
+    cpu.pc = if cpu.condition(1) { 0x0294 } else { 0x0291 };
+    Some(24)
+}
+
+fn block_0291<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0291, &[0xCD, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0291: CALL 06A0H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x0294, Access::Stack);
+    cpu.pc = 0x06A0;
+    Some(17)
+}
+
+fn block_0294<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0294, &[0xE6, 0x00, 0xDC, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0294: ANI 00H
+    let value = 0x00;
+    let result = cpu.a & value;
+    cpu.set_zsp(result);
+    cpu.set_carry(false);
+    cpu.set_aux_carry((cpu.a | value) & 0x08 != 0);
+    cpu.a = result;
+    // 0296: CC 06A0H
+    let cycles = if cpu.condition(3) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x0299, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x0299;
+        11
+    };
+    Some(7 + cycles)
+}
+
+fn block_0299<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0299, &[0xE4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0299: CPO 06A0H
+    let cycles = if cpu.condition(4) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x029C, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x029C;
+        11
+    };
+    Some(cycles)
+}
+
+fn block_029c<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x029C, &[0xFC, 0xA0, 0x06]) {
+        return None;
+    }
+    // 029C: CM 06A0H
+    let cycles = if cpu.condition(7) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x029F, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x029F;
+        11
+    };
+    Some(cycles)
+}
+
+fn block_029f<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x029F, &[0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 029F: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x02A2, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x02A2;
+        11
+    };
+    Some(cycles)
+}
+
+fn block_02a2<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x02A2, &[0xFE, 0x00, 0xCA, 0xAA, 0x02]) {
+        return None;
+    }
+    // 02A2: CPI 00H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x00, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 02A4: JZ 02AAH
+    cpu.pc = if cpu.condition(1) { 0x02AA } else { 0x02A7 };
+    Some(17)
+}
+
+fn block_02a7<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x02A7, &[0xCD, 0xA0, 0x06]) {
+        return None;
+    }
+    // 02A7: CALL 06A0H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x02AA, Access::Stack);
+    cpu.pc = 0x06A0;
+    Some(17)
+}
+
+fn block_02aa<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x02AA, &[0xD6, 0x77, 0xD4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 02AA: SUI 77H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x77, false, true);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 02AC: CNC 06A0H
+    let cycles = if cpu.condition(2) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x02AF, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x02AF;
+        11
+    };
+    Some(7 + cycles)
+}
+
+fn block_02af<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x02AF, &[0xEC, 0xA0, 0x06]) {
+        return None;
+    }
+    // 02AF: CPE 06A0H
+    let cycles = if cpu.condition(5) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x02B2, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x02B2;
+        11
+    };
+    Some(cycles)
+}
+
+fn block_02b2<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x02B2, &[0xF4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 02B2: CP 06A0H
+    let cycles = if cpu.condition(6) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x02B5, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x02B5;
+        11
+    };
+    Some(cycles)
+}
+
+fn block_02b5<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x02B5, &[0xCC, 0xA0, 0x06]) {
+        return None;
+    }
+    // 02B5: CZ 06A0H
+    let cycles = if cpu.condition(1) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x02B8, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x02B8;
+        11
+    };
+    Some(cycles)
+}
+
+fn block_02b8<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x02B8, &[0xFE, 0x89, 0xCA, 0xC0, 0x02]) {
+        return None;
+    }
+    // 02B8: CPI 89H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x89, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 02BA: JZ 02C0H
+    cpu.pc = if cpu.condition(1) { 0x02C0 } else { 0x02BD };
+    Some(17)
+}
+
+fn block_02bd<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x02BD, &[0xCD, 0xA0, 0x06]) {
+        return None;
+    }
+    // 02BD: CALL 06A0H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x02C0, Access::Stack);
+    cpu.pc = 0x06A0;
+    Some(17)
+}
+
+fn block_02c0<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x02C0, &[0xE6, 0xFF, 0xE4, 0xCD, 0x02]) {
+        return None;
+    }
+    // 02C0: ANI FFH
+    let value = 0xFF;
+    let result = cpu.a & value;
+    cpu.set_zsp(result);
+    cpu.set_carry(false);
+    cpu.set_aux_carry((cpu.a | value) & 0x08 != 0);
+    cpu.a = result;
+    // 02C2: CPO 02CDH
+    let cycles = if cpu.condition(4) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x02C5, Access::Stack);
+        cpu.pc = 0x02CD;
+        17
+    } else {
+        cpu.pc = 0x02C5;
+        11
+    };
+    Some(7 + cycles)
+}
+
+fn block_02c5<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x02C5, &[0xFE, 0xD9, 0xCA, 0x2A, 0x03]) {
+        return None;
+    }
+    // 02C5: CPI D9H
+    let (result, carry, aux_carry) = arith(cpu.a, 0xD9, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 02C7: JZ 032AH
+    cpu.pc = if cpu.condition(1) { 0x032A } else { 0x02CA };
+    Some(17)
+}
+
+fn block_02ca<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x02CA, &[0xCD, 0xA0, 0x06]) {
+        return None;
+    }
+    // 02CA: CALL 06A0H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x02CD, Access::Stack);
+    cpu.pc = 0x06A0;
+    Some(17)
+}
+
+fn block_02cd<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x02CD, &[0xE8]) {
+        return None;
+    }
+    // 02CD: RPE
+    let cycles = if cpu.condition(5) {
+        cpu.pc = bus.read_word(cpu.sp, Access::Stack);
+        cpu.sp = cpu.sp.wrapping_add(2);
+        11
+    } else {
+        cpu.pc = 0x02CE;
+        5
+    };
+    Some(cycles)
+}
+
+fn block_02ce<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x02CE, &[0xC6, 0x10, 0xEC, 0xD9, 0x02]) {
+        return None;
+    }
+    // 02CE: ADI 10H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x10, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 02D0: CPE 02D9H
+    let cycles = if cpu.condition(5) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x02D3, Access::Stack);
+        cpu.pc = 0x02D9;
+        17
+    } else {
+        cpu.pc = 0x02D3;
+        11
+    };
+    Some(7 + cycles)
+}
+
+fn block_02d3<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x02D3, &[0xC6, 0x02, 0xE0]) {
+        return None;
+    }
+    // 02D3: ADI 02H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x02, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 02D5: RPO
+    let cycles = if cpu.condition(4) {
+        cpu.pc = bus.read_word(cpu.sp, Access::Stack);
+        cpu.sp = cpu.sp.wrapping_add(2);
+        11
+    } else {
+        cpu.pc = 0x02D6;
+        5
+    };
+    Some(7 + cycles)
+}
+
+fn block_02d6<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x02D6, &[0xCD, 0xA0, 0x06]) {
+        return None;
+    }
+    // 02D6: CALL 06A0H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x02D9, Access::Stack);
+    cpu.pc = 0x06A0;
+    Some(17)
+}
+
+fn block_02d9<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x02D9, &[0xE0]) {
+        return None;
+    }
+    // 02D9: RPO
+    let cycles = if cpu.condition(4) {
+        cpu.pc = bus.read_word(cpu.sp, Access::Stack);
+        cpu.sp = cpu.sp.wrapping_add(2);
+        11
+    } else {
+        cpu.pc = 0x02DA;
+        5
+    };
+    Some(cycles)
+}
+
+fn block_02da<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x02DA, &[0xC6, 0x20, 0xFC, 0xE5, 0x02]) {
+        return None;
+    }
+    // 02DA: ADI 20H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x20, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 02DC: CM 02E5H
+    let cycles = if cpu.condition(7) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x02DF, Access::Stack);
+        cpu.pc = 0x02E5;
+        17
+    } else {
+        cpu.pc = 0x02DF;
+        11
+    };
+    Some(7 + cycles)
+}
+
+fn block_02df<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x02DF, &[0xC6, 0x04, 0xE8]) {
+        return None;
+    }
+    // 02DF: ADI 04H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x04, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 02E1: RPE
+    let cycles = if cpu.condition(5) {
+        cpu.pc = bus.read_word(cpu.sp, Access::Stack);
+        cpu.sp = cpu.sp.wrapping_add(2);
+        11
+    } else {
+        cpu.pc = 0x02E2;
+        5
+    };
+    Some(7 + cycles)
+}
+
+fn block_02e2<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x02E2, &[0xCD, 0xA0, 0x06]) {
+        return None;
+    }
+    // 02E2: CALL 06A0H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x02E5, Access::Stack);
+    cpu.pc = 0x06A0;
+    Some(17)
+}
+
+fn block_02e5<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x02E5, &[0xF0]) {
+        return None;
+    }
+    // 02E5: RP
+    let cycles = if cpu.condition(6) {
+        cpu.pc = bus.read_word(cpu.sp, Access::Stack);
+        cpu.sp = cpu.sp.wrapping_add(2);
+        11
+    } else {
+        cpu.pc = 0x02E6;
+        5
+    };
+    Some(cycles)
+}
+
+fn block_02e6<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x02E6, &[0xC6, 0x80, 0xF4, 0xF1, 0x02]) {
+        return None;
+    }
+    // 02E6: ADI 80H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x80, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 02E8: CP 02F1H
+    let cycles = if cpu.condition(6) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x02EB, Access::Stack);
+        cpu.pc = 0x02F1;
+        17
+    } else {
+        cpu.pc = 0x02EB;
+        11
+    };
+    Some(7 + cycles)
+}
+
+fn block_02eb<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x02EB, &[0xC6, 0x80, 0xF8]) {
+        return None;
+    }
+    // 02EB: ADI 80H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x80, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 02ED: RM
+    let cycles = if cpu.condition(7) {
+        cpu.pc = bus.read_word(cpu.sp, Access::Stack);
+        cpu.sp = cpu.sp.wrapping_add(2);
+        11
+    } else {
+        cpu.pc = 0x02EE;
+        5
+    };
+    Some(7 + cycles)
+}
+
+fn block_02ee<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x02EE, &[0xCD, 0xA0, 0x06]) {
+        return None;
+    }
+    // 02EE: CALL 06A0H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x02F1, Access::Stack);
+    cpu.pc = 0x06A0;
+    Some(17)
+}
+
+fn block_02f1<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x02F1, &[0xF8]) {
+        return None;
+    }
+    // 02F1: RM
+    let cycles = if cpu.condition(7) {
+        cpu.pc = bus.read_word(cpu.sp, Access::Stack);
+        cpu.sp = cpu.sp.wrapping_add(2);
+        11
+    } else {
+        cpu.pc = 0x02F2;
+        5
+    };
+    Some(cycles)
+}
+
+fn block_02f2<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x02F2, &[0xC6, 0x40, 0xD4, 0xFD, 0x02]) {
+        return None;
+    }
+    // 02F2: ADI 40H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x40, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 02F4: CNC 02FDH
+    let cycles = if cpu.condition(2) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x02F7, Access::Stack);
+        cpu.pc = 0x02FD;
+        17
+    } else {
+        cpu.pc = 0x02F7;
+        11
+    };
+    Some(7 + cycles)
+}
+
+fn block_02f7<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x02F7, &[0xC6, 0x40, 0xF0]) {
+        return None;
+    }
+    // 02F7: ADI 40H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x40, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 02F9: RP
+    let cycles = if cpu.condition(6) {
+        cpu.pc = bus.read_word(cpu.sp, Access::Stack);
+        cpu.sp = cpu.sp.wrapping_add(2);
+        11
+    } else {
+        cpu.pc = 0x02FA;
+        5
+    };
+    Some(7 + cycles)
+}
+
+fn block_02fa<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x02FA, &[0xCD, 0xA0, 0x06]) {
+        return None;
+    }
+    // 02FA: CALL 06A0H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x02FD, Access::Stack);
+    cpu.pc = 0x06A0;
+    Some(17)
+}
+
+fn block_02fd<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x02FD, &[0xD8]) {
+        return None;
+    }
+    // 02FD: RC
+    let cycles = if cpu.condition(3) {
+        cpu.pc = bus.read_word(cpu.sp, Access::Stack);
+        cpu.sp = cpu.sp.wrapping_add(2);
+        11
+    } else {
+        cpu.pc = 0x02FE;
+        5
+    };
+    Some(cycles)
+}
+
+fn block_02fe<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x02FE, &[0xC6, 0x8F, 0xDC, 0x09, 0x03]) {
+        return None;
+    }
+    // 02FE: ADI 8FH
+    let (result, carry, aux_carry) = arith(cpu.a, 0x8F, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0300: CC 0309H
+    let cycles = if cpu.condition(3) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x0303, Access::Stack);
+        cpu.pc = 0x0309;
+        17
+    } else {
+        cpu.pc = 0x0303;
+        11
+    };
+    Some(7 + cycles)
+}
+
+fn block_0303<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0303, &[0xD6, 0x02, 0xD0]) {
+        return None;
+    }
+    // 0303: SUI 02H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x02, false, true);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0305: RNC
+    let cycles = if cpu.condition(2) {
+        cpu.pc = bus.read_word(cpu.sp, Access::Stack);
+        cpu.sp = cpu.sp.wrapping_add(2);
+        11
+    } else {
+        cpu.pc = 0x0306;
+        5
+    };
+    Some(7 + cycles)
+}
+
+fn block_0306<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0306, &[0xCD, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0306: CALL 06A0H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x0309, Access::Stack);
+    cpu.pc = 0x06A0;
+    Some(17)
+}
+
+fn block_0309<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0309, &[0xD0]) {
+        return None;
+    }
+    // 0309: RNC
+    let cycles = if cpu.condition(2) {
+        cpu.pc = bus.read_word(cpu.sp, Access::Stack);
+        cpu.sp = cpu.sp.wrapping_add(2);
+        11
+    } else {
+        cpu.pc = 0x030A;
+        5
+    };
+    Some(cycles)
+}
+
+fn block_030a<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x030A, &[0xC6, 0xF7, 0xC4, 0x15, 0x03]) {
+        return None;
+    }
+    // 030A: ADI F7H
+    let (result, carry, aux_carry) = arith(cpu.a, 0xF7, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 030C: CNZ 0315H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x030F, Access::Stack);
+        cpu.pc = 0x0315;
+        17
+    } else {
+        cpu.pc = 0x030F;
+        11
+    };
+    Some(7 + cycles)
+}
+
+fn block_030f<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x030F, &[0xC6, 0xFE, 0xD8]) {
+        return None;
+    }
+    // 030F: ADI FEH
+    let (result, carry, aux_carry) = arith(cpu.a, 0xFE, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0311: RC
+    let cycles = if cpu.condition(3) {
+        cpu.pc = bus.read_word(cpu.sp, Access::Stack);
+        cpu.sp = cpu.sp.wrapping_add(2);
+        11
+    } else {
+        cpu.pc = 0x0312;
+        5
+    };
+    Some(7 + cycles)
+}
+
+fn block_0312<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0312, &[0xCD, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0312: CALL 06A0H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x0315, Access::Stack);
+    cpu.pc = 0x06A0;
+    Some(17)
+}
+
+fn block_0315<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0315, &[0xC8]) {
+        return None;
+    }
+    // 0315: RZ
+    let cycles = if cpu.condition(1) {
+        cpu.pc = bus.read_word(cpu.sp, Access::Stack);
+        cpu.sp = cpu.sp.wrapping_add(2);
+        11
+    } else {
+        cpu.pc = 0x0316;
+        5
+    };
+    Some(cycles)
+}
+
+fn block_0316<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0316, &[0xC6, 0x01, 0xCC, 0x21, 0x03]) {
+        return None;
+    }
+    // 0316: ADI 01H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x01, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0318: CZ 0321H
+    let cycles = if cpu.condition(1) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x031B, Access::Stack);
+        cpu.pc = 0x0321;
+        17
+    } else {
+        cpu.pc = 0x031B;
+        11
+    };
+    Some(7 + cycles)
+}
+
+fn block_031b<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x031B, &[0xC6, 0xD0, 0xC0]) {
+        return None;
+    }
+    // 031B: ADI D0H
+    let (result, carry, aux_carry) = arith(cpu.a, 0xD0, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 031D: RNZ
+    let cycles = if cpu.condition(0) {
+        cpu.pc = bus.read_word(cpu.sp, Access::Stack);
+        cpu.sp = cpu.sp.wrapping_add(2);
+        11
+    } else {
+        cpu.pc = 0x031E;
+        5
+    };
+    Some(7 + cycles)
+}
+
+fn block_031e<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x031E, &[0xCD, 0xA0, 0x06]) {
+        return None;
+    }
+    // 031E: CALL 06A0H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x0321, Access::Stack);
+    cpu.pc = 0x06A0;
+    Some(17)
+}
+
+fn block_0321<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0321, &[0xC0]) {
+        return None;
+    }
+    // 0321: RNZ
+    let cycles = if cpu.condition(0) {
+        cpu.pc = bus.read_word(cpu.sp, Access::Stack);
+        cpu.sp = cpu.sp.wrapping_add(2);
+        11
+    } else {
+        cpu.pc = 0x0322;
+        5
+    };
+    Some(cycles)
+}
+
+fn block_0322<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0322, &[0xC6, 0x47, 0xFE, 0x47, 0xC8]) {
+        return None;
+    }
+    // 0322: ADI 47H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x47, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0324: CPI 47H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x47, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0326: RZ
+    let cycles = if cpu.condition(1) {
+        cpu.pc = bus.read_word(cpu.sp, Access::Stack);
+        cpu.sp = cpu.sp.wrapping_add(2);
+        11
+    } else {
+        cpu.pc = 0x0327;
+        5
+    };
+    Some(14 + cycles)
+}
+
+fn block_0327<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0327, &[0xCD, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0327: CALL 06A0H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x032A, Access::Stack);
+    cpu.pc = 0x06A0;
+    Some(17)
+}
+
+fn block_032a<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x032A, &[
+        0x3E, 0x77, 0x3C, 0x47, 0x04, 0x48, 0x0D, 0x51, 0x5A, 0x63, 0x6C, 0x7D, 0x3D, 0x4F, 0x59, 0x6B,
+        0x45, 0x50, 0x62, 0x7C, 0x57, 0x14, 0x6A, 0x4D, 0x0C, 0x61, 0x44, 0x05, 0x58, 0x7B, 0x5F, 0x1C,
+        0x43, 0x60, 0x24, 0x4C, 0x69, 0x55, 0x15, 0x7A, 0x67, 0x25, 0x54, 0x42, 0x68, 0x2C, 0x5D, 0x1D,
+        0x4B, 0x79, 0x6F, 0x2D, 0x65, 0x5C, 0x53, 0x4A, 0x41, 0x78, 0xFE, 0x77, 0xC4, 0xA0, 0x06,
+    ]) {
+        return None;
+    }
+    // 032A: MVI A,77H
+    cpu.a = 0x77;
+    // 032C: INR A
+    let value = cpu.a;
+    let result = value.wrapping_add(1);
+    cpu.set_zsp(result);
+    cpu.set_aux_carry(value & 0x0F == 0x0F);
+    cpu.a = result;
+    // 032D: MOV B,A
+    cpu.b = cpu.a;
+    // 032E: INR B
+    let value = cpu.b;
+    let result = value.wrapping_add(1);
+    cpu.set_zsp(result);
+    cpu.set_aux_carry(value & 0x0F == 0x0F);
+    cpu.b = result;
+    // 032F: MOV C,B
+    cpu.c = cpu.b;
+    // 0330: DCR C
+    let value = cpu.c;
+    let result = value.wrapping_sub(1);
+    cpu.set_zsp(result);
+    cpu.set_aux_carry(result & 0x0F != 0x0F);
+    cpu.c = result;
+    // 0331: MOV D,C
+    cpu.d = cpu.c;
+    // 0332: MOV E,D
+    cpu.e = cpu.d;
+    // 0333: MOV H,E
+    cpu.h = cpu.e;
+    // 0334: MOV L,H
+    cpu.l = cpu.h;
+    // 0335: MOV A,L
+    cpu.a = cpu.l;
+    // 0336: DCR A
+    let value = cpu.a;
+    let result = value.wrapping_sub(1);
+    cpu.set_zsp(result);
+    cpu.set_aux_carry(result & 0x0F != 0x0F);
+    cpu.a = result;
+    // 0337: MOV C,A
+    cpu.c = cpu.a;
+    // 0338: MOV E,C
+    cpu.e = cpu.c;
+    // 0339: MOV L,E
+    cpu.l = cpu.e;
+    // 033A: MOV B,L
+    cpu.b = cpu.l;
+    // 033B: MOV D,B
+    cpu.d = cpu.b;
+    // 033C: MOV H,D
+    cpu.h = cpu.d;
+    // 033D: MOV A,H
+    cpu.a = cpu.h;
+    // 033E: MOV D,A
+    cpu.d = cpu.a;
+    // 033F: INR D
+    let value = cpu.d;
+    let result = value.wrapping_add(1);
+    cpu.set_zsp(result);
+    cpu.set_aux_carry(value & 0x0F == 0x0F);
+    cpu.d = result;
+    // 0340: MOV L,D
+    cpu.l = cpu.d;
+    // 0341: MOV C,L
+    cpu.c = cpu.l;
+    // 0342: INR C
+    let value = cpu.c;
+    let result = value.wrapping_add(1);
+    cpu.set_zsp(result);
+    cpu.set_aux_carry(value & 0x0F == 0x0F);
+    cpu.c = result;
+    // 0343: MOV H,C
+    cpu.h = cpu.c;
+    // 0344: MOV B,H
+    cpu.b = cpu.h;
+    // 0345: DCR B
+    let value = cpu.b;
+    let result = value.wrapping_sub(1);
+    cpu.set_zsp(result);
+    cpu.set_aux_carry(result & 0x0F != 0x0F);
+    cpu.b = result;
+    // 0346: MOV E,B
+    cpu.e = cpu.b;
+    // 0347: MOV A,E
+    cpu.a = cpu.e;
+    // 0348: MOV E,A
+    cpu.e = cpu.a;
+    // 0349: INR E
+    let value = cpu.e;
+    let result = value.wrapping_add(1);
+    cpu.set_zsp(result);
+    cpu.set_aux_carry(value & 0x0F == 0x0F);
+    cpu.e = result;
+    // 034A: MOV B,E
+    cpu.b = cpu.e;
+    // 034B: MOV H,B
+    cpu.h = cpu.b;
+    // 034C: INR H
+    let value = cpu.h;
+    let result = value.wrapping_add(1);
+    cpu.set_zsp(result);
+    cpu.set_aux_carry(value & 0x0F == 0x0F);
+    cpu.h = result;
+    // 034D: MOV C,H
+    cpu.c = cpu.h;
+    // 034E: MOV L,C
+    cpu.l = cpu.c;
+    // 034F: MOV D,L
+    cpu.d = cpu.l;
+    // 0350: DCR D
+    let value = cpu.d;
+    let result = value.wrapping_sub(1);
+    cpu.set_zsp(result);
+    cpu.set_aux_carry(result & 0x0F != 0x0F);
+    cpu.d = result;
+    // 0351: MOV A,D
+    cpu.a = cpu.d;
+    // 0352: MOV H,A
+    cpu.h = cpu.a;
+    // 0353: DCR H
+    let value = cpu.h;
+    let result = value.wrapping_sub(1);
+    cpu.set_zsp(result);
+    cpu.set_aux_carry(result & 0x0F != 0x0F);
+    cpu.h = result;
+    // 0354: MOV D,H
+    cpu.d = cpu.h;
+    // 0355: MOV B,D
+    cpu.b = cpu.d;
+    // 0356: MOV L,B
+    cpu.l = cpu.b;
+    // 0357: INR L
+    let value = cpu.l;
+    let result = value.wrapping_add(1);
+    cpu.set_zsp(result);
+    cpu.set_aux_carry(value & 0x0F == 0x0F);
+    cpu.l = result;
+    // 0358: MOV E,L
+    cpu.e = cpu.l;
+    // 0359: DCR E
+    let value = cpu.e;
+    let result = value.wrapping_sub(1);
+    cpu.set_zsp(result);
+    cpu.set_aux_carry(result & 0x0F != 0x0F);
+    cpu.e = result;
+    // 035A: MOV C,E
+    cpu.c = cpu.e;
+    // 035B: MOV A,C
+    cpu.a = cpu.c;
+    // 035C: MOV L,A
+    cpu.l = cpu.a;
+    // 035D: DCR L
+    let value = cpu.l;
+    let result = value.wrapping_sub(1);
+    cpu.set_zsp(result);
+    cpu.set_aux_carry(result & 0x0F != 0x0F);
+    cpu.l = result;
+    // 035E: MOV H,L
+    cpu.h = cpu.l;
+    // 035F: MOV E,H
+    cpu.e = cpu.h;
+    // 0360: MOV D,E
+    cpu.d = cpu.e;
+    // 0361: MOV C,D
+    cpu.c = cpu.d;
+    // 0362: MOV B,C
+    cpu.b = cpu.c;
+    // 0363: MOV A,B
+    cpu.a = cpu.b;
+    // 0364: CPI 77H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x77, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0366: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x0369, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x0369;
+        11
+    };
+    Some(294 + cycles)
+}
+
+fn block_0369<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0369, &[
+        0xAF, 0x06, 0x01, 0x0E, 0x03, 0x16, 0x07, 0x1E, 0x0F, 0x26, 0x1F, 0x2E, 0x3F, 0x80, 0x81, 0x82,
+        0x83, 0x84, 0x85, 0x87, 0xFE, 0xF0, 0xC4, 0xA0, 0x06,
+    ]) {
+        return None;
+    }
+    // 0369: XRA A
+    cpu.a ^= cpu.a;
+    cpu.set_zsp(cpu.a);
+    cpu.set_carry(false);
+    cpu.set_aux_carry(false);
+    // 036A: MVI B,01H
+    cpu.b = 0x01;
+    // 036C: MVI C,03H
+    cpu.c = 0x03;
+    // 036E: MVI D,07H
+    cpu.d = 0x07;
+    // 0370: MVI E,0FH
+    cpu.e = 0x0F;
+    // 0372: MVI H,1FH
+    cpu.h = 0x1F;
+    // 0374: MVI L,3FH
+    cpu.l = 0x3F;
+    // 0376: ADD B
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.b, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0377: ADD C
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.c, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0378: ADD D
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.d, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0379: ADD E
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.e, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 037A: ADD H
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.h, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 037B: ADD L
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.l, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 037C: ADD A
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.a, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 037D: CPI F0H
+    let (result, carry, aux_carry) = arith(cpu.a, 0xF0, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 037F: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x0382, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x0382;
+        11
+    };
+    Some(81 + cycles)
+}
+
+fn block_0382<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0382, &[0x90, 0x91, 0x92, 0x93, 0x94, 0x95, 0xFE, 0x78, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0382: SUB B
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.b, false, true);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0383: SUB C
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.c, false, true);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0384: SUB D
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.d, false, true);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0385: SUB E
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.e, false, true);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0386: SUB H
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.h, false, true);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0387: SUB L
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.l, false, true);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0388: CPI 78H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x78, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 038A: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x038D, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x038D;
+        11
+    };
+    Some(31 + cycles)
+}
+
+fn block_038d<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x038D, &[0x97, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 038D: SUB A
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.a, false, true);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 038E: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x0391, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x0391;
+        11
+    };
+    Some(4 + cycles)
+}
+
+fn block_0391<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0391, &[
+        0x3E, 0x80, 0x87, 0x06, 0x01, 0x0E, 0x02, 0x16, 0x03, 0x1E, 0x04, 0x26, 0x05, 0x2E, 0x06, 0x88,
+        0x06, 0x80, 0x80, 0x80, 0x89, 0x80, 0x80, 0x8A, 0x80, 0x80, 0x8B, 0x80, 0x80, 0x8C, 0x80, 0x80,
+        0x8D, 0x80, 0x80, 0x8F, 0xFE, 0x37, 0xC4, 0xA0, 0x06,
+    ]) {
+        return None;
+    }
+    // 0391: MVI A,80H
+    cpu.a = 0x80;
+    // 0393: ADD A
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.a, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0394: MVI B,01H
+    cpu.b = 0x01;
+    // 0396: MVI C,02H
+    cpu.c = 0x02;
+    // 0398: MVI D,03H
+    cpu.d = 0x03;
+    // 039A: MVI E,04H
+    cpu.e = 0x04;
+    // 039C: MVI H,05H
+    cpu.h = 0x05;
+    // 039E: MVI L,06H
+    cpu.l = 0x06;
+    // 03A0: ADC B
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.b, cpu.carry(), false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03A1: MVI B,80H
+    cpu.b = 0x80;
+    // 03A3: ADD B
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.b, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03A4: ADD B
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.b, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03A5: ADC C
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.c, cpu.carry(), false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03A6: ADD B
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.b, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03A7: ADD B
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.b, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03A8: ADC D
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.d, cpu.carry(), false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03A9: ADD B
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.b, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03AA: ADD B
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.b, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03AB: ADC E
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.e, cpu.carry(), false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03AC: ADD B
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.b, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03AD: ADD B
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.b, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03AE: ADC H
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.h, cpu.carry(), false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03AF: ADD B
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.b, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03B0: ADD B
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.b, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03B1: ADC L
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.l, cpu.carry(), false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03B2: ADD B
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.b, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03B3: ADD B
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.b, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03B4: ADC A
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.a, cpu.carry(), false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03B5: CPI 37H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x37, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03B7: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x03BA, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x03BA;
+        11
+    };
+    Some(143 + cycles)
+}
+
+fn block_03ba<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x03BA, &[
+        0x3E, 0x80, 0x87, 0x06, 0x01, 0x98, 0x06, 0xFF, 0x80, 0x99, 0x80, 0x9A, 0x80, 0x9B, 0x80, 0x9C,
+        0x80, 0x9D, 0xFE, 0xE0, 0xC4, 0xA0, 0x06,
+    ]) {
+        return None;
+    }
+    // 03BA: MVI A,80H
+    cpu.a = 0x80;
+    // 03BC: ADD A
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.a, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03BD: MVI B,01H
+    cpu.b = 0x01;
+    // 03BF: SBB B
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.b, cpu.carry(), true);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03C0: MVI B,FFH
+    cpu.b = 0xFF;
+    // 03C2: ADD B
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.b, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03C3: SBB C
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.c, cpu.carry(), true);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03C4: ADD B
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.b, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03C5: SBB D
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.d, cpu.carry(), true);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03C6: ADD B
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.b, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03C7: SBB E
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.e, cpu.carry(), true);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03C8: ADD B
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.b, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03C9: SBB H
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.h, cpu.carry(), true);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03CA: ADD B
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.b, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03CB: SBB L
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.l, cpu.carry(), true);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03CC: CPI E0H
+    let (result, carry, aux_carry) = arith(cpu.a, 0xE0, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03CE: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x03D1, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x03D1;
+        11
+    };
+    Some(76 + cycles)
+}
+
+fn block_03d1<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x03D1, &[0x3E, 0x80, 0x87, 0x9F, 0xFE, 0xFF, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 03D1: MVI A,80H
+    cpu.a = 0x80;
+    // 03D3: ADD A
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.a, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03D4: SBB A
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.a, cpu.carry(), true);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03D5: CPI FFH
+    let (result, carry, aux_carry) = arith(cpu.a, 0xFF, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03D7: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x03DA, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x03DA;
+        11
+    };
+    Some(22 + cycles)
+}
+
+fn block_03da<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x03DA, &[
+        0x3E, 0xFF, 0x06, 0xFE, 0x0E, 0xFC, 0x16, 0xEF, 0x1E, 0x7F, 0x26, 0xF4, 0x2E, 0xBF, 0xA0, 0xA1,
+        0xA2, 0xA3, 0xA4, 0xA5, 0xA7, 0xFE, 0x24, 0xC4, 0xA0, 0x06,
+    ]) {
+        return None;
+    }
+    // 03DA: MVI A,FFH
+    cpu.a = 0xFF;
+    // 03DC: MVI B,FEH
+    cpu.b = 0xFE;
+    // 03DE: MVI C,FCH
+    cpu.c = 0xFC;
+    // 03E0: MVI D,EFH
+    cpu.d = 0xEF;
+    // 03E2: MVI E,7FH
+    cpu.e = 0x7F;
+    // 03E4: MVI H,F4H
+    cpu.h = 0xF4;
+    // 03E6: MVI L,BFH
+    cpu.l = 0xBF;
+    // 03E8: ANA B
+    let value = cpu.b;
+    let result = cpu.a & value;
+    cpu.set_zsp(result);
+    cpu.set_carry(false);
+    cpu.set_aux_carry((cpu.a | value) & 0x08 != 0);
+    cpu.a = result;
+    // 03E9: ANA C
+    let value = cpu.c;
+    let result = cpu.a & value;
+    cpu.set_zsp(result);
+    cpu.set_carry(false);
+    cpu.set_aux_carry((cpu.a | value) & 0x08 != 0);
+    cpu.a = result;
+    // 03EA: ANA D
+    let value = cpu.d;
+    let result = cpu.a & value;
+    cpu.set_zsp(result);
+    cpu.set_carry(false);
+    cpu.set_aux_carry((cpu.a | value) & 0x08 != 0);
+    cpu.a = result;
+    // 03EB: ANA E
+    let value = cpu.e;
+    let result = cpu.a & value;
+    cpu.set_zsp(result);
+    cpu.set_carry(false);
+    cpu.set_aux_carry((cpu.a | value) & 0x08 != 0);
+    cpu.a = result;
+    // 03EC: ANA H
+    let value = cpu.h;
+    let result = cpu.a & value;
+    cpu.set_zsp(result);
+    cpu.set_carry(false);
+    cpu.set_aux_carry((cpu.a | value) & 0x08 != 0);
+    cpu.a = result;
+    // 03ED: ANA L
+    let value = cpu.l;
+    let result = cpu.a & value;
+    cpu.set_zsp(result);
+    cpu.set_carry(false);
+    cpu.set_aux_carry((cpu.a | value) & 0x08 != 0);
+    cpu.a = result;
+    // 03EE: ANA A
+    let value = cpu.a;
+    let result = cpu.a & value;
+    cpu.set_zsp(result);
+    cpu.set_carry(false);
+    cpu.set_aux_carry((cpu.a | value) & 0x08 != 0);
+    cpu.a = result;
+    // 03EF: CPI 24H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x24, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 03F1: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x03F4, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x03F4;
+        11
+    };
+    Some(84 + cycles)
+}
+
+fn block_03f4<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x03F4, &[
+        0xAF, 0x06, 0x01, 0x0E, 0x02, 0x16, 0x04, 0x1E, 0x08, 0x26, 0x10, 0x2E, 0x20, 0xB0, 0xB1, 0xB2,
+        0xB3, 0xB4, 0xB5, 0xB7, 0xFE, 0x3F, 0xC4, 0xA0, 0x06,
+    ]) {
+        return None;
+    }
+    // 03F4: XRA A
+    cpu.a ^= cpu.a;
+    cpu.set_zsp(cpu.a);
+    cpu.set_carry(false);
+    cpu.set_aux_carry(false);
+    // 03F5: MVI B,01H
+    cpu.b = 0x01;
+    // 03F7: MVI C,02H
+    cpu.c = 0x02;
+    // 03F9: MVI D,04H
+    cpu.d = 0x04;
+    // 03FB: MVI E,08H
+    cpu.e = 0x08;
+    // 03FD: MVI H,10H
+    cpu.h = 0x10;
+    // 03FF: MVI L,20H
+    cpu.l = 0x20;
+    // 0401: ORA B
+    cpu.a |= cpu.b;
+    cpu.set_zsp(cpu.a);
+    cpu.set_carry(false);
+    cpu.set_aux_carry(false);
+    // 0402: ORA C
+    cpu.a |= cpu.c;
+    cpu.set_zsp(cpu.a);
+    cpu.set_carry(false);
+    cpu.set_aux_carry(false);
+    // 0403: ORA D
+    cpu.a |= cpu.d;
+    cpu.set_zsp(cpu.a);
+    cpu.set_carry(false);
+    cpu.set_aux_carry(false);
+    // 0404: ORA E
+    cpu.a |= cpu.e;
+    cpu.set_zsp(cpu.a);
+    cpu.set_carry(false);
+    cpu.set_aux_carry(false);
+    // 0405: ORA H
+    cpu.a |= cpu.h;
+    cpu.set_zsp(cpu.a);
+    cpu.set_carry(false);
+    cpu.set_aux_carry(false);
+    // 0406: ORA L
+    cpu.a |= cpu.l;
+    cpu.set_zsp(cpu.a);
+    cpu.set_carry(false);
+    cpu.set_aux_carry(false);
+    // 0407: ORA A
+    cpu.a |= cpu.a;
+    cpu.set_zsp(cpu.a);
+    cpu.set_carry(false);
+    cpu.set_aux_carry(false);
+    // 0408: CPI 3FH
+    let (result, carry, aux_carry) = arith(cpu.a, 0x3F, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 040A: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x040D, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x040D;
+        11
+    };
+    Some(81 + cycles)
+}
+
+fn block_040d<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x040D, &[
+        0x3E, 0x00, 0x26, 0x8F, 0x2E, 0x4F, 0xA8, 0xA9, 0xAA, 0xAB, 0xAC, 0xAD, 0xFE, 0xCF, 0xC4, 0xA0,
+        0x06,
+    ]) {
+        return None;
+    }
+    // 040D: MVI A,00H
+    cpu.a = 0x00;
+    // 040F: MVI H,8FH
+    cpu.h = 0x8F;
+    // 0411: MVI L,4FH
+    cpu.l = 0x4F;
+    // 0413: XRA B
+    cpu.a ^= cpu.b;
+    cpu.set_zsp(cpu.a);
+    cpu.set_carry(false);
+    cpu.set_aux_carry(false);
+    // 0414: XRA C
+    cpu.a ^= cpu.c;
+    cpu.set_zsp(cpu.a);
+    cpu.set_carry(false);
+    cpu.set_aux_carry(false);
+    // 0415: XRA D
+    cpu.a ^= cpu.d;
+    cpu.set_zsp(cpu.a);
+    cpu.set_carry(false);
+    cpu.set_aux_carry(false);
+    // 0416: XRA E
+    cpu.a ^= cpu.e;
+    cpu.set_zsp(cpu.a);
+    cpu.set_carry(false);
+    cpu.set_aux_carry(false);
+    // 0417: XRA H
+    cpu.a ^= cpu.h;
+    cpu.set_zsp(cpu.a);
+    cpu.set_carry(false);
+    cpu.set_aux_carry(false);
+    // 0418: XRA L
+    cpu.a ^= cpu.l;
+    cpu.set_zsp(cpu.a);
+    cpu.set_carry(false);
+    cpu.set_aux_carry(false);
+    // 0419: CPI CFH
+    let (result, carry, aux_carry) = arith(cpu.a, 0xCF, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 041B: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x041E, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x041E;
+        11
+    };
+    Some(52 + cycles)
+}
+
+fn block_041e<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x041E, &[0xAF, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 041E: XRA A
+    cpu.a ^= cpu.a;
+    cpu.set_zsp(cpu.a);
+    cpu.set_carry(false);
+    cpu.set_aux_carry(false);
+    // 041F: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x0422, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x0422;
+        11
+    };
+    Some(4 + cycles)
+}
+
+fn block_0422<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0422, &[0x06, 0x44, 0x0E, 0x45, 0x16, 0x46, 0x1E, 0x47, 0x26, 0x06, 0x2E, 0xBF, 0x70]) {
+        return None;
+    }
+    // 0422: MVI B,44H
+    cpu.b = 0x44;
+    // 0424: MVI C,45H
+    cpu.c = 0x45;
+    // 0426: MVI D,46H
+    cpu.d = 0x46;
+    // 0428: MVI E,47H
+    cpu.e = 0x47;
+    // 042A: MVI H,06H
+    cpu.h = 0x06;
+    // 042C: MVI L,BFH
+    cpu.l = 0xBF;
+    // 042E: MOV M,B
+    bus.write_access(cpu.hl(), cpu.b, Access::Data);
+    cpu.pc = 0x042F;
+    Some(49)
+}
+
+fn block_042f<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x042F, &[0x06, 0x00, 0x46, 0x3E, 0x44, 0xB8, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 042F: MVI B,00H
+    cpu.b = 0x00;
+    // 0431: MOV B,M
+    cpu.b = bus.read_access(cpu.hl(), Access::Data);
+    // 0432: MVI A,44H
+    cpu.a = 0x44;
+    // 0434: CMP B
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.b, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0435: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x0438, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x0438;
+        11
+    };
+    Some(25 + cycles)
+}
+
+fn block_0438<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0438, &[0x71]) {
+        return None;
+    }
+    // 0438: MOV M,C
+    bus.write_access(cpu.hl(), cpu.c, Access::Data);
+    cpu.pc = 0x0439;
+    Some(7)
+}
+
+fn block_0439<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0439, &[0x0E, 0x00, 0x4E, 0x3E, 0x45, 0xB9, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0439: MVI C,00H
+    cpu.c = 0x00;
+    // 043B: MOV C,M
+    cpu.c = bus.read_access(cpu.hl(), Access::Data);
+    // 043C: MVI A,45H
+    cpu.a = 0x45;
+    // 043E: CMP C
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.c, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 043F: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x0442, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x0442;
+        11
+    };
+    Some(25 + cycles)
+}
+
+fn block_0442<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0442, &[0x72]) {
+        return None;
+    }
+    // 0442: MOV M,D
+    bus.write_access(cpu.hl(), cpu.d, Access::Data);
+    cpu.pc = 0x0443;
+    Some(7)
+}
+
+fn block_0443<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0443, &[0x16, 0x00, 0x56, 0x3E, 0x46, 0xBA, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0443: MVI D,00H
+    cpu.d = 0x00;
+    // 0445: MOV D,M
+    cpu.d = bus.read_access(cpu.hl(), Access::Data);
+    // 0446: MVI A,46H
+    cpu.a = 0x46;
+    // 0448: CMP D
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.d, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0449: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x044C, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x044C;
+        11
+    };
+    Some(25 + cycles)
+}
+
+fn block_044c<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x044C, &[0x73]) {
+        return None;
+    }
+    // 044C: MOV M,E
+    bus.write_access(cpu.hl(), cpu.e, Access::Data);
+    cpu.pc = 0x044D;
+    Some(7)
+}
+
+fn block_044d<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x044D, &[0x1E, 0x00, 0x5E, 0x3E, 0x47, 0xBB, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 044D: MVI E,00H
+    cpu.e = 0x00;
+    // 044F: MOV E,M
+    cpu.e = bus.read_access(cpu.hl(), Access::Data);
+    // 0450: MVI A,47H
+    cpu.a = 0x47;
+    // 0452: CMP E
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.e, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0453: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x0456, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x0456;
+        11
+    };
+    Some(25 + cycles)
+}
+
+fn block_0456<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0456, &[0x74]) {
+        return None;
+    }
+    // 0456: MOV M,H
+    bus.write_access(cpu.hl(), cpu.h, Access::Data);
+    cpu.pc = 0x0457;
+    Some(7)
+}
+
+fn block_0457<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0457, &[0x26, 0x06, 0x2E, 0xBF, 0x66, 0x3E, 0x06, 0xBC, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0457: MVI H,06H
+    cpu.h = 0x06;
+    // 0459: MVI L,BFH
+    cpu.l = 0xBF;
+    // 045B: MOV H,M
+    cpu.h = bus.read_access(cpu.hl(), Access::Data);
+    // 045C: MVI A,06H
+    cpu.a = 0x06;
+    // 045E: CMP H
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.h, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 045F: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x0462, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x0462;
+        11
+    };
+    Some(32 + cycles)
+}
+
+fn block_0462<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0462, &[0x75]) {
+        return None;
+    }
+    // 0462: MOV M,L
+    bus.write_access(cpu.hl(), cpu.l, Access::Data);
+    cpu.pc = 0x0463;
+    Some(7)
+}
+
+fn block_0463<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0463, &[0x26, 0x06, 0x2E, 0xBF, 0x6E, 0x3E, 0xBF, 0xBD, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0463: MVI H,06H
+    cpu.h = 0x06;
+    // 0465: MVI L,BFH
+    cpu.l = 0xBF;
+    // 0467: MOV L,M
+    cpu.l = bus.read_access(cpu.hl(), Access::Data);
+    // 0468: MVI A,BFH
+    cpu.a = 0xBF;
+    // 046A: CMP L
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.l, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 046B: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x046E, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x046E;
+        11
+    };
+    Some(32 + cycles)
+}
+
+fn block_046e<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x046E, &[0x26, 0x06, 0x2E, 0xBF, 0x3E, 0x32, 0x77]) {
+        return None;
+    }
+    // 046E: MVI H,06H
+    cpu.h = 0x06;
+    // 0470: MVI L,BFH
+    cpu.l = 0xBF;
+    // 0472: MVI A,32H
+    cpu.a = 0x32;
+    // 0474: MOV M,A
+    bus.write_access(cpu.hl(), cpu.a, Access::Data);
+    cpu.pc = 0x0475;
+    Some(28)
+}
+
+fn block_0475<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0475, &[0xBE, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0475: CMP M
+    let (result, carry, aux_carry) = arith(cpu.a, bus.read_access(cpu.hl(), Access::Data), false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0476: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x0479, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x0479;
+        11
+    };
+    Some(7 + cycles)
+}
+
+fn block_0479<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0479, &[0x86, 0xFE, 0x64, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0479: ADD M
+    let (result, carry, aux_carry) = arith(cpu.a, bus.read_access(cpu.hl(), Access::Data), false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 047A: CPI 64H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x64, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 047C: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x047F, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x047F;
+        11
+    };
+    Some(14 + cycles)
+}
+
+fn block_047f<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x047F, &[0xAF, 0x7E, 0xFE, 0x32, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 047F: XRA A
+    cpu.a ^= cpu.a;
+    cpu.set_zsp(cpu.a);
+    cpu.set_carry(false);
+    cpu.set_aux_carry(false);
+    // 0480: MOV A,M
+    cpu.a = bus.read_access(cpu.hl(), Access::Data);
+    // 0481: CPI 32H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x32, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0483: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x0486, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x0486;
+        11
+    };
+    Some(18 + cycles)
+}
+
+fn block_0486<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0486, &[0x26, 0x06, 0x2E, 0xBF, 0x7E, 0x96, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0486: MVI H,06H
+    cpu.h = 0x06;
+    // 0488: MVI L,BFH
+    cpu.l = 0xBF;
+    // 048A: MOV A,M
+    cpu.a = bus.read_access(cpu.hl(), Access::Data);
+    // 048B: SUB M
+    let (result, carry, aux_carry) = arith(cpu.a, bus.read_access(cpu.hl(), Access::Data), false, true);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 048C: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x048F, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x048F;
+        11
+    };
+    Some(28 + cycles)
+}
+
+fn block_048f<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x048F, &[0x3E, 0x80, 0x87, 0x8E, 0xFE, 0x33, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 048F: MVI A,80H
+    cpu.a = 0x80;
+    // 0491: ADD A
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.a, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0492: ADC M
+    let (result, carry, aux_carry) = arith(cpu.a, bus.read_access(cpu.hl(), Access::Data), cpu.carry(), false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0493: CPI 33H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x33, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0495: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x0498, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x0498;
+        11
+    };
+    Some(25 + cycles)
+}
+
+fn block_0498<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0498, &[0x3E, 0x80, 0x87, 0x9E, 0xFE, 0xCD, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0498: MVI A,80H
+    cpu.a = 0x80;
+    // 049A: ADD A
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.a, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 049B: SBB M
+    let (result, carry, aux_carry) = arith(cpu.a, bus.read_access(cpu.hl(), Access::Data), cpu.carry(), true);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 049C: CPI CDH
+    let (result, carry, aux_carry) = arith(cpu.a, 0xCD, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 049E: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x04A1, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x04A1;
+        11
+    };
+    Some(25 + cycles)
+}
+
+fn block_04a1<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x04A1, &[0xA6, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 04A1: ANA M
+    let value = bus.read_access(cpu.hl(), Access::Data);
+    let result = cpu.a & value;
+    cpu.set_zsp(result);
+    cpu.set_carry(false);
+    cpu.set_aux_carry((cpu.a | value) & 0x08 != 0);
+    cpu.a = result;
+    // 04A2: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x04A5, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x04A5;
+        11
+    };
+    Some(7 + cycles)
+}
+
+fn block_04a5<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x04A5, &[0x3E, 0x25, 0xB6, 0xFE, 0x37, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 04A5: MVI A,25H
+    cpu.a = 0x25;
+    // 04A7: ORA M
+    cpu.a |= bus.read_access(cpu.hl(), Access::Data);
+    cpu.set_zsp(cpu.a);
+    cpu.set_carry(false);
+    cpu.set_aux_carry(false);
+    // 04A8: CPI 37H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x37, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 04AA: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x04AD, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x04AD;
+        11
+    };
+    Some(21 + cycles)
+}
+
+fn block_04ad<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x04AD, &[0xAE, 0xFE, 0x05, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 04AD: XRA M
+    cpu.a ^= bus.read_access(cpu.hl(), Access::Data);
+    cpu.set_zsp(cpu.a);
+    cpu.set_carry(false);
+    cpu.set_aux_carry(false);
+    // 04AE: CPI 05H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x05, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 04B0: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x04B3, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x04B3;
+        11
+    };
+    Some(14 + cycles)
+}
+
+fn block_04b3<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x04B3, &[0x36, 0x55]) {
+        return None;
+    }
+    // 04B3: MVI M,55H
+    bus.write_access(cpu.hl(), 0x55, Access::Data);
+    cpu.pc = 0x04B5;
+    Some(10)
+}
+
+fn block_04b5<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x04B5, &[0x34]) {
+        return None;
+    }
+    // 04B5: INR M
+    let value = bus.read_access(cpu.hl(), Access::Data);
+    let result = value.wrapping_add(1);
+    cpu.set_zsp(result);
+    cpu.set_aux_carry(value & 0x0F == 0x0F);
+    bus.write_access(cpu.hl(), result, Access::Data);
+    cpu.pc = 0x04B6;
+    Some(10)
+}
+
+fn block_04b6<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x04B6, &[0x35]) {
+        return None;
+    }
+    // 04B6: DCR M
+    let value = bus.read_access(cpu.hl(), Access::Data);
+    let result = value.wrapping_sub(1);
+    cpu.set_zsp(result);
+    cpu.set_aux_carry(result & 0x0F != 0x0F);
+    bus.write_access(cpu.hl(), result, Access::Data);
+    cpu.pc = 0x04B7;
+    Some(10)
+}
+
+fn block_04b7<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x04B7, &[0x86, 0xFE, 0x5A, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 04B7: ADD M
+    let (result, carry, aux_carry) = arith(cpu.a, bus.read_access(cpu.hl(), Access::Data), false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 04B8: CPI 5AH
+    let (result, carry, aux_carry) = arith(cpu.a, 0x5A, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 04BA: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x04BD, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x04BD;
+        11
+    };
+    Some(14 + cycles)
+}
+
+fn block_04bd<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x04BD, &[
+        0x01, 0xFF, 0x12, 0x11, 0xFF, 0x12, 0x21, 0xFF, 0x12, 0x03, 0x13, 0x23, 0x3E, 0x13, 0xB8, 0xC4,
+        0xA0, 0x06,
+    ]) {
+        return None;
+    }
+    // 04BD: LXI B,12FFH
+    cpu.set_bc(0x12FF);
+    // 04C0: LXI D,12FFH
+    cpu.set_de(0x12FF);
+    // 04C3: LXI H,12FFH
+    cpu.set_hl(0x12FF);
+    // 04C6: INX B
+    cpu.set_bc(cpu.bc().wrapping_add(1));
+    // 04C7: INX D
+    cpu.set_de(cpu.de().wrapping_add(1));
+    // 04C8: INX H
+    cpu.set_hl(cpu.hl().wrapping_add(1));
+    // 04C9: MVI A,13H
+    cpu.a = 0x13;
+    // 04CB: CMP B
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.b, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 04CC: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x04CF, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x04CF;
+        11
+    };
+    Some(56 + cycles)
+}
+
+fn block_04cf<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x04CF, &[0xBA, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 04CF: CMP D
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.d, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 04D0: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x04D3, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x04D3;
+        11
+    };
+    Some(4 + cycles)
+}
+
+fn block_04d3<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x04D3, &[0xBC, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 04D3: CMP H
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.h, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 04D4: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x04D7, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x04D7;
+        11
+    };
+    Some(4 + cycles)
+}
+
+fn block_04d7<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x04D7, &[0x3E, 0x00, 0xB9, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 04D7: MVI A,00H
+    cpu.a = 0x00;
+    // 04D9: CMP C
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.c, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 04DA: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x04DD, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x04DD;
+        11
+    };
+    Some(11 + cycles)
+}
+
+fn block_04dd<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x04DD, &[0xBB, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 04DD: CMP E
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.e, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 04DE: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x04E1, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x04E1;
+        11
+    };
+    Some(4 + cycles)
+}
+
+fn block_04e1<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x04E1, &[0xBD, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 04E1: CMP L
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.l, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 04E2: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x04E5, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x04E5;
+        11
+    };
+    Some(4 + cycles)
+}
+
+fn block_04e5<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x04E5, &[0x0B, 0x1B, 0x2B, 0x3E, 0x12, 0xB8, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 04E5: DCX B
+    cpu.set_bc(cpu.bc().wrapping_sub(1));
+    // 04E6: DCX D
+    cpu.set_de(cpu.de().wrapping_sub(1));
+    // 04E7: DCX H
+    cpu.set_hl(cpu.hl().wrapping_sub(1));
+    // 04E8: MVI A,12H
+    cpu.a = 0x12;
+    // 04EA: CMP B
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.b, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 04EB: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x04EE, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x04EE;
+        11
+    };
+    Some(26 + cycles)
+}
+
+fn block_04ee<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x04EE, &[0xBA, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 04EE: CMP D
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.d, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 04EF: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x04F2, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x04F2;
+        11
+    };
+    Some(4 + cycles)
+}
+
+fn block_04f2<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x04F2, &[0xBC, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 04F2: CMP H
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.h, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 04F3: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x04F6, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x04F6;
+        11
+    };
+    Some(4 + cycles)
+}
+
+fn block_04f6<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x04F6, &[0x3E, 0xFF, 0xB9, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 04F6: MVI A,FFH
+    cpu.a = 0xFF;
+    // 04F8: CMP C
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.c, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 04F9: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x04FC, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x04FC;
+        11
+    };
+    Some(11 + cycles)
+}
+
+fn block_04fc<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x04FC, &[0xBB, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 04FC: CMP E
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.e, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 04FD: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x0500, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x0500;
+        11
+    };
+    Some(4 + cycles)
+}
+
+fn block_0500<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0500, &[0xBD, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0500: CMP L
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.l, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0501: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x0504, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x0504;
+        11
+    };
+    Some(4 + cycles)
+}
+
+fn block_0504<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0504, &[0x32, 0xBF, 0x06]) {
+        return None;
+    }
+    // 0504: STA 06BFH
+    bus.write_access(0x06BF, cpu.a, Access::Data);
+    cpu.pc = 0x0507;
+    Some(13)
+}
+
+fn block_0507<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0507, &[0xAF, 0x3A, 0xBF, 0x06, 0xFE, 0xFF, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0507: XRA A
+    cpu.a ^= cpu.a;
+    cpu.set_zsp(cpu.a);
+    cpu.set_carry(false);
+    cpu.set_aux_carry(false);
+    // 0508: LDA 06BFH
+    cpu.a = bus.read_access(0x06BF, Access::Data);
+    // 050B: CPI FFH
+    let (result, carry, aux_carry) = arith(cpu.a, 0xFF, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 050D: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x0510, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x0510;
+        11
+    };
+    Some(24 + cycles)
+}
+
+fn block_0510<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0510, &[0x2A, 0xBD, 0x06, 0x22, 0xBF, 0x06]) {
+        return None;
+    }
+    // 0510: LHLD 06BDH
+    let value = bus.read_word(0x06BD, Access::Data);
+    cpu.set_hl(value);
+    // 0513: SHLD 06BFH
+    bus.write_word(0x06BF, cpu.hl(), Access::Data);
+    cpu.pc = 0x0516;
+    Some(32)
+}
+
+fn block_0516<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0516, &[0x3A, 0xBD, 0x06, 0x47, 0x3A, 0xBF, 0x06, 0xB8, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0516: LDA 06BDH
+    cpu.a = bus.read_access(0x06BD, Access::Data);
+    // 0519: MOV B,A
+    cpu.b = cpu.a;
+    // 051A: LDA 06BFH
+    cpu.a = bus.read_access(0x06BF, Access::Data);
+    // 051D: CMP B
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.b, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 051E: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x0521, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x0521;
+        11
+    };
+    Some(35 + cycles)
+}
+
+fn block_0521<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0521, &[0x3A, 0xBE, 0x06, 0x47, 0x3A, 0xC0, 0x06, 0xB8, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0521: LDA 06BEH
+    cpu.a = bus.read_access(0x06BE, Access::Data);
+    // 0524: MOV B,A
+    cpu.b = cpu.a;
+    // 0525: LDA 06C0H
+    cpu.a = bus.read_access(0x06C0, Access::Data);
+    // 0528: CMP B
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.b, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0529: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x052C, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x052C;
+        11
+    };
+    Some(35 + cycles)
+}
+
+fn block_052c<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x052C, &[0x3E, 0xAA, 0x32, 0xBF, 0x06]) {
+        return None;
+    }
+    // 052C: MVI A,AAH
+    cpu.a = 0xAA;
+    // 052E: STA 06BFH
+    bus.write_access(0x06BF, cpu.a, Access::Data);
+    cpu.pc = 0x0531;
+    Some(20)
+}
+
+fn block_0531<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0531, &[0x44, 0x4D, 0xAF, 0x0A, 0xFE, 0xAA, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0531: MOV B,H
+    cpu.b = cpu.h;
+    // 0532: MOV C,L
+    cpu.c = cpu.l;
+    // 0533: XRA A
+    cpu.a ^= cpu.a;
+    cpu.set_zsp(cpu.a);
+    cpu.set_carry(false);
+    cpu.set_aux_carry(false);
+    // 0534: LDAX B
+    cpu.a = bus.read_access(cpu.bc(), Access::Data);
+    // 0535: CPI AAH
+    let (result, carry, aux_carry) = arith(cpu.a, 0xAA, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0537: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x053A, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x053A;
+        11
+    };
+    Some(28 + cycles)
+}
+
+fn block_053a<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x053A, &[0x3C, 0x02]) {
+        return None;
+    }
+    // 053A: INR A
+    let value = cpu.a;
+    let result = value.wrapping_add(1);
+    cpu.set_zsp(result);
+    cpu.set_aux_carry(value & 0x0F == 0x0F);
+    cpu.a = result;
+    // 053B: STAX B
+    bus.write_access(cpu.bc(), cpu.a, Access::Data);
+    cpu.pc = 0x053C;
+    Some(12)
+}
+
+fn block_053c<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x053C, &[0x3A, 0xBF, 0x06, 0xFE, 0xAB, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 053C: LDA 06BFH
+    cpu.a = bus.read_access(0x06BF, Access::Data);
+    // 053F: CPI ABH
+    let (result, carry, aux_carry) = arith(cpu.a, 0xAB, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0541: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x0544, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x0544;
+        11
+    };
+    Some(20 + cycles)
+}
+
+fn block_0544<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0544, &[0x3E, 0x77, 0x32, 0xBF, 0x06]) {
+        return None;
+    }
+    // 0544: MVI A,77H
+    cpu.a = 0x77;
+    // 0546: STA 06BFH
+    bus.write_access(0x06BF, cpu.a, Access::Data);
+    cpu.pc = 0x0549;
+    Some(20)
+}
+
+fn block_0549<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0549, &[0x2A, 0xBD, 0x06, 0x11, 0x00, 0x00, 0xEB, 0xAF, 0x1A, 0xFE, 0x77, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0549: LHLD 06BDH
+    let value = bus.read_word(0x06BD, Access::Data);
+    cpu.set_hl(value);
+    // 054C: LXI D,0000H
+    cpu.set_de(0x0000);
+    // 054F: XCHG
+    let value = cpu.de();
+    cpu.set_de(cpu.hl());
+    cpu.set_hl(value);
+    // 0550: XRA A
+    cpu.a ^= cpu.a;
+    cpu.set_zsp(cpu.a);
+    cpu.set_carry(false);
+    cpu.set_aux_carry(false);
+    // 0551: LDAX D
+    cpu.a = bus.read_access(cpu.de(), Access::Data);
+    // 0552: CPI 77H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x77, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0554: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x0557, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x0557;
+        11
+    };
+    Some(49 + cycles)
+}
+
+fn block_0557<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0557, &[0xAF, 0x84, 0x85, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0557: XRA A
+    cpu.a ^= cpu.a;
+    cpu.set_zsp(cpu.a);
+    cpu.set_carry(false);
+    cpu.set_aux_carry(false);
+    // 0558: ADD H
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.h, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0559: ADD L
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.l, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 055A: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x055D, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x055D;
+        11
+    };
+    Some(12 + cycles)
+}
+
+fn block_055d<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x055D, &[0x3E, 0xCC, 0x12]) {
+        return None;
+    }
+    // 055D: MVI A,CCH
+    cpu.a = 0xCC;
+    // 055F: STAX D
+    bus.write_access(cpu.de(), cpu.a, Access::Data);
+    cpu.pc = 0x0560;
+    Some(14)
+}
+
+fn block_0560<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0560, &[0x3A, 0xBF, 0x06, 0xFE, 0xCC, 0x12]) {
+        return None;
+    }
+    // 0560: LDA 06BFH
+    cpu.a = bus.read_access(0x06BF, Access::Data);
+    // 0563: CPI CCH
+    let (result, carry, aux_carry) = arith(cpu.a, 0xCC, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0565: STAX D
+    bus.write_access(cpu.de(), cpu.a, Access::Data);
+    cpu.pc = 0x0566;
+    Some(27)
+}
+
+fn block_0566<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0566, &[0x3A, 0xBF, 0x06, 0xFE, 0xCC, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0566: LDA 06BFH
+    cpu.a = bus.read_access(0x06BF, Access::Data);
+    // 0569: CPI CCH
+    let (result, carry, aux_carry) = arith(cpu.a, 0xCC, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 056B: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x056E, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x056E;
+        11
+    };
+    Some(20 + cycles)
+}
+
+fn block_056e<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x056E, &[0x21, 0x77, 0x77, 0x29, 0x3E, 0xEE, 0xBC, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 056E: LXI H,7777H
+    cpu.set_hl(0x7777);
+    // 0571: DAD H
+    let (value, carry) = cpu.hl().overflowing_add(cpu.hl());
+    cpu.set_hl(value);
+    cpu.set_carry(carry);
+    // 0572: MVI A,EEH
+    cpu.a = 0xEE;
+    // 0574: CMP H
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.h, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0575: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x0578, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x0578;
+        11
+    };
+    Some(31 + cycles)
+}
+
+fn block_0578<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0578, &[0xBD, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0578: CMP L
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.l, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0579: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x057C, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x057C;
+        11
+    };
+    Some(4 + cycles)
+}
+
+fn block_057c<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x057C, &[0x21, 0x55, 0x55, 0x01, 0xFF, 0xFF, 0x09, 0x3E, 0x55, 0xD4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 057C: LXI H,5555H
+    cpu.set_hl(0x5555);
+    // 057F: LXI B,FFFFH
+    cpu.set_bc(0xFFFF);
+    // 0582: DAD B
+    let (value, carry) = cpu.hl().overflowing_add(cpu.bc());
+    cpu.set_hl(value);
+    cpu.set_carry(carry);
+    // 0583: MVI A,55H
+    cpu.a = 0x55;
+    // 0585: CNC 06A0H
+    let cycles = if cpu.condition(2) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x0588, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x0588;
+        11
+    };
+    Some(37 + cycles)
+}
+
+fn block_0588<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0588, &[0xBC, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0588: CMP H
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.h, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0589: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x058C, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x058C;
+        11
+    };
+    Some(4 + cycles)
+}
+
+fn block_058c<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x058C, &[0x3E, 0x54, 0xBD, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 058C: MVI A,54H
+    cpu.a = 0x54;
+    // 058E: CMP L
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.l, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 058F: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x0592, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x0592;
+        11
+    };
+    Some(11 + cycles)
+}
+
+fn block_0592<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0592, &[0x21, 0xAA, 0xAA, 0x11, 0x33, 0x33, 0x19, 0x3E, 0xDD, 0xBC, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0592: LXI H,AAAAH
+    cpu.set_hl(0xAAAA);
+    // 0595: LXI D,3333H
+    cpu.set_de(0x3333);
+    // 0598: DAD D
+    let (value, carry) = cpu.hl().overflowing_add(cpu.de());
+    cpu.set_hl(value);
+    cpu.set_carry(carry);
+    // 0599: MVI A,DDH
+    cpu.a = 0xDD;
+    // 059B: CMP H
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.h, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 059C: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x059F, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x059F;
+        11
+    };
+    Some(41 + cycles)
+}
+
+fn block_059f<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x059F, &[0xBD, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 059F: CMP L
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.l, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 05A0: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x05A3, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x05A3;
+        11
+    };
+    Some(4 + cycles)
+}
+
+fn block_05a3<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x05A3, &[0x37, 0xD4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 05A3: STC
+    cpu.set_carry(true);
+    // 05A4: CNC 06A0H
+    let cycles = if cpu.condition(2) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x05A7, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x05A7;
+        11
+    };
+    Some(4 + cycles)
+}
+
+fn block_05a7<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x05A7, &[0x3F, 0xDC, 0xA0, 0x06]) {
+        return None;
+    }
+    // 05A7: CMC
+    cpu.set_carry(!cpu.carry());
+    // 05A8: CC 06A0H
+    let cycles = if cpu.condition(3) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x05AB, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x05AB;
+        11
+    };
+    Some(4 + cycles)
+}
+
+fn block_05ab<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x05AB, &[0x3E, 0xAA, 0x2F, 0xFE, 0x55, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 05AB: MVI A,AAH
+    cpu.a = 0xAA;
+    // 05AD: CMA
+    cpu.a = !cpu.a;
+    // 05AE: CPI 55H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x55, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 05B0: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x05B3, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x05B3;
+        11
+    };
+    Some(18 + cycles)
+}
+
+fn block_05b3<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x05B3, &[0xB7, 0x27, 0xFE, 0x55, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 05B3: ORA A
+    cpu.a |= cpu.a;
+    cpu.set_zsp(cpu.a);
+    cpu.set_carry(false);
+    cpu.set_aux_carry(false);
+    // 05B4: DAA
+    cpu.daa();
+    // 05B5: CPI 55H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x55, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 05B7: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x05BA, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x05BA;
+        11
+    };
+    Some(15 + cycles)
+}
+
+fn block_05ba<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x05BA, &[0x3E, 0x88, 0x87, 0x27, 0xFE, 0x76, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 05BA: MVI A,88H
+    cpu.a = 0x88;
+    // 05BC: ADD A
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.a, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 05BD: DAA
+    cpu.daa();
+    // 05BE: CPI 76H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x76, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 05C0: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x05C3, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x05C3;
+        11
+    };
+    Some(22 + cycles)
+}
+
+fn block_05c3<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x05C3, &[0xAF, 0x3E, 0xAA, 0x27, 0xD4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 05C3: XRA A
+    cpu.a ^= cpu.a;
+    cpu.set_zsp(cpu.a);
+    cpu.set_carry(false);
+    cpu.set_aux_carry(false);
+    // 05C4: MVI A,AAH
+    cpu.a = 0xAA;
+    // 05C6: DAA
+    cpu.daa();
+    // 05C7: CNC 06A0H
+    let cycles = if cpu.condition(2) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x05CA, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x05CA;
+        11
+    };
+    Some(15 + cycles)
+}
+
+fn block_05ca<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x05CA, &[0xFE, 0x10, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 05CA: CPI 10H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x10, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 05CC: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x05CF, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x05CF;
+        11
+    };
+    Some(7 + cycles)
+}
+
+fn block_05cf<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x05CF, &[0xAF, 0x3E, 0x9A, 0x27, 0xD4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 05CF: XRA A
+    cpu.a ^= cpu.a;
+    cpu.set_zsp(cpu.a);
+    cpu.set_carry(false);
+    cpu.set_aux_carry(false);
+    // 05D0: MVI A,9AH
+    cpu.a = 0x9A;
+    // 05D2: DAA
+    cpu.daa();
+    // 05D3: CNC 06A0H
+    let cycles = if cpu.condition(2) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x05D6, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x05D6;
+        11
+    };
+    Some(15 + cycles)
+}
+
+fn block_05d6<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x05D6, &[0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 05D6: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x05D9, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x05D9;
+        11
+    };
+    Some(cycles)
+}
+
+fn block_05d9<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x05D9, &[0x37, 0x3E, 0x42, 0x07, 0xDC, 0xA0, 0x06]) {
+        return None;
+    }
+    // 05D9: STC
+    cpu.set_carry(true);
+    // 05DA: MVI A,42H
+    cpu.a = 0x42;
+    // 05DC: RLC
+    cpu.set_carry(cpu.a & 0x80 != 0);
+    cpu.a = cpu.a.rotate_left(1);
+    // 05DD: CC 06A0H
+    let cycles = if cpu.condition(3) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x05E0, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x05E0;
+        11
+    };
+    Some(15 + cycles)
+}
+
+fn block_05e0<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x05E0, &[0x07, 0xD4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 05E0: RLC
+    cpu.set_carry(cpu.a & 0x80 != 0);
+    cpu.a = cpu.a.rotate_left(1);
+    // 05E1: CNC 06A0H
+    let cycles = if cpu.condition(2) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x05E4, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x05E4;
+        11
+    };
+    Some(4 + cycles)
+}
+
+fn block_05e4<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x05E4, &[0xFE, 0x09, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 05E4: CPI 09H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x09, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 05E6: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x05E9, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x05E9;
+        11
+    };
+    Some(7 + cycles)
+}
+
+fn block_05e9<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x05E9, &[0x0F, 0xD4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 05E9: RRC
+    cpu.set_carry(cpu.a & 0x01 != 0);
+    cpu.a = cpu.a.rotate_right(1);
+    // 05EA: CNC 06A0H
+    let cycles = if cpu.condition(2) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x05ED, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x05ED;
+        11
+    };
+    Some(4 + cycles)
+}
+
+fn block_05ed<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x05ED, &[0x0F, 0xFE, 0x42, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 05ED: RRC
+    cpu.set_carry(cpu.a & 0x01 != 0);
+    cpu.a = cpu.a.rotate_right(1);
+    // 05EE: CPI 42H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x42, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 05F0: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x05F3, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x05F3;
+        11
+    };
+    Some(11 + cycles)
+}
+
+fn block_05f3<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x05F3, &[0x17, 0x17, 0xD4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 05F3: RAL
+    let msb = cpu.a & 0x80;
+    cpu.a = (cpu.a << 1) | cpu.carry() as u8;
+    cpu.set_carry(msb != 0);
+    // 05F4: RAL
+    let msb = cpu.a & 0x80;
+    cpu.a = (cpu.a << 1) | cpu.carry() as u8;
+    cpu.set_carry(msb != 0);
+    // 05F5: CNC 06A0H
+    let cycles = if cpu.condition(2) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x05F8, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x05F8;
+        11
+    };
+    Some(8 + cycles)
+}
+
+fn block_05f8<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x05F8, &[0xFE, 0x08, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 05F8: CPI 08H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x08, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 05FA: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x05FD, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x05FD;
+        11
+    };
+    Some(7 + cycles)
+}
+
+fn block_05fd<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x05FD, &[0x1F, 0x1F, 0xDC, 0xA0, 0x06]) {
+        return None;
+    }
+    // 05FD: RAR
+    let lsb = cpu.a & 0x01;
+    cpu.a = (cpu.a >> 1) | (cpu.carry() as u8) << 7;
+    cpu.set_carry(lsb != 0);
+    // 05FE: RAR
+    let lsb = cpu.a & 0x01;
+    cpu.a = (cpu.a >> 1) | (cpu.carry() as u8) << 7;
+    cpu.set_carry(lsb != 0);
+    // 05FF: CC 06A0H
+    let cycles = if cpu.condition(3) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x0602, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x0602;
+        11
+    };
+    Some(8 + cycles)
+}
+
+fn block_0602<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0602, &[0xFE, 0x02, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0602: CPI 02H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x02, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0604: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x0607, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x0607;
+        11
+    };
+    Some(7 + cycles)
+}
+
+fn block_0607<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0607, &[0x01, 0x34, 0x12, 0x11, 0xAA, 0xAA, 0x21, 0x55, 0x55, 0xAF, 0xC5]) {
+        return None;
+    }
+    // 0607: LXI B,1234H
+    cpu.set_bc(0x1234);
+    // 060A: LXI D,AAAAH
+    cpu.set_de(0xAAAA);
+    // 060D: LXI H,5555H
+    cpu.set_hl(0x5555);
+    // 0610: XRA A
+    cpu.a ^= cpu.a;
+    cpu.set_zsp(cpu.a);
+    cpu.set_carry(false);
+    cpu.set_aux_carry(false);
+    // 0611: PUSH B
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, cpu.bc(), Access::Stack);
+    cpu.pc = 0x0612;
+    Some(45)
+}
+
+fn block_0612<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0612, &[0xD5]) {
+        return None;
+    }
+    // 0612: PUSH D
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, cpu.de(), Access::Stack);
+    cpu.pc = 0x0613;
+    Some(11)
+}
+
+fn block_0613<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0613, &[0xE5]) {
+        return None;
+    }
+    // 0613: PUSH H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, cpu.hl(), Access::Stack);
+    cpu.pc = 0x0614;
+    Some(11)
+}
+
+fn block_0614<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0614, &[0xF5]) {
+        return None;
+    }
+    // 0614: PUSH PSW
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, cpu.af(), Access::Stack);
+    cpu.pc = 0x0615;
+    Some(11)
+}
+
+fn block_0615<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0615, &[
+        0x01, 0x00, 0x00, 0x11, 0x00, 0x00, 0x21, 0x00, 0x00, 0x3E, 0xC0, 0xC6, 0xF0, 0xF1, 0xE1, 0xD1,
+        0xC1, 0xDC, 0xA0, 0x06,
+    ]) {
+        return None;
+    }
+    // 0615: LXI B,0000H
+    cpu.set_bc(0x0000);
+    // 0618: LXI D,0000H
+    cpu.set_de(0x0000);
+    // 061B: LXI H,0000H
+    cpu.set_hl(0x0000);
+    // 061E: MVI A,C0H
+    cpu.a = 0xC0;
+    // 0620: ADI F0H
+    let (result, carry, aux_carry) = arith(cpu.a, 0xF0, false, false);
+    cpu.a = result;
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0622: POP PSW
+    let value = bus.read_word(cpu.sp, Access::Stack);
+    cpu.sp = cpu.sp.wrapping_add(2);
+    cpu.set_af(value);
+    // 0623: POP H
+    let value = bus.read_word(cpu.sp, Access::Stack);
+    cpu.sp = cpu.sp.wrapping_add(2);
+    cpu.set_hl(value);
+    // 0624: POP D
+    let value = bus.read_word(cpu.sp, Access::Stack);
+    cpu.sp = cpu.sp.wrapping_add(2);
+    cpu.set_de(value);
+    // 0625: POP B
+    let value = bus.read_word(cpu.sp, Access::Stack);
+    cpu.sp = cpu.sp.wrapping_add(2);
+    cpu.set_bc(value);
+    // 0626: CC 06A0H
+    let cycles = if cpu.condition(3) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x0629, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x0629;
+        11
+    };
+    Some(84 + cycles)
+}
+
+fn block_0629<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0629, &[0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0629: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x062C, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x062C;
+        11
+    };
+    Some(cycles)
+}
+
+fn block_062c<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x062C, &[0xE4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 062C: CPO 06A0H
+    let cycles = if cpu.condition(4) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x062F, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x062F;
+        11
+    };
+    Some(cycles)
+}
+
+fn block_062f<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x062F, &[0xFC, 0xA0, 0x06]) {
+        return None;
+    }
+    // 062F: CM 06A0H
+    let cycles = if cpu.condition(7) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x0632, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x0632;
+        11
+    };
+    Some(cycles)
+}
+
+fn block_0632<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0632, &[0x3E, 0x12, 0xB8, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0632: MVI A,12H
+    cpu.a = 0x12;
+    // 0634: CMP B
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.b, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0635: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x0638, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x0638;
+        11
+    };
+    Some(11 + cycles)
+}
+
+fn block_0638<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0638, &[0x3E, 0x34, 0xB9, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0638: MVI A,34H
+    cpu.a = 0x34;
+    // 063A: CMP C
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.c, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 063B: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x063E, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x063E;
+        11
+    };
+    Some(11 + cycles)
+}
+
+fn block_063e<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x063E, &[0x3E, 0xAA, 0xBA, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 063E: MVI A,AAH
+    cpu.a = 0xAA;
+    // 0640: CMP D
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.d, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0641: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x0644, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x0644;
+        11
+    };
+    Some(11 + cycles)
+}
+
+fn block_0644<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0644, &[0xBB, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0644: CMP E
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.e, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0645: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x0648, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x0648;
+        11
+    };
+    Some(4 + cycles)
+}
+
+fn block_0648<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0648, &[0x3E, 0x55, 0xBC, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0648: MVI A,55H
+    cpu.a = 0x55;
+    // 064A: CMP H
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.h, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 064B: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x064E, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x064E;
+        11
+    };
+    Some(11 + cycles)
+}
+
+fn block_064e<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x064E, &[0xBD, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 064E: CMP L
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.l, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 064F: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x0652, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x0652;
+        11
+    };
+    Some(4 + cycles)
+}
+
+fn block_0652<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0652, &[0x21, 0x00, 0x00, 0x39, 0x22, 0xC4, 0x06]) {
+        return None;
+    }
+    // 0652: LXI H,0000H
+    cpu.set_hl(0x0000);
+    // 0655: DAD SP
+    let (value, carry) = cpu.hl().overflowing_add(cpu.sp);
+    cpu.set_hl(value);
+    cpu.set_carry(carry);
+    // 0656: SHLD 06C4H
+    bus.write_word(0x06C4, cpu.hl(), Access::Data);
+    cpu.pc = 0x0659;
+    Some(36)
+}
+
+fn block_0659<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0659, &[0x31, 0xC3, 0x06, 0x3B, 0x3B, 0x33, 0x3B, 0x3E, 0x55, 0x32, 0xC1, 0x06]) {
+        return None;
+    }
+    // 0659: LXI SP,06C3H
+    cpu.sp = 0x06C3;
+    // 065C: DCX SP
+    cpu.sp = cpu.sp.wrapping_sub(1);
+    // 065D: DCX SP
+    cpu.sp = cpu.sp.wrapping_sub(1);
+    // 065E: INX SP
+    cpu.sp = cpu.sp.wrapping_add(1);
+    // 065F: DCX SP
+    cpu.sp = cpu.sp.wrapping_sub(1);
+    // 0660: MVI A,55H
+    cpu.a = 0x55;
+    // 0662: STA 06C1H
+    bus.write_access(0x06C1, cpu.a, Access::Data);
+    cpu.pc = 0x0665;
+    Some(50)
+}
+
+fn block_0665<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0665, &[0x2F, 0x32, 0xC2, 0x06]) {
+        return None;
+    }
+    // 0665: CMA
+    cpu.a = !cpu.a;
+    // 0666: STA 06C2H
+    bus.write_access(0x06C2, cpu.a, Access::Data);
+    cpu.pc = 0x0669;
+    Some(17)
+}
+
+fn block_0669<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0669, &[0xC1, 0xB8, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0669: POP B
+    let value = bus.read_word(cpu.sp, Access::Stack);
+    cpu.sp = cpu.sp.wrapping_add(2);
+    cpu.set_bc(value);
+    // 066A: CMP B
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.b, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 066B: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x066E, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x066E;
+        11
+    };
+    Some(14 + cycles)
+}
+
+fn block_066e<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x066E, &[0x2F, 0xB9, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 066E: CMA
+    cpu.a = !cpu.a;
+    // 066F: CMP C
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.c, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0670: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x0673, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x0673;
+        11
+    };
+    Some(8 + cycles)
+}
+
+fn block_0673<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0673, &[0x21, 0xC3, 0x06, 0xF9, 0x21, 0x33, 0x77, 0x3B, 0x3B, 0xE3]) {
+        return None;
+    }
+    // 0673: LXI H,06C3H
+    cpu.set_hl(0x06C3);
+    // 0676: SPHL
+    cpu.sp = cpu.hl();
+    // 0677: LXI H,7733H
+    cpu.set_hl(0x7733);
+    // 067A: DCX SP
+    cpu.sp = cpu.sp.wrapping_sub(1);
+    // 067B: DCX SP
+    cpu.sp = cpu.sp.wrapping_sub(1);
+    // 067C: XTHL
+    let value = bus.read_word(cpu.sp, Access::Stack);
+    bus.write_word(cpu.sp, cpu.hl(), Access::Stack);
+    cpu.set_hl(value);
+    cpu.pc = 0x067D;
+    Some(54)
+}
+
+fn block_067d<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x067D, &[0x3A, 0xC2, 0x06, 0xFE, 0x77, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 067D: LDA 06C2H
+    cpu.a = bus.read_access(0x06C2, Access::Data);
+    // 0680: CPI 77H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x77, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0682: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x0685, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x0685;
+        11
+    };
+    Some(20 + cycles)
+}
+
+fn block_0685<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0685, &[0x3A, 0xC1, 0x06, 0xFE, 0x33, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0685: LDA 06C1H
+    cpu.a = bus.read_access(0x06C1, Access::Data);
+    // 0688: CPI 33H
+    let (result, carry, aux_carry) = arith(cpu.a, 0x33, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 068A: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x068D, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x068D;
+        11
+    };
+    Some(20 + cycles)
+}
+
+fn block_068d<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x068D, &[0x3E, 0x55, 0xBD, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 068D: MVI A,55H
+    cpu.a = 0x55;
+    // 068F: CMP L
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.l, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0690: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x0693, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x0693;
+        11
+    };
+    Some(11 + cycles)
+}
+
+fn block_0693<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0693, &[0x2F, 0xBC, 0xC4, 0xA0, 0x06]) {
+        return None;
+    }
+    // 0693: CMA
+    cpu.a = !cpu.a;
+    // 0694: CMP H
+    let (result, carry, aux_carry) = arith(cpu.a, cpu.h, false, true);
+    cpu.set_zsp(result);
+    cpu.set_carry(carry);
+    cpu.set_aux_carry(aux_carry);
+    // 0695: CNZ 06A0H
+    let cycles = if cpu.condition(0) {
+        cpu.sp = cpu.sp.wrapping_sub(2);
+        bus.write_word(cpu.sp, 0x0698, Access::Stack);
+        cpu.pc = 0x06A0;
+        17
+    } else {
+        cpu.pc = 0x0698;
+        11
+    };
+    Some(8 + cycles)
+}
+
+fn block_0698<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x0698, &[0x2A, 0xC4, 0x06, 0xF9, 0x21, 0xB4, 0x06, 0xE9]) {
+        return None;
+    }
+    // 0698: LHLD 06C4H
+    let value = bus.read_word(0x06C4, Access::Data);
+    cpu.set_hl(value);
+    // 069B: SPHL
+    cpu.sp = cpu.hl();
+    // 069C: LXI H,06B4H
+    cpu.set_hl(0x06B4);
+    // 069F: PCHL
+    cpu.pc = cpu.hl();
+    Some(37)
+}
+
+fn block_06a0<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x06A0, &[0x21, 0x90, 0x01, 0xCD, 0x4B, 0x01]) {
+        return None;
+    }
+    // 06A0: LXI H,0190H
+    cpu.set_hl(0x0190);
+    // 06A3: CALL 014BH
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x06A6, Access::Stack);
+    cpu.pc = 0x014B;
+    Some(27)
+}
+
+fn block_06a6<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x06A6, &[0xE1, 0xE5]) {
+        return None;
+    }
+    // 06A6: POP H
+    let value = bus.read_word(cpu.sp, Access::Stack);
+    cpu.sp = cpu.sp.wrapping_add(2);
+    cpu.set_hl(value);
+    // 06A7: PUSH H
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, cpu.hl(), Access::Stack);
+    cpu.pc = 0x06A8;
+    Some(21)
+}
+
+fn block_06a8<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x06A8, &[0x7C, 0xCD, 0x5A, 0x01]) {
+        return None;
+    }
+    // 06A8: MOV A,H
+    cpu.a = cpu.h;
+    // 06A9: CALL 015AH
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x06AC, Access::Stack);
+    cpu.pc = 0x015A;
+    Some(22)
+}
+
+fn block_06ac<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x06AC, &[0xE1, 0x7D, 0xCD, 0x5A, 0x01]) {
+        return None;
+    }
+    // 06AC: POP H
+    let value = bus.read_word(cpu.sp, Access::Stack);
+    cpu.sp = cpu.sp.wrapping_add(2);
+    cpu.set_hl(value);
+    // 06AD: MOV A,L
+    cpu.a = cpu.l;
+    // 06AE: CALL 015AH
+    cpu.sp = cpu.sp.wrapping_sub(2);
+    bus.write_word(cpu.sp, 0x06B1, Access::Stack);
+    cpu.pc = 0x015A;
+    Some(32)
+}
+
+fn block_06b1<B: Bus + ?Sized>(cpu: &mut Cpu, bus: &mut B) -> Option<Cycles> {
+    if !intact(bus, 0x06B1, &[0xC3, 0x00, 0x00]) {
+        return None;
+    }
+    // 06B1: JMP 0000H
+    cpu.pc = 0x0000;
+    Some(10)
+}
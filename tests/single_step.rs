@@ -0,0 +1,40 @@
+use std::fs;
+use std::path::PathBuf;
+
+use remu::single_step;
+
+// Runs every <opcode>.json in $SINGLE_STEP_TESTS, e.g. a checkout of the
+// SingleStepTests 8080 vectors (v1 directory). Skipped when unset.
+#[test]
+fn single_step_vectors() {
+    let Some(dir) = std::env::var_os("SINGLE_STEP_TESTS") else {
+        eprintln!("SINGLE_STEP_TESTS not set, skipping");
+        return;
+    };
+    let mut files: Vec<PathBuf> = fs::read_dir(&dir)
+        .expect("failed to read vector directory")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|e| e == "json"))
+        .collect();
+    files.sort();
+
+    let mut failed = Vec::new();
+    for path in &files {
+        let opcode = path.file_stem().unwrap().to_string_lossy();
+        let report =
+            single_step::run_file(path).unwrap_or_else(|err| panic!("{}: {}", path.display(), err));
+        if report.failures.is_empty() {
+            continue;
+        }
+        println!("{}: {}/{} passed", opcode, report.passed, report.total());
+        for failure in report.failures.iter().take(3) {
+            println!("  {}: {}", failure.name, failure.mismatches.join(", "));
+        }
+        failed.push(opcode.to_string());
+    }
+    assert!(
+        failed.is_empty(),
+        "opcodes with failures: {}",
+        failed.join(" ")
+    );
+}
@@ -0,0 +1,76 @@
+use std::path::Path;
+
+use remu::machine::{SimpleMachine, StopReason};
+use remu::video::{Bitmap, Display, Framebuffer, Video};
+
+// The frame as a binary PPM. Goldens compare these pixels rather than the
+// PNG, whose bytes change with the encoder.
+fn ppm(frame: &Framebuffer) -> Vec<u8> {
+    let mut ppm = format!("P6\n{} {}\n255\n", frame.width, frame.height).into_bytes();
+    for y in 0..frame.height {
+        for x in 0..frame.width {
+            ppm.extend(frame.color(x, y));
+        }
+    }
+    ppm
+}
+
+// Compare a frame with tests/video/<name>.ppm, or write that file and a
+// PNG to look at when REMU_BLESS is set, after a deliberate change to what
+// is drawn.
+fn golden(name: &str, frame: &Framebuffer) {
+    let path = Path::new("tests/video").join(format!("{}.ppm", name));
+    let pixels = ppm(frame);
+    if std::env::var_os("REMU_BLESS").is_some() {
+        std::fs::write(&path, &pixels).unwrap();
+        frame.write_png(path.with_extension("png")).unwrap();
+        return;
+    }
+    let expected = std::fs::read(&path).unwrap_or_else(|err| panic!("{}: {}", path.display(), err));
+    assert!(pixels == expected, "frame differs from {}", path.display());
+}
+
+#[test]
+fn test_bitmap_frames() {
+    // fill the 64x32 bitmap at 4000h with its own addresses a byte per
+    // frame's worth of cycles, then spin
+    let mut machine = SimpleMachine::new();
+    machine.load(
+        0x0000,
+        &[
+            0x21, 0x00, 0x40, // LXI H,4000h
+            0x75, //             MOV M,L
+            0x2C, //             INR L
+            0xC2, 0x03, 0x00, // JNZ 0003h
+            0xC3, 0x08, 0x00, // JMP 0008h
+        ],
+    );
+    let mut frames = Vec::new();
+    let mut display = Display::new(Bitmap::new(0x4000, 64, 32, 2000));
+    display.on_frame(|number, frame| frames.push((number, frame.clone())));
+    let stop = display.run(&mut machine, 10_000);
+    assert_eq!(stop.reason, StopReason::CycleBudget);
+    assert_eq!(display.frames(), 5);
+    let last = display.video().framebuffer().clone();
+    drop(display);
+    assert_eq!(frames[4].1, last);
+
+    // the first frame catches the fill part way, the last sees all of it
+    assert_eq!(frames[0].0, 1);
+    assert_ne!(frames[0].1, frames[4].1);
+    golden("fill-1", &frames[0].1);
+    golden("fill-5", &frames[4].1);
+}
+
+#[test]
+fn test_rgb_frame() {
+    let mut frame = Framebuffer::rgb(16, 8);
+    for y in 0..8 {
+        for x in 0..16 {
+            let pixel = &mut frame.pixels[(y * 16 + x) * 3..][..3];
+            pixel.copy_from_slice(&[x as u8 * 16, y as u8 * 32, 0x80]);
+        }
+    }
+    assert_eq!(frame.color(15, 7), [0xF0, 0xE0, 0x80]);
+    golden("gradient", &frame);
+}